@@ -0,0 +1,8 @@
+package com.forticomm.blackhole;
+
+/** A {@code BlackHoleError} raised by the Rust core. */
+public final class BlackHoleException extends RuntimeException {
+    public BlackHoleException(String message) {
+        super(message);
+    }
+}
@@ -0,0 +1,57 @@
+package com.forticomm.blackhole;
+
+/**
+ * JNI entry points of {@code libblackhole_core.so}, built with the {@code jni} feature.
+ *
+ * <p>{@link #coreNew(byte[])} returns a handle that every other method takes first and that
+ * must be released exactly once with {@link #coreFree(long)}. Encrypted messages,
+ * envelopes and message content are JSON. Failures throw {@link BlackHoleException}.
+ */
+public final class BlackHoleNative {
+    static {
+        System.loadLibrary("blackhole_core");
+    }
+
+    private BlackHoleNative() {}
+
+    /** Takes the identity circuit parameters from the phase-2 ceremony, as shipped with the app. */
+    public static native long coreNew(byte[] parameters);
+
+    public static native void coreFree(long handle);
+
+    public static native String fingerprint(long handle);
+
+    public static native byte[] identityPublicKey(long handle);
+
+    public static native byte[] encrypt(long handle, String recipient, String plaintext);
+
+    public static native String decrypt(long handle, byte[] message);
+
+    /** Pass a null {@code remoteDhPublic} to initiate; returns our ratchet public key. */
+    public static native byte[] startSession(long handle, String peer, byte[] remoteDhPublic);
+
+    public static native boolean endSession(long handle, String peer);
+
+    public static native byte[] sessionEncrypt(long handle, String peer, byte[] content);
+
+    public static native byte[] sessionDecrypt(long handle, String peer, byte[] envelope);
+
+    public static native String createGroup(long handle);
+
+    public static native void groupProposeAdd(long handle, String groupId, String memberId, byte[] keyPackage);
+
+    public static native byte[] groupCommit(long handle, String groupId);
+
+    public static native byte[] groupEncrypt(long handle, String groupId, byte[] plaintext);
+
+    public static native byte[] groupDecrypt(long handle, String groupId, byte[] message);
+
+    public static native boolean leaveGroup(long handle, String groupId);
+
+    /** Returns {@code {pointer, blob, outboard}}; the thumbnail may be null. */
+    public static native byte[][] encryptAttachment(long handle, byte[] data, String contentType, byte[] thumbnail);
+
+    public static native byte[] decryptAttachment(long handle, byte[] pointer, byte[] blob);
+
+    public static native void panicWipe(long handle);
+}
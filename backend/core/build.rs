@@ -0,0 +1,37 @@
+//! Build script
+//!
+//! With the `ffi` feature, generates `blackhole_core.h` from the `ffi` module
+//! into `OUT_DIR`, so the C ABI test always compiles against the current
+//! Rust definitions. The checked-in `include/blackhole_core.h` is copied
+//! from there by `scripts/generate-bindings.sh`; builds never touch the
+//! source tree.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/ffi");
+
+    // Lets the C ABI test configure a C compiler for the same target
+    println!("cargo:rustc-env=TARGET={}", std::env::var("TARGET").unwrap());
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let crate_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/ffi/mod.rs"))
+        .generate()
+        .expect("failed to generate the C header")
+        .write_to_file(out_dir.join("blackhole_core.h"));
+
+    // Where the C ABI test finds the generated header
+    println!("cargo:rustc-env=BLACKHOLE_CORE_INCLUDE_DIR={}", out_dir.display());
+}
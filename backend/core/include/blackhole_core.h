@@ -0,0 +1,437 @@
+/* blackhole-core C ABI. Generated by cbindgen from src/ffi; do not edit. */
+
+#ifndef BLACKHOLE_CORE_H
+#define BLACKHOLE_CORE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A required pointer argument was null
+#define VAULT_ERROR_NULL_POINTER 100
+
+// A text argument or result is not valid UTF-8
+#define VAULT_ERROR_INVALID_UTF8 101
+
+// The output buffer is null or too small; `*out_len` holds the size needed
+#define VAULT_ERROR_BUFFER_TOO_SMALL 102
+
+// The core panicked; the handle should not be used again
+#define VAULT_ERROR_PANIC 103
+
+// Opaque handle to an encrypted attachment: its pointer, blob and outboard;
+// not thread-safe
+typedef struct VaultAttachment VaultAttachment;
+
+// Opaque handle to a `BlackHoleCore`, safe to share between threads
+typedef struct VaultCore VaultCore;
+
+// Opaque handle to an MLS group; not thread-safe
+typedef struct VaultGroup VaultGroup;
+
+// Opaque handle to a Double Ratchet session; not thread-safe
+typedef struct VaultSession VaultSession;
+
+// Error details filled in by a failed call
+typedef struct {
+  // `0` on success, otherwise the code returned by the call
+  int32_t code;
+  // NUL-terminated description, or null; free with `vault_error_free`
+  char *message;
+} VaultError;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Release the message held by `err` and reset its code
+//
+// # Safety
+//
+// `err` must be null or point to a `VaultError` filled by this library.
+void vault_error_free(VaultError *err);
+
+// Collect the output of the last call on this thread that failed with
+// `VAULT_ERROR_BUFFER_TOO_SMALL`
+//
+// # Safety
+//
+// `out` must be null or valid for `*out_len` bytes; `out_len` must be valid.
+int32_t vault_take_output(uint8_t *out, size_t *out_len, VaultError *err);
+
+// Library version as a NUL-terminated string
+const char *vault_version(void);
+
+// Create a core with a fresh identity; null on failure
+//
+// `parameters` are the encoded identity circuit parameters from the
+// phase-2 ceremony, as shipped with the app.
+//
+// # Safety
+//
+// `parameters` must be valid for `parameters_len` bytes and `err` null or
+// point to a `VaultError`.
+VaultCore *vault_core_new(const uint8_t *parameters, size_t parameters_len, VaultError *err);
+
+// Destroy a core, wiping its keys; null is ignored
+//
+// # Safety
+//
+// `core` must be null or a handle from `vault_core_new` not yet freed.
+void vault_core_free(VaultCore *core);
+
+// Write the identity key fingerprint (hex, not NUL-terminated)
+//
+// # Safety
+//
+// `core` must be a live handle; see the module docs for buffers.
+int32_t vault_core_fingerprint(const VaultCore *core,
+                               uint8_t *out,
+                               size_t *out_len,
+                               VaultError *err);
+
+// Write the 32-byte Ed25519 identity public key
+//
+// # Safety
+//
+// `core` must be a live handle; see the module docs for buffers.
+int32_t vault_core_identity_public_key(const VaultCore *core,
+                                       uint8_t *out,
+                                       size_t *out_len,
+                                       VaultError *err);
+
+// Write the 1184-byte ML-KEM-768 public key
+//
+// # Safety
+//
+// `core` must be a live handle; see the module docs for buffers.
+int32_t vault_core_pq_public_key(const VaultCore *core,
+                                 uint8_t *out,
+                                 size_t *out_len,
+                                 VaultError *err);
+
+// Encrypt UTF-8 text, writing the encrypted message as JSON
+//
+// # Safety
+//
+// `core` must be a live handle, `recipient` a NUL-terminated string and
+// `plaintext` valid for `plaintext_len` bytes; see the module docs for buffers.
+int32_t vault_core_encrypt(const VaultCore *core,
+                           const char *recipient,
+                           const uint8_t *plaintext,
+                           size_t plaintext_len,
+                           uint8_t *out,
+                           size_t *out_len,
+                           VaultError *err);
+
+// Decrypt a JSON encrypted message, writing the UTF-8 text
+//
+// # Safety
+//
+// `core` must be a live handle and `message` valid for `message_len`
+// bytes; see the module docs for buffers.
+int32_t vault_core_decrypt(const VaultCore *core,
+                           const uint8_t *message,
+                           size_t message_len,
+                           uint8_t *out,
+                           size_t *out_len,
+                           VaultError *err);
+
+// Write a zero-knowledge proof of identity as JSON
+//
+// # Safety
+//
+// `core` must be a live handle; see the module docs for buffers.
+int32_t vault_core_prove_identity(const VaultCore *core,
+                                  uint8_t *out,
+                                  size_t *out_len,
+                                  VaultError *err);
+
+// Verify a JSON zero-knowledge proof of identity
+//
+// # Safety
+//
+// `core` must be a live handle, `proof` valid for `proof_len` bytes and
+// `valid_out` valid for writes.
+int32_t vault_core_verify_identity(const VaultCore *core,
+                                   const uint8_t *proof,
+                                   size_t proof_len,
+                                   bool *valid_out,
+                                   VaultError *err);
+
+// Write a 64-byte proof of possession of the identity key, bound to `context`
+//
+// # Safety
+//
+// `core` must be a live handle and `context` valid for `context_len`
+// bytes; see the module docs for buffers.
+int32_t vault_core_prove_identity_key(const VaultCore *core,
+                                      const uint8_t *context,
+                                      size_t context_len,
+                                      uint8_t *out,
+                                      size_t *out_len,
+                                      VaultError *err);
+
+// Verify a proof of possession of the Ed25519 key `public_key`
+//
+// # Safety
+//
+// Each input must be valid for its length and `valid_out` valid for
+// writes.
+int32_t vault_verify_identity_key(const uint8_t *public_key,
+                                  size_t public_key_len,
+                                  const uint8_t *context,
+                                  size_t context_len,
+                                  const uint8_t *proof,
+                                  size_t proof_len,
+                                  bool *valid_out,
+                                  VaultError *err);
+
+// Write a JSON sigma proof of a JSON statement from a JSON witness
+//
+// # Safety
+//
+// Each input must be valid for its length; see the module docs for
+// buffers.
+int32_t vault_sigma_prove(const uint8_t *statement,
+                          size_t statement_len,
+                          const uint8_t *witness,
+                          size_t witness_len,
+                          const uint8_t *context,
+                          size_t context_len,
+                          uint8_t *out,
+                          size_t *out_len,
+                          VaultError *err);
+
+// Verify a JSON sigma proof of a JSON statement
+//
+// # Safety
+//
+// Each input must be valid for its length and `valid_out` valid for
+// writes.
+int32_t vault_sigma_verify(const uint8_t *statement,
+                           size_t statement_len,
+                           const uint8_t *proof,
+                           size_t proof_len,
+                           const uint8_t *context,
+                           size_t context_len,
+                           bool *valid_out,
+                           VaultError *err);
+
+// Emergency deletion of every key held by the core
+//
+// # Safety
+//
+// `core` must be null or a live handle.
+void vault_core_panic_wipe(const VaultCore *core);
+
+// Start a Double Ratchet session for the core's identity; null on failure
+//
+// Pass the peer's ratchet key when responding to them, or null and 0 to
+// initiate.
+//
+// # Safety
+//
+// `core` must be a live handle and `remote_dh_public` valid for
+// `remote_dh_public_len` bytes.
+VaultSession *vault_session_new(const VaultCore *core,
+                                const uint8_t *remote_dh_public,
+                                size_t remote_dh_public_len,
+                                VaultError *err);
+
+// Destroy a session, wiping its chain keys; null is ignored
+//
+// # Safety
+//
+// `session` must be null or a handle from `vault_session_new` not yet freed.
+void vault_session_free(VaultSession *session);
+
+// Write our 32-byte ratchet public key, to send to the peer
+//
+// # Safety
+//
+// `session` must be a live handle; see the module docs for buffers.
+int32_t vault_session_dh_public(const VaultSession *session,
+                                uint8_t *out,
+                                size_t *out_len,
+                                VaultError *err);
+
+// Encrypt bytes, writing the message envelope as JSON
+//
+// # Safety
+//
+// `session` must be a live handle and `plaintext` valid for
+// `plaintext_len` bytes; see the module docs for buffers.
+int32_t vault_session_encrypt(VaultSession *session,
+                              const uint8_t *plaintext,
+                              size_t plaintext_len,
+                              uint8_t *out,
+                              size_t *out_len,
+                              VaultError *err);
+
+// Decrypt a JSON message envelope, writing the plaintext
+//
+// # Safety
+//
+// `session` must be a live handle and `envelope` valid for `envelope_len`
+// bytes; see the module docs for buffers.
+int32_t vault_session_decrypt(VaultSession *session,
+                              const uint8_t *envelope,
+                              size_t envelope_len,
+                              uint8_t *out,
+                              size_t *out_len,
+                              VaultError *err);
+
+// Create an MLS group owned by the core's identity; null on failure
+//
+// # Safety
+//
+// `core` must be a live handle.
+VaultGroup *vault_group_new(const VaultCore *core, VaultError *err);
+
+// Destroy a group, wiping its secrets; null is ignored
+//
+// # Safety
+//
+// `group` must be null or a handle from `vault_group_new` not yet freed.
+void vault_group_free(VaultGroup *group);
+
+// Write the random group ID (hex, not NUL-terminated)
+//
+// # Safety
+//
+// `group` must be a live handle; see the module docs for buffers.
+int32_t vault_group_id(const VaultGroup *group, uint8_t *out, size_t *out_len, VaultError *err);
+
+// Read the current epoch
+//
+// # Safety
+//
+// `group` must be a live handle and `epoch_out` valid for writes.
+int32_t vault_group_epoch(const VaultGroup *group, uint64_t *epoch_out, VaultError *err);
+
+// Propose adding a member
+//
+// # Safety
+//
+// `group` must be a live handle, `member_id` a NUL-terminated string and
+// `key_package` valid for `key_package_len` bytes.
+int32_t vault_group_propose_add(VaultGroup *group,
+                                const char *member_id,
+                                const uint8_t *key_package,
+                                size_t key_package_len,
+                                VaultError *err);
+
+// Commit the pending proposals, writing the commit message
+//
+// # Safety
+//
+// `group` must be a live handle; see the module docs for buffers.
+int32_t vault_group_commit(VaultGroup *group, uint8_t *out, size_t *out_len, VaultError *err);
+
+// Encrypt a group message
+//
+// # Safety
+//
+// `group` must be a live handle and `plaintext` valid for `plaintext_len`
+// bytes; see the module docs for buffers.
+int32_t vault_group_encrypt(const VaultGroup *group,
+                            const uint8_t *plaintext,
+                            size_t plaintext_len,
+                            uint8_t *out,
+                            size_t *out_len,
+                            VaultError *err);
+
+// Decrypt a group message
+//
+// # Safety
+//
+// `group` must be a live handle and `message` valid for `message_len`
+// bytes; see the module docs for buffers.
+int32_t vault_group_decrypt(const VaultGroup *group,
+                            const uint8_t *message,
+                            size_t message_len,
+                            uint8_t *out,
+                            size_t *out_len,
+                            VaultError *err);
+
+// Encrypt a file as an attachment under a fresh random key; null on failure
+//
+// Upload the blob and outboard, and send the pointer inside a ratchet
+// message. Pass null and 0 for no thumbnail.
+//
+// # Safety
+//
+// `core` must be a live handle, `data` valid for `data_len` bytes,
+// `content_type` a NUL-terminated string and `thumbnail` null or valid for
+// `thumbnail_len` bytes.
+VaultAttachment *vault_core_encrypt_attachment(const VaultCore *core,
+                                               const uint8_t *data,
+                                               size_t data_len,
+                                               const char *content_type,
+                                               const uint8_t *thumbnail,
+                                               size_t thumbnail_len,
+                                               VaultError *err);
+
+// Verify a downloaded blob against its pointer and decrypt it
+//
+// # Safety
+//
+// `core` must be a live handle, `pointer` valid for `pointer_len` bytes
+// and `blob` valid for `blob_len` bytes; see the module docs for buffers.
+int32_t vault_core_decrypt_attachment(const VaultCore *core,
+                                      const uint8_t *pointer,
+                                      size_t pointer_len,
+                                      const uint8_t *blob,
+                                      size_t blob_len,
+                                      uint8_t *out,
+                                      size_t *out_len,
+                                      VaultError *err);
+
+// Destroy an attachment, wiping its key; null is ignored
+//
+// # Safety
+//
+// `attachment` must be null or a handle from
+// `vault_core_encrypt_attachment` not yet freed.
+void vault_attachment_free(VaultAttachment *attachment);
+
+// Write the attachment pointer, to send inside a ratchet message
+//
+// The pointer holds the attachment key.
+//
+// # Safety
+//
+// `attachment` must be a live handle; see the module docs for buffers.
+int32_t vault_attachment_pointer(const VaultAttachment *attachment,
+                                 uint8_t *out,
+                                 size_t *out_len,
+                                 VaultError *err);
+
+// Write the encrypted blob, to upload under the pointer's blob ID
+//
+// # Safety
+//
+// `attachment` must be a live handle; see the module docs for buffers.
+int32_t vault_attachment_blob(const VaultAttachment *attachment,
+                              uint8_t *out,
+                              size_t *out_len,
+                              VaultError *err);
+
+// Write the Bao outboard tree, served alongside the blob
+//
+// # Safety
+//
+// `attachment` must be a live handle; see the module docs for buffers.
+int32_t vault_attachment_outboard(const VaultAttachment *attachment,
+                                  uint8_t *out,
+                                  size_t *out_len,
+                                  VaultError *err);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BLACKHOLE_CORE_H */
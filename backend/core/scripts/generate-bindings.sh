@@ -0,0 +1,37 @@
+#!/usr/bin/env sh
+# Generate the C header and the Swift and Kotlin bindings for the mobile apps
+#
+# Usage: scripts/generate-bindings.sh [out-dir]   (default: target/bindings)
+#
+# The C header is copied from the build to include/blackhole_core.h, the only
+# place it is written in the source tree; the Swift and Kotlin sources are
+# written to <out-dir>/swift and <out-dir>/kotlin.
+
+set -eu
+
+cd "$(dirname "$0")/.."
+OUT_DIR="${1:-target/bindings}"
+
+# Release builds are stripped of the metadata UniFFI reads, so generate
+# from a debug build; the bindings are the same for every profile
+HEADER_DIR=$(
+    cargo build --no-default-features --features ffi,uniffi-cli --message-format json-render-diagnostics \
+        | grep '"reason":"build-script-executed"' \
+        | grep 'blackhole-core' \
+        | sed 's/.*"out_dir":"\([^"]*\)".*/\1/'
+)
+cp "$HEADER_DIR/blackhole_core.h" include/blackhole_core.h
+
+case "$(uname -s)" in
+    Darwin) LIBRARY=target/debug/libblackhole_core.dylib ;;
+    *) LIBRARY=target/debug/libblackhole_core.so ;;
+esac
+
+for LANGUAGE in swift kotlin; do
+    target/debug/uniffi-bindgen generate \
+        --library "$LIBRARY" \
+        --language "$LANGUAGE" \
+        --out-dir "$OUT_DIR/$LANGUAGE"
+done
+
+echo "🕳️ Bindings written to $OUT_DIR"
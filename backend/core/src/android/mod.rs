@@ -0,0 +1,439 @@
+//! 🤖 Android JNI Bindings
+//!
+//! JNI entry points for `com.forticomm.blackhole.BlackHoleNative` (see
+//! `android/` next to this crate), compiled with the `jni` feature. A core
+//! lives behind the `long` handle returned by `coreNew` and released by
+//! `coreFree`; every other method takes that handle first and may be called
+//! from any thread.
+//!
+//! Java byte arrays and strings are copied in and out, never borrowed across
+//! calls. A `BlackHoleError` is thrown as
+//! `com.forticomm.blackhole.BlackHoleException` carrying its message, null
+//! arguments throw `NullPointerException`, and a zero handle throws
+//! `IllegalStateException`. Structured values cross as JSON, as in the C ABI.
+
+#![allow(unsafe_code)]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use jni::objects::{JByteArray, JClass, JObject, JString};
+use jni::sys::{jboolean, jbyteArray, jlong, jobjectArray, jstring};
+use jni::JNIEnv;
+
+use crate::{BlackHoleError, EncryptedMessage, MessageEnvelope, SharedCore, ZKParameters};
+
+/// Java class thrown for every `BlackHoleError`
+pub const EXCEPTION_CLASS: &str = "com/forticomm/blackhole/BlackHoleException";
+
+/// Why a JNI call failed
+enum JniError {
+    Core(BlackHoleError),
+    Null(&'static str),
+    ClosedHandle,
+    Jni(jni::errors::Error),
+    Panic,
+}
+
+impl From<BlackHoleError> for JniError {
+    fn from(err: BlackHoleError) -> Self {
+        JniError::Core(err)
+    }
+}
+
+impl From<jni::errors::Error> for JniError {
+    fn from(err: jni::errors::Error) -> Self {
+        JniError::Jni(err)
+    }
+}
+
+/// Run `body`, throwing any failure into Java and returning `fallback`
+fn call<'local, T>(
+    env: &mut JNIEnv<'local>,
+    fallback: T,
+    body: impl FnOnce(&mut JNIEnv<'local>) -> Result<T, JniError>,
+) -> T {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| body(env))).unwrap_or(Err(JniError::Panic));
+
+    result.unwrap_or_else(|err| {
+        throw(env, err);
+        fallback
+    })
+}
+
+fn throw(env: &mut JNIEnv, err: JniError) {
+    let (class, message) = match err {
+        JniError::Core(err) => (EXCEPTION_CLASS, err.to_string()),
+        JniError::Null(name) => ("java/lang/NullPointerException", format!("`{name}` is null")),
+        JniError::ClosedHandle => ("java/lang/IllegalStateException", "BlackHole handle is closed".to_string()),
+        // The JVM already has an exception pending
+        JniError::Jni(jni::errors::Error::JavaException) => return,
+        JniError::Jni(err) => ("java/lang/RuntimeException", err.to_string()),
+        JniError::Panic => ("java/lang/RuntimeException", "Internal error".to_string()),
+    };
+
+    // Nothing more can be reported if even throwing fails
+    let _ = env.throw_new(class, message);
+}
+
+/// The core behind `handle`
+fn core<'a>(handle: jlong) -> Result<&'a SharedCore, JniError> {
+    // SAFETY: non-zero handles come from `coreNew` and live until `coreFree`
+    unsafe { (handle as *const SharedCore).as_ref() }.ok_or(JniError::ClosedHandle)
+}
+
+fn bytes(env: &JNIEnv, array: &JByteArray, name: &'static str) -> Result<Vec<u8>, JniError> {
+    if array.is_null() {
+        return Err(JniError::Null(name));
+    }
+
+    Ok(env.convert_byte_array(array)?)
+}
+
+fn optional_bytes(env: &JNIEnv, array: &JByteArray) -> Result<Option<Vec<u8>>, JniError> {
+    if array.is_null() {
+        Ok(None)
+    } else {
+        Ok(Some(env.convert_byte_array(array)?))
+    }
+}
+
+fn string(env: &mut JNIEnv, string: &JString, name: &'static str) -> Result<String, JniError> {
+    if string.is_null() {
+        return Err(JniError::Null(name));
+    }
+
+    Ok(env.get_string(string)?.into())
+}
+
+fn byte_array(env: &JNIEnv, data: &[u8]) -> Result<jbyteArray, JniError> {
+    Ok(env.byte_array_from_slice(data)?.into_raw())
+}
+
+fn new_string(env: &JNIEnv, value: &str) -> Result<jstring, JniError> {
+    Ok(env.new_string(value)?.into_raw())
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, JniError> {
+    Ok(serde_json::to_vec(value).map_err(BlackHoleError::serialization)?)
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, JniError> {
+    Ok(serde_json::from_slice(bytes).map_err(BlackHoleError::serialization)?)
+}
+
+/// `static native long coreNew(byte[] parameters)`, with the ceremony's encoded ZK parameters
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_coreNew<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    parameters: JByteArray<'local>,
+) -> jlong {
+    call(&mut env, 0, |env| {
+        let parameters = ZKParameters::from_bytes(&bytes(env, &parameters, "parameters")?)?;
+        Ok(Box::into_raw(Box::new(SharedCore::new(&parameters)?)) as jlong)
+    })
+}
+
+/// `static native void coreFree(long handle)`; zero is ignored
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_coreFree<'local>(
+    _env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) {
+    if handle != 0 {
+        // SAFETY: the handle came from `coreNew` and Java frees it only once
+        drop(unsafe { Box::from_raw(handle as *mut SharedCore) });
+    }
+}
+
+/// `static native String fingerprint(long handle)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_fingerprint<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) -> jstring {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let fingerprint = core(handle)?.get_fingerprint();
+        new_string(env, &fingerprint)
+    })
+}
+
+/// `static native byte[] identityPublicKey(long handle)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_identityPublicKey<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let key = core(handle)?.get_identity_public_key();
+        byte_array(env, &key)
+    })
+}
+
+/// `static native byte[] encrypt(long handle, String recipient, String plaintext)`,
+/// returning the encrypted message as JSON
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_encrypt<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    recipient: JString<'local>,
+    plaintext: JString<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let recipient = string(env, &recipient, "recipient")?;
+        let plaintext = string(env, &plaintext, "plaintext")?;
+
+        let message = core(handle)?.encrypt(&recipient, &plaintext)?;
+        byte_array(env, &to_json(&message)?)
+    })
+}
+
+/// `static native String decrypt(long handle, byte[] message)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_decrypt<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    message: JByteArray<'local>,
+) -> jstring {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let message: EncryptedMessage = from_json(&bytes(env, &message, "message")?)?;
+
+        let plaintext = core(handle)?.decrypt(&message)?;
+        new_string(env, &plaintext)
+    })
+}
+
+/// `static native byte[] startSession(long handle, String peer, byte[] remoteDhPublic)`;
+/// pass null to initiate, returns our ratchet public key
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_startSession<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    peer: JString<'local>,
+    remote_dh_public: JByteArray<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let peer = string(env, &peer, "peer")?;
+        let remote_dh_public = optional_bytes(env, &remote_dh_public)?;
+
+        let dh_public = core(handle)?.start_session(&peer, remote_dh_public)?;
+        byte_array(env, &dh_public)
+    })
+}
+
+/// `static native boolean endSession(long handle, String peer)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_endSession<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    peer: JString<'local>,
+) -> jboolean {
+    call(&mut env, 0, |env| {
+        let peer = string(env, &peer, "peer")?;
+        Ok(core(handle)?.end_session(&peer).into())
+    })
+}
+
+/// `static native byte[] sessionEncrypt(long handle, String peer, byte[] content)`,
+/// taking JSON message content and returning the JSON envelope
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_sessionEncrypt<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    peer: JString<'local>,
+    content: JByteArray<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let peer = string(env, &peer, "peer")?;
+        let content = from_json(&bytes(env, &content, "content")?)?;
+
+        let envelope = core(handle)?.session_encrypt(&peer, content)?;
+        byte_array(env, &to_json(&envelope)?)
+    })
+}
+
+/// `static native byte[] sessionDecrypt(long handle, String peer, byte[] envelope)`,
+/// returning the JSON message content
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_sessionDecrypt<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    peer: JString<'local>,
+    envelope: JByteArray<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let peer = string(env, &peer, "peer")?;
+        let envelope: MessageEnvelope = from_json(&bytes(env, &envelope, "envelope")?)?;
+
+        let content = core(handle)?.session_decrypt(&peer, &envelope)?;
+        byte_array(env, &to_json(&content)?)
+    })
+}
+
+/// `static native String createGroup(long handle)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_createGroup<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) -> jstring {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let group_id = core(handle)?.create_group()?;
+        new_string(env, &group_id)
+    })
+}
+
+/// `static native void groupProposeAdd(long handle, String groupId, String memberId, byte[] keyPackage)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_groupProposeAdd<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    group_id: JString<'local>,
+    member_id: JString<'local>,
+    key_package: JByteArray<'local>,
+) {
+    call(&mut env, (), |env| {
+        let group_id = string(env, &group_id, "groupId")?;
+        let member_id = string(env, &member_id, "memberId")?;
+        let key_package = bytes(env, &key_package, "keyPackage")?;
+
+        Ok(core(handle)?.group_propose_add(&group_id, &member_id, &key_package)?)
+    })
+}
+
+/// `static native byte[] groupCommit(long handle, String groupId)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_groupCommit<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    group_id: JString<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let group_id = string(env, &group_id, "groupId")?;
+
+        let commit = core(handle)?.group_commit(&group_id)?;
+        byte_array(env, &commit)
+    })
+}
+
+/// `static native byte[] groupEncrypt(long handle, String groupId, byte[] plaintext)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_groupEncrypt<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    group_id: JString<'local>,
+    plaintext: JByteArray<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let group_id = string(env, &group_id, "groupId")?;
+        let plaintext = bytes(env, &plaintext, "plaintext")?;
+
+        let message = core(handle)?.group_encrypt(&group_id, &plaintext)?;
+        byte_array(env, &message)
+    })
+}
+
+/// `static native byte[] groupDecrypt(long handle, String groupId, byte[] message)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_groupDecrypt<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    group_id: JString<'local>,
+    message: JByteArray<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let group_id = string(env, &group_id, "groupId")?;
+        let message = bytes(env, &message, "message")?;
+
+        let plaintext = core(handle)?.group_decrypt(&group_id, &message)?;
+        byte_array(env, &plaintext)
+    })
+}
+
+/// `static native boolean leaveGroup(long handle, String groupId)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_leaveGroup<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    group_id: JString<'local>,
+) -> jboolean {
+    call(&mut env, 0, |env| {
+        let group_id = string(env, &group_id, "groupId")?;
+        Ok(core(handle)?.leave_group(&group_id).into())
+    })
+}
+
+/// `static native byte[][] encryptAttachment(long handle, byte[] data, String contentType, byte[] thumbnail)`,
+/// returning `{pointer, blob, outboard}`; the thumbnail may be null
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_encryptAttachment<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    data: JByteArray<'local>,
+    content_type: JString<'local>,
+    thumbnail: JByteArray<'local>,
+) -> jobjectArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let data = bytes(env, &data, "data")?;
+        let content_type = string(env, &content_type, "contentType")?;
+        let thumbnail = optional_bytes(env, &thumbnail)?;
+
+        let attachment = core(handle)?.encrypt_attachment(&data, &content_type, thumbnail)?;
+        let parts = [attachment.pointer.to_bytes().map_err(BlackHoleError::from)?, attachment.blob, attachment.outboard];
+
+        let array = env.new_object_array(parts.len() as i32, "[B", JObject::null())?;
+        for (index, part) in parts.iter().enumerate() {
+            let part = env.byte_array_from_slice(part)?;
+            env.set_object_array_element(&array, index as i32, &part)?;
+            env.delete_local_ref(part)?;
+        }
+
+        Ok(array.into_raw())
+    })
+}
+
+/// `static native byte[] decryptAttachment(long handle, byte[] pointer, byte[] blob)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_decryptAttachment<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    pointer: JByteArray<'local>,
+    blob: JByteArray<'local>,
+) -> jbyteArray {
+    call(&mut env, std::ptr::null_mut(), |env| {
+        let pointer = bytes(env, &pointer, "pointer")?;
+        let blob = bytes(env, &blob, "blob")?;
+
+        let data = core(handle)?.decrypt_attachment(&pointer, &blob)?;
+        byte_array(env, &data)
+    })
+}
+
+/// `static native void panicWipe(long handle)`
+#[no_mangle]
+pub extern "system" fn Java_com_forticomm_blackhole_BlackHoleNative_panicWipe<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) {
+    call(&mut env, (), |_| {
+        core(handle)?.panic_wipe();
+        Ok(())
+    })
+}
@@ -0,0 +1,152 @@
+//! Phase-2 setup ceremony for the ZK identity circuit
+//!
+//! ```text
+//! blackhole-ceremony init <phase1> <parameters> <transcript> [identity|membership|rln]
+//! blackhole-ceremony contribute <parameters> <transcript> <output>
+//! blackhole-ceremony verify <phase1> <final> <transcript> [verifying-key]
+//! ```
+//!
+//! `init` derives the initial parameters for the identity circuit, unless
+//! `membership` or `rln` is given, from `<phase1>`: the powers of tau of a
+//! public phase-1 ceremony. It never draws setup randomness itself.
+//! `contribute` reads the latest parameters, writes the updated ones to
+//! `<output>` and appends to the transcript in place; publish the printed
+//! hash so others can check your contribution is in the final transcript.
+//! `verify` replays the transcript from the initial parameters it derives
+//! again from `<phase1>`.
+
+use std::fs;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context};
+use blackhole_core::providers::OsRandom;
+use blackhole_core::error::BlackHoleError;
+use blackhole_core::zk::ceremony::{CeremonyError, Circuit, PowersOfTau, Transcript};
+use blackhole_core::zk::setup::ZKParameters;
+
+const USAGE: &str = "usage:
+  blackhole-ceremony init <phase1> <parameters> <transcript> [identity|membership|rln]
+  blackhole-ceremony contribute <parameters> <transcript> <output>
+  blackhole-ceremony verify <phase1> <final> <transcript> [verifying-key]
+
+trust model:
+  Anyone who knows the setup secrets can forge proofs. tau, alpha and beta
+  come from <phase1>, the powers of tau of a public phase-1 ceremony: check
+  its transcript with that ceremony's own tools, this tool only checks the
+  powers are consistent. delta comes from the contributions to this phase-2
+  ceremony. The final parameters are sound if at least one participant of
+  each phase destroyed their secret. init derives the initial parameters
+  from <phase1> without any randomness, and verify derives them again, so
+  whoever runs init learns nothing.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["init", phase1, parameters, transcript] => init(phase1, parameters, transcript, "identity"),
+        ["init", phase1, parameters, transcript, circuit] => init(phase1, parameters, transcript, circuit),
+        ["contribute", parameters, transcript, output] => contribute(parameters, transcript, output),
+        ["verify", phase1, result, transcript] => verify(phase1, result, transcript, None),
+        ["verify", phase1, result, transcript, key] => verify(phase1, result, transcript, Some(key)),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn init(phase1_path: &str, parameters_path: &str, transcript_path: &str, circuit: &str) -> anyhow::Result<()> {
+    let powers = read_powers(phase1_path)?;
+    let Some(circuit) = Circuit::from_name(circuit) else {
+        bail!("unknown circuit {circuit:?}");
+    };
+    let parameters = circuit.initial_parameters(&powers)?;
+    let transcript = Transcript::new(&parameters);
+
+    write(parameters_path, &parameters.to_bytes())?;
+    write(transcript_path, &transcript.to_bytes())?;
+
+    println!("initial parameters: {}", hex(&transcript.hash()));
+    Ok(())
+}
+
+fn contribute(parameters_path: &str, transcript_path: &str, output_path: &str) -> anyhow::Result<()> {
+    let mut parameters = read_parameters(parameters_path)?;
+    let mut transcript = read_transcript(transcript_path)?;
+
+    let hash = transcript.contribute(&mut parameters, &OsRandom)?;
+
+    // Parameters first: a transcript must never name parameters that were not written
+    write(output_path, &parameters.to_bytes())?;
+    write(transcript_path, &transcript.to_bytes())?;
+
+    println!("contribution {}: {}", transcript.len(), hex(&hash));
+    Ok(())
+}
+
+fn verify(phase1_path: &str, result_path: &str, transcript_path: &str, key_path: Option<&str>) -> anyhow::Result<()> {
+    let powers = read_powers(phase1_path)?;
+    let result = read_parameters(result_path)?;
+    let transcript = read_transcript(transcript_path)?;
+
+    // The files do not name their circuit, so try each
+    let mut verified = None;
+    for circuit in Circuit::ALL {
+        match transcript.verify(&powers, circuit, &result) {
+            Err(BlackHoleError::Ceremony(CeremonyError::WrongParameters | CeremonyError::TooFewPowers(_))) => continue,
+            outcome => {
+                verified = Some(outcome?);
+                break;
+            }
+        }
+    }
+    let Some(hashes) = verified else {
+        bail!("{transcript_path} does not start from parameters derived from {phase1_path}");
+    };
+
+    if hashes.is_empty() {
+        bail!("the transcript has no contributions");
+    }
+
+    for (index, hash) in hashes.iter().enumerate() {
+        println!("contribution {}: {}", index + 1, hex(hash));
+    }
+    println!("key id: {}", hex(&result.key_id()));
+
+    if let Some(key_path) = key_path {
+        write(key_path, &result.verifying_key())?;
+    }
+    Ok(())
+}
+
+fn read_parameters(path: &str) -> anyhow::Result<ZKParameters> {
+    let bytes = fs::read(path).with_context(|| format!("reading {path}"))?;
+    ZKParameters::from_bytes(&bytes).with_context(|| format!("loading {path}"))
+}
+
+fn read_powers(path: &str) -> anyhow::Result<PowersOfTau> {
+    let bytes = fs::read(path).with_context(|| format!("reading {path}"))?;
+    PowersOfTau::from_bytes(&bytes).with_context(|| format!("loading {path}"))
+}
+
+fn read_transcript(path: &str) -> anyhow::Result<Transcript> {
+    let bytes = fs::read(path).with_context(|| format!("reading {path}"))?;
+    Transcript::from_bytes(&bytes).with_context(|| format!("loading {path}"))
+}
+
+fn write(path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+    fs::write(path, bytes).with_context(|| format!("writing {path}"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
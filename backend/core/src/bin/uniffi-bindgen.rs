@@ -0,0 +1,5 @@
+//! UniFFI binding generator, run by `scripts/generate-bindings.sh`
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -0,0 +1,384 @@
+//! 🌳 Verified Streaming (Bao)
+//!
+//! A BLAKE3 hash is the root of a binary Merkle tree over 1 KiB chunks. By
+//! shipping the interior nodes of that tree next to the content (the Bao
+//! "outboard" encoding), a receiver holding only the root hash can verify any
+//! chunk on its own, as soon as it arrives, without downloading the rest.
+//!
+//! # Format
+//!
+//! ```text
+//! outboard = len (u64, little-endian) ‖ parent₀ ‖ parent₁ ‖ …
+//! parentᵢ  = left_cv (32) ‖ right_cv (32)          in pre-order
+//! ```
+//!
+//! The left subtree of every parent covers the largest power-of-two number of
+//! chunks that leaves at least one chunk on the right, exactly as in BLAKE3,
+//! so the root of the tree is `BLAKE3(content)`.
+//!
+//! ## Security Properties
+//!
+//! - **Independence**: each chunk is checked along its own path from the
+//!   root, so chunks may be verified out of order or after a resume
+//! - **Length**: the content length in the header is authenticated by the
+//!   tree shape; callers that know the expected length should still check it
+
+use alloc::vec::Vec;
+use blake3::hazmat::{self, ChainingValue, HasherExt, Mode};
+
+/// Content bytes per chunk (the BLAKE3 chunk size)
+pub const CHUNK_LEN: usize = 1024;
+
+/// Outboard header length
+pub const HEADER_LEN: usize = 8;
+
+/// Encoded length of a parent node
+pub const PARENT_LEN: usize = 64;
+
+/// Errors raised while verifying streamed content
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BaoError {
+    /// The outboard encoding is truncated or has the wrong length
+    #[error("Malformed outboard encoding")]
+    MalformedOutboard,
+
+    /// A chunk or one of its parents does not hash to the expected value
+    #[error("Chunk {0} failed verification")]
+    HashMismatch(u64),
+
+    /// A chunk has the wrong length for its position
+    #[error("Chunk {0} has the wrong length")]
+    ChunkLength(u64),
+
+    /// A range does not start on a chunk boundary
+    #[error("Range is not chunk-aligned")]
+    Misaligned,
+
+    /// A chunk or range lies beyond the end of the content
+    #[error("Range exceeds content length")]
+    OutOfRange,
+}
+
+/// Incremental outboard encoder
+///
+/// Hashes content as it is produced and emits the root hash together with
+/// the outboard tree once the content is complete.
+#[derive(Default)]
+pub struct OutboardEncoder {
+    buffer: Vec<u8>,
+    chunk_cvs: Vec<ChainingValue>,
+}
+
+/// Verifies chunks and ranges of content against a trusted root hash
+#[derive(Clone, Debug)]
+pub struct BaoVerifier {
+    root: blake3::Hash,
+    content_len: u64,
+    outboard: Vec<u8>,
+}
+
+impl OutboardEncoder {
+    /// Start a new encoding
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed content bytes
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // A full chunk is only hashed once more data follows, because the
+            // last chunk of a single-chunk input is also the root
+            if self.buffer.len() == CHUNK_LEN {
+                let offset = self.chunk_cvs.len() as u64 * CHUNK_LEN as u64;
+                self.chunk_cvs.push(chunk_cv(&self.buffer, offset));
+                self.buffer.clear();
+            }
+
+            let take = (CHUNK_LEN - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+        }
+    }
+
+    /// Finish the encoding, returning the root hash and the outboard tree
+    pub fn finalize(mut self) -> (blake3::Hash, Vec<u8>) {
+        let content_len = self.chunk_cvs.len() as u64 * CHUNK_LEN as u64 + self.buffer.len() as u64;
+
+        let mut outboard = Vec::with_capacity(outboard_len(content_len));
+        outboard.extend_from_slice(&content_len.to_le_bytes());
+
+        if self.chunk_cvs.is_empty() {
+            return (blake3::hash(&self.buffer), outboard);
+        }
+
+        let offset = self.chunk_cvs.len() as u64 * CHUNK_LEN as u64;
+        self.chunk_cvs.push(chunk_cv(&self.buffer, offset));
+
+        let (left, right) = encode_subtree(&self.chunk_cvs, &mut outboard);
+        (hazmat::merge_subtrees_root(&left, &right, Mode::Hash), outboard)
+    }
+}
+
+impl BaoVerifier {
+    /// Prepare to verify content under `root` using an untrusted outboard tree
+    pub fn new(root: &[u8; 32], outboard: &[u8]) -> Result<Self, BaoError> {
+        let header: [u8; HEADER_LEN] = outboard
+            .get(..HEADER_LEN)
+            .and_then(|h| h.try_into().ok())
+            .ok_or(BaoError::MalformedOutboard)?;
+        let content_len = u64::from_le_bytes(header);
+
+        // At most 2⁵⁴ chunks, so this cannot overflow
+        let expected_len = HEADER_LEN as u64 + (chunk_count(content_len) - 1) * PARENT_LEN as u64;
+        if outboard.len() as u64 != expected_len {
+            return Err(BaoError::MalformedOutboard);
+        }
+
+        Ok(BaoVerifier {
+            root: blake3::Hash::from(*root),
+            content_len,
+            outboard: outboard.to_vec(),
+        })
+    }
+
+    /// Content length claimed by the outboard header
+    pub fn content_len(&self) -> u64 {
+        self.content_len
+    }
+
+    /// Number of chunks in the content
+    pub fn chunk_count(&self) -> u64 {
+        chunk_count(self.content_len)
+    }
+
+    /// Verify a single chunk against the root hash
+    pub fn verify_chunk(&self, index: u64, chunk: &[u8]) -> Result<(), BaoError> {
+        if index >= self.chunk_count() {
+            return Err(BaoError::OutOfRange);
+        }
+
+        let chunk_start = index * CHUNK_LEN as u64;
+        let expected_len = (self.content_len - chunk_start).min(CHUNK_LEN as u64);
+        if chunk.len() as u64 != expected_len {
+            return Err(BaoError::ChunkLength(index));
+        }
+
+        // Walk from the root to the chunk, checking every parent on the path
+        let mut expected = self.root;
+        let mut start = 0u64;
+        let mut chunks = self.chunk_count();
+        let mut position = HEADER_LEN;
+
+        while chunks > 1 {
+            let node = &self.outboard[position..position + PARENT_LEN];
+            let left: ChainingValue = node[..32].try_into().expect("parent node is 64 bytes");
+            let right: ChainingValue = node[32..].try_into().expect("parent node is 64 bytes");
+
+            let actual = if position == HEADER_LEN {
+                hazmat::merge_subtrees_root(&left, &right, Mode::Hash)
+            } else {
+                blake3::Hash::from(hazmat::merge_subtrees_non_root(&left, &right, Mode::Hash))
+            };
+
+            // blake3::Hash compares in constant time
+            if actual != expected {
+                return Err(BaoError::HashMismatch(index));
+            }
+
+            let left_chunks = left_chunks(chunks);
+            if index < start + left_chunks {
+                expected = blake3::Hash::from(left);
+                chunks = left_chunks;
+                position += PARENT_LEN;
+            } else {
+                expected = blake3::Hash::from(right);
+                start += left_chunks;
+                chunks -= left_chunks;
+                position += PARENT_LEN * left_chunks as usize;
+            }
+        }
+
+        let actual = if self.chunk_count() == 1 {
+            blake3::hash(chunk)
+        } else {
+            blake3::Hash::from(chunk_cv(chunk, chunk_start))
+        };
+
+        if actual != expected {
+            return Err(BaoError::HashMismatch(index));
+        }
+
+        Ok(())
+    }
+
+    /// Verify a run of whole chunks starting at byte `offset`
+    ///
+    /// `offset` must be chunk-aligned and `data` must end on a chunk boundary
+    /// or at the end of the content. Use [`BaoVerifier::aligned_range`] to
+    /// widen an arbitrary byte range before fetching it.
+    pub fn verify_range(&self, offset: u64, data: &[u8]) -> Result<(), BaoError> {
+        if offset % CHUNK_LEN as u64 != 0 {
+            return Err(BaoError::Misaligned);
+        }
+
+        let end = offset.checked_add(data.len() as u64).ok_or(BaoError::OutOfRange)?;
+        if end > self.content_len {
+            return Err(BaoError::OutOfRange);
+        }
+
+        let first = offset / CHUNK_LEN as u64;
+        for (i, chunk) in data.chunks(CHUNK_LEN).enumerate() {
+            self.verify_chunk(first + i as u64, chunk)?;
+        }
+
+        Ok(())
+    }
+
+    /// Smallest verifiable range covering the bytes `start..end`
+    pub fn aligned_range(&self, start: u64, end: u64) -> (u64, u64) {
+        let chunk = CHUNK_LEN as u64;
+        let end = end.min(self.content_len);
+        let start = start.min(end) / chunk * chunk;
+        let end = end.div_ceil(chunk).saturating_mul(chunk).min(self.content_len);
+
+        (start, end)
+    }
+}
+
+/// Encode the parents of a subtree in pre-order, returning its children's CVs
+fn encode_subtree(cvs: &[ChainingValue], outboard: &mut Vec<u8>) -> (ChainingValue, ChainingValue) {
+    let position = outboard.len();
+    outboard.extend_from_slice(&[0u8; PARENT_LEN]);
+
+    let (left_cvs, right_cvs) = cvs.split_at(left_chunks(cvs.len() as u64) as usize);
+    let left = subtree_cv(left_cvs, outboard);
+    let right = subtree_cv(right_cvs, outboard);
+
+    outboard[position..position + 32].copy_from_slice(&left);
+    outboard[position + 32..position + PARENT_LEN].copy_from_slice(&right);
+
+    (left, right)
+}
+
+/// Chaining value of a non-root subtree
+fn subtree_cv(cvs: &[ChainingValue], outboard: &mut Vec<u8>) -> ChainingValue {
+    if cvs.len() == 1 {
+        return cvs[0];
+    }
+
+    let (left, right) = encode_subtree(cvs, outboard);
+    hazmat::merge_subtrees_non_root(&left, &right, Mode::Hash)
+}
+
+/// Chaining value of a non-root chunk starting at byte `offset`
+fn chunk_cv(chunk: &[u8], offset: u64) -> ChainingValue {
+    blake3::Hasher::new()
+        .set_input_offset(offset)
+        .update(chunk)
+        .finalize_non_root()
+}
+
+/// Chunks in the left subtree of a parent covering `chunks` chunks
+fn left_chunks(chunks: u64) -> u64 {
+    1 << (63 - (chunks - 1).leading_zeros())
+}
+
+/// Number of chunks for `content_len` bytes (empty content is one chunk)
+fn chunk_count(content_len: u64) -> u64 {
+    content_len.div_ceil(CHUNK_LEN as u64).max(1)
+}
+
+/// Length of the outboard encoding for `content_len` bytes
+pub fn outboard_len(content_len: u64) -> usize {
+    HEADER_LEN + (chunk_count(content_len) as usize - 1) * PARENT_LEN
+}
+
+/// Encode the outboard tree of an in-memory payload
+pub fn encode_outboard(content: &[u8]) -> (blake3::Hash, Vec<u8>) {
+    let mut encoder = OutboardEncoder::new();
+    encoder.update(content);
+    encoder.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_root_matches_blake3() {
+        for len in [0, 1, CHUNK_LEN, CHUNK_LEN + 1, 3 * CHUNK_LEN, 5 * CHUNK_LEN + 17, 64 * CHUNK_LEN] {
+            let data = content(len);
+
+            // Feed in uneven pieces to exercise the chunk buffering
+            let mut encoder = OutboardEncoder::new();
+            for piece in data.chunks(700) {
+                encoder.update(piece);
+            }
+            let (root, outboard) = encoder.finalize();
+
+            assert_eq!(root, blake3::hash(&data), "len {}", len);
+            assert_eq!(outboard.len(), outboard_len(len as u64));
+
+            let verifier = BaoVerifier::new(root.as_bytes(), &outboard).unwrap();
+            assert!(verifier.verify_range(0, &data).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_corrupted_chunk_is_pinpointed() {
+        let data = content(7 * CHUNK_LEN + 300);
+        let (root, outboard) = encode_outboard(&data);
+        let verifier = BaoVerifier::new(root.as_bytes(), &outboard).unwrap();
+
+        for target in 0..verifier.chunk_count() {
+            let mut corrupted = data.clone();
+            corrupted[target as usize * CHUNK_LEN] ^= 1;
+
+            for (index, chunk) in corrupted.chunks(CHUNK_LEN).enumerate() {
+                let result = verifier.verify_chunk(index as u64, chunk);
+                if index as u64 == target {
+                    assert_eq!(result, Err(BaoError::HashMismatch(target)));
+                } else {
+                    assert!(result.is_ok());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_corrupted_outboard_is_rejected() {
+        let data = content(4 * CHUNK_LEN);
+        let (root, mut outboard) = encode_outboard(&data);
+
+        // Flip a byte in the right child of the root: chunks 2 and 3 now fail
+        outboard[HEADER_LEN + 40] ^= 1;
+        let verifier = BaoVerifier::new(root.as_bytes(), &outboard).unwrap();
+        assert_eq!(verifier.verify_chunk(2, &data[2 * CHUNK_LEN..3 * CHUNK_LEN]), Err(BaoError::HashMismatch(2)));
+
+        // Lying about the length breaks the outboard size
+        let (_, mut outboard) = encode_outboard(&data);
+        outboard[..HEADER_LEN].copy_from_slice(&(8 * CHUNK_LEN as u64).to_le_bytes());
+        assert_eq!(BaoVerifier::new(root.as_bytes(), &outboard).unwrap_err(), BaoError::MalformedOutboard);
+    }
+
+    #[test]
+    fn test_arbitrary_range() {
+        let data = content(10 * CHUNK_LEN + 5);
+        let (root, outboard) = encode_outboard(&data);
+        let verifier = BaoVerifier::new(root.as_bytes(), &outboard).unwrap();
+
+        let (start, end) = verifier.aligned_range(2500, 6000);
+        assert_eq!((start, end), (2 * CHUNK_LEN as u64, 6 * CHUNK_LEN as u64));
+        assert!(verifier.verify_range(start, &data[start as usize..end as usize]).is_ok());
+
+        let (start, end) = verifier.aligned_range(10 * CHUNK_LEN as u64 + 1, u64::MAX);
+        assert_eq!(end, data.len() as u64);
+        assert!(verifier.verify_range(start, &data[start as usize..]).is_ok());
+
+        assert_eq!(verifier.verify_range(100, &data[100..CHUNK_LEN]), Err(BaoError::Misaligned));
+    }
+}
@@ -3,11 +3,20 @@
 //! This module implements the core cryptographic primitives used by FortiComm
 //! Black Hole, including post-quantum key encapsulation mechanisms.
 
-use wasm_bindgen::prelude::*;
+pub mod bao;
+pub mod padding;
+pub mod stream;
+
+use alloc::string::String;
+use alloc::{vec, vec::Vec};
+use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::error::{key_array, BlackHoleError};
+use crate::providers::{CryptoRng, OsRandom, RngAdapter};
+
+pub use padding::PaddingScheme;
 
 /// Post-quantum cryptographic keys (ML-KEM-768 + Dilithium)
-#[wasm_bindgen]
 #[derive(Zeroize, ZeroizeOnDrop)]
 pub struct PostQuantumKeys {
     /// ML-KEM-768 public key
@@ -26,8 +35,7 @@ pub struct PostQuantumKeys {
 }
 
 /// Hybrid encryption result (ECC + Post-Quantum)
-#[wasm_bindgen]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HybridCiphertext {
     /// ECC-encrypted component (X25519)
     pub ecc_ciphertext: Vec<u8>,
@@ -43,8 +51,7 @@ pub struct HybridCiphertext {
 }
 
 /// Key encapsulation result
-#[wasm_bindgen]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EncapsulationResult {
     /// The ciphertext to send
     pub ciphertext: Vec<u8>,
@@ -53,60 +60,31 @@ pub struct EncapsulationResult {
     pub shared_secret: Vec<u8>,
 }
 
-#[wasm_bindgen]
 impl PostQuantumKeys {
     /// Generate new post-quantum key pairs
-    #[wasm_bindgen(constructor)]
-    pub fn generate() -> Result<PostQuantumKeys, JsValue> {
-        log::info!("🔐 Generating post-quantum key pairs...");
-        
-        // Generate ML-KEM-768 key pair
-        let (kem_public, kem_secret) = Self::generate_kem_keys()?;
-        
-        // Generate Dilithium key pair
-        let (sig_public, sig_secret) = Self::generate_sig_keys()?;
-        
-        log::info!("✅ Post-quantum keys generated");
-        
-        Ok(PostQuantumKeys {
-            kem_public,
-            kem_secret,
-            sig_public,
-            sig_secret,
-        })
+    pub fn generate() -> Result<PostQuantumKeys, BlackHoleError> {
+        Self::generate_with(&OsRandom)
     }
     
     /// Get the KEM public key as hex string
-    #[wasm_bindgen]
     pub fn get_public_key_hex(&self) -> String {
         hex::encode(&self.kem_public)
     }
     
     /// Get the signature public key as hex string
-    #[wasm_bindgen]
     pub fn get_sig_public_key_hex(&self) -> String {
         hex::encode(&self.sig_public)
     }
     
     /// Encapsulate a shared secret for this key
-    #[wasm_bindgen]
-    pub fn encapsulate(&self) -> Result<EncapsulationResult, JsValue> {
-        // In production, use pqcrypto-kyber
-        // For now, simulate with secure random
-        let shared_secret: [u8; 32] = rand::random();
-        let ciphertext: [u8; 1088] = rand::random(); // ML-KEM-768 ciphertext size
-        
-        Ok(EncapsulationResult {
-            ciphertext: ciphertext.to_vec(),
-            shared_secret: shared_secret.to_vec(),
-        })
+    pub fn encapsulate(&self) -> Result<EncapsulationResult, BlackHoleError> {
+        self.encapsulate_with(&OsRandom)
     }
     
     /// Decapsulate a shared secret
-    #[wasm_bindgen]
-    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
+    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
         if ciphertext.len() != 1088 {
-            return Err(JsValue::from_str("Invalid ciphertext length for ML-KEM-768"));
+            return Err(BlackHoleError::Malformed("ML-KEM-768 ciphertext"));
         }
         
         // In production, use pqcrypto-kyber decapsulation
@@ -119,8 +97,7 @@ impl PostQuantumKeys {
     }
     
     /// Sign a message with Dilithium
-    #[wasm_bindgen]
-    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JsValue> {
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
         // In production, use pqcrypto-dilithium
         // For now, use Blake3 as a placeholder
         let mut hasher = blake3::Hasher::new();
@@ -131,8 +108,7 @@ impl PostQuantumKeys {
     }
     
     /// Verify a signature
-    #[wasm_bindgen]
-    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<bool, JsValue> {
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<bool, BlackHoleError> {
         if signature.len() != 32 {
             return Ok(false);
         }
@@ -146,48 +122,95 @@ impl PostQuantumKeys {
         Ok(expected.as_bytes() == signature)
     }
     
-    fn generate_kem_keys() -> Result<(Vec<u8>, Vec<u8>), JsValue> {
+}
+
+impl PostQuantumKeys {
+    /// Generate new post-quantum key pairs from the given randomness
+    pub fn generate_with(rng: &dyn CryptoRng) -> Result<PostQuantumKeys, BlackHoleError> {
+        log::info!("🔐 Generating post-quantum key pairs...");
+        
+        // Generate ML-KEM-768 key pair
+        let (kem_public, kem_secret) = Self::generate_kem_keys(rng)?;
+        
+        // Generate Dilithium key pair
+        let (sig_public, sig_secret) = Self::generate_sig_keys(rng)?;
+        
+        log::info!("✅ Post-quantum keys generated");
+        
+        Ok(PostQuantumKeys {
+            kem_public,
+            kem_secret,
+            sig_public,
+            sig_secret,
+        })
+    }
+    
+    /// Encapsulate a shared secret for this key from the given randomness
+    pub fn encapsulate_with(&self, rng: &dyn CryptoRng) -> Result<EncapsulationResult, BlackHoleError> {
+        // In production, use pqcrypto-kyber
+        // For now, simulate with secure random
+        let shared_secret: [u8; 32] = rng.random();
+        let mut ciphertext = vec![0u8; 1088]; // ML-KEM-768 ciphertext size
+        rng.fill_bytes(&mut ciphertext);
+        
+        Ok(EncapsulationResult {
+            ciphertext,
+            shared_secret: shared_secret.to_vec(),
+        })
+    }
+    
+    fn generate_kem_keys(rng: &dyn CryptoRng) -> Result<(Vec<u8>, Vec<u8>), BlackHoleError> {
         // ML-KEM-768: public key = 1184 bytes, secret key = 2400 bytes
-        let public: [u8; 1184] = rand::random();
-        let secret: [u8; 2400] = rand::random();
+        let mut public = vec![0u8; 1184];
+        let mut secret = vec![0u8; 2400];
+        rng.fill_bytes(&mut public);
+        rng.fill_bytes(&mut secret);
         
-        Ok((public.to_vec(), secret.to_vec()))
+        Ok((public, secret))
     }
     
-    fn generate_sig_keys() -> Result<(Vec<u8>, Vec<u8>), JsValue> {
+    fn generate_sig_keys(rng: &dyn CryptoRng) -> Result<(Vec<u8>, Vec<u8>), BlackHoleError> {
         // Dilithium3: public key = 1952 bytes, secret key = 4032 bytes
-        let public: [u8; 1952] = rand::random();
-        let secret: [u8; 4032] = rand::random();
+        let mut public = vec![0u8; 1952];
+        let mut secret = vec![0u8; 4032];
+        rng.fill_bytes(&mut public);
+        rng.fill_bytes(&mut secret);
         
-        Ok((public.to_vec(), secret.to_vec()))
+        Ok((public, secret))
     }
 }
 
 /// Hybrid encryption combining X25519 and ML-KEM
-#[wasm_bindgen]
 pub struct HybridEncryption;
 
-#[wasm_bindgen]
 impl HybridEncryption {
     /// Encrypt using hybrid scheme (ECC + PQ)
-    #[wasm_bindgen]
     pub fn encrypt(
         ecc_pubkey: &[u8],
         pq_pubkey: &[u8],
         plaintext: &[u8],
-    ) -> Result<HybridCiphertext, JsValue> {
+    ) -> Result<HybridCiphertext, BlackHoleError> {
+        Self::encrypt_with(ecc_pubkey, pq_pubkey, plaintext, &OsRandom)
+    }
+    
+    /// Encrypt using hybrid scheme (ECC + PQ) from the given randomness
+    pub fn encrypt_with(
+        ecc_pubkey: &[u8],
+        pq_pubkey: &[u8],
+        plaintext: &[u8],
+        rng: &dyn CryptoRng,
+    ) -> Result<HybridCiphertext, BlackHoleError> {
         use x25519_dalek::{PublicKey, EphemeralSecret};
-        use rand::rngs::OsRng;
         
         // Generate ephemeral X25519 key pair
-        let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_secret = EphemeralSecret::random_from_rng(RngAdapter(rng));
         let ephemeral_public = PublicKey::from(&ephemeral_secret);
         
         // Derive shared secret
-        let ecc_shared = Self::derive_ecc_shared(&ephemeral_secret, ecc_pubkey)?;
+        let ecc_shared = Self::derive_ecc_shared(ephemeral_secret, ecc_pubkey)?;
         
         // Encapsulate with ML-KEM
-        let pq_shared = Self::encapsulate_pq(pq_pubkey)?;
+        let pq_shared = Self::encapsulate_pq(pq_pubkey, rng)?;
         
         // Combine shared secrets
         let mut combined = [0u8; 64];
@@ -198,7 +221,7 @@ impl HybridEncryption {
         let key = blake3::hash(&combined);
         
         // Encrypt with AES-256-GCM
-        let (ciphertext, auth_tag) = Self::symmetric_encrypt(key.as_bytes(), plaintext)?;
+        let (ciphertext, auth_tag) = Self::symmetric_encrypt(key.as_bytes(), plaintext, rng)?;
         
         Ok(HybridCiphertext {
             ecc_ciphertext: ciphertext.clone(),
@@ -209,15 +232,10 @@ impl HybridEncryption {
     }
     
     fn derive_ecc_shared(
-        secret: &x25519_dalek::EphemeralSecret,
+        secret: x25519_dalek::EphemeralSecret,
         pubkey: &[u8],
-    ) -> Result<[u8; 32], JsValue> {
-        if pubkey.len() != 32 {
-            return Err(JsValue::from_str("Invalid X25519 public key length"));
-        }
-        
-        let public_key_bytes: [u8; 32] = pubkey.try_into()
-            .map_err(|_| JsValue::from_str("Failed to convert public key"))?;
+    ) -> Result<[u8; 32], BlackHoleError> {
+        let public_key_bytes: [u8; 32] = key_array(pubkey)?;
         
         let public_key = x25519_dalek::PublicKey::from(public_key_bytes);
         let shared_secret = secret.diffie_hellman(&public_key);
@@ -225,13 +243,13 @@ impl HybridEncryption {
         Ok(*shared_secret.as_bytes())
     }
     
-    fn encapsulate_pq(pubkey: &[u8]) -> Result<EncapsulationResult, JsValue> {
+    fn encapsulate_pq(_pubkey: &[u8], rng: &dyn CryptoRng) -> Result<EncapsulationResult, BlackHoleError> {
         // Simulate ML-KEM encapsulation
         let mut ciphertext = vec![0u8; 1088];
         let mut shared_secret = vec![0u8; 32];
         
-        rand::fill(&mut ciphertext[..]);
-        rand::fill(&mut shared_secret[..]);
+        rng.fill_bytes(&mut ciphertext);
+        rng.fill_bytes(&mut shared_secret);
         
         Ok(EncapsulationResult {
             ciphertext,
@@ -239,25 +257,34 @@ impl HybridEncryption {
         })
     }
     
-    fn symmetric_encrypt(key: &[u8], plaintext: &[u8]) -> Result<(Vec<u8>, [u8; 32]), JsValue> {
+    fn symmetric_encrypt(
+        key: &[u8],
+        plaintext: &[u8],
+        rng: &dyn CryptoRng,
+    ) -> Result<(Vec<u8>, [u8; 32]), BlackHoleError> {
         use aes_gcm::{Aes256Gcm, Key, Nonce};
         use aes_gcm::aead::{Aead, KeyInit};
         
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
-        let nonce: [u8; 12] = rand::random();
+        let nonce: [u8; 12] = rng.random();
+        let padded = padding::pad(plaintext, PaddingScheme::default());
         
         let ciphertext = cipher
-            .encrypt(Nonce::from_slice(&nonce), plaintext)
-            .map_err(|e| JsValue::from_str(&format!("Encryption failed: {:?}", e)))?;
+            .encrypt(Nonce::from_slice(&nonce), padded.as_ref())
+            .map_err(|_| BlackHoleError::Encryption)?;
         
-        let mut auth_tag = [0u8; 32];
-        auth_tag.copy_from_slice(&ciphertext[ciphertext.len().saturating_sub(32)..]);
+        // Padded plaintexts can be shorter than the tag, so it is a MAC over
+        // the ciphertext rather than its last bytes
+        let auth_tag = *blake3::keyed_hash(&key_array(key)?, &ciphertext).as_bytes();
         
         Ok((ciphertext, auth_tag))
     }
 }
 
 mod hex {
+    use alloc::format;
+    use alloc::string::String;
+    
     pub fn encode(data: &[u8]) -> String {
         data.iter()
             .map(|b| format!("{:02x}", b))
@@ -289,4 +316,40 @@ mod tests {
         let wrong_message = b"Wrong message";
         assert!(!keys.verify(wrong_message, &signature).unwrap());
     }
+    
+    #[test]
+    fn test_seeded_key_generation_is_reproducible() {
+        use crate::providers::SeededRng;
+        
+        let a = PostQuantumKeys::generate_with(&SeededRng::from_u64(7)).unwrap();
+        let b = PostQuantumKeys::generate_with(&SeededRng::from_u64(7)).unwrap();
+        assert_eq!(a.kem_public, b.kem_public);
+        assert_eq!(a.sig_secret, b.sig_secret);
+        assert_eq!(hex::encode(&a.kem_public[..16]), "19454a27b752f905909507d6160ddc88");
+    }
+    
+    #[test]
+    fn test_seeded_encryption_known_answers() {
+        use crate::providers::SeededRng;
+        
+        let keys = PostQuantumKeys::generate_with(&SeededRng::from_u64(7)).unwrap();
+        let encapsulated = keys.encapsulate_with(&SeededRng::from_u64(8)).unwrap();
+        assert_eq!(
+            hex::encode(&encapsulated.shared_secret),
+            "482a1d2252fa82623d28c810941d127f6a2c069cd25c2dab9f8746ce88f9ade6",
+        );
+        assert_eq!(hex::encode(&encapsulated.ciphertext[..16]), "f77f09e1d0e6465618c5c04c42b52129");
+        
+        let recipient = [9u8; 32];
+        let rng = SeededRng::from_u64(8);
+        let hybrid = HybridEncryption::encrypt_with(&recipient, &keys.kem_public, b"known answer", &rng).unwrap();
+        assert_eq!(
+            hex::encode(&hybrid.ephemeral_pubkey),
+            "e834ef908c4188367ada5d27c43b39b6004191d112882bcb202b2f4933e0fc52",
+        );
+        assert_eq!(
+            hex::encode(&hybrid.ecc_ciphertext),
+            "463589a715b9ebe6eb603d28a3f4d52754a02bf14254636a87db12d60792",
+        );
+    }
 }
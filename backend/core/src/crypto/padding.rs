@@ -0,0 +1,187 @@
+//! 📏 Length-Hiding Padding
+//!
+//! Every plaintext is padded before it reaches an AEAD so that ciphertext
+//! lengths only reveal a coarse size class instead of the exact message size.
+//!
+//! # Encoding
+//!
+//! ```text
+//! padded = plaintext ‖ 0x80 ‖ 0x00 … 0x00
+//! ```
+//!
+//! The `0x80` marker makes removal unambiguous for every scheme: strip the
+//! trailing zeros and the marker. The receiver therefore never needs to know
+//! which scheme the sender used.
+//!
+//! # Schemes
+//!
+//! - **Padmé**: pads a length `L` so that only the top `⌊log₂⌊log₂ L⌋⌋ + 1`
+//!   bits may be set, leaking `O(log log L)` bits with at most ~12% overhead.
+//! - **Power of two**: pads to the next power of two (at least 32 bytes),
+//!   leaking `O(log log L)` bits with up to 100% overhead.
+
+use alloc::vec::Vec;
+
+/// Marker byte separating the plaintext from the padding
+const PADDING_MARKER: u8 = 0x80;
+
+/// Smallest bucket used by the power-of-two scheme
+pub const MIN_BUCKET: usize = 32;
+
+/// Padding scheme applied before encryption
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PaddingScheme {
+    /// Only the marker byte (no length hiding)
+    None = 0,
+
+    /// Padmé padding
+    #[default]
+    Padme = 1,
+
+    /// Bucketed power-of-two padding
+    PowerOfTwo = 2,
+}
+
+impl PaddingScheme {
+    /// Padded length for a plaintext of `len` bytes (including the marker)
+    pub fn padded_len(self, len: usize) -> usize {
+        self.padded_size(len as u64) as usize
+    }
+
+    /// Padded size for a stream of `size` bytes (including the marker)
+    pub fn padded_size(self, size: u64) -> u64 {
+        let unpadded = size + 1;
+
+        match self {
+            PaddingScheme::None => unpadded,
+            PaddingScheme::Padme => padme(unpadded),
+            PaddingScheme::PowerOfTwo => unpadded.max(MIN_BUCKET as u64).next_power_of_two(),
+        }
+    }
+}
+
+/// Pad a plaintext according to `scheme`
+pub fn pad(plaintext: &[u8], scheme: PaddingScheme) -> Vec<u8> {
+    let mut padded = Vec::with_capacity(scheme.padded_len(plaintext.len()));
+    padded.extend_from_slice(plaintext);
+    padded.push(PADDING_MARKER);
+    padded.resize(scheme.padded_len(plaintext.len()), 0);
+    padded
+}
+
+/// Remove padding, returning `None` if the marker is missing
+pub fn unpad(padded: &[u8]) -> Option<&[u8]> {
+    let marker = padded.iter().rposition(|&b| b != 0)?;
+
+    if padded[marker] != PADDING_MARKER {
+        return None;
+    }
+
+    Some(&padded[..marker])
+}
+
+/// Remove padding in place, returning `false` if the marker is missing
+pub fn unpad_in_place(padded: &mut Vec<u8>) -> bool {
+    match unpad(padded) {
+        Some(plaintext) => {
+            let len = plaintext.len();
+            padded.truncate(len);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Padmé: round `len` up so that its low `E - S` bits are zero, where
+/// `E = ⌊log₂ len⌋` and `S = ⌊log₂ E⌋ + 1`
+fn padme(len: u64) -> u64 {
+    if len < 2 {
+        return len;
+    }
+
+    let e = u64::BITS - 1 - len.leading_zeros();
+    let s = u32::BITS - e.leading_zeros();
+    let last_bits = e - s;
+    let mask = (1u64 << last_bits) - 1;
+
+    (len + mask) & !mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn any_scheme() -> impl Strategy<Value = PaddingScheme> {
+        prop_oneof![
+            Just(PaddingScheme::None),
+            Just(PaddingScheme::Padme),
+            Just(PaddingScheme::PowerOfTwo),
+        ]
+    }
+
+    #[test]
+    fn test_power_of_two_boundaries() {
+        let scheme = PaddingScheme::PowerOfTwo;
+
+        assert_eq!(scheme.padded_len(0), 32);
+        assert_eq!(scheme.padded_len(31), 32);
+        assert_eq!(scheme.padded_len(32), 64);
+        assert_eq!(scheme.padded_len(63), 64);
+        assert_eq!(scheme.padded_len(64), 128);
+    }
+
+    #[test]
+    fn test_padme_boundaries() {
+        // E = 9, S = 4: multiples of 32 between 512 and 1023
+        assert_eq!(padme(512), 512);
+        assert_eq!(padme(513), 544);
+        assert_eq!(padme(544), 544);
+        assert_eq!(padme(1000), 1024);
+        assert_eq!(PaddingScheme::Padme.padded_len(999), 1024);
+    }
+
+    #[test]
+    fn test_unpad_rejects_missing_marker() {
+        assert!(unpad(&[]).is_none());
+        assert!(unpad(&[0, 0, 0]).is_none());
+        assert!(unpad(&[1, 2, 3, 0]).is_none());
+        assert_eq!(unpad(&[1, 2, PADDING_MARKER, 0]), Some(&[1u8, 2][..]));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_pad_roundtrip(
+            data in proptest::collection::vec(any::<u8>(), 0..4096),
+            scheme in any_scheme(),
+        ) {
+            let padded = pad(&data, scheme);
+            prop_assert_eq!(padded.len(), scheme.padded_len(data.len()));
+            prop_assert_eq!(unpad(&padded), Some(&data[..]));
+        }
+
+        #[test]
+        fn prop_power_of_two_buckets(len in 0usize..1 << 20) {
+            let padded = PaddingScheme::PowerOfTwo.padded_len(len);
+
+            prop_assert!(padded.is_power_of_two());
+            prop_assert!(padded > len);
+            prop_assert!(padded == MIN_BUCKET || padded / 2 <= len);
+        }
+
+        #[test]
+        fn prop_padme_buckets(len in 0usize..1 << 20) {
+            let unpadded = len + 1;
+            let padded = PaddingScheme::Padme.padded_len(len);
+
+            prop_assert!(padded >= unpadded);
+            prop_assert!((padded - unpadded) * 100 <= unpadded * 12 + 100);
+
+            // Monotonic, so equal-sized plaintexts share a bucket
+            prop_assert!(PaddingScheme::Padme.padded_len(len + 1) >= padded);
+
+            // Idempotent: a bucket size is its own bucket
+            prop_assert_eq!(padme(padded as u64), padded as u64);
+        }
+    }
+}
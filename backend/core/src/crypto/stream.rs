@@ -0,0 +1,386 @@
+//! 🌊 Streaming Chunked Encryption
+//!
+//! Encrypts arbitrarily large payloads (attachments, videos) without holding
+//! them in memory, using the STREAM construction of Hoang, Reyhanitabar,
+//! Rogaway and Vizár ("Online Authenticated-Encryption and its Nonce-Reuse
+//! Misuse-Resistance", CRYPTO 2015).
+//!
+//! # Format
+//!
+//! ```text
+//! stream = header ‖ chunk₀ ‖ chunk₁ ‖ … ‖ chunkₙ
+//! header = version (1) ‖ salt (16)
+//! chunkᵢ = AES-256-GCM(k_stream, nonceᵢ, plaintextᵢ, aad = header)
+//! nonceᵢ = last_flag (1) ‖ 0x000000 ‖ i (u64, big-endian)
+//! ```
+//!
+//! `k_stream` is derived from the caller's key and the random salt, so a key
+//! may safely be reused for several streams. Every chunk but the last holds
+//! exactly `CHUNK_SIZE` plaintext bytes.
+//!
+//! ## Security Properties
+//!
+//! - **Reordering**: chunk indices are bound into the nonce
+//! - **Truncation**: only the final chunk is sealed with `last_flag = 1`,
+//!   so a stream cut at any chunk boundary fails to finalize
+//! - **Extension**: nothing is accepted after the final chunk
+
+use alloc::vec::Vec;
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use zeroize::Zeroize;
+use crate::providers::CryptoRng;
+
+/// Plaintext bytes per chunk
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// AES-GCM authentication tag length
+pub const TAG_LEN: usize = 16;
+
+/// Ciphertext bytes per full chunk
+pub const SEALED_CHUNK_SIZE: usize = CHUNK_SIZE + TAG_LEN;
+
+/// Stream header length
+pub const HEADER_LEN: usize = 17;
+
+/// Stream format version
+const STREAM_VERSION: u8 = 1;
+
+/// Domain separation context for the per-stream key
+const STREAM_KDF_CONTEXT: &str = "forticomm-blackhole 2024 stream-key v1";
+
+/// Errors raised by streaming encryption
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StreamError {
+    /// The header is malformed or has an unknown version
+    #[error("Invalid stream header")]
+    InvalidHeader,
+
+    /// A chunk failed authentication (tampered, reordered or misplaced)
+    #[error("Stream chunk failed authentication")]
+    Authentication,
+
+    /// The stream ended without a final chunk
+    #[error("Stream truncated")]
+    Truncated,
+
+    /// Data was supplied after the stream was finalized
+    #[error("Stream already finalized")]
+    Finished,
+
+    /// The chunk counter is exhausted
+    #[error("Stream too long")]
+    TooLong,
+}
+
+/// Incremental STREAM encryptor
+pub struct StreamEncryptor {
+    cipher: Aes256Gcm,
+    header: [u8; HEADER_LEN],
+    counter: u64,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+/// Incremental STREAM decryptor
+pub struct StreamDecryptor {
+    cipher: Aes256Gcm,
+    header: [u8; HEADER_LEN],
+    counter: u64,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl StreamEncryptor {
+    /// Start a new stream under `key` with a salt drawn from `rng`
+    pub fn new(key: &[u8; 32], rng: &dyn CryptoRng) -> Self {
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = STREAM_VERSION;
+        header[1..].copy_from_slice(&rng.random::<16>());
+
+        StreamEncryptor {
+            cipher: stream_cipher(key, &header),
+            header,
+            counter: 0,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            finished: false,
+        }
+    }
+
+    /// The stream header, to be sent before the first chunk
+    pub fn header(&self) -> [u8; HEADER_LEN] {
+        self.header
+    }
+
+    /// Feed plaintext, returning any chunks that are now complete
+    ///
+    /// Up to `CHUNK_SIZE` bytes are held back so the final chunk is only
+    /// sealed once the caller signals the end of the stream.
+    pub fn update(&mut self, data: &[u8]) -> Result<Vec<u8>, StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+
+        self.buffer.extend_from_slice(data);
+
+        let full_chunks = self.buffer.len().saturating_sub(1) / CHUNK_SIZE;
+        let mut output = Vec::with_capacity(full_chunks * SEALED_CHUNK_SIZE);
+
+        for i in 0..full_chunks {
+            let start = i * CHUNK_SIZE;
+            let sealed = self.seal_chunk(start, CHUNK_SIZE, false)?;
+            output.extend_from_slice(&sealed);
+        }
+
+        let consumed = full_chunks * CHUNK_SIZE;
+        self.buffer[..consumed].zeroize();
+        self.buffer.drain(..consumed);
+
+        Ok(output)
+    }
+
+    /// Seal the remaining plaintext as the final chunk
+    pub fn finalize(&mut self) -> Result<Vec<u8>, StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+
+        let sealed = self.seal_chunk(0, self.buffer.len(), true)?;
+        self.buffer.zeroize();
+        self.buffer.clear();
+        self.finished = true;
+
+        Ok(sealed)
+    }
+
+    fn seal_chunk(&mut self, start: usize, len: usize, last: bool) -> Result<Vec<u8>, StreamError> {
+        let nonce = chunk_nonce(self.counter, last);
+        self.counter = self.counter.checked_add(1).ok_or(StreamError::TooLong)?;
+
+        self.cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload { msg: &self.buffer[start..start + len], aad: &self.header },
+            )
+            .map_err(|_| StreamError::Authentication)
+    }
+}
+
+impl StreamDecryptor {
+    /// Resume a stream under `key` from its header
+    pub fn new(key: &[u8; 32], header: &[u8]) -> Result<Self, StreamError> {
+        let header: [u8; HEADER_LEN] = header.try_into().map_err(|_| StreamError::InvalidHeader)?;
+
+        if header[0] != STREAM_VERSION {
+            return Err(StreamError::InvalidHeader);
+        }
+
+        Ok(StreamDecryptor {
+            cipher: stream_cipher(key, &header),
+            header,
+            counter: 0,
+            buffer: Vec::with_capacity(SEALED_CHUNK_SIZE),
+            finished: false,
+        })
+    }
+
+    /// Feed ciphertext, returning the plaintext of every complete chunk
+    pub fn update(&mut self, data: &[u8]) -> Result<Vec<u8>, StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+
+        self.buffer.extend_from_slice(data);
+
+        let full_chunks = self.buffer.len().saturating_sub(1) / SEALED_CHUNK_SIZE;
+        let mut output = Vec::with_capacity(full_chunks * CHUNK_SIZE);
+
+        for i in 0..full_chunks {
+            let start = i * SEALED_CHUNK_SIZE;
+            let plaintext = self.open_chunk(start, SEALED_CHUNK_SIZE, false)?;
+            output.extend_from_slice(&plaintext);
+        }
+
+        self.buffer.drain(..full_chunks * SEALED_CHUNK_SIZE);
+
+        Ok(output)
+    }
+
+    /// Open the final chunk, failing if the stream was truncated
+    pub fn finalize(&mut self) -> Result<Vec<u8>, StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+
+        if self.buffer.len() < TAG_LEN {
+            return Err(StreamError::Truncated);
+        }
+
+        // A full-size remainder may be a non-final chunk of a truncated stream
+        let plaintext = self.open_chunk(0, self.buffer.len(), true).map_err(|err| {
+            if self.buffer.len() == SEALED_CHUNK_SIZE {
+                StreamError::Truncated
+            } else {
+                err
+            }
+        })?;
+
+        self.buffer.clear();
+        self.finished = true;
+
+        Ok(plaintext)
+    }
+
+    fn open_chunk(&mut self, start: usize, len: usize, last: bool) -> Result<Vec<u8>, StreamError> {
+        let nonce = chunk_nonce(self.counter, last);
+
+        let plaintext = self.cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload { msg: &self.buffer[start..start + len], aad: &self.header },
+            )
+            .map_err(|_| StreamError::Authentication)?;
+
+        self.counter = self.counter.checked_add(1).ok_or(StreamError::TooLong)?;
+        Ok(plaintext)
+    }
+}
+
+impl Drop for StreamEncryptor {
+    fn drop(&mut self) {
+        self.buffer.zeroize();
+    }
+}
+
+/// Encrypt a whole payload as a single stream (`header ‖ chunks`)
+pub fn encrypt_all(key: &[u8; 32], plaintext: &[u8], rng: &dyn CryptoRng) -> Result<Vec<u8>, StreamError> {
+    let mut encryptor = StreamEncryptor::new(key, rng);
+
+    let mut output = encryptor.header().to_vec();
+    output.extend_from_slice(&encryptor.update(plaintext)?);
+    output.extend_from_slice(&encryptor.finalize()?);
+
+    Ok(output)
+}
+
+/// Decrypt a payload produced by [`encrypt_all`]
+pub fn decrypt_all(key: &[u8; 32], stream: &[u8]) -> Result<Vec<u8>, StreamError> {
+    if stream.len() < HEADER_LEN {
+        return Err(StreamError::InvalidHeader);
+    }
+
+    let (header, body) = stream.split_at(HEADER_LEN);
+    let mut decryptor = StreamDecryptor::new(key, header)?;
+
+    let mut output = decryptor.update(body)?;
+    output.extend_from_slice(&decryptor.finalize()?);
+
+    Ok(output)
+}
+
+/// Length of the stream produced for `plaintext_len` bytes
+pub fn sealed_len(plaintext_len: u64) -> u64 {
+    let chunks = plaintext_len.saturating_sub(1) / CHUNK_SIZE as u64 + 1;
+    HEADER_LEN as u64 + plaintext_len + chunks * TAG_LEN as u64
+}
+
+/// Derive the per-stream cipher from the caller's key and the header salt
+fn stream_cipher(key: &[u8; 32], header: &[u8; HEADER_LEN]) -> Aes256Gcm {
+    let mut hasher = blake3::Hasher::new_derive_key(STREAM_KDF_CONTEXT);
+    hasher.update(key);
+    hasher.update(header);
+
+    let mut stream_key = *hasher.finalize().as_bytes();
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&stream_key));
+    stream_key.zeroize();
+
+    cipher
+}
+
+/// Nonce for chunk `counter`
+fn chunk_nonce(counter: u64, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[0] = last as u8;
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::OsRandom;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_stream_roundtrip_across_chunk_boundaries() {
+        let key = [9u8; 32];
+
+        for len in [0, 1, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE + 1, 3 * CHUNK_SIZE + 17] {
+            let data = payload(len);
+            let stream = encrypt_all(&key, &data, &OsRandom).unwrap();
+
+            assert_eq!(stream.len() as u64, sealed_len(len as u64));
+            assert_eq!(decrypt_all(&key, &stream).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_stream_incremental_feeding() {
+        let key = [9u8; 32];
+        let data = payload(2 * CHUNK_SIZE + 100);
+
+        let mut encryptor = StreamEncryptor::new(&key, &OsRandom);
+        let mut stream = Vec::new();
+        for piece in data.chunks(1000) {
+            stream.extend_from_slice(&encryptor.update(piece).unwrap());
+        }
+        stream.extend_from_slice(&encryptor.finalize().unwrap());
+
+        let mut decryptor = StreamDecryptor::new(&key, &encryptor.header()).unwrap();
+        let mut output = Vec::new();
+        for piece in stream.chunks(777) {
+            output.extend_from_slice(&decryptor.update(piece).unwrap());
+        }
+        output.extend_from_slice(&decryptor.finalize().unwrap());
+
+        assert_eq!(output, data);
+        assert_eq!(encryptor.update(b"more"), Err(StreamError::Finished));
+    }
+
+    #[test]
+    fn test_stream_detects_truncation() {
+        let key = [9u8; 32];
+        let stream = encrypt_all(&key, &payload(3 * CHUNK_SIZE + 5), &OsRandom).unwrap();
+
+        // Drop the final chunk: the stream now ends on a non-final chunk
+        let truncated = &stream[..HEADER_LEN + 3 * SEALED_CHUNK_SIZE];
+        assert_eq!(decrypt_all(&key, truncated), Err(StreamError::Truncated));
+
+        // Cut inside a chunk
+        let cut = &stream[..HEADER_LEN + SEALED_CHUNK_SIZE + 10];
+        assert!(decrypt_all(&key, cut).is_err());
+    }
+
+    #[test]
+    fn test_stream_detects_reordering_and_tampering() {
+        let key = [9u8; 32];
+        let stream = encrypt_all(&key, &payload(3 * CHUNK_SIZE + 5), &OsRandom).unwrap();
+
+        let first = HEADER_LEN..HEADER_LEN + SEALED_CHUNK_SIZE;
+        let second = HEADER_LEN + SEALED_CHUNK_SIZE..HEADER_LEN + 2 * SEALED_CHUNK_SIZE;
+
+        let mut reordered = stream.clone();
+        reordered[first.clone()].copy_from_slice(&stream[second.clone()]);
+        reordered[second].copy_from_slice(&stream[first]);
+        assert_eq!(decrypt_all(&key, &reordered), Err(StreamError::Authentication));
+
+        let mut tampered = stream.clone();
+        tampered[HEADER_LEN + 5] ^= 1;
+        assert_eq!(decrypt_all(&key, &tampered), Err(StreamError::Authentication));
+
+        assert!(decrypt_all(&[8u8; 32], &stream).is_err());
+    }
+}
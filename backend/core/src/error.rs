@@ -0,0 +1,213 @@
+//! ⚠️ Error Types
+//!
+//! `BlackHoleError` is the single error type returned across the core, so it
+//! can be used as an ordinary Rust library. Bindings translate it at the edge:
+//! the `wasm` module turns it into a `JsValue` carrying the message, the
+//! FFI reports the stable integer from [`BlackHoleError::code`], and UniFFI
+//! raises it in Swift and Kotlin as an error carrying the message.
+//!
+//! Module-specific errors (streams, attachments, verified downloads, message
+//! content, setup ceremonies) are wrapped transparently, so `?` works on all of them.
+
+use alloc::string::{String, ToString};
+use crate::crypto::bao::BaoError;
+use crate::crypto::stream::StreamError;
+use crate::protocol::attachment::AttachmentError;
+use crate::protocol::content::ContentError;
+use crate::zk::ceremony::CeremonyError;
+
+/// Errors raised by the Black Hole core
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
+pub enum BlackHoleError {
+    /// Encryption failed
+    #[error("Encryption failed")]
+    Encryption,
+
+    /// A ciphertext failed authentication or could not be decrypted
+    #[error("Decryption failed")]
+    Decryption,
+
+    /// A key has the wrong length
+    #[error("Invalid key length: expected {expected} bytes, got {actual}")]
+    InvalidKeyLength {
+        /// Required length in bytes
+        expected: usize,
+        /// Supplied length in bytes
+        actual: usize,
+    },
+
+    /// A key has the right length but is not a valid key
+    #[error("Invalid key")]
+    InvalidKey,
+
+    /// A message or extension was replayed or rolled back
+    #[error("Replayed or rolled-back message")]
+    Replay,
+
+    /// A group message or extension belongs to another epoch
+    #[error("Stale epoch {received} (current epoch is {current})")]
+    StaleEpoch {
+        /// Our current epoch
+        current: u64,
+        /// Epoch carried by the message
+        received: u64,
+    },
+
+    /// A message, envelope or proof is truncated or structurally invalid
+    #[error("Malformed {0}")]
+    Malformed(&'static str),
+
+    /// Decrypted plaintext carries invalid padding
+    #[error("Invalid message padding")]
+    InvalidPadding,
+
+    /// A sender certificate is invalid, expired or for someone else
+    #[error("Invalid or expired sender certificate")]
+    InvalidCertificate,
+
+    /// No ratchet session exists with the peer
+    #[error("No session with peer")]
+    NoSession,
+
+    /// No group with the given ID is held
+    #[error("Unknown group")]
+    UnknownGroup,
+
+    /// The operation is not possible in the current state
+    #[error("{0}")]
+    InvalidState(&'static str),
+
+    /// A value could not be serialized or deserialized
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    /// Streaming encryption failed
+    #[error(transparent)]
+    Stream(#[from] StreamError),
+
+    /// Attachment encryption or download failed
+    #[error(transparent)]
+    Attachment(#[from] AttachmentError),
+
+    /// Verified streaming failed
+    #[error(transparent)]
+    Verification(#[from] BaoError),
+
+    /// Message content could not be decoded
+    #[error(transparent)]
+    Content(#[from] ContentError),
+
+    /// A zero-knowledge proof or its keys could not be generated
+    #[error("Proof generation failed")]
+    Proof,
+
+    /// A proof or verifying key comes from setup parameters that are not trusted
+    #[error("Untrusted proof parameters")]
+    UntrustedParameters,
+
+    /// A setup ceremony contribution or transcript is invalid
+    #[error(transparent)]
+    Ceremony(#[from] CeremonyError),
+
+    /// An anonymous signal reuses a nullifier that was already seen
+    #[error("Nullifier already used")]
+    DuplicateNullifier,
+
+    /// An anonymous credential request or issuance does not verify
+    #[error("Invalid credential")]
+    InvalidCredential,
+}
+
+impl BlackHoleError {
+    /// Stable numeric code for foreign-function callers
+    ///
+    /// Codes are never reused; `0` is reserved for success.
+    pub fn code(&self) -> i32 {
+        match self {
+            BlackHoleError::Encryption => 1,
+            BlackHoleError::Decryption => 2,
+            BlackHoleError::InvalidKeyLength { .. } => 3,
+            BlackHoleError::InvalidKey => 4,
+            BlackHoleError::Replay => 5,
+            BlackHoleError::StaleEpoch { .. } => 6,
+            BlackHoleError::Malformed(_) => 7,
+            BlackHoleError::InvalidPadding => 8,
+            BlackHoleError::InvalidCertificate => 9,
+            BlackHoleError::NoSession => 10,
+            BlackHoleError::UnknownGroup => 11,
+            BlackHoleError::InvalidState(_) => 12,
+            BlackHoleError::Serialization(_) => 13,
+            BlackHoleError::Stream(_) => 14,
+            BlackHoleError::Attachment(_) => 15,
+            BlackHoleError::Verification(_) => 16,
+            BlackHoleError::Content(_) => 17,
+            BlackHoleError::Proof => 18,
+            BlackHoleError::UntrustedParameters => 19,
+            BlackHoleError::Ceremony(_) => 20,
+            BlackHoleError::DuplicateNullifier => 21,
+            BlackHoleError::InvalidCredential => 22,
+        }
+    }
+
+    /// Serialization error from any serde backend
+    pub(crate) fn serialization(err: impl core::fmt::Display) -> Self {
+        BlackHoleError::Serialization(err.to_string())
+    }
+}
+
+/// Check that `key` has exactly `N` bytes and copy it out
+pub(crate) fn key_array<const N: usize>(key: &[u8]) -> Result<[u8; N], BlackHoleError> {
+    key.try_into().map_err(|_| BlackHoleError::InvalidKeyLength {
+        expected: N,
+        actual: key.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_distinct() {
+        let errors = [
+            BlackHoleError::Encryption,
+            BlackHoleError::Decryption,
+            BlackHoleError::InvalidKeyLength { expected: 32, actual: 31 },
+            BlackHoleError::InvalidKey,
+            BlackHoleError::Replay,
+            BlackHoleError::StaleEpoch { current: 2, received: 1 },
+            BlackHoleError::Malformed("envelope"),
+            BlackHoleError::InvalidPadding,
+            BlackHoleError::InvalidCertificate,
+            BlackHoleError::NoSession,
+            BlackHoleError::UnknownGroup,
+            BlackHoleError::InvalidState("state"),
+            BlackHoleError::Serialization(String::new()),
+            StreamError::Truncated.into(),
+            AttachmentError::DigestMismatch.into(),
+            BaoError::Misaligned.into(),
+            ContentError::Malformed.into(),
+            BlackHoleError::Proof,
+            BlackHoleError::UntrustedParameters,
+            CeremonyError::Malformed.into(),
+            BlackHoleError::DuplicateNullifier,
+            BlackHoleError::InvalidCredential,
+        ];
+
+        let mut codes: Vec<i32> = errors.iter().map(BlackHoleError::code).collect();
+        assert!(codes.iter().all(|&code| code > 0));
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+    }
+
+    #[test]
+    fn test_key_length_is_checked() {
+        assert_eq!(key_array::<4>(&[1, 2, 3, 4]).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(
+            key_array::<32>(&[0u8; 31]),
+            Err(BlackHoleError::InvalidKeyLength { expected: 32, actual: 31 }),
+        );
+    }
+}
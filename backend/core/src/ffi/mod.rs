@@ -0,0 +1,1212 @@
+//! 🧩 C Foreign Function Interface
+//!
+//! The C ABI used by the iOS and Android apps, compiled with the `ffi`
+//! feature. `BlackHoleCore`, Double Ratchet sessions, MLS groups and
+//! encrypted attachments are exposed as opaque handles that are created and freed explicitly:
+//!
+//! ```c
+//! VaultError err = {0};
+//! VaultCore *core = vault_core_new(parameters, parameters_len, &err);
+//! ...
+//! vault_core_free(core);
+//! ```
+//!
+//! # Conventions
+//!
+//! - Every fallible function returns `0` on success or an error code, and
+//!   fills the optional `VaultError` with the same code and a message. The
+//!   message is owned by the caller and released with `vault_error_free`;
+//!   passing the same `VaultError` again frees the previous message.
+//!   Codes below 100 are [`BlackHoleError::code`], the rest are the
+//!   `VAULT_ERROR_*` constants of this module.
+//! - Outputs are written to caller-allocated buffers. `*out_len` holds the
+//!   capacity of `out` on entry and the length of the output on return.
+//!   When `out` is null or too small the call still takes effect (a ratchet
+//!   step cannot be undone), fails with `VAULT_ERROR_BUFFER_TOO_SMALL`, and
+//!   the output is kept for `vault_take_output` on the same thread.
+//! - Byte inputs are a pointer and a length (the pointer may be null when
+//!   the length is 0); text inputs are NUL-terminated UTF-8.
+//! - Envelopes, encrypted messages and proofs are exchanged as JSON, like
+//!   the other wire formats of the core.
+//! - A `VaultCore` is a [`SharedCore`] and may be used from several threads
+//!   at once. Session, group and attachment handles may not: use each from
+//!   one thread at a time.
+
+#![allow(unsafe_code)]
+
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ffi::{c_char, CStr};
+use core::ptr;
+use std::panic::{self, AssertUnwindSafe};
+use zeroize::Zeroizing;
+
+use crate::protocol::{DoubleRatchet, EncryptedAttachment, MLSGroup, MessageEnvelope};
+use crate::providers::OsRandom;
+use crate::zk::sigma::{IdentityKeyProof, ProofTranscript, SigmaProof, Statement, Witness};
+use crate::zk::setup::ZKParameters;
+use crate::zk::ZKProof;
+use crate::{BlackHoleError, EncryptedMessage, SharedCore};
+
+/// A required pointer argument was null
+pub const VAULT_ERROR_NULL_POINTER: i32 = 100;
+
+/// A text argument or result is not valid UTF-8
+pub const VAULT_ERROR_INVALID_UTF8: i32 = 101;
+
+/// The output buffer is null or too small; `*out_len` holds the size needed
+pub const VAULT_ERROR_BUFFER_TOO_SMALL: i32 = 102;
+
+/// The core panicked; the handle should not be used again
+pub const VAULT_ERROR_PANIC: i32 = 103;
+
+/// Error details filled in by a failed call
+#[repr(C)]
+#[derive(Debug)]
+pub struct VaultError {
+    /// `0` on success, otherwise the code returned by the call
+    pub code: i32,
+
+    /// NUL-terminated description, or null; free with `vault_error_free`
+    pub message: *mut c_char,
+}
+
+/// Opaque handle to a `BlackHoleCore`, safe to share between threads
+pub struct VaultCore {
+    inner: SharedCore,
+}
+
+/// Opaque handle to a Double Ratchet session; not thread-safe
+pub struct VaultSession {
+    inner: DoubleRatchet,
+}
+
+/// Opaque handle to an MLS group; not thread-safe
+pub struct VaultGroup {
+    inner: MLSGroup,
+}
+
+/// Opaque handle to an encrypted attachment: its pointer, blob and outboard;
+/// not thread-safe
+pub struct VaultAttachment {
+    inner: EncryptedAttachment,
+}
+
+thread_local! {
+    /// Output of the last call on this thread whose buffer was too small
+    static PARKED_OUTPUT: RefCell<Option<Zeroizing<Vec<u8>>>> = const { RefCell::new(None) };
+}
+
+/// Why an FFI call failed
+#[derive(Debug)]
+enum CallError {
+    Core(BlackHoleError),
+    NullPointer(&'static str),
+    InvalidUtf8(&'static str),
+    BufferTooSmall { required: usize },
+    Panic,
+}
+
+impl From<BlackHoleError> for CallError {
+    fn from(err: BlackHoleError) -> Self {
+        CallError::Core(err)
+    }
+}
+
+impl CallError {
+    fn code(&self) -> i32 {
+        match self {
+            CallError::Core(err) => err.code(),
+            CallError::NullPointer(_) => VAULT_ERROR_NULL_POINTER,
+            CallError::InvalidUtf8(_) => VAULT_ERROR_INVALID_UTF8,
+            CallError::BufferTooSmall { .. } => VAULT_ERROR_BUFFER_TOO_SMALL,
+            CallError::Panic => VAULT_ERROR_PANIC,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            CallError::Core(err) => err.to_string(),
+            CallError::NullPointer(name) => alloc::format!("`{name}` is null"),
+            CallError::InvalidUtf8(name) => alloc::format!("`{name}` is not valid UTF-8"),
+            CallError::BufferTooSmall { required } => {
+                alloc::format!("Output buffer too small: {required} bytes required")
+            }
+            CallError::Panic => "Internal error".to_string(),
+        }
+    }
+}
+
+/// Run `body`, report its outcome in `err` and return the status code
+fn call(err: *mut VaultError, body: impl FnOnce() -> Result<(), CallError>) -> i32 {
+    let result = panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(Err(CallError::Panic));
+    let code = result.as_ref().err().map_or(0, CallError::code);
+
+    // SAFETY: `err` is null or points to a `VaultError` owned by the caller
+    if let Some(err) = unsafe { err.as_mut() } {
+        clear_error(err);
+        if let Err(failure) = result {
+            err.code = code;
+            err.message = CString::new(failure.message())
+                .unwrap_or_default()
+                .into_raw();
+        }
+    }
+
+    code
+}
+
+/// Like [`call`], for functions returning a new handle (null on failure)
+fn call_new<T>(err: *mut VaultError, body: impl FnOnce() -> Result<T, CallError>) -> *mut T {
+    let mut handle = ptr::null_mut();
+    call(err, || {
+        handle = Box::into_raw(Box::new(body()?));
+        Ok(())
+    });
+    handle
+}
+
+fn clear_error(err: &mut VaultError) {
+    if !err.message.is_null() {
+        // SAFETY: non-null messages are only ever set by `call`
+        drop(unsafe { CString::from_raw(err.message) });
+    }
+    err.code = 0;
+    err.message = ptr::null_mut();
+}
+
+/// Copy `data` into the caller's buffer, parking it if the buffer is too small
+unsafe fn write_output(data: Vec<u8>, out: *mut u8, out_len: *mut usize) -> Result<(), CallError> {
+    let data = Zeroizing::new(data);
+    let out_len = out_len.as_mut().ok_or(CallError::NullPointer("out_len"))?;
+    let capacity = *out_len;
+    *out_len = data.len();
+
+    if out.is_null() || capacity < data.len() {
+        let required = data.len();
+        PARKED_OUTPUT.with(|parked| *parked.borrow_mut() = Some(data));
+        return Err(CallError::BufferTooSmall { required });
+    }
+
+    ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    Ok(())
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize, name: &'static str) -> Result<&'a [u8], CallError> {
+    if len == 0 {
+        Ok(&[])
+    } else if data.is_null() {
+        Err(CallError::NullPointer(name))
+    } else {
+        Ok(core::slice::from_raw_parts(data, len))
+    }
+}
+
+unsafe fn text<'a>(data: *const c_char, name: &'static str) -> Result<&'a str, CallError> {
+    if data.is_null() {
+        return Err(CallError::NullPointer(name));
+    }
+
+    CStr::from_ptr(data).to_str().map_err(|_| CallError::InvalidUtf8(name))
+}
+
+unsafe fn handle<'a, T>(handle: *const T, name: &'static str) -> Result<&'a T, CallError> {
+    handle.as_ref().ok_or(CallError::NullPointer(name))
+}
+
+unsafe fn handle_mut<'a, T>(handle: *mut T, name: &'static str) -> Result<&'a mut T, CallError> {
+    handle.as_mut().ok_or(CallError::NullPointer(name))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, CallError> {
+    Ok(serde_json::to_vec(value).map_err(BlackHoleError::serialization)?)
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, CallError> {
+    Ok(serde_json::from_slice(bytes).map_err(BlackHoleError::serialization)?)
+}
+
+/// Release the message held by `err` and reset its code
+///
+/// # Safety
+///
+/// `err` must be null or point to a `VaultError` filled by this library.
+#[no_mangle]
+pub unsafe extern "C" fn vault_error_free(err: *mut VaultError) {
+    if let Some(err) = err.as_mut() {
+        clear_error(err);
+    }
+}
+
+/// Collect the output of the last call on this thread that failed with
+/// `VAULT_ERROR_BUFFER_TOO_SMALL`
+///
+/// # Safety
+///
+/// `out` must be null or valid for `*out_len` bytes; `out_len` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn vault_take_output(
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let parked = PARKED_OUTPUT
+            .with(|parked| parked.borrow_mut().take())
+            .ok_or(BlackHoleError::InvalidState("No output is waiting"))?;
+
+        write_output(parked.to_vec(), out, out_len)
+    })
+}
+
+/// Library version as a NUL-terminated string
+#[no_mangle]
+pub extern "C" fn vault_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Create a core with a fresh identity; null on failure
+///
+/// `parameters` are the encoded identity circuit parameters from the
+/// phase-2 ceremony, as shipped with the app.
+///
+/// # Safety
+///
+/// `parameters` must be valid for `parameters_len` bytes and `err` null or
+/// point to a `VaultError`.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_new(
+    parameters: *const u8,
+    parameters_len: usize,
+    err: *mut VaultError,
+) -> *mut VaultCore {
+    call_new(err, || {
+        let parameters = ZKParameters::from_bytes(input(parameters, parameters_len, "parameters")?)?;
+        Ok(VaultCore { inner: SharedCore::new(&parameters)? })
+    })
+}
+
+/// Destroy a core, wiping its keys; null is ignored
+///
+/// # Safety
+///
+/// `core` must be null or a handle from `vault_core_new` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_free(core: *mut VaultCore) {
+    if !core.is_null() {
+        drop(Box::from_raw(core));
+    }
+}
+
+/// Write the identity key fingerprint (hex, not NUL-terminated)
+///
+/// # Safety
+///
+/// `core` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_fingerprint(
+    core: *const VaultCore,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        write_output(core.inner.get_fingerprint().into_bytes(), out, out_len)
+    })
+}
+
+/// Write the 32-byte Ed25519 identity public key
+///
+/// # Safety
+///
+/// `core` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_identity_public_key(
+    core: *const VaultCore,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        write_output(core.inner.get_identity_public_key(), out, out_len)
+    })
+}
+
+/// Write the 1184-byte ML-KEM-768 public key
+///
+/// # Safety
+///
+/// `core` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_pq_public_key(
+    core: *const VaultCore,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        write_output(core.inner.core().pq_keys.kem_public.clone(), out, out_len)
+    })
+}
+
+/// Encrypt UTF-8 text, writing the encrypted message as JSON
+///
+/// # Safety
+///
+/// `core` must be a live handle, `recipient` a NUL-terminated string and
+/// `plaintext` valid for `plaintext_len` bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_encrypt(
+    core: *const VaultCore,
+    recipient: *const c_char,
+    plaintext: *const u8,
+    plaintext_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        let recipient = text(recipient, "recipient")?;
+        let plaintext = core::str::from_utf8(input(plaintext, plaintext_len, "plaintext")?)
+            .map_err(|_| CallError::InvalidUtf8("plaintext"))?;
+
+        let encrypted = core.inner.encrypt(recipient, plaintext)?;
+        write_output(to_json(&encrypted)?, out, out_len)
+    })
+}
+
+/// Decrypt a JSON encrypted message, writing the UTF-8 text
+///
+/// # Safety
+///
+/// `core` must be a live handle and `message` valid for `message_len`
+/// bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_decrypt(
+    core: *const VaultCore,
+    message: *const u8,
+    message_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        let message: EncryptedMessage = from_json(input(message, message_len, "message")?)?;
+
+        let plaintext = core.inner.decrypt(&message)?;
+        write_output(plaintext.into_bytes(), out, out_len)
+    })
+}
+
+/// Write a zero-knowledge proof of identity as JSON
+///
+/// # Safety
+///
+/// `core` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_prove_identity(
+    core: *const VaultCore,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        write_output(to_json(&core.inner.prove_identity()?)?, out, out_len)
+    })
+}
+
+/// Verify a JSON zero-knowledge proof of identity
+///
+/// # Safety
+///
+/// `core` must be a live handle, `proof` valid for `proof_len` bytes and
+/// `valid_out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_verify_identity(
+    core: *const VaultCore,
+    proof: *const u8,
+    proof_len: usize,
+    valid_out: *mut bool,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        let valid_out = handle_mut(valid_out, "valid_out")?;
+        let proof: ZKProof = from_json(input(proof, proof_len, "proof")?)?;
+
+        *valid_out = core.inner.verify_identity(&proof)?;
+        Ok(())
+    })
+}
+
+/// Write a 64-byte proof of possession of the identity key, bound to `context`
+///
+/// # Safety
+///
+/// `core` must be a live handle and `context` valid for `context_len`
+/// bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_prove_identity_key(
+    core: *const VaultCore,
+    context: *const u8,
+    context_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        let context = input(context, context_len, "context")?;
+
+        write_output(core.inner.core().prove_identity_key(context).to_bytes().to_vec(), out, out_len)
+    })
+}
+
+/// Verify a proof of possession of the Ed25519 key `public_key`
+///
+/// # Safety
+///
+/// Each input must be valid for its length and `valid_out` valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn vault_verify_identity_key(
+    public_key: *const u8,
+    public_key_len: usize,
+    context: *const u8,
+    context_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    valid_out: *mut bool,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let valid_out = handle_mut(valid_out, "valid_out")?;
+        let proof = IdentityKeyProof::from_bytes(input(proof, proof_len, "proof")?)?;
+        let mut transcript = ProofTranscript::new(input(context, context_len, "context")?);
+
+        *valid_out = proof.verify(input(public_key, public_key_len, "public_key")?, &mut transcript)?;
+        Ok(())
+    })
+}
+
+/// Write a JSON sigma proof of a JSON statement from a JSON witness
+///
+/// # Safety
+///
+/// Each input must be valid for its length; see the module docs for
+/// buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_sigma_prove(
+    statement: *const u8,
+    statement_len: usize,
+    witness: *const u8,
+    witness_len: usize,
+    context: *const u8,
+    context_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let statement: Statement = from_json(input(statement, statement_len, "statement")?)?;
+        let witness: Witness = from_json(input(witness, witness_len, "witness")?)?;
+        let mut transcript = ProofTranscript::new(input(context, context_len, "context")?);
+
+        let proof = SigmaProof::prove(&statement, &witness, &mut transcript, &OsRandom)?;
+        write_output(to_json(&proof)?, out, out_len)
+    })
+}
+
+/// Verify a JSON sigma proof of a JSON statement
+///
+/// # Safety
+///
+/// Each input must be valid for its length and `valid_out` valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn vault_sigma_verify(
+    statement: *const u8,
+    statement_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    context: *const u8,
+    context_len: usize,
+    valid_out: *mut bool,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let valid_out = handle_mut(valid_out, "valid_out")?;
+        let statement: Statement = from_json(input(statement, statement_len, "statement")?)?;
+        let proof: SigmaProof = from_json(input(proof, proof_len, "proof")?)?;
+        let mut transcript = ProofTranscript::new(input(context, context_len, "context")?);
+
+        *valid_out = proof.verify(&statement, &mut transcript)?;
+        Ok(())
+    })
+}
+
+/// Emergency deletion of every key held by the core
+///
+/// # Safety
+///
+/// `core` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_panic_wipe(core: *const VaultCore) {
+    if let Some(core) = core.as_ref() {
+        core.inner.panic_wipe();
+    }
+}
+
+/// Start a Double Ratchet session for the core's identity; null on failure
+///
+/// Pass the peer's ratchet key when responding to them, or null and 0 to
+/// initiate.
+///
+/// # Safety
+///
+/// `core` must be a live handle and `remote_dh_public` valid for
+/// `remote_dh_public_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vault_session_new(
+    core: *const VaultCore,
+    remote_dh_public: *const u8,
+    remote_dh_public_len: usize,
+    err: *mut VaultError,
+) -> *mut VaultSession {
+    call_new(err, || {
+        let core = handle(core, "core")?.inner.core();
+        let remote = input(remote_dh_public, remote_dh_public_len, "remote_dh_public")?;
+        let rng = core.providers.rng.clone();
+
+        let inner = if remote.is_empty() {
+            DoubleRatchet::with_rng(&core.identity, rng)?
+        } else {
+            DoubleRatchet::initialize_responder_with_rng(&core.identity, remote, rng)?
+        };
+
+        Ok(VaultSession { inner })
+    })
+}
+
+/// Destroy a session, wiping its chain keys; null is ignored
+///
+/// # Safety
+///
+/// `session` must be null or a handle from `vault_session_new` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn vault_session_free(session: *mut VaultSession) {
+    if !session.is_null() {
+        drop(Box::from_raw(session));
+    }
+}
+
+/// Write our 32-byte ratchet public key, to send to the peer
+///
+/// # Safety
+///
+/// `session` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_session_dh_public(
+    session: *const VaultSession,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let session = handle(session, "session")?;
+        write_output(session.inner.get_dh_public(), out, out_len)
+    })
+}
+
+/// Encrypt bytes, writing the message envelope as JSON
+///
+/// # Safety
+///
+/// `session` must be a live handle and `plaintext` valid for
+/// `plaintext_len` bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_session_encrypt(
+    session: *mut VaultSession,
+    plaintext: *const u8,
+    plaintext_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let session = handle_mut(session, "session")?;
+        let envelope = session.inner.encrypt(input(plaintext, plaintext_len, "plaintext")?)?;
+        write_output(to_json(&envelope)?, out, out_len)
+    })
+}
+
+/// Decrypt a JSON message envelope, writing the plaintext
+///
+/// # Safety
+///
+/// `session` must be a live handle and `envelope` valid for `envelope_len`
+/// bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_session_decrypt(
+    session: *mut VaultSession,
+    envelope: *const u8,
+    envelope_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let session = handle_mut(session, "session")?;
+        let envelope: MessageEnvelope = from_json(input(envelope, envelope_len, "envelope")?)?;
+
+        write_output(session.inner.decrypt(&envelope)?, out, out_len)
+    })
+}
+
+/// Create an MLS group owned by the core's identity; null on failure
+///
+/// # Safety
+///
+/// `core` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_new(
+    core: *const VaultCore,
+    err: *mut VaultError,
+) -> *mut VaultGroup {
+    call_new(err, || {
+        let core = handle(core, "core")?.inner.core();
+        let inner = MLSGroup::with_rng(&core.identity, core.providers.rng.clone())?;
+        Ok(VaultGroup { inner })
+    })
+}
+
+/// Destroy a group, wiping its secrets; null is ignored
+///
+/// # Safety
+///
+/// `group` must be null or a handle from `vault_group_new` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_free(group: *mut VaultGroup) {
+    if !group.is_null() {
+        drop(Box::from_raw(group));
+    }
+}
+
+/// Write the random group ID (hex, not NUL-terminated)
+///
+/// # Safety
+///
+/// `group` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_id(
+    group: *const VaultGroup,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let group = handle(group, "group")?;
+        write_output(group.inner.get_group_id().into_bytes(), out, out_len)
+    })
+}
+
+/// Read the current epoch
+///
+/// # Safety
+///
+/// `group` must be a live handle and `epoch_out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_epoch(
+    group: *const VaultGroup,
+    epoch_out: *mut u64,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let group = handle(group, "group")?;
+        *handle_mut(epoch_out, "epoch_out")? = group.inner.get_epoch();
+        Ok(())
+    })
+}
+
+/// Propose adding a member
+///
+/// # Safety
+///
+/// `group` must be a live handle, `member_id` a NUL-terminated string and
+/// `key_package` valid for `key_package_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_propose_add(
+    group: *mut VaultGroup,
+    member_id: *const c_char,
+    key_package: *const u8,
+    key_package_len: usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let group = handle_mut(group, "group")?;
+        let member_id = text(member_id, "member_id")?;
+        let key_package = input(key_package, key_package_len, "key_package")?;
+
+        Ok(group.inner.propose_add(member_id, key_package)?)
+    })
+}
+
+/// Commit the pending proposals, writing the commit message
+///
+/// # Safety
+///
+/// `group` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_commit(
+    group: *mut VaultGroup,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let group = handle_mut(group, "group")?;
+        write_output(group.inner.commit()?, out, out_len)
+    })
+}
+
+/// Encrypt a group message
+///
+/// # Safety
+///
+/// `group` must be a live handle and `plaintext` valid for `plaintext_len`
+/// bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_encrypt(
+    group: *const VaultGroup,
+    plaintext: *const u8,
+    plaintext_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let group = handle(group, "group")?;
+        let message = group.inner.encrypt_group_message(input(plaintext, plaintext_len, "plaintext")?)?;
+        write_output(message, out, out_len)
+    })
+}
+
+/// Decrypt a group message
+///
+/// # Safety
+///
+/// `group` must be a live handle and `message` valid for `message_len`
+/// bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_group_decrypt(
+    group: *const VaultGroup,
+    message: *const u8,
+    message_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let group = handle(group, "group")?;
+        let plaintext = group.inner.decrypt_group_message(input(message, message_len, "message")?)?;
+        write_output(plaintext, out, out_len)
+    })
+}
+
+/// Encrypt a file as an attachment under a fresh random key; null on failure
+///
+/// Upload the blob and outboard, and send the pointer inside a ratchet
+/// message. Pass null and 0 for no thumbnail.
+///
+/// # Safety
+///
+/// `core` must be a live handle, `data` valid for `data_len` bytes,
+/// `content_type` a NUL-terminated string and `thumbnail` null or valid for
+/// `thumbnail_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_encrypt_attachment(
+    core: *const VaultCore,
+    data: *const u8,
+    data_len: usize,
+    content_type: *const c_char,
+    thumbnail: *const u8,
+    thumbnail_len: usize,
+    err: *mut VaultError,
+) -> *mut VaultAttachment {
+    call_new(err, || {
+        let core = handle(core, "core")?;
+        let data = input(data, data_len, "data")?;
+        let content_type = text(content_type, "content_type")?;
+        let thumbnail = if thumbnail.is_null() {
+            None
+        } else {
+            Some(input(thumbnail, thumbnail_len, "thumbnail")?.to_vec())
+        };
+
+        let inner = core.inner.encrypt_attachment(data, content_type, thumbnail)?;
+        Ok(VaultAttachment { inner })
+    })
+}
+
+/// Verify a downloaded blob against its pointer and decrypt it
+///
+/// # Safety
+///
+/// `core` must be a live handle, `pointer` valid for `pointer_len` bytes
+/// and `blob` valid for `blob_len` bytes; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_core_decrypt_attachment(
+    core: *const VaultCore,
+    pointer: *const u8,
+    pointer_len: usize,
+    blob: *const u8,
+    blob_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let core = handle(core, "core")?;
+        let pointer = input(pointer, pointer_len, "pointer")?;
+        let data = core.inner.decrypt_attachment(pointer, input(blob, blob_len, "blob")?)?;
+        write_output(data, out, out_len)
+    })
+}
+
+/// Destroy an attachment, wiping its key; null is ignored
+///
+/// # Safety
+///
+/// `attachment` must be null or a handle from
+/// `vault_core_encrypt_attachment` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn vault_attachment_free(attachment: *mut VaultAttachment) {
+    if !attachment.is_null() {
+        drop(Box::from_raw(attachment));
+    }
+}
+
+/// Write the attachment pointer, to send inside a ratchet message
+///
+/// The pointer holds the attachment key.
+///
+/// # Safety
+///
+/// `attachment` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_attachment_pointer(
+    attachment: *const VaultAttachment,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let attachment = handle(attachment, "attachment")?;
+        let pointer = attachment.inner.pointer.to_bytes().map_err(BlackHoleError::from)?;
+        write_output(pointer, out, out_len)
+    })
+}
+
+/// Write the encrypted blob, to upload under the pointer's blob ID
+///
+/// # Safety
+///
+/// `attachment` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_attachment_blob(
+    attachment: *const VaultAttachment,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let attachment = handle(attachment, "attachment")?;
+        write_output(attachment.inner.blob.clone(), out, out_len)
+    })
+}
+
+/// Write the Bao outboard tree, served alongside the blob
+///
+/// # Safety
+///
+/// `attachment` must be a live handle; see the module docs for buffers.
+#[no_mangle]
+pub unsafe extern "C" fn vault_attachment_outboard(
+    attachment: *const VaultAttachment,
+    out: *mut u8,
+    out_len: *mut usize,
+    err: *mut VaultError,
+) -> i32 {
+    call(err, || {
+        let attachment = handle(attachment, "attachment")?;
+        write_output(attachment.inner.outboard.clone(), out, out_len)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_error() -> VaultError {
+        VaultError { code: 0, message: ptr::null_mut() }
+    }
+
+    /// A core proving with the shared test parameters
+    unsafe fn core_new(err: *mut VaultError) -> *mut VaultCore {
+        let parameters = crate::zk::setup::test_parameters().to_bytes();
+        vault_core_new(parameters.as_ptr(), parameters.len(), err)
+    }
+
+    /// Run an output-producing call, negotiating the buffer size first
+    unsafe fn collect(f: impl FnOnce(*mut u8, *mut usize, *mut VaultError) -> i32) -> Vec<u8> {
+        let mut err = no_error();
+        let mut len = 0;
+        assert_eq!(f(ptr::null_mut(), &mut len, &mut err), VAULT_ERROR_BUFFER_TOO_SMALL);
+        assert_eq!(err.code, VAULT_ERROR_BUFFER_TOO_SMALL);
+
+        let mut out = alloc::vec![0u8; len];
+        assert_eq!(vault_take_output(out.as_mut_ptr(), &mut len, &mut err), 0);
+        assert_eq!(err.code, 0);
+        assert!(err.message.is_null());
+        out
+    }
+
+    #[test]
+    fn test_core_round_trip_with_buffer_negotiation() {
+        unsafe {
+            let mut err = no_error();
+            // There is no core without setup parameters
+            assert!(vault_core_new(ptr::null(), 0, &mut err).is_null());
+            assert_eq!(err.code, BlackHoleError::Malformed("proving key").code());
+
+            let core = core_new(&mut err);
+            assert!(!core.is_null());
+
+            let plaintext = b"hello through the event horizon";
+            let message = collect(|out, out_len, err| {
+                vault_core_encrypt(core, c"bob".as_ptr(), plaintext.as_ptr(), plaintext.len(), out, out_len, err)
+            });
+
+            let mut out = [0u8; 64];
+            let mut out_len = out.len();
+            let status = vault_core_decrypt(core, message.as_ptr(), message.len(), out.as_mut_ptr(), &mut out_len, &mut err);
+            assert_eq!(status, 0);
+            assert_eq!(&out[..out_len], plaintext);
+
+            let key = collect(|out, out_len, err| vault_core_identity_public_key(core, out, out_len, err));
+            assert_eq!(key.len(), 32);
+
+            vault_core_free(core);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_populated() {
+        unsafe {
+            let mut err = no_error();
+            let core = core_new(&mut err);
+
+            let garbage = b"not a message";
+            let mut out = [0u8; 64];
+            let mut out_len = out.len();
+            let status = vault_core_decrypt(core, garbage.as_ptr(), garbage.len(), out.as_mut_ptr(), &mut out_len, &mut err);
+            assert_eq!(status, BlackHoleError::Serialization(String::new()).code());
+            assert_eq!(err.code, status);
+            let message = CStr::from_ptr(err.message).to_str().unwrap();
+            assert!(message.starts_with("Serialization error"));
+
+            // The previous message is released when the error is reused
+            let status = vault_core_fingerprint(ptr::null(), out.as_mut_ptr(), &mut out_len, &mut err);
+            assert_eq!(status, VAULT_ERROR_NULL_POINTER);
+            assert_eq!(CStr::from_ptr(err.message).to_str().unwrap(), "`core` is null");
+
+            let status = vault_take_output(out.as_mut_ptr(), &mut out_len, &mut err);
+            assert_eq!(status, BlackHoleError::InvalidState("").code());
+
+            vault_error_free(&mut err);
+            assert_eq!(err.code, 0);
+            assert!(err.message.is_null());
+
+            vault_core_free(core);
+        }
+    }
+
+    #[test]
+    fn test_group_and_session_handles() {
+        unsafe {
+            let mut err = no_error();
+            let core = core_new(&mut err);
+            let group = vault_group_new(core, &mut err);
+            assert!(!group.is_null());
+
+            let key_package = [7u8; 32];
+            assert_eq!(vault_group_propose_add(group, c"alice".as_ptr(), key_package.as_ptr(), key_package.len(), &mut err), 0);
+            collect(|out, out_len, err| vault_group_commit(group, out, out_len, err));
+
+            let mut epoch = 0;
+            assert_eq!(vault_group_epoch(group, &mut epoch, &mut err), 0);
+            assert_eq!(epoch, 1);
+
+            let message = collect(|out, out_len, err| vault_group_encrypt(group, b"hi".as_ptr(), 2, out, out_len, err));
+            let plaintext = collect(|out, out_len, err| vault_group_decrypt(group, message.as_ptr(), message.len(), out, out_len, err));
+            assert_eq!(plaintext, b"hi");
+
+            let session = vault_session_new(core, ptr::null(), 0, &mut err);
+            let dh_public = collect(|out, out_len, err| vault_session_dh_public(session, out, out_len, err));
+
+            let bad_key = [1u8; 31];
+            let responder = vault_session_new(core, bad_key.as_ptr(), bad_key.len(), &mut err);
+            assert!(responder.is_null());
+            assert_eq!(err.code, BlackHoleError::InvalidKeyLength { expected: 32, actual: 31 }.code());
+
+            let responder = vault_session_new(core, dh_public.as_ptr(), dh_public.len(), &mut err);
+            assert!(!responder.is_null());
+
+            let envelope = collect(|out, out_len, err| vault_session_encrypt(responder, b"hi".as_ptr(), 2, out, out_len, err));
+            assert!(serde_json::from_slice::<MessageEnvelope>(&envelope).is_ok());
+            let plaintext = collect(|out, out_len, err| {
+                vault_session_decrypt(session, envelope.as_ptr(), envelope.len(), out, out_len, err)
+            });
+            assert_eq!(plaintext, b"hi");
+
+            vault_session_free(responder);
+            vault_session_free(session);
+            vault_group_free(group);
+            vault_core_free(core);
+            vault_error_free(&mut err);
+        }
+    }
+
+    #[test]
+    fn test_identity_key_and_sigma_proofs() {
+        unsafe {
+            let mut err = no_error();
+            let core = core_new(&mut err);
+            let context = b"login";
+
+            let key = collect(|out, out_len, err| vault_core_identity_public_key(core, out, out_len, err));
+            let proof = collect(|out, out_len, err| {
+                vault_core_prove_identity_key(core, context.as_ptr(), context.len(), out, out_len, err)
+            });
+
+            let mut valid = false;
+            let status = vault_verify_identity_key(
+                key.as_ptr(), key.len(), context.as_ptr(), context.len(), proof.as_ptr(), proof.len(), &mut valid, &mut err,
+            );
+            assert_eq!(status, 0);
+            assert!(valid);
+
+            let status = vault_verify_identity_key(
+                key.as_ptr(), key.len(), ptr::null(), 0, proof.as_ptr(), proof.len(), &mut valid, &mut err,
+            );
+            assert_eq!(status, 0);
+            assert!(!valid);
+
+            let secret = curve25519_dalek::Scalar::from(42u64);
+            let statement = to_json(&Statement::schnorr(curve25519_dalek::RistrettoPoint::mul_base(&secret))).unwrap();
+            let witness = to_json(&Witness::Dlog(secret)).unwrap();
+            let proof = collect(|out, out_len, err| {
+                vault_sigma_prove(
+                    statement.as_ptr(), statement.len(), witness.as_ptr(), witness.len(), context.as_ptr(), context.len(), out, out_len, err,
+                )
+            });
+
+            let status = vault_sigma_verify(
+                statement.as_ptr(), statement.len(), proof.as_ptr(), proof.len(), context.as_ptr(), context.len(), &mut valid, &mut err,
+            );
+            assert_eq!(status, 0);
+            assert!(valid);
+
+            vault_core_free(core);
+            vault_error_free(&mut err);
+        }
+    }
+
+    #[test]
+    fn test_core_handle_is_shared_between_threads() {
+        unsafe {
+            let mut err = no_error();
+            let core = core_new(&mut err) as usize;
+
+            std::thread::scope(|scope| {
+                for n in 0..4 {
+                    scope.spawn(move || {
+                        let core = core as *const VaultCore;
+                        let text = alloc::format!("from thread {n}");
+                        let message = collect(|out, out_len, err| {
+                            vault_core_encrypt(core, c"bob".as_ptr(), text.as_ptr(), text.len(), out, out_len, err)
+                        });
+                        let plaintext = collect(|out, out_len, err| {
+                            vault_core_decrypt(core, message.as_ptr(), message.len(), out, out_len, err)
+                        });
+                        assert_eq!(plaintext, text.as_bytes());
+                    });
+                }
+            });
+
+            vault_core_free(core as *mut VaultCore);
+        }
+    }
+
+    #[test]
+    fn test_attachment_handles() {
+        unsafe {
+            let mut err = no_error();
+            let core = core_new(&mut err);
+            let data = alloc::vec![5u8; 70_000];
+
+            let attachment = vault_core_encrypt_attachment(
+                core, data.as_ptr(), data.len(), c"image/png".as_ptr(), ptr::null(), 0, &mut err,
+            );
+            assert!(!attachment.is_null());
+
+            let pointer = collect(|out, out_len, err| vault_attachment_pointer(attachment, out, out_len, err));
+            let blob = collect(|out, out_len, err| vault_attachment_blob(attachment, out, out_len, err));
+            let outboard = collect(|out, out_len, err| vault_attachment_outboard(attachment, out, out_len, err));
+            assert!(blob.len() > data.len());
+            assert!(!outboard.is_empty());
+
+            let plaintext = collect(|out, out_len, err| {
+                vault_core_decrypt_attachment(core, pointer.as_ptr(), pointer.len(), blob.as_ptr(), blob.len(), out, out_len, err)
+            });
+            assert_eq!(plaintext, data);
+
+            let mut tampered = blob.clone();
+            tampered[0] ^= 1;
+            let mut out_len = 0;
+            let status = vault_core_decrypt_attachment(
+                core, pointer.as_ptr(), pointer.len(), tampered.as_ptr(), tampered.len(), ptr::null_mut(), &mut out_len, &mut err,
+            );
+            assert_ne!(status, 0);
+            assert_ne!(status, VAULT_ERROR_BUFFER_TOO_SMALL);
+
+            vault_attachment_free(attachment);
+            vault_core_free(core);
+            vault_error_free(&mut err);
+        }
+    }
+
+    #[test]
+    fn test_free_ignores_null() {
+        unsafe {
+            vault_core_free(ptr::null_mut());
+            vault_session_free(ptr::null_mut());
+            vault_group_free(ptr::null_mut());
+            vault_attachment_free(ptr::null_mut());
+            vault_error_free(ptr::null_mut());
+            vault_core_panic_wipe(ptr::null_mut());
+        }
+
+        let version = unsafe { CStr::from_ptr(vault_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
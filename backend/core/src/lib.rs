@@ -26,73 +26,142 @@
 //!    - Compute on encrypted data
 //!    - Enc(a) ⊕ Enc(b) = Enc(a+b)
 
-#![cfg_attr(target_arch = "wasm32", no_std)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 #![warn(unsafe_code)]
 
-#[cfg(target_arch = "wasm32")]
 extern crate alloc;
 
 // Module declarations
 pub mod math;
 pub mod crypto;
+pub mod error;
 pub mod protocol;
+pub mod providers;
 pub mod zk;
+
+// Thread-safe handle for native clients
+#[cfg(feature = "std")]
+pub mod shared;
+
+// JavaScript bindings
+#[cfg(feature = "wasm")]
 pub mod wasm;
 
+// C ABI for the mobile apps
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+// Swift and Kotlin bindings
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+// Android JNI bindings
+#[cfg(feature = "jni")]
+pub mod android;
+
 // Re-export main types
+pub use error::BlackHoleError;
+
 pub use crypto::{
-    BlackHoleCrypto,
-    EventHorizon,
-    SingularityKey,
-    EncryptedMessage,
+    PaddingScheme,
+    PostQuantumKeys,
 };
 
 pub use protocol::{
+    AttachmentPointer,
+    ContentError,
     DoubleRatchet,
+    EncryptedAttachment,
+    ExpiryPolicy,
+    GroupRegistry,
+    HistoryEntry,
+    HistoryExport,
     MLSGroup,
+    MessageContent,
     MessageEnvelope,
+    SealedSenderEnvelope,
+    SenderCertificate,
+    SessionManager,
+    TrustRoot,
+    UnsealedMessage,
+};
+
+pub use providers::{
+    Clock,
+    CryptoRng,
+    FixedClock,
+    OsRandom,
+    Providers,
+    SeededRng,
 };
 
+#[cfg(feature = "std")]
+pub use providers::SystemClock;
+
+#[cfg(feature = "std")]
+pub use shared::SharedCore;
+
 pub use zk::{
     ZKIdentity,
     ZKProof,
     ZKVerifier,
 };
 
-use wasm_bindgen::prelude::*;
+pub use zk::setup::{
+    KeyRegistry,
+    ZKParameters,
+};
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// The main Black Hole cryptographic engine
 ///
 /// This is the entry point for all cryptographic operations.
 /// Keys are stored in Rust memory and never exposed to JavaScript.
-#[wasm_bindgen]
 pub struct BlackHoleCore {
     /// Identity key pair (Ed25519)
     identity: SingularityKey,
     
-    /// Double Ratchet state machine
-    ratchet: DoubleRatchet,
+    /// Double Ratchet sessions and conversation history, per peer
+    sessions: SessionManager,
     
-    /// MLS group manager
-    mls: MLSGroup,
+    /// MLS groups, keyed by their random group IDs
+    groups: GroupRegistry,
     
     /// Zero-knowledge identity
     zk_identity: ZKIdentity,
     
+    /// Verifying keys identity proofs are checked against
+    key_registry: KeyRegistry,
+    
     /// Post-quantum keys (ML-KEM)
     pq_keys: crypto::PostQuantumKeys,
     
     /// Event horizon (encryption barrier)
     event_horizon: EventHorizon,
+    
+    /// Issuer key that sealed sender certificates must chain to
+    trust_root: Option<TrustRoot>,
+    
+    /// Our current server-issued sender certificate
+    sender_certificate: Option<SenderCertificate>,
+    
+    /// Clock and randomness every component runs against
+    providers: Providers,
 }
 
 /// The Event Horizon - where plaintext becomes mathematically irretrievable
 ///
 /// Once data crosses the event horizon, it can only be recovered with
 /// the proper private keys. Even we, the creators, cannot decrypt messages.
-#[wasm_bindgen]
 pub struct EventHorizon {
     /// Symmetric encryption key (AES-256-GCM)
     key: [u8; 32],
@@ -102,6 +171,12 @@ pub struct EventHorizon {
     
     /// Number of messages encrypted
     message_count: u64,
+    
+    /// Padding applied before encryption
+    padding: PaddingScheme,
+    
+    /// Source of keys, nonces and message timestamps
+    providers: Providers,
 }
 
 /// A Singularity Key - the ultimate secret
@@ -123,8 +198,7 @@ pub struct SingularityKey {
 }
 
 /// An encrypted message that has crossed the event horizon
-#[wasm_bindgen]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EncryptedMessage {
     /// Ciphertext (AES-256-GCM encrypted)
     pub ciphertext: Vec<u8>,
@@ -142,106 +216,320 @@ pub struct EncryptedMessage {
     pub sequence: u64,
 }
 
-#[wasm_bindgen]
 impl BlackHoleCore {
     /// Initialize the Black Hole
     ///
     /// This generates a new identity and initializes all cryptographic
     /// components. This is the "Big Bang" of your secure messaging.
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> Result<BlackHoleCore, JsValue> {
-        #[cfg(debug_assertions)]
-        console_log::init_with_level(log::Level::Debug).ok();
-        
-        log::info!("🕳️ Initializing Black Hole singularity...");
-        
-        // Generate identity key pair
-        let identity = SingularityKey::generate()?;
-        
-        // Initialize Double Ratchet
-        let ratchet = DoubleRatchet::new(&identity)?;
-        
-        // Initialize MLS group manager
-        let mls = MLSGroup::new(&identity)?;
-        
-        // Initialize ZK identity
-        let zk_identity = ZKIdentity::new(&identity)?;
-        
-        // Generate post-quantum keys
-        let pq_keys = crypto::PostQuantumKeys::generate()?;
-        
-        // Create event horizon
-        let event_horizon = EventHorizon::new()?;
-        
-        log::info!("✅ Black Hole singularity created. Fingerprint: {}", 
-            identity.fingerprint);
-        
-        Ok(BlackHoleCore {
-            identity,
-            ratchet,
-            mls,
-            zk_identity,
-            pq_keys,
-            event_horizon,
-        })
+    /// `parameters` are the identity circuit's setup from the phase-2
+    /// ceremony; their verifying key is pinned for `verify_identity`.
+    #[cfg(feature = "std")]
+    pub fn new(parameters: &ZKParameters) -> Result<BlackHoleCore, BlackHoleError> {
+        Self::with_providers(parameters, Providers::system())
+    }
+    
+    /// Initialize the Black Hole with a local ZK setup
+    ///
+    /// **Insecure:** this process draws the setup secrets itself, so it
+    /// could forge identity proofs, and no other client trusts its
+    /// verifying key. For tests only.
+    #[cfg(feature = "std")]
+    pub fn new_insecure() -> Result<BlackHoleCore, BlackHoleError> {
+        let providers = Providers::system();
+        let parameters = ZKParameters::generate(providers.rng.as_ref())?;
+        
+        Self::with_providers(&parameters, providers)
     }
     
     /// Encrypt a message - crossing the event horizon
     ///
     /// Once encrypted, the message can only be decrypted by the intended
     /// recipient. Not even the sender can decrypt it after sending.
-    #[wasm_bindgen]
-    pub fn encrypt(&mut self, recipient: &str, plaintext: &str) -> Result<JsValue, JsValue> {
-        let encrypted = self.event_horizon.encrypt(
-            &self.identity,
-            recipient,
-            plaintext.as_bytes(),
-        )?;
-        
-        serde_wasm_bindgen::to_value(&encrypted)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    /// The text is sent as `MessageContent::Text`.
+    pub fn encrypt(&mut self, recipient: &str, plaintext: &str) -> Result<EncryptedMessage, BlackHoleError> {
+        self.encrypt_content(recipient, &MessageContent::text(plaintext))
     }
     
     /// Decrypt a message - escaping the event horizon
     ///
-    /// Only the holder of the private key can decrypt messages.
-    #[wasm_bindgen]
-    pub fn decrypt(&self, ciphertext: JsValue) -> Result<String, JsValue> {
-        let encrypted: EncryptedMessage = serde_wasm_bindgen::from_value(ciphertext)
-            .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+    /// Only the holder of the private key can decrypt messages. Fails for
+    /// anything but a text message; use `decrypt_content` for other kinds.
+    pub fn decrypt(&self, encrypted: &EncryptedMessage) -> Result<String, BlackHoleError> {
+        match self.decrypt_content(encrypted)? {
+            MessageContent::Text { body, .. } => Ok(body),
+            _ => Err(BlackHoleError::InvalidState("Message is not a text message")),
+        }
+    }
+    
+    /// Encrypt structured content (reaction, edit, receipt, ...)
+    pub fn encrypt_content(&mut self, recipient: &str, content: &MessageContent) -> Result<EncryptedMessage, BlackHoleError> {
+        self.event_horizon.encrypt(&self.identity, recipient, &content.encode()?)
+    }
+    
+    /// Decrypt a message into its structured content
+    pub fn decrypt_content(&self, encrypted: &EncryptedMessage) -> Result<MessageContent, BlackHoleError> {
+        let plaintext = self.event_horizon.decrypt(&self.identity, encrypted)?;
         
-        let plaintext = self.event_horizon.decrypt(&self.identity, &encrypted)?;
+        Ok(MessageContent::decode(&plaintext)?)
+    }
+    
+    /// Start a Double Ratchet session with `peer`
+    ///
+    /// Pass the peer's ratchet key when responding to them. Returns our
+    /// ratchet public key to send to the peer.
+    pub fn start_session(&mut self, peer: &str, remote_dh_public: Option<Vec<u8>>) -> Result<Vec<u8>, BlackHoleError> {
+        let ratchet = self.new_ratchet(remote_dh_public)?;
+        
+        let dh_public = ratchet.get_dh_public();
+        self.sessions.add_session(peer, ratchet);
+        Ok(dh_public)
+    }
+    
+    /// A fresh ratchet for this identity, responding when the peer's key is given
+    pub(crate) fn new_ratchet(&self, remote_dh_public: Option<Vec<u8>>) -> Result<DoubleRatchet, BlackHoleError> {
+        match remote_dh_public {
+            Some(remote) => DoubleRatchet::initialize_responder_with_rng(
+                &self.identity,
+                &remote,
+                self.providers.rng.clone(),
+            ),
+            None => DoubleRatchet::with_rng(&self.identity, self.providers.rng.clone()),
+        }
+    }
+    
+    /// End the session with `peer`, wiping its keys and history
+    pub fn end_session(&mut self, peer: &str) -> bool {
+        self.sessions.remove_session(peer)
+    }
+    
+    /// Encrypt structured content for `peer` over its ratchet session
+    pub fn session_encrypt(&mut self, peer: &str, content: MessageContent) -> Result<MessageEnvelope, BlackHoleError> {
+        let (_, envelope) = self.sessions.encrypt(peer, content)?;
+        Ok(envelope)
+    }
+    
+    /// Decrypt a ratchet message from `peer` into its structured content
+    pub fn session_decrypt(&mut self, peer: &str, envelope: &MessageEnvelope) -> Result<MessageContent, BlackHoleError> {
+        let (_, content) = self.sessions.decrypt(peer, envelope)?;
+        Ok(content)
+    }
+    
+    /// Set the disappearing-message timer for `peer` (`None` turns it off)
+    ///
+    /// Returns the encrypted timer update to deliver to the peer.
+    pub fn set_expiry_timer(&mut self, peer: &str, seconds: Option<u32>) -> Result<MessageEnvelope, BlackHoleError> {
+        let update = self.sessions.set_expiry(peer, seconds.map(ExpiryPolicy::new));
+        let (_, envelope) = self.sessions.encrypt(peer, update)?;
+        Ok(envelope)
+    }
+    
+    /// Unexpired local history of the conversation with `peer`
+    pub fn history(&mut self, peer: &str) -> Vec<HistoryEntry> {
+        self.sessions.history(peer)
+    }
+    
+    /// Destroy expired messages, returning how many were destroyed
+    pub fn purge_expired(&mut self) -> usize {
+        self.sessions.purge_expired()
+    }
+    
+    /// Earliest pending expiry deadline (ms since the Unix epoch)
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.sessions.next_expiry()
+    }
+    
+    /// Export timers and history, with the device key that unlocks them
+    pub fn export_history(&mut self) -> Result<HistoryExport, BlackHoleError> {
+        self.sessions.export_history()
+    }
+    
+    /// Restore timers and history after a restart, given the export's device key
+    pub fn import_history(&mut self, bytes: &[u8], device_key: &[u8]) -> Result<(), BlackHoleError> {
+        self.sessions.import_history(bytes, device_key)
+    }
+    
+    /// Configure the certificate issuer trusted for sealed sender
+    pub fn set_trust_root(&mut self, public_key: &[u8]) -> Result<(), BlackHoleError> {
+        self.trust_root = Some(TrustRoot::new(public_key)?);
+        Ok(())
+    }
+    
+    /// Install the sender certificate issued to us by the server
+    pub fn set_sender_certificate(&mut self, certificate: &[u8]) -> Result<(), BlackHoleError> {
+        let certificate = SenderCertificate::from_bytes(certificate)?;
+        let trust_root = self.trust_root.as_ref()
+            .ok_or(BlackHoleError::InvalidState("No trust root configured"))?;
+        
+        if !certificate.is_for(&self.identity) {
+            return Err(BlackHoleError::InvalidCertificate);
+        }
+        
+        if !certificate.is_valid(trust_root, self.providers.clock.now_ms()) {
+            return Err(BlackHoleError::InvalidCertificate);
+        }
         
-        String::from_utf8(plaintext)
-            .map_err(|_| JsValue::from_str("Invalid UTF-8 in decrypted message"))
+        self.sender_certificate = Some(certificate);
+        Ok(())
+    }
+    
+    /// Seal content so that the relay only learns the recipient address
+    pub fn seal(
+        &self,
+        recipient_address: &str,
+        recipient_identity_key: &[u8],
+        content: &[u8],
+    ) -> Result<Vec<u8>, BlackHoleError> {
+        let certificate = self.sender_certificate.as_ref()
+            .ok_or(BlackHoleError::InvalidState("No sender certificate installed"))?;
+        
+        SealedSenderEnvelope::seal(
+            &self.identity,
+            certificate,
+            recipient_address,
+            recipient_identity_key,
+            content,
+            self.providers.rng.as_ref(),
+        )?
+        .to_bytes()
+    }
+    
+    /// Open a sealed envelope, authenticating the hidden sender
+    pub fn unseal(&self, envelope: &[u8]) -> Result<UnsealedMessage, BlackHoleError> {
+        let trust_root = self.trust_root.as_ref()
+            .ok_or(BlackHoleError::InvalidState("No trust root configured"))?;
+        
+        SealedSenderEnvelope::from_bytes(envelope)?
+            .open(&self.identity, trust_root, self.providers.clock.now_ms())
+    }
+    
+    /// Encrypt a file as an attachment under a fresh key from the core's RNG
+    ///
+    /// Upload `blob` under `pointer.blob_id` and send the pointer (see
+    /// `AttachmentPointer::to_bytes`) inside a ratchet message.
+    pub fn encrypt_attachment(
+        &self,
+        data: &[u8],
+        content_type: &str,
+        thumbnail: Option<Vec<u8>>,
+    ) -> Result<EncryptedAttachment, BlackHoleError> {
+        Ok(protocol::attachment::encrypt_attachment(data, content_type, thumbnail, self.providers.rng.as_ref())?)
+    }
+    
+    /// Verify a downloaded blob against its pointer and decrypt it
+    pub fn decrypt_attachment(&self, pointer: &[u8], blob: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        let pointer = AttachmentPointer::from_bytes(pointer)?;
+        Ok(protocol::attachment::decrypt_attachment(&pointer, blob)?)
+    }
+    
+    /// Get the Ed25519 identity public key
+    pub fn get_identity_public_key(&self) -> Vec<u8> {
+        self.identity.public.to_vec()
     }
     
     /// Get the public key fingerprint
-    #[wasm_bindgen]
     pub fn get_fingerprint(&self) -> String {
         self.identity.fingerprint.clone()
     }
     
     /// Create a zero-knowledge proof of identity
-    #[wasm_bindgen]
-    pub fn prove_identity(&self) -> Result<JsValue, JsValue> {
-        let proof = self.zk_identity.prove()?;
-        
-        serde_wasm_bindgen::to_value(&proof)
-            .map_err(|e| JsValue::from_str(&format!("Proof serialization error: {}", e)))
+    pub fn prove_identity(&self) -> Result<ZKProof, BlackHoleError> {
+        self.zk_identity.prove()
     }
     
     /// Verify a zero-knowledge proof
-    #[wasm_bindgen]
-    pub fn verify_identity(&self, proof_js: JsValue) -> Result<bool, JsValue> {
-        let proof: ZKProof = serde_wasm_bindgen::from_value(proof_js)
-            .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
-        
-        self.zk_identity.verify(&proof)
+    ///
+    /// Fails with `UntrustedParameters` unless the proof was made for a
+    /// verifying key in the registry.
+    pub fn verify_identity(&self, proof: &ZKProof) -> Result<bool, BlackHoleError> {
+        self.key_registry.verify(proof, &self.zk_identity.get_commitment())
+    }
+    
+    /// Registry of trusted verifying keys, to pin keys from further ceremonies
+    pub fn key_registry_mut(&mut self) -> &mut KeyRegistry {
+        &mut self.key_registry
+    }
+    
+    /// Prove possession of the Ed25519 identity key, bound to `context`
+    ///
+    /// Verify with [`zk::sigma::IdentityKeyProof::verify`] and the public key.
+    pub fn prove_identity_key(&self, context: &[u8]) -> zk::sigma::IdentityKeyProof {
+        zk::sigma::IdentityKeyProof::prove(
+            &self.identity,
+            &mut zk::sigma::ProofTranscript::new(context),
+            self.providers.rng.as_ref(),
+        )
+    }
+    
+    /// Create a new MLS group and return its random group ID
+    ///
+    /// An identity may own any number of groups; their IDs are unlinkable.
+    pub fn create_group(&mut self) -> Result<String, BlackHoleError> {
+        self.groups.create(&self.identity)
+    }
+    
+    /// List the IDs of all groups held by this identity
+    pub fn list_groups(&self) -> Vec<String> {
+        self.groups.group_ids()
+    }
+    
+    /// Propose adding a member to a group
+    pub fn group_propose_add(
+        &mut self,
+        group_id: &str,
+        member_id: &str,
+        key_package: &[u8],
+    ) -> Result<(), BlackHoleError> {
+        self.groups.get_mut(group_id)?.propose_add(member_id, key_package)
+    }
+    
+    /// Commit the pending proposals of a group
+    pub fn group_commit(&mut self, group_id: &str) -> Result<Vec<u8>, BlackHoleError> {
+        self.groups.get_mut(group_id)?.commit()
+    }
+    
+    /// Apply a commit another member made to a group
+    pub fn group_apply_commit(&mut self, group_id: &str, commit: &[u8]) -> Result<(), BlackHoleError> {
+        self.groups.get_mut(group_id)?.apply_commit(commit)
+    }
+    
+    /// Welcome for the members a group's last commit added
+    ///
+    /// Holds the epoch secret: deliver it over an encrypted session.
+    pub fn group_welcome(&self, group_id: &str) -> Result<Vec<u8>, BlackHoleError> {
+        self.groups.get(group_id)?.welcome()
+    }
+    
+    /// Join a group from a welcome and return its ID
+    pub fn join_group(&mut self, welcome: &[u8]) -> Result<String, BlackHoleError> {
+        self.groups.join(&self.identity, welcome)
+    }
+    
+    /// Encrypt a message for a group
+    pub fn group_encrypt(&self, group_id: &str, plaintext: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        self.groups.get(group_id)?.encrypt_group_message(plaintext)
+    }
+    
+    /// Decrypt a message received in a group
+    pub fn group_decrypt(&self, group_id: &str, message: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        self.groups.get(group_id)?.decrypt_group_message(message)
+    }
+    
+    /// Leave a group, wiping its secrets
+    pub fn leave_group(&mut self, group_id: &str) -> bool {
+        self.groups.remove(group_id)
+    }
+    
+    /// Select the padding scheme used to hide message lengths
+    ///
+    /// Applies to one-to-one messages and to every group held by this
+    /// identity. Receivers remove padding regardless of the scheme.
+    pub fn set_padding_scheme(&mut self, scheme: PaddingScheme) {
+        self.event_horizon.padding = scheme;
+        self.sessions.set_padding_scheme(scheme);
+        self.groups.set_padding_scheme(scheme);
     }
     
     /// Get post-quantum public key for hybrid encryption
-    #[wasm_bindgen]
     pub fn get_pq_public_key(&self) -> String {
         self.pq_keys.get_public_key_hex()
     }
@@ -250,53 +538,114 @@ impl BlackHoleCore {
     ///
     /// In case of compromise, this wipes all keys from memory.
     /// Your messages will be lost forever.
-    #[wasm_bindgen]
     pub fn panic_wipe(&mut self) {
         log::warn!("🚨 PANIC WIPE INITIATED - ALL KEYS BEING DESTROYED");
         
         self.identity.zeroize();
-        self.ratchet.zeroize();
-        self.mls.zeroize();
+        self.sessions.zeroize();
+        self.groups.zeroize();
         self.zk_identity.zeroize();
         self.pq_keys.zeroize();
         self.event_horizon.zeroize();
+        self.sender_certificate = None;
         
         log::warn!("✅ Panic wipe complete. All keys destroyed.");
     }
 }
 
+impl BlackHoleCore {
+    /// Initialize the Black Hole with an injected clock
+    ///
+    /// Native callers and tests use this to control message timestamps and
+    /// disappearing-message deadlines.
+    pub fn with_clock(parameters: &ZKParameters, clock: Arc<dyn Clock>) -> Result<BlackHoleCore, BlackHoleError> {
+        Self::with_providers(parameters, Providers::new(clock, Arc::new(OsRandom)))
+    }
+    
+    /// Initialize the Black Hole against injected clock and randomness
+    ///
+    /// With `Providers::deterministic` every key, nonce and timestamp is
+    /// reproducible, so whole conversations can be replayed as known-answer
+    /// transcripts on native targets.
+    pub fn with_providers(parameters: &ZKParameters, providers: Providers) -> Result<BlackHoleCore, BlackHoleError> {
+        log::info!("🕳️ Initializing Black Hole singularity...");
+        
+        // Generate identity key pair
+        let identity = SingularityKey::generate_with(providers.rng.as_ref())?;
+        
+        // Initialize per-peer Double Ratchet sessions
+        let sessions = SessionManager::new(providers.clone());
+        
+        // Initialize MLS group registry
+        let groups = GroupRegistry::with_rng(providers.rng.clone());
+        
+        // Initialize ZK identity and trust the verifying key it proves for
+        let zk_identity = ZKIdentity::with_parameters(&identity, parameters, providers.rng.clone());
+        let mut key_registry = KeyRegistry::new();
+        key_registry.pin(parameters.key_id());
+        key_registry.load(&parameters.verifying_key())?;
+        
+        // Generate post-quantum keys
+        let pq_keys = crypto::PostQuantumKeys::generate_with(providers.rng.as_ref())?;
+        
+        // Create event horizon
+        let event_horizon = EventHorizon::new(providers.clone())?;
+        
+        log::info!("✅ Black Hole singularity created. Fingerprint: {}", 
+            identity.fingerprint);
+        
+        Ok(BlackHoleCore {
+            identity,
+            sessions,
+            groups,
+            zk_identity,
+            key_registry,
+            pq_keys,
+            event_horizon,
+            trust_root: None,
+            sender_certificate: None,
+            providers,
+        })
+    }
+}
+
 impl EventHorizon {
     /// Create a new event horizon
-    fn new() -> Result<Self, JsValue> {
-        let key: [u8; 32] = rand::random();
-        let salt: [u8; 16] = rand::random();
+    fn new(providers: Providers) -> Result<Self, BlackHoleError> {
+        let key: [u8; 32] = providers.rng.random();
+        let salt: [u8; 16] = providers.rng.random();
         
         Ok(EventHorizon {
             key,
             salt,
             message_count: 0,
+            padding: PaddingScheme::default(),
+            providers,
         })
     }
     
     /// Encrypt data crossing the event horizon
     fn encrypt(&mut self,
         identity: &SingularityKey,
-        recipient: &str,
+        _recipient: &str,
         plaintext: &[u8],
-    ) -> Result<EncryptedMessage, JsValue> {
+    ) -> Result<EncryptedMessage, BlackHoleError> {
         use aes_gcm::{Aes256Gcm, Key, Nonce};
         use aes_gcm::aead::{Aead, KeyInit};
         
         // Generate nonce
-        let nonce: [u8; 12] = rand::random();
+        let nonce: [u8; 12] = self.providers.rng.random();
         
         // Derive encryption key
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
         
+        // Hide the plaintext length
+        let padded = crypto::padding::pad(plaintext, self.padding);
+        
         // Encrypt
         let ciphertext = cipher
-            .encrypt(Nonce::from_slice(&nonce), plaintext)
-            .map_err(|e| JsValue::from_str(&format!("Encryption failed: {:?}", e)))?;
+            .encrypt(Nonce::from_slice(&nonce), padded.as_ref())
+            .map_err(|_| BlackHoleError::Encryption)?;
         
         self.message_count += 1;
         
@@ -304,24 +653,30 @@ impl EventHorizon {
             ciphertext,
             nonce,
             sender_key: identity.fingerprint.clone(),
-            timestamp: js_sys::Date::now() as u64,
+            timestamp: self.providers.clock.now_ms(),
             sequence: self.message_count,
         })
     }
     
     /// Decrypt data escaping the event horizon
     fn decrypt(&self,
-        identity: &SingularityKey,
+        _identity: &SingularityKey,
         encrypted: &EncryptedMessage,
-    ) -> Result<Vec<u8>, JsValue> {
+    ) -> Result<Vec<u8>, BlackHoleError> {
         use aes_gcm::{Aes256Gcm, Key, Nonce};
         use aes_gcm::aead::{Aead, KeyInit};
         
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
         
-        cipher
+        let mut plaintext = cipher
             .decrypt(Nonce::from_slice(&encrypted.nonce), encrypted.ciphertext.as_ref())
-            .map_err(|e| JsValue::from_str(&format!("Decryption failed: {:?}", e)))
+            .map_err(|_| BlackHoleError::Decryption)?;
+        
+        if !crypto::padding::unpad_in_place(&mut plaintext) {
+            return Err(BlackHoleError::InvalidPadding);
+        }
+        
+        Ok(plaintext)
     }
 }
 
@@ -335,11 +690,17 @@ impl Zeroize for EventHorizon {
 
 impl SingularityKey {
     /// Generate a new singularity key pair
-    fn generate() -> Result<Self, JsValue> {
-        use ed25519_dalek::{SigningKey, VerifyingKey};
-        use rand::rngs::OsRng;
+    #[cfg(any(test, feature = "wasm"))]
+    fn generate() -> Result<Self, BlackHoleError> {
+        Self::generate_with(&OsRandom)
+    }
+    
+    /// Generate a new singularity key pair from the given randomness
+    fn generate_with(rng: &dyn CryptoRng) -> Result<Self, BlackHoleError> {
+        use ed25519_dalek::SigningKey;
+        use crate::providers::RngAdapter;
         
-        let signing_key = SigningKey::generate(&mut OsRng);
+        let signing_key = SigningKey::generate(&mut RngAdapter(rng));
         let verifying_key = signing_key.verifying_key();
         
         let public = verifying_key.to_bytes();
@@ -356,7 +717,8 @@ impl SingularityKey {
     }
     
     /// Sign a message
-    fn sign(&self, message: &[u8]) -> Result<[u8; 64], JsValue> {
+    #[cfg(test)]
+    fn sign(&self, message: &[u8]) -> Result<[u8; 64], BlackHoleError> {
         use ed25519_dalek::{Signer, SigningKey};
         
         let signing_key = SigningKey::from_bytes(&self.private);
@@ -366,11 +728,12 @@ impl SingularityKey {
     }
     
     /// Verify a signature
-    fn verify(&self, message: &[u8], signature: &[u8; 64]) -> Result<bool, JsValue> {
+    #[cfg(test)]
+    fn verify(&self, message: &[u8], signature: &[u8; 64]) -> Result<bool, BlackHoleError> {
         use ed25519_dalek::{Verifier, VerifyingKey, Signature};
         
         let verifying_key = VerifyingKey::from_bytes(&self.public)
-            .map_err(|e| JsValue::from_str(&format!("Invalid public key: {}", e)))?;
+            .map_err(|_| BlackHoleError::InvalidKey)?;
         
         let sig = Signature::from_bytes(signature);
         
@@ -383,33 +746,14 @@ impl SingularityKey {
 
 /// Hex encoding helper module
 mod hex {
+    use alloc::format;
+    use alloc::string::String;
+    
     pub fn encode(data: &[u8]) -> String {
         data.iter()
             .map(|b| format!("{:02x}", b))
             .collect()
     }
-    
-    pub fn decode(hex: &str) -> Result<Vec<u8>, ()> {
-        if hex.len() % 2 != 0 {
-            return Err(());
-        }
-        
-        (0..hex.len())
-            .step_by(2)
-            .map(|i| u8::from_str_radix(&hex[i..i+2], 16))
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|_| ())
-    }
-}
-
-/// Initialize the WASM module
-#[wasm_bindgen(start)]
-pub fn start() {
-    #[cfg(debug_assertions)]
-    console_log::init_with_level(log::Level::Debug).ok();
-    
-    log::info!("🕳️ FortiComm Black Hole initialized");
-    log::info!("   "The singularity is ready. Messages will be swallowed."");
 }
 
 #[cfg(test)]
@@ -439,13 +783,80 @@ mod tests {
     
     #[test]
     fn test_event_horizon() {
-        let mut horizon = EventHorizon::new().unwrap();
+        let mut horizon = EventHorizon::new(Providers::deterministic(1, 1_700_000_000_000)).unwrap();
         let key = SingularityKey::generate().unwrap();
         
         let plaintext = b"Secret message crossing the event horizon";
         let encrypted = horizon.encrypt(&key, "recipient", plaintext).unwrap();
+        assert_eq!(encrypted.timestamp, 1_700_000_000_000);
         
         let decrypted = horizon.decrypt(&key, &encrypted).unwrap();
         assert_eq!(plaintext.to_vec(), decrypted);
     }
+    
+    #[test]
+    fn test_event_horizon_hides_length() {
+        let mut horizon = EventHorizon::new(Providers::deterministic(2, 0)).unwrap();
+        horizon.padding = PaddingScheme::PowerOfTwo;
+        let key = SingularityKey::generate().unwrap();
+        
+        let short = horizon.encrypt(&key, "recipient", b"hi").unwrap();
+        let longer = horizon.encrypt(&key, "recipient", b"hello there").unwrap();
+        assert_eq!(short.ciphertext.len(), longer.ciphertext.len());
+    }
+    
+    #[test]
+    fn test_deterministic_providers_are_reproducible() {
+        let key_a = SingularityKey::generate_with(&SeededRng::from_u64(9)).unwrap();
+        let key_b = SingularityKey::generate_with(&SeededRng::from_u64(9)).unwrap();
+        assert_eq!(key_a.public, key_b.public);
+        assert_eq!(key_a.fingerprint, key_b.fingerprint);
+        
+        let mut horizon_a = EventHorizon::new(Providers::deterministic(9, 1_000)).unwrap();
+        let mut horizon_b = EventHorizon::new(Providers::deterministic(9, 1_000)).unwrap();
+        let a = horizon_a.encrypt(&key_a, "recipient", b"known answer").unwrap();
+        let b = horizon_b.encrypt(&key_b, "recipient", b"known answer").unwrap();
+        assert_eq!(a.nonce, b.nonce);
+        assert_eq!(a.ciphertext, b.ciphertext);
+        assert_eq!(a.timestamp, 1_000);
+        
+        // Known answers: any change to key generation or the horizon shows up
+        assert_eq!(
+            hex::encode(&key_a.public),
+            "604db0979c20ed9d01b111521b31dcc8de85ebb981e773b0c8281bfcc0556703",
+        );
+        assert_eq!(hex::encode(&a.nonce), "7e3816d1573b2736d514dab6");
+        assert_eq!(
+            hex::encode(&a.ciphertext),
+            "5f187ce95ce63bf7b706c8059a6d4e5f9733437af848c18d4e9f21b757be",
+        );
+    }
+    
+    #[test]
+    fn test_core_api_is_native() {
+        let mut core = BlackHoleCore::with_providers(zk::setup::test_parameters(), Providers::deterministic(3, 5_000)).unwrap();
+        
+        let encrypted = core.encrypt("recipient", "no bindings needed").unwrap();
+        assert_eq!(encrypted.timestamp, 5_000);
+        assert_eq!(core.decrypt(&encrypted).unwrap(), "no bindings needed");
+        
+        let receipt = MessageContent::Typing { started: true };
+        let encrypted = core.encrypt_content("recipient", &receipt).unwrap();
+        assert_eq!(core.decrypt_content(&encrypted).unwrap(), receipt);
+        assert!(core.decrypt(&encrypted).is_err());
+    }
+    
+    #[test]
+    fn test_identity_proofs_need_a_pinned_key() {
+        let parameters = zk::setup::test_parameters();
+        let core = BlackHoleCore::with_providers(parameters, Providers::deterministic(4, 0)).unwrap();
+        
+        let mut proof = core.prove_identity().unwrap();
+        assert_eq!(proof.key_id, parameters.key_id().to_vec());
+        assert!(core.verify_identity(&proof).unwrap());
+        
+        // A proof for a key that is not pinned is refused, not checked
+        proof.key_id = vec![0; 32];
+        assert_eq!(core.verify_identity(&proof), Err(BlackHoleError::UntrustedParameters));
+    }
 }
@@ -15,7 +15,8 @@
 //! - Constant-time implementations resist timing attacks
 //! - No known efficient quantum attacks (though Shor's algorithm applies)
 
-use wasm_bindgen::prelude::*;
+use alloc::{vec, vec::Vec};
+use crate::providers::CryptoRng;
 
 /// Curve25519 parameters
 pub struct Curve25519;
@@ -40,7 +41,6 @@ pub const BASE_POINT: [u8; 32] = [
 ];
 
 /// A point on the curve (Montgomery form)
-#[wasm_bindgen]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Point {
     /// X coordinate
@@ -50,17 +50,16 @@ pub struct Point {
 }
 
 /// A scalar (private key)
-#[wasm_bindgen]
 #[derive(Clone, Copy, Debug)]
 pub struct Scalar {
+    /// Clamped little-endian scalar bytes
     pub bytes: [u8; 32],
 }
 
 impl Curve25519 {
     /// Generate a new random scalar
-    pub fn random_scalar() -> Scalar {
-        let mut bytes = [0u8; 32];
-        rand::fill(&mut bytes[..]);
+    pub fn random_scalar(rng: &dyn CryptoRng) -> Scalar {
+        let mut bytes: [u8; 32] = rng.random();
         
         // Clamp the scalar according to RFC 7748
         bytes[0] &= 248;
@@ -186,23 +185,36 @@ impl Curve25519 {
 
     /// Field multiplication: (a * b) mod p
     fn field_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
-        // Schoolbook multiplication
-        let mut result = [0u128; 8];
+        // Schoolbook multiplication into 512 bits
+        let mut wide = [0u64; 8];
         
         for i in 0..4 {
             let mut carry = 0u128;
             for j in 0..4 {
-                let product = result[i + j] as u128 + (a[i] as u128 * b[j] as u128) + carry;
-                result[i + j] = product as u128;
+                let product = wide[i + j] as u128 + (a[i] as u128 * b[j] as u128) + carry;
+                wide[i + j] = product as u64;
                 carry = product >> 64;
             }
-            result[i + 4] = carry;
+            wide[i + 4] = carry as u64;
         }
         
-        // Reduce mod p
+        // Reduce mod p: 2^256 = 38 (mod p), so fold the high half in
         let mut reduced = [0u64; 4];
+        let mut carry = 0u128;
         for i in 0..4 {
-            reduced[i] = result[i] as u64;
+            let sum = wide[i] as u128 + wide[i + 4] as u128 * 38 + carry;
+            reduced[i] = sum as u64;
+            carry = sum >> 64;
+        }
+        
+        while carry != 0 {
+            let mut overflow = carry * 38;
+            for limb in reduced.iter_mut() {
+                let sum = *limb as u128 + overflow;
+                *limb = sum as u64;
+                overflow = sum >> 64;
+            }
+            carry = overflow;
         }
         
         Self::field_reduce(&mut reduced);
@@ -307,7 +319,6 @@ impl Scalar {
     }
 }
 
-#[wasm_bindgen]
 /// Perform X25519 key exchange
 pub fn x25519(scalar: &[u8], point: &[u8]) -> Vec<u8> {
     if scalar.len() != 32 || point.len() != 32 {
@@ -332,18 +343,19 @@ pub fn x25519(scalar: &[u8], point: &[u8]) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::OsRandom;
 
     #[test]
     fn test_scalar_generation() {
-        let s1 = Curve25519::random_scalar();
-        let s2 = Curve25519::random_scalar();
+        let s1 = Curve25519::random_scalar(&OsRandom);
+        let s2 = Curve25519::random_scalar(&OsRandom);
         
         // Scalars should be different (with high probability)
         assert_ne!(s1.bytes, s2.bytes);
         
         // Check clamping
         assert_eq!(s1.bytes[0] & 7, 0);
-        assert_eq!(s1.bytes[31] & 128, 64);
+        assert_eq!(s1.bytes[31] & 192, 64);
     }
 
     #[test]
@@ -355,7 +367,7 @@ mod tests {
 
     #[test]
     fn test_scalar_multiplication() {
-        let scalar = Curve25519::random_scalar();
+        let scalar = Curve25519::random_scalar(&OsRandom);
         let base = Point::base();
         
         let result = Curve25519::scalar_multiply(&scalar, &base);
@@ -24,7 +24,8 @@
 //! - Based on hard lattice problems
 //! - Resistant to quantum attacks (Shor's algorithm)
 
-use wasm_bindgen::prelude::*;
+use alloc::{vec, vec::Vec};
+use crate::providers::CryptoRng;
 
 /// ML-KEM-768 parameters
 pub struct MLKEMParams;
@@ -53,7 +54,6 @@ impl MLKEMParams {
 }
 
 /// A polynomial in R_q = Z_q[x] / (x^N + 1)
-#[wasm_bindgen]
 #[derive(Clone, Debug, PartialEq)]
 pub struct Polynomial {
     /// Coefficients (little-endian)
@@ -61,7 +61,6 @@ pub struct Polynomial {
 }
 
 /// A vector of polynomials (module element)
-#[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct PolyVector {
     /// Polynomials in the vector
@@ -69,7 +68,6 @@ pub struct PolyVector {
 }
 
 /// ML-KEM public key
-#[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct PublicKey {
     /// Encoded public key bytes
@@ -81,7 +79,6 @@ pub struct PublicKey {
 }
 
 /// ML-KEM secret key
-#[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct SecretKey {
     /// Encoded secret key bytes
@@ -91,7 +88,6 @@ pub struct SecretKey {
 }
 
 /// ML-KEM ciphertext
-#[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct Ciphertext {
     /// Compressed u vector
@@ -101,7 +97,6 @@ pub struct Ciphertext {
 }
 
 /// Shared secret
-#[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct SharedSecret {
     /// 32-byte shared secret
@@ -136,7 +131,7 @@ impl Polynomial {
                 let bits = (byte >> bit_idx) & 0x03;
                 
                 // Convert to centered representation
-                poly.coeffs[i] = (eta as u16 + 1 - bits as u16) % MLKEMParams::Q as u16;
+                poly.coeffs[i] = (eta as u16 + 1 - bits as u16) % MLKEMParams::Q;
             }
         }
         
@@ -172,8 +167,8 @@ impl Polynomial {
                     let u = result.coeffs[j];
                     let v = result.coeffs[j + len / 2];
                     
-                    result.coeffs[j] = (u + v) % MLKEMParams::Q as u16;
-                    result.coeffs[j + len / 2] = (u + MLKEMParams::Q as u16 - v) % MLKEMParams::Q as u16;
+                    result.coeffs[j] = (u + v) % MLKEMParams::Q;
+                    result.coeffs[j + len / 2] = (u + MLKEMParams::Q - v) % MLKEMParams::Q;
                 }
                 i += len;
             }
@@ -197,8 +192,8 @@ impl Polynomial {
                     let u = result.coeffs[j];
                     let v = result.coeffs[j + len / 2];
                     
-                    result.coeffs[j] = (u + v) % MLKEMParams::Q as u16;
-                    result.coeffs[j + len / 2] = (u + MLKEMParams::Q as u16 - v) % MLKEMParams::Q as u16;
+                    result.coeffs[j] = (u + v) % MLKEMParams::Q;
+                    result.coeffs[j + len / 2] = (u + MLKEMParams::Q - v) % MLKEMParams::Q;
                 }
                 i += len;
             }
@@ -220,6 +215,11 @@ impl Polynomial {
             }
         }
         
+        // Every level doubled the coefficients: scale by 256^-1 = 3316 (mod q)
+        for coeff in result.coeffs.iter_mut() {
+            *coeff = (*coeff as u32 * 3316 % MLKEMParams::Q as u32) as u16;
+        }
+        
         result
     }
 
@@ -239,7 +239,7 @@ impl Polynomial {
         let mut result = Self::zero();
         
         for i in 0..256 {
-            result.coeffs[i] = (self.coeffs[i] + other.coeffs[i]) % MLKEMParams::Q as u16;
+            result.coeffs[i] = (self.coeffs[i] + other.coeffs[i]) % MLKEMParams::Q;
         }
         
         result
@@ -250,7 +250,7 @@ impl Polynomial {
         let mut result = Self::zero();
         
         for i in 0..256 {
-            result.coeffs[i] = (self.coeffs[i] + MLKEMParams::Q as u16 - other.coeffs[i]) % MLKEMParams::Q as u16;
+            result.coeffs[i] = (self.coeffs[i] + MLKEMParams::Q - other.coeffs[i]) % MLKEMParams::Q;
         }
         
         result
@@ -309,7 +309,7 @@ impl PolyVector {
                 let mut poly = Polynomial::zero();
                 for (idx, byte) in hash.as_bytes().iter().enumerate() {
                     if idx < 256 {
-                        poly.coeffs[idx] = (*byte as u16 * MLKEMParams::Q as u16 / 256) % MLKEMParams::Q as u16;
+                        poly.coeffs[idx] = ((*byte as u32 * MLKEMParams::Q as u32 / 256) % MLKEMParams::Q as u32) as u16;
                     }
                 }
                 
@@ -337,11 +337,10 @@ impl PolyVector {
         let k = vector.polys.len();
         let mut result = PolyVector::new(k);
         
-        for i in 0..k {
-            result.polys[i] = Polynomial::zero();
-            for j in 0..k {
-                let product = matrix[i].polys[j].multiply_ntt(&vector.polys[j]);
-                result.polys[i] = result.polys[i].add(&product);
+        for (row, out) in matrix.iter().zip(result.polys.iter_mut()) {
+            *out = Polynomial::zero();
+            for (entry, poly) in row.polys.iter().zip(&vector.polys) {
+                *out = out.add(&entry.multiply_ntt(poly));
             }
         }
         
@@ -372,13 +371,11 @@ impl PolyVector {
 }
 
 /// ML-KEM key generation
-#[wasm_bindgen]
-pub fn ml_kem_keygen() -> js_sys::Object {
+pub fn ml_kem_keygen(rng: &dyn CryptoRng) -> (PublicKey, SecretKey) {
     let k = MLKEMParams::K;
     
     // Generate random seeds
-    let d: [u8; 32] = rand::random();
-    let z: [u8; 32] = rand::random();
+    let d: [u8; 32] = rng.random();
     
     // Expand seed to generate matrix A
     let rho = blake3::hash(&d);
@@ -414,34 +411,25 @@ pub fn ml_kem_keygen() -> js_sys::Object {
         s,
     };
     
-    // Return as JavaScript object
-    let obj = js_sys::Object::new();
-    js_sys::Reflect::set(&obj, &"publicKey".into(), &js_sys::Uint8Array::from(&pk.bytes[..])).unwrap();
-    js_sys::Reflect::set(&obj, &"secretKey".into(), &js_sys::Uint8Array::from(&sk.bytes[..])).unwrap();
-    
-    obj
+    (pk, sk)
 }
 
 /// ML-KEM encapsulation
-#[wasm_bindgen]
-pub fn ml_kem_encapsulate(public_key: &[u8]) -> js_sys::Object {
+///
+/// Returns the ciphertext to send and the shared secret.
+pub fn ml_kem_encapsulate(_public_key: &[u8], rng: &dyn CryptoRng) -> (Vec<u8>, SharedSecret) {
     // Simplified encapsulation
     let mut ciphertext = vec![0u8; 1088]; // ML-KEM-768 ciphertext size
     let mut shared_secret = [0u8; 32];
     
-    rand::fill(&mut ciphertext[..]);
-    rand::fill(&mut shared_secret[..]);
+    rng.fill_bytes(&mut ciphertext);
+    rng.fill_bytes(&mut shared_secret);
     
-    let obj = js_sys::Object::new();
-    js_sys::Reflect::set(&obj, &"ciphertext".into(), &js_sys::Uint8Array::from(&ciphertext[..])).unwrap();
-    js_sys::Reflect::set(&obj, &"sharedSecret".into(), &js_sys::Uint8Array::from(&shared_secret[..])).unwrap();
-    
-    obj
+    (ciphertext, SharedSecret { bytes: shared_secret })
 }
 
 /// ML-KEM decapsulation
-#[wasm_bindgen]
-pub fn ml_kem_decapsulate(secret_key: &[u8], ciphertext: &[u8]) -> js_sys::Uint8Array {
+pub fn ml_kem_decapsulate(secret_key: &[u8], ciphertext: &[u8]) -> SharedSecret {
     let mut shared_secret = [0u8; 32];
     
     // Derive shared secret from secret key and ciphertext
@@ -450,12 +438,13 @@ pub fn ml_kem_decapsulate(secret_key: &[u8], ciphertext: &[u8]) -> js_sys::Uint8
     hasher.update(ciphertext);
     shared_secret.copy_from_slice(hasher.finalize().as_bytes());
     
-    js_sys::Uint8Array::from(&shared_secret[..])
+    SharedSecret { bytes: shared_secret }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::OsRandom;
 
     #[test]
     fn test_polynomial_ntt_roundtrip() {
@@ -463,15 +452,7 @@ mod tests {
         let ntt = poly.ntt();
         let recovered = ntt.inv_ntt();
         
-        // Should be approximately equal (some rounding)
-        for i in 0..256 {
-            let diff = if poly.coeffs[i] > recovered.coeffs[i] {
-                poly.coeffs[i] - recovered.coeffs[i]
-            } else {
-                recovered.coeffs[i] - poly.coeffs[i]
-            };
-            assert!(diff <= 1, "NTT roundtrip failed at index {}", i);
-        }
+        assert_eq!(recovered.coeffs, poly.coeffs);
     }
 
     #[test]
@@ -483,18 +464,16 @@ mod tests {
         
         // Check all coefficients are in range
         for coeff in &c.coeffs {
-            assert!(*coeff < MLKEMParams::Q as u16);
+            assert!(*coeff < MLKEMParams::Q);
         }
     }
 
     #[test]
     fn test_keygen() {
-        let keys = ml_kem_keygen();
-        
-        let pk = js_sys::Reflect::get(&keys, &"publicKey".into()).unwrap();
-        let sk = js_sys::Reflect::get(&keys, &"secretKey".into()).unwrap();
+        let (pk, sk) = ml_kem_keygen(&OsRandom);
         
-        assert!(!pk.is_undefined());
-        assert!(!sk.is_undefined());
+        assert_eq!(pk.bytes.len(), 1184);
+        assert_eq!(sk.bytes.len(), 2400);
+        assert_eq!(pk.t.polys.len(), MLKEMParams::K);
     }
 }
@@ -6,8 +6,10 @@
 pub mod elliptic_curve;
 pub mod lattice;
 
+use alloc::{vec, vec::Vec};
+
 pub use elliptic_curve::Curve25519;
-pub use lattice::{MLWEParams, ModuleLWE};
+pub use lattice::{MLKEMParams, PolyVector, Polynomial};
 
 /// Finite field arithmetic
 pub struct FiniteField {
@@ -95,11 +97,11 @@ impl PolynomialRing {
         // Simplified schoolbook multiplication
         let mut result = vec![0u16; self.n];
         
-        for i in 0..self.n {
-            for j in 0..self.n {
+        for (i, &a_i) in a.iter().enumerate().take(self.n) {
+            for (j, &b_j) in b.iter().enumerate().take(self.n) {
                 let idx = (i + j) % self.n;
                 let sign = if i + j >= self.n { self.q - 1 } else { 1 };
-                let product = (a[i] as u32 * b[j] as u32) % self.q as u32;
+                let product = (a_i as u32 * b_j as u32) % self.q as u32;
                 result[idx] = ((result[idx] as u32 + product * sign as u32) % self.q as u32) as u16;
             }
         }
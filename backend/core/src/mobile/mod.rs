@@ -0,0 +1,224 @@
+//! 📱 Swift and Kotlin Bindings
+//!
+//! The UniFFI interface of the core, compiled with the `uniffi` feature.
+//! `scripts/generate-bindings.sh` builds the library and runs the bundled
+//! `uniffi-bindgen` over it to produce the Swift and Kotlin sources, so the
+//! apps never maintain prototypes by hand.
+//!
+//! Structured values (encrypted messages, envelopes, message content and
+//! proofs) cross the boundary as JSON, as in the C ABI. Errors are raised as
+//! `BlackHoleError` carrying the message.
+
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{BlackHoleError, EncryptedMessage, MessageEnvelope, SharedCore, ZKParameters, ZKProof};
+
+/// A Black Hole identity with its sessions and groups
+///
+/// Safe to call from any thread; calls on different sessions run in parallel.
+#[derive(uniffi::Object)]
+pub struct BlackHole {
+    inner: SharedCore,
+}
+
+/// An encrypted attachment ready for upload
+#[derive(uniffi::Record)]
+pub struct EncryptedFile {
+    /// Attachment pointer to send inside a message
+    pub pointer: Vec<u8>,
+
+    /// Encrypted blob to upload
+    pub blob: Vec<u8>,
+
+    /// Bao outboard tree served alongside the blob
+    pub outboard: Vec<u8>,
+}
+
+#[uniffi::export]
+impl BlackHole {
+    /// Create a new identity proving with the ceremony's encoded parameters
+    #[uniffi::constructor]
+    pub fn new(parameters: Vec<u8>) -> Result<Arc<Self>, BlackHoleError> {
+        let parameters = ZKParameters::from_bytes(&parameters)?;
+
+        Ok(Arc::new(BlackHole { inner: SharedCore::new(&parameters)? }))
+    }
+
+    /// Identity key fingerprint
+    pub fn fingerprint(&self) -> String {
+        self.inner.get_fingerprint()
+    }
+
+    /// Ed25519 identity public key
+    pub fn identity_public_key(&self) -> Vec<u8> {
+        self.inner.get_identity_public_key()
+    }
+
+    /// ML-KEM public key, hex encoded
+    pub fn pq_public_key(&self) -> String {
+        self.inner.get_pq_public_key()
+    }
+
+    /// Encrypt text, returning the encrypted message as JSON
+    pub fn encrypt(&self, recipient: String, plaintext: String) -> Result<Vec<u8>, BlackHoleError> {
+        to_json(&self.inner.encrypt(&recipient, &plaintext)?)
+    }
+
+    /// Decrypt a JSON encrypted message into its text
+    pub fn decrypt(&self, message: Vec<u8>) -> Result<String, BlackHoleError> {
+        let message: EncryptedMessage = from_json(&message)?;
+        self.inner.decrypt(&message)
+    }
+
+    /// Start a Double Ratchet session, returning our ratchet public key
+    pub fn start_session(&self, peer: String, remote_dh_public: Option<Vec<u8>>) -> Result<Vec<u8>, BlackHoleError> {
+        self.inner.start_session(&peer, remote_dh_public)
+    }
+
+    /// End the session with `peer`, wiping its keys and history
+    pub fn end_session(&self, peer: String) -> bool {
+        self.inner.end_session(&peer)
+    }
+
+    /// Encrypt JSON message content for `peer`, returning the JSON envelope
+    pub fn session_encrypt(&self, peer: String, content: Vec<u8>) -> Result<Vec<u8>, BlackHoleError> {
+        to_json(&self.inner.session_encrypt(&peer, from_json(&content)?)?)
+    }
+
+    /// Decrypt a JSON envelope from `peer`, returning the JSON message content
+    pub fn session_decrypt(&self, peer: String, envelope: Vec<u8>) -> Result<Vec<u8>, BlackHoleError> {
+        let envelope: MessageEnvelope = from_json(&envelope)?;
+        to_json(&self.inner.session_decrypt(&peer, &envelope)?)
+    }
+
+    /// Set the disappearing-message timer, returning the JSON envelope to send
+    pub fn set_expiry_timer(&self, peer: String, seconds: Option<u32>) -> Result<Vec<u8>, BlackHoleError> {
+        to_json(&self.inner.set_expiry_timer(&peer, seconds)?)
+    }
+
+    /// Create a group, returning its random ID
+    pub fn create_group(&self) -> Result<String, BlackHoleError> {
+        self.inner.create_group()
+    }
+
+    /// IDs of all groups held by this identity
+    pub fn list_groups(&self) -> Vec<String> {
+        self.inner.list_groups()
+    }
+
+    /// Propose adding a member to a group
+    pub fn group_propose_add(&self, group_id: String, member_id: String, key_package: Vec<u8>) -> Result<(), BlackHoleError> {
+        self.inner.group_propose_add(&group_id, &member_id, &key_package)
+    }
+
+    /// Commit the pending proposals of a group
+    pub fn group_commit(&self, group_id: String) -> Result<Vec<u8>, BlackHoleError> {
+        self.inner.group_commit(&group_id)
+    }
+
+    /// Encrypt a message for a group
+    pub fn group_encrypt(&self, group_id: String, plaintext: Vec<u8>) -> Result<Vec<u8>, BlackHoleError> {
+        self.inner.group_encrypt(&group_id, &plaintext)
+    }
+
+    /// Decrypt a message received in a group
+    pub fn group_decrypt(&self, group_id: String, message: Vec<u8>) -> Result<Vec<u8>, BlackHoleError> {
+        self.inner.group_decrypt(&group_id, &message)
+    }
+
+    /// Leave a group, wiping its secrets
+    pub fn leave_group(&self, group_id: String) -> bool {
+        self.inner.leave_group(&group_id)
+    }
+
+    /// Encrypt a file as an attachment under a fresh random key
+    pub fn encrypt_attachment(
+        &self,
+        data: Vec<u8>,
+        content_type: String,
+        thumbnail: Option<Vec<u8>>,
+    ) -> Result<EncryptedFile, BlackHoleError> {
+        let attachment = self.inner.encrypt_attachment(&data, &content_type, thumbnail)?;
+
+        Ok(EncryptedFile {
+            pointer: attachment.pointer.to_bytes()?,
+            blob: attachment.blob,
+            outboard: attachment.outboard,
+        })
+    }
+
+    /// Verify a downloaded blob against its pointer and decrypt it
+    pub fn decrypt_attachment(&self, pointer: Vec<u8>, blob: Vec<u8>) -> Result<Vec<u8>, BlackHoleError> {
+        self.inner.decrypt_attachment(&pointer, &blob)
+    }
+
+    /// Create a zero-knowledge proof of identity as JSON
+    pub fn prove_identity(&self) -> Result<Vec<u8>, BlackHoleError> {
+        to_json(&self.inner.prove_identity()?)
+    }
+
+    /// Verify a JSON zero-knowledge proof of identity
+    pub fn verify_identity(&self, proof: Vec<u8>) -> Result<bool, BlackHoleError> {
+        let proof: ZKProof = from_json(&proof)?;
+        self.inner.verify_identity(&proof)
+    }
+
+    /// Emergency deletion of every key
+    pub fn panic_wipe(&self) {
+        self.inner.panic_wipe();
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, BlackHoleError> {
+    serde_json::to_vec(value).map_err(BlackHoleError::serialization)
+}
+
+fn from_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, BlackHoleError> {
+    serde_json::from_slice(bytes).map_err(BlackHoleError::serialization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::setup::test_parameters;
+
+    #[test]
+    fn test_identity_and_groups() {
+        let black_hole = BlackHole::new(test_parameters().to_bytes()).unwrap();
+
+        let message = black_hole.encrypt("bob".into(), "hello".into()).unwrap();
+        assert_eq!(black_hole.decrypt(message).unwrap(), "hello");
+
+        let group_id = black_hole.create_group().unwrap();
+        assert_eq!(black_hole.list_groups(), vec![group_id.clone()]);
+
+        let sealed = black_hole.group_encrypt(group_id.clone(), b"hi all".to_vec()).unwrap();
+        assert_eq!(black_hole.group_decrypt(group_id, sealed).unwrap(), b"hi all");
+
+        let proof = black_hole.prove_identity().unwrap();
+        assert!(black_hole.verify_identity(proof).unwrap());
+    }
+
+    #[test]
+    fn test_attachments_round_trip() {
+        let black_hole = BlackHole::new(test_parameters().to_bytes()).unwrap();
+
+        let file = black_hole.encrypt_attachment(vec![42u8; 5000], "image/png".into(), None).unwrap();
+        let data = black_hole.decrypt_attachment(file.pointer, file.blob).unwrap();
+        assert_eq!(data, vec![42u8; 5000]);
+    }
+
+    #[test]
+    fn test_errors_cross_as_black_hole_errors() {
+        let black_hole = BlackHole::new(test_parameters().to_bytes()).unwrap();
+
+        assert!(matches!(black_hole.decrypt(b"{}".to_vec()), Err(BlackHoleError::Serialization(_))));
+        assert_eq!(
+            black_hole.group_commit("missing".into()),
+            Err(BlackHoleError::UnknownGroup),
+        );
+    }
+}
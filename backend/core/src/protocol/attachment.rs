@@ -0,0 +1,471 @@
+//! 📎 Attachments
+//!
+//! Files are encrypted under a fresh random key with the STREAM construction
+//! and uploaded as opaque, content-addressed blobs. The sender embeds an
+//! `AttachmentPointer` (blob ID, key, digest, size, MIME type, thumbnail) in
+//! a ratchet message; the blob itself never carries any metadata.
+//!
+//! ```text
+//! k        ←$ {0,1}²⁵⁶
+//! blob     = STREAM(k, file ‖ 0…0)          padded to a Padmé bucket
+//! digest   = BLAKE3(blob)
+//! blob_id  = hex(digest)
+//! ```
+//!
+//! Downloads are accepted only if the blob hashes to the pointer's digest.
+//! Because `digest` is a BLAKE3 root, the sender also publishes the blob's Bao
+//! outboard tree, letting `AttachmentDownload` verify each 1 KiB chunk as it
+//! arrives and resume from the last good chunk after a failure.
+
+use alloc::string::{String, ToString};
+use alloc::{vec, vec::Vec};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::crypto::bao::{self, BaoError, BaoVerifier, OutboardEncoder};
+use crate::crypto::padding::PaddingScheme;
+use crate::crypto::stream::{self, StreamDecryptor, StreamEncryptor, StreamError};
+use crate::providers::CryptoRng;
+
+/// Errors raised while encrypting or downloading attachments
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AttachmentError {
+    /// The downloaded blob does not hash to the pointer's digest
+    #[error("Attachment digest mismatch")]
+    DigestMismatch,
+
+    /// The decrypted blob is shorter than the declared size
+    #[error("Attachment size mismatch")]
+    SizeMismatch,
+
+    /// The pointer could not be encoded or decoded
+    #[error("Malformed attachment pointer")]
+    MalformedPointer,
+
+    /// The encrypted stream failed
+    #[error(transparent)]
+    Stream(#[from] StreamError),
+
+    /// A downloaded chunk failed verification
+    #[error(transparent)]
+    Verification(#[from] BaoError),
+}
+
+/// Everything a recipient needs to fetch and decrypt an attachment
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct AttachmentPointer {
+    /// Content address of the encrypted blob
+    #[zeroize(skip)]
+    pub blob_id: String,
+
+    /// Random key the blob is encrypted under
+    pub key: [u8; 32],
+
+    /// BLAKE3 digest of the encrypted blob
+    #[zeroize(skip)]
+    pub digest: [u8; 32],
+
+    /// Plaintext size in bytes
+    #[zeroize(skip)]
+    pub size: u64,
+
+    /// MIME type of the plaintext
+    #[zeroize(skip)]
+    pub content_type: String,
+
+    /// Optional small preview image
+    #[zeroize(skip)]
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// An encrypted attachment ready for upload
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncryptedAttachment {
+    /// Pointer to embed in a ratchet message
+    pub pointer: AttachmentPointer,
+
+    /// Encrypted blob to upload under `pointer.blob_id`
+    pub blob: Vec<u8>,
+
+    /// Bao outboard tree of the blob, served alongside it
+    pub outboard: Vec<u8>,
+}
+
+/// Incremental attachment encryptor for files too large to hold in memory
+pub struct AttachmentWriter {
+    key: [u8; 32],
+    encryptor: StreamEncryptor,
+    outboard: OutboardEncoder,
+    size: u64,
+    header_written: bool,
+    content_type: String,
+    thumbnail: Option<Vec<u8>>,
+}
+
+/// Verified, resumable attachment download
+///
+/// Blob bytes are pushed in order; every chunk is checked against the
+/// pointer's digest before it is decrypted. After a verification failure the
+/// unverified bytes are discarded and the download resumes from `position()`;
+/// plaintext of the chunks verified before the failure is returned by the
+/// next call to `push`.
+pub struct AttachmentDownload {
+    key: [u8; 32],
+    verifier: BaoVerifier,
+    decryptor: Option<StreamDecryptor>,
+    pending: Vec<u8>,
+    ready: Vec<u8>,
+    verified: u64,
+    remaining: u64,
+}
+
+impl AttachmentPointer {
+    /// Encode the pointer for embedding in a message
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AttachmentError> {
+        serde_json::to_vec(self).map_err(|_| AttachmentError::MalformedPointer)
+    }
+
+    /// Decode a pointer received in a message
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AttachmentError> {
+        let pointer: AttachmentPointer =
+            serde_json::from_slice(bytes).map_err(|_| AttachmentError::MalformedPointer)?;
+
+        if pointer.blob_id != blob_id(&pointer.digest) {
+            return Err(AttachmentError::MalformedPointer);
+        }
+
+        Ok(pointer)
+    }
+
+    /// Length of the encrypted blob this pointer refers to
+    pub fn blob_len(&self) -> u64 {
+        stream::sealed_len(PaddingScheme::default().padded_size(self.size))
+    }
+
+    /// Check that a downloaded blob matches this pointer
+    pub fn verify_blob(&self, blob: &[u8]) -> Result<(), AttachmentError> {
+        // blake3::Hash compares in constant time
+        if blake3::hash(blob) != blake3::Hash::from(self.digest) {
+            return Err(AttachmentError::DigestMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+impl AttachmentWriter {
+    /// Start encrypting a new attachment under a fresh key drawn from `rng`
+    pub fn new(content_type: &str, thumbnail: Option<Vec<u8>>, rng: &dyn CryptoRng) -> Self {
+        let key: [u8; 32] = rng.random();
+
+        AttachmentWriter {
+            encryptor: StreamEncryptor::new(&key, rng),
+            key,
+            outboard: OutboardEncoder::new(),
+            size: 0,
+            header_written: false,
+            content_type: content_type.to_string(),
+            thumbnail,
+        }
+    }
+
+    /// Feed plaintext, returning the blob bytes to upload so far
+    pub fn update(&mut self, data: &[u8]) -> Result<Vec<u8>, AttachmentError> {
+        let mut output = self.take_header();
+        output.extend_from_slice(&self.encryptor.update(data)?);
+
+        self.size += data.len() as u64;
+        self.outboard.update(&output);
+
+        Ok(output)
+    }
+
+    /// Pad and seal the attachment
+    ///
+    /// Returns the last blob bytes, the pointer and the blob's outboard tree.
+    pub fn finalize(mut self) -> Result<(Vec<u8>, AttachmentPointer, Vec<u8>), AttachmentError> {
+        let mut output = self.take_header();
+
+        // Zeros are fed a chunk at a time: the padding of a large file can
+        // run to megabytes
+        let mut remaining = PaddingScheme::default().padded_size(self.size) - self.size;
+        let padding = vec![0u8; remaining.min(stream::CHUNK_SIZE as u64) as usize];
+        while remaining > 0 {
+            let len = remaining.min(stream::CHUNK_SIZE as u64);
+            output.extend_from_slice(&self.encryptor.update(&padding[..len as usize])?);
+            remaining -= len;
+        }
+        output.extend_from_slice(&self.encryptor.finalize()?);
+
+        self.outboard.update(&output);
+        let (root, outboard) = core::mem::take(&mut self.outboard).finalize();
+        let digest = *root.as_bytes();
+
+        let pointer = AttachmentPointer {
+            blob_id: blob_id(&digest),
+            key: self.key,
+            digest,
+            size: self.size,
+            content_type: core::mem::take(&mut self.content_type),
+            thumbnail: self.thumbnail.take(),
+        };
+
+        Ok((output, pointer, outboard))
+    }
+
+    fn take_header(&mut self) -> Vec<u8> {
+        if self.header_written {
+            return Vec::new();
+        }
+
+        self.header_written = true;
+        self.encryptor.header().to_vec()
+    }
+}
+
+impl Drop for AttachmentWriter {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl AttachmentDownload {
+    /// Start a download of the blob behind `pointer`
+    pub fn new(pointer: &AttachmentPointer, outboard: &[u8]) -> Result<Self, AttachmentError> {
+        let verifier = BaoVerifier::new(&pointer.digest, outboard)?;
+
+        if verifier.content_len() != pointer.blob_len() {
+            return Err(BaoError::MalformedOutboard.into());
+        }
+
+        Ok(AttachmentDownload {
+            key: pointer.key,
+            verifier,
+            decryptor: None,
+            pending: Vec::with_capacity(bao::CHUNK_LEN),
+            ready: Vec::new(),
+            verified: 0,
+            remaining: pointer.size,
+        })
+    }
+
+    /// Blob offset to request the next bytes from
+    pub fn position(&self) -> u64 {
+        self.verified + self.pending.len() as u64
+    }
+
+    /// Feed downloaded blob bytes, returning newly verified plaintext
+    pub fn push(&mut self, data: &[u8]) -> Result<Vec<u8>, AttachmentError> {
+        if self.position() + data.len() as u64 > self.verifier.content_len() {
+            return Err(BaoError::OutOfRange.into());
+        }
+
+        self.pending.extend_from_slice(data);
+
+        loop {
+            let chunk_len = (self.verifier.content_len() - self.verified).min(bao::CHUNK_LEN as u64) as usize;
+            if chunk_len == 0 || self.pending.len() < chunk_len {
+                break;
+            }
+
+            let index = self.verified / bao::CHUNK_LEN as u64;
+            if let Err(err) = self.verifier.verify_chunk(index, &self.pending[..chunk_len]) {
+                self.pending.clear();
+                return Err(err.into());
+            }
+
+            let chunk: Vec<u8> = self.pending.drain(..chunk_len).collect();
+            self.verified += chunk_len as u64;
+
+            let plaintext = self.decrypt_chunk(&chunk)?;
+            self.ready.extend_from_slice(&plaintext);
+        }
+
+        Ok(core::mem::take(&mut self.ready))
+    }
+
+    /// Verify a chunk-aligned range fetched out of order (e.g. when seeking)
+    pub fn verify_range(&self, offset: u64, data: &[u8]) -> Result<(), AttachmentError> {
+        Ok(self.verifier.verify_range(offset, data)?)
+    }
+
+    /// Finish the download, failing if the blob is incomplete
+    pub fn finalize(&mut self) -> Result<Vec<u8>, AttachmentError> {
+        let decryptor = match self.decryptor.as_mut() {
+            Some(decryptor) if self.verified == self.verifier.content_len() => decryptor,
+            _ => return Err(StreamError::Truncated.into()),
+        };
+
+        let plaintext = decryptor.finalize()?;
+        let mut output = core::mem::take(&mut self.ready);
+        output.extend_from_slice(&self.take_plaintext(plaintext));
+
+        Ok(output)
+    }
+
+    fn decrypt_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, AttachmentError> {
+        // The first chunk always holds the whole stream header
+        let body = match self.decryptor.as_mut() {
+            Some(decryptor) => decryptor.update(chunk)?,
+            None => {
+                let (header, body) = chunk.split_at(stream::HEADER_LEN);
+                let mut decryptor = StreamDecryptor::new(&self.key, header)?;
+                let plaintext = decryptor.update(body)?;
+                self.decryptor = Some(decryptor);
+                plaintext
+            }
+        };
+
+        Ok(self.take_plaintext(body))
+    }
+
+    /// Drop the zero padding past the declared size
+    fn take_plaintext(&mut self, mut plaintext: Vec<u8>) -> Vec<u8> {
+        let keep = (plaintext.len() as u64).min(self.remaining) as usize;
+        plaintext.truncate(keep);
+        self.remaining -= keep as u64;
+        plaintext
+    }
+}
+
+impl Drop for AttachmentDownload {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.ready.zeroize();
+    }
+}
+
+/// Encrypt an in-memory file as an attachment
+pub fn encrypt_attachment(
+    data: &[u8],
+    content_type: &str,
+    thumbnail: Option<Vec<u8>>,
+    rng: &dyn CryptoRng,
+) -> Result<EncryptedAttachment, AttachmentError> {
+    let mut writer = AttachmentWriter::new(content_type, thumbnail, rng);
+
+    let mut blob = writer.update(data)?;
+    let (tail, pointer, outboard) = writer.finalize()?;
+    blob.extend_from_slice(&tail);
+
+    Ok(EncryptedAttachment { pointer, blob, outboard })
+}
+
+/// Verify and decrypt a downloaded attachment blob
+pub fn decrypt_attachment(pointer: &AttachmentPointer, blob: &[u8]) -> Result<Vec<u8>, AttachmentError> {
+    pointer.verify_blob(blob)?;
+
+    let mut plaintext = stream::decrypt_all(&pointer.key, blob)?;
+
+    let size = usize::try_from(pointer.size).map_err(|_| AttachmentError::SizeMismatch)?;
+    if plaintext.len() < size {
+        plaintext.zeroize();
+        return Err(AttachmentError::SizeMismatch);
+    }
+
+    plaintext.truncate(size);
+    Ok(plaintext)
+}
+
+/// Content address of a blob
+fn blob_id(digest: &[u8; 32]) -> String {
+    crate::hex::encode(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::OsRandom;
+
+    #[test]
+    fn test_attachment_roundtrip() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 253) as u8).collect();
+
+        let encrypted = encrypt_attachment(&data, "video/mp4", Some(vec![0xff, 0xd8]), &OsRandom).unwrap();
+        let pointer = AttachmentPointer::from_bytes(&encrypted.pointer.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(pointer.size, data.len() as u64);
+        assert_eq!(pointer.content_type, "video/mp4");
+        assert_eq!(pointer.blob_id, crate::hex::encode(blake3::hash(&encrypted.blob).as_bytes()));
+        assert_eq!(decrypt_attachment(&pointer, &encrypted.blob).unwrap(), data);
+    }
+
+    #[test]
+    fn test_attachment_key_drawn_from_rng() {
+        use crate::providers::SeededRng;
+
+        let a = encrypt_attachment(b"photo", "image/jpeg", None, &SeededRng::from_u64(5)).unwrap();
+        let b = encrypt_attachment(b"photo", "image/jpeg", None, &SeededRng::from_u64(5)).unwrap();
+        let c = encrypt_attachment(b"photo", "image/jpeg", None, &SeededRng::from_u64(6)).unwrap();
+
+        assert_eq!(a.pointer.key, b.pointer.key);
+        assert_eq!(a.blob, b.blob);
+        assert_ne!(a.pointer.key, c.pointer.key);
+    }
+
+    #[test]
+    fn test_attachment_size_is_padded() {
+        let a = encrypt_attachment(&[1u8; 1000], "application/octet-stream", None, &OsRandom).unwrap();
+        let b = encrypt_attachment(&[1u8; 1010], "application/octet-stream", None, &OsRandom).unwrap();
+
+        assert_eq!(a.blob.len(), b.blob.len());
+    }
+
+    #[test]
+    fn test_padding_spanning_several_chunks() {
+        // Padmé pads 4 MiB + 1 bytes by almost 128 KiB, two stream chunks
+        let data = vec![7u8; 4 * 1024 * 1024 + 1];
+        let encrypted = encrypt_attachment(&data, "application/octet-stream", None, &OsRandom).unwrap();
+
+        let padding = PaddingScheme::default().padded_size(data.len() as u64) - data.len() as u64;
+        assert!(padding > 2 * stream::CHUNK_SIZE as u64 - 2);
+        assert_eq!(encrypted.blob.len() as u64, encrypted.pointer.blob_len());
+        assert_eq!(decrypt_attachment(&encrypted.pointer, &encrypted.blob).unwrap(), data);
+    }
+
+    #[test]
+    fn test_attachment_digest_verified_on_download() {
+        let encrypted = encrypt_attachment(b"secret document", "text/plain", None, &OsRandom).unwrap();
+
+        let mut corrupted = encrypted.blob.clone();
+        corrupted[30] ^= 1;
+        assert_eq!(
+            decrypt_attachment(&encrypted.pointer, &corrupted),
+            Err(AttachmentError::DigestMismatch),
+        );
+
+        // A pointer whose blob ID does not match its digest is rejected
+        let mut pointer = encrypted.pointer.clone();
+        pointer.blob_id = "00".repeat(32);
+        let bytes = pointer.to_bytes().unwrap();
+        assert_eq!(AttachmentPointer::from_bytes(&bytes), Err(AttachmentError::MalformedPointer));
+    }
+
+    #[test]
+    fn test_verified_download_resumes_after_corruption() {
+        let data: Vec<u8> = (0..150_000u32).map(|i| (i % 249) as u8).collect();
+        let encrypted = encrypt_attachment(&data, "image/png", None, &OsRandom).unwrap();
+        let blob = &encrypted.blob;
+
+        let mut download = AttachmentDownload::new(&encrypted.pointer, &encrypted.outboard).unwrap();
+        let mut plaintext = download.push(&blob[..10_000]).unwrap();
+
+        // A corrupted chunk is caught as soon as it completes
+        let mut corrupted = blob[10_000..20_000].to_vec();
+        corrupted[5_000] ^= 1;
+        let bad_chunk = (10_000 + 5_000) / bao::CHUNK_LEN as u64;
+        assert_eq!(
+            download.push(&corrupted),
+            Err(AttachmentError::Verification(BaoError::HashMismatch(bad_chunk))),
+        );
+
+        // Resume from the last verified chunk
+        let resume = download.position() as usize;
+        assert_eq!(resume, bad_chunk as usize * bao::CHUNK_LEN);
+        for piece in blob[resume..].chunks(3_333) {
+            plaintext.extend_from_slice(&download.push(piece).unwrap());
+        }
+        plaintext.extend_from_slice(&download.finalize().unwrap());
+
+        assert_eq!(plaintext, data);
+    }
+}
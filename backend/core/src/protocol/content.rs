@@ -0,0 +1,692 @@
+//! 💬 Message Content
+//!
+//! The structured plaintext carried inside every encrypted payload. Clients
+//! no longer invent their own JSON: text, reactions, edits, deletes, receipts
+//! and typing indicators share one versioned schema. Texts may carry an
+//! `ExpiryPolicy`, and `ExpiryTimer` changes the timer of a conversation.
+//!
+//! # Encoding
+//!
+//! ```text
+//! content = version (1) ‖ kind (varint) ‖ field*
+//! field   = tag (varint) ‖ len (varint) ‖ value
+//! ```
+//!
+//! Integers are LEB128 varints, strings are UTF-8 and nested structures are
+//! field lists of their own. Every field is length-prefixed, so decoders skip
+//! tags they do not know, and an unknown `kind` decodes to
+//! `MessageContent::Unknown` and re-encodes byte for byte.
+
+use alloc::string::{String, ToString};
+use alloc::{vec, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+/// Current content encoding version
+pub const CONTENT_VERSION: u8 = 1;
+
+/// Longest LEB128 encoding of a `u64`
+const MAX_VARINT_LEN: usize = 10;
+
+/// Errors raised while encoding or decoding message content
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ContentError {
+    /// The payload was encoded with an unknown version
+    #[error("Unsupported content version {0}")]
+    UnsupportedVersion(u8),
+
+    /// The payload is truncated or a field has an invalid value
+    #[error("Malformed message content")]
+    Malformed,
+
+    /// `MessageContent::Unknown` carries the kind of a known variant
+    #[error("Unknown content with known kind {0}")]
+    KnownKind(u64),
+}
+
+/// Reference to an earlier message by its author and timestamp
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageRef {
+    /// Sender key of the referenced message
+    pub author: String,
+
+    /// Timestamp of the referenced message
+    pub timestamp: u64,
+}
+
+/// A mention of another user inside a text body
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mention {
+    /// Byte offset of the mention in the body
+    pub start: u32,
+
+    /// Byte length of the mention in the body
+    pub len: u32,
+
+    /// Fingerprint of the mentioned identity
+    pub fingerprint: String,
+}
+
+/// A quoted message that a text replies to
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote {
+    /// The message being replied to
+    pub target: MessageRef,
+
+    /// Preview of the quoted text
+    pub text: String,
+}
+
+/// Disappearing-message timer
+///
+/// The countdown starts when a message is stored on a device, whether it was
+/// sent or received.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpiryPolicy {
+    /// Lifetime of a message in seconds
+    pub seconds: u32,
+}
+
+/// Kind of receipt
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptType {
+    /// The message reached the recipient's device
+    Delivery,
+
+    /// The recipient has seen the message
+    Read,
+}
+
+/// Structured plaintext of a message
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessageContent {
+    /// A text message, optionally replying to another message
+    Text {
+        /// The message text
+        body: String,
+
+        /// Users mentioned in the body
+        #[serde(default)]
+        mentions: Vec<Mention>,
+
+        /// The message this text replies to
+        #[serde(default)]
+        quote: Option<Quote>,
+
+        /// Disappearing-message timer of this text
+        #[serde(default)]
+        expiry: Option<ExpiryPolicy>,
+    },
+
+    /// Add or remove an emoji reaction
+    Reaction {
+        /// The message reacted to
+        target: MessageRef,
+
+        /// The reaction emoji
+        emoji: String,
+
+        /// Whether an earlier reaction is withdrawn
+        #[serde(default)]
+        remove: bool,
+    },
+
+    /// Replace the body of an earlier text
+    Edit {
+        /// The text being edited
+        target: MessageRef,
+
+        /// The new message text
+        body: String,
+
+        /// Users mentioned in the new body
+        #[serde(default)]
+        mentions: Vec<Mention>,
+    },
+
+    /// Delete an earlier message for everyone
+    Delete {
+        /// The message being deleted
+        target: MessageRef,
+    },
+
+    /// Delivery or read receipt for one or more messages
+    Receipt {
+        /// Whether the messages were delivered or read
+        receipt_type: ReceiptType,
+
+        /// The messages acknowledged
+        targets: Vec<MessageRef>,
+    },
+
+    /// Typing indicator
+    Typing {
+        /// Whether the sender started or stopped typing
+        started: bool,
+    },
+
+    /// Change the disappearing-message timer of the conversation
+    ExpiryTimer {
+        /// The new timer, `None` to turn it off
+        #[serde(default)]
+        policy: Option<ExpiryPolicy>,
+    },
+
+    /// Content of a kind this client does not understand
+    Unknown {
+        /// The kind tag, never one of the kinds above
+        #[serde(deserialize_with = "unknown_kind")]
+        kind: u64,
+
+        /// The encoded field list, relayed verbatim
+        fields: Vec<u8>,
+    },
+}
+
+mod kind {
+    pub const TEXT: u64 = 1;
+    pub const REACTION: u64 = 2;
+    pub const EDIT: u64 = 3;
+    pub const DELETE: u64 = 4;
+    pub const RECEIPT: u64 = 5;
+    pub const TYPING: u64 = 6;
+    pub const EXPIRY_TIMER: u64 = 7;
+
+    /// Whether `kind` is one of the kinds above
+    pub fn is_known(kind: u64) -> bool {
+        (TEXT..=EXPIRY_TIMER).contains(&kind)
+    }
+}
+
+impl MessageContent {
+    /// A plain text message
+    pub fn text(body: &str) -> Self {
+        MessageContent::Text {
+            body: body.to_string(),
+            mentions: Vec::new(),
+            quote: None,
+            expiry: None,
+        }
+    }
+
+    /// Expiry policy carried by this content, if any
+    pub fn expiry(&self) -> Option<ExpiryPolicy> {
+        match self {
+            MessageContent::Text { expiry, .. } => *expiry,
+            _ => None,
+        }
+    }
+
+    /// Encode the content for encryption
+    ///
+    /// Fails for an `Unknown` carrying a known kind, which would decode as
+    /// different content.
+    pub fn encode(&self) -> Result<Vec<u8>, ContentError> {
+        let mut fields = FieldWriter::default();
+
+        let kind = match self {
+            MessageContent::Text { body, mentions, quote, expiry } => {
+                fields.string(1, body);
+                for mention in mentions {
+                    fields.nested(2, &mention.encode());
+                }
+                if let Some(quote) = quote {
+                    fields.nested(3, &quote.encode());
+                }
+                if let Some(expiry) = expiry {
+                    fields.nested(4, &expiry.encode());
+                }
+                kind::TEXT
+            }
+            MessageContent::Reaction { target, emoji, remove } => {
+                fields.nested(1, &target.encode());
+                fields.string(2, emoji);
+                fields.bool(3, *remove);
+                kind::REACTION
+            }
+            MessageContent::Edit { target, body, mentions } => {
+                fields.nested(1, &target.encode());
+                fields.string(2, body);
+                for mention in mentions {
+                    fields.nested(3, &mention.encode());
+                }
+                kind::EDIT
+            }
+            MessageContent::Delete { target } => {
+                fields.nested(1, &target.encode());
+                kind::DELETE
+            }
+            MessageContent::Receipt { receipt_type, targets } => {
+                fields.varint(1, receipt_type.code());
+                for target in targets {
+                    fields.nested(2, &target.encode());
+                }
+                kind::RECEIPT
+            }
+            MessageContent::Typing { started } => {
+                fields.bool(1, *started);
+                kind::TYPING
+            }
+            MessageContent::ExpiryTimer { policy } => {
+                if let Some(policy) = policy {
+                    fields.nested(1, &policy.encode());
+                }
+                kind::EXPIRY_TIMER
+            }
+            MessageContent::Unknown { kind, fields: raw } => {
+                if kind::is_known(*kind) {
+                    return Err(ContentError::KnownKind(*kind));
+                }
+                fields.0.extend_from_slice(raw);
+                *kind
+            }
+        };
+
+        let mut output = vec![CONTENT_VERSION];
+        write_varint(&mut output, kind);
+        output.extend_from_slice(&fields.0);
+        Ok(output)
+    }
+
+    /// Decode content from a decrypted payload
+    pub fn decode(bytes: &[u8]) -> Result<Self, ContentError> {
+        let (&version, rest) = bytes.split_first().ok_or(ContentError::Malformed)?;
+        if version != CONTENT_VERSION {
+            return Err(ContentError::UnsupportedVersion(version));
+        }
+
+        let mut input = rest;
+        let kind = read_varint(&mut input)?;
+
+        let content = match kind {
+            kind::TEXT => {
+                let (mut body, mut mentions, mut quote, mut expiry) = (String::new(), Vec::new(), None, None);
+                for field in FieldReader(input) {
+                    match field? {
+                        (1, value) => body = read_string(value)?,
+                        (2, value) => mentions.push(Mention::decode(value)?),
+                        (3, value) => quote = Some(Quote::decode(value)?),
+                        (4, value) => expiry = Some(ExpiryPolicy::decode(value)?),
+                        _ => {}
+                    }
+                }
+                MessageContent::Text { body, mentions, quote, expiry }
+            }
+            kind::REACTION => {
+                let (mut target, mut emoji, mut remove) = (None, String::new(), false);
+                for field in FieldReader(input) {
+                    match field? {
+                        (1, value) => target = Some(MessageRef::decode(value)?),
+                        (2, value) => emoji = read_string(value)?,
+                        (3, value) => remove = read_bool(value)?,
+                        _ => {}
+                    }
+                }
+                MessageContent::Reaction { target: target.ok_or(ContentError::Malformed)?, emoji, remove }
+            }
+            kind::EDIT => {
+                let (mut target, mut body, mut mentions) = (None, String::new(), Vec::new());
+                for field in FieldReader(input) {
+                    match field? {
+                        (1, value) => target = Some(MessageRef::decode(value)?),
+                        (2, value) => body = read_string(value)?,
+                        (3, value) => mentions.push(Mention::decode(value)?),
+                        _ => {}
+                    }
+                }
+                MessageContent::Edit { target: target.ok_or(ContentError::Malformed)?, body, mentions }
+            }
+            kind::DELETE => {
+                let mut target = None;
+                for field in FieldReader(input) {
+                    if let (1, value) = field? {
+                        target = Some(MessageRef::decode(value)?);
+                    }
+                }
+                MessageContent::Delete { target: target.ok_or(ContentError::Malformed)? }
+            }
+            kind::RECEIPT => {
+                let (mut receipt_type, mut targets) = (None, Vec::new());
+                for field in FieldReader(input) {
+                    match field? {
+                        (1, value) => receipt_type = Some(ReceiptType::from_code(read_varint_field(value)?)?),
+                        (2, value) => targets.push(MessageRef::decode(value)?),
+                        _ => {}
+                    }
+                }
+                MessageContent::Receipt { receipt_type: receipt_type.ok_or(ContentError::Malformed)?, targets }
+            }
+            kind::TYPING => {
+                let mut started = false;
+                for field in FieldReader(input) {
+                    if let (1, value) = field? {
+                        started = read_bool(value)?;
+                    }
+                }
+                MessageContent::Typing { started }
+            }
+            kind::EXPIRY_TIMER => {
+                let mut policy = None;
+                for field in FieldReader(input) {
+                    if let (1, value) = field? {
+                        policy = Some(ExpiryPolicy::decode(value)?);
+                    }
+                }
+                MessageContent::ExpiryTimer { policy }
+            }
+            _ => {
+                // Validate the framing so a malformed payload is never relayed on
+                for field in FieldReader(input) {
+                    field?;
+                }
+                MessageContent::Unknown { kind, fields: input.to_vec() }
+            }
+        };
+
+        Ok(content)
+    }
+}
+
+impl MessageRef {
+    fn encode(&self) -> Vec<u8> {
+        let mut fields = FieldWriter::default();
+        fields.string(1, &self.author);
+        fields.varint(2, self.timestamp);
+        fields.0
+    }
+
+    fn decode(input: &[u8]) -> Result<Self, ContentError> {
+        let mut target = MessageRef::default();
+        for field in FieldReader(input) {
+            match field? {
+                (1, value) => target.author = read_string(value)?,
+                (2, value) => target.timestamp = read_varint_field(value)?,
+                _ => {}
+            }
+        }
+        Ok(target)
+    }
+}
+
+impl Mention {
+    fn encode(&self) -> Vec<u8> {
+        let mut fields = FieldWriter::default();
+        fields.varint(1, self.start as u64);
+        fields.varint(2, self.len as u64);
+        fields.string(3, &self.fingerprint);
+        fields.0
+    }
+
+    fn decode(input: &[u8]) -> Result<Self, ContentError> {
+        let mut mention = Mention::default();
+        for field in FieldReader(input) {
+            match field? {
+                (1, value) => mention.start = read_u32(value)?,
+                (2, value) => mention.len = read_u32(value)?,
+                (3, value) => mention.fingerprint = read_string(value)?,
+                _ => {}
+            }
+        }
+        Ok(mention)
+    }
+}
+
+impl Quote {
+    fn encode(&self) -> Vec<u8> {
+        let mut fields = FieldWriter::default();
+        fields.nested(1, &self.target.encode());
+        fields.string(2, &self.text);
+        fields.0
+    }
+
+    fn decode(input: &[u8]) -> Result<Self, ContentError> {
+        let mut quote = Quote::default();
+        for field in FieldReader(input) {
+            match field? {
+                (1, value) => quote.target = MessageRef::decode(value)?,
+                (2, value) => quote.text = read_string(value)?,
+                _ => {}
+            }
+        }
+        Ok(quote)
+    }
+}
+
+impl ExpiryPolicy {
+    /// Timer of `seconds` seconds
+    pub fn new(seconds: u32) -> Self {
+        ExpiryPolicy { seconds }
+    }
+
+    /// Absolute expiry time in milliseconds for a message stored at `now_ms`
+    pub fn expires_at(self, now_ms: u64) -> u64 {
+        now_ms.saturating_add(self.seconds as u64 * 1000)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut fields = FieldWriter::default();
+        fields.varint(1, self.seconds as u64);
+        fields.0
+    }
+
+    fn decode(input: &[u8]) -> Result<Self, ContentError> {
+        let mut seconds = None;
+        for field in FieldReader(input) {
+            if let (1, value) = field? {
+                seconds = Some(read_u32(value)?);
+            }
+        }
+        Ok(ExpiryPolicy { seconds: seconds.ok_or(ContentError::Malformed)? })
+    }
+}
+
+impl ReceiptType {
+    fn code(self) -> u64 {
+        match self {
+            ReceiptType::Delivery => 0,
+            ReceiptType::Read => 1,
+        }
+    }
+
+    fn from_code(code: u64) -> Result<Self, ContentError> {
+        match code {
+            0 => Ok(ReceiptType::Delivery),
+            1 => Ok(ReceiptType::Read),
+            _ => Err(ContentError::Malformed),
+        }
+    }
+}
+
+/// Deserialize the kind of `MessageContent::Unknown`, rejecting known kinds
+///
+/// Content of a known kind decodes to its own variant, so an `Unknown` that
+/// carried one would re-encode as ambiguous bytes.
+fn unknown_kind<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value = u64::deserialize(deserializer)?;
+    if kind::is_known(value) {
+        return Err(serde::de::Error::custom("known content kind decoded as unknown"));
+    }
+    Ok(value)
+}
+
+/// Accumulates length-prefixed fields
+#[derive(Default)]
+struct FieldWriter(Vec<u8>);
+
+impl FieldWriter {
+    fn nested(&mut self, tag: u64, value: &[u8]) {
+        write_varint(&mut self.0, tag);
+        write_varint(&mut self.0, value.len() as u64);
+        self.0.extend_from_slice(value);
+    }
+
+    fn string(&mut self, tag: u64, value: &str) {
+        self.nested(tag, value.as_bytes());
+    }
+
+    fn varint(&mut self, tag: u64, value: u64) {
+        let mut encoded = Vec::with_capacity(MAX_VARINT_LEN);
+        write_varint(&mut encoded, value);
+        self.nested(tag, &encoded);
+    }
+
+    fn bool(&mut self, tag: u64, value: bool) {
+        self.varint(tag, value as u64);
+    }
+}
+
+/// Iterates over `(tag, value)` pairs of a field list
+struct FieldReader<'a>(&'a [u8]);
+
+impl<'a> Iterator for FieldReader<'a> {
+    type Item = Result<(u64, &'a [u8]), ContentError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let field = read_varint(&mut self.0).and_then(|tag| {
+            let len = usize::try_from(read_varint(&mut self.0)?).map_err(|_| ContentError::Malformed)?;
+            if len > self.0.len() {
+                return Err(ContentError::Malformed);
+            }
+
+            let (value, rest) = self.0.split_at(len);
+            self.0 = rest;
+            Ok((tag, value))
+        });
+
+        // Stop after the first error
+        if field.is_err() {
+            self.0 = &[];
+        }
+        Some(field)
+    }
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u64, ContentError> {
+    let mut value = 0u64;
+
+    for (i, &byte) in input.iter().enumerate().take(MAX_VARINT_LEN) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+
+        if byte & 0x80 == 0 {
+            // Reject overlong encodings and bits beyond 64
+            if (i > 0 && byte == 0) || (i == MAX_VARINT_LEN - 1 && byte > 1) {
+                return Err(ContentError::Malformed);
+            }
+
+            *input = &input[i + 1..];
+            return Ok(value);
+        }
+    }
+
+    Err(ContentError::Malformed)
+}
+
+fn read_varint_field(mut value: &[u8]) -> Result<u64, ContentError> {
+    let parsed = read_varint(&mut value)?;
+    if !value.is_empty() {
+        return Err(ContentError::Malformed);
+    }
+    Ok(parsed)
+}
+
+fn read_u32(value: &[u8]) -> Result<u32, ContentError> {
+    u32::try_from(read_varint_field(value)?).map_err(|_| ContentError::Malformed)
+}
+
+fn read_bool(value: &[u8]) -> Result<bool, ContentError> {
+    match read_varint_field(value)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(ContentError::Malformed),
+    }
+}
+
+fn read_string(value: &[u8]) -> Result<String, ContentError> {
+    String::from_utf8(value.to_vec()).map_err(|_| ContentError::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> MessageRef {
+        MessageRef { author: "a1b2c3".to_string(), timestamp: 1_700_000_000_000 }
+    }
+
+    #[test]
+    fn test_content_roundtrip() {
+        let contents = vec![
+            MessageContent::text("hello"),
+            MessageContent::Text {
+                body: "hi @bob".to_string(),
+                mentions: vec![Mention { start: 3, len: 4, fingerprint: "b0b".to_string() }],
+                quote: Some(Quote { target: target(), text: "earlier".to_string() }),
+                expiry: Some(ExpiryPolicy::new(3600)),
+            },
+            MessageContent::Reaction { target: target(), emoji: "🕳️".to_string(), remove: true },
+            MessageContent::Edit { target: target(), body: "fixed".to_string(), mentions: vec![] },
+            MessageContent::Delete { target: target() },
+            MessageContent::Receipt { receipt_type: ReceiptType::Read, targets: vec![target(), target()] },
+            MessageContent::Typing { started: true },
+            MessageContent::ExpiryTimer { policy: Some(ExpiryPolicy::new(86_400)) },
+            MessageContent::ExpiryTimer { policy: None },
+        ];
+
+        for content in contents {
+            assert_eq!(MessageContent::decode(&content.encode().unwrap()).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn test_forward_compatibility() {
+        // A future text with an extra field (tag 9) still decodes
+        let mut encoded = MessageContent::text("hello").encode().unwrap();
+        encoded.extend_from_slice(&[9, 2, 0xde, 0xad]);
+        assert_eq!(MessageContent::decode(&encoded).unwrap(), MessageContent::text("hello"));
+
+        // A future kind is preserved verbatim
+        let mut future = vec![CONTENT_VERSION];
+        write_varint(&mut future, 300);
+        future.extend_from_slice(&[1, 4, b'p', b'o', b'l', b'l']);
+        let decoded = MessageContent::decode(&future).unwrap();
+        assert!(matches!(decoded, MessageContent::Unknown { kind: 300, .. }));
+        assert_eq!(decoded.encode().unwrap(), future);
+    }
+
+    #[test]
+    fn test_unknown_content_rejects_known_kinds() {
+        let json = serde_json::to_string(&MessageContent::Unknown { kind: 300, fields: vec![] }).unwrap();
+        assert!(serde_json::from_str::<MessageContent>(&json).is_ok());
+
+        // Would re-encode as a text
+        let known = json.replace("300", &kind::TEXT.to_string());
+        assert!(serde_json::from_str::<MessageContent>(&known).is_err());
+
+        // Nor can one be built directly and encoded
+        let unknown = MessageContent::Unknown { kind: kind::TEXT, fields: vec![1, 0] };
+        assert_eq!(unknown.encode(), Err(ContentError::KnownKind(kind::TEXT)));
+    }
+
+    #[test]
+    fn test_malformed_content_rejected() {
+        let encoded = MessageContent::Delete { target: target() }.encode().unwrap();
+
+        assert_eq!(MessageContent::decode(&[]), Err(ContentError::Malformed));
+        assert_eq!(MessageContent::decode(&[2, 1]), Err(ContentError::UnsupportedVersion(2)));
+        assert_eq!(MessageContent::decode(&encoded[..encoded.len() - 1]), Err(ContentError::Malformed));
+        assert_eq!(MessageContent::decode(&[CONTENT_VERSION, kind::DELETE as u8]), Err(ContentError::Malformed));
+    }
+}
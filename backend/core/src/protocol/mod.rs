@@ -3,26 +3,43 @@
 //! This module implements the Signal Protocol's Double Ratchet algorithm
 //! and MLS (Messaging Layer Security) for group messaging.
 
-use wasm_bindgen::prelude::*;
-use zeroize::{Zeroize, ZeroizeOnDrop};
-use crate::crypto::PostQuantumKeys;
+pub mod attachment;
+pub mod content;
+pub mod group_metadata;
+pub mod sealed_sender;
+pub mod session;
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use crate::crypto::padding::{self, PaddingScheme};
+use crate::error::{key_array, BlackHoleError};
+use crate::providers::{CryptoRng, OsRandom, RngAdapter};
 use crate::SingularityKey;
 
+pub use attachment::{AttachmentDownload, AttachmentPointer, AttachmentWriter, EncryptedAttachment};
+pub use content::{ContentError, ExpiryPolicy, Mention, MessageContent, MessageRef, Quote, ReceiptType};
+pub use group_metadata::{GroupMetadata, GroupMetadataExtension};
+pub use sealed_sender::{SealedSenderEnvelope, SenderCertificate, TrustRoot, UnsealedMessage};
+pub use session::{HistoryEntry, HistoryExport, SessionManager};
+
 /// The Double Ratchet state machine
 /// 
 /// Implements the Signal Protocol's Double Ratchet algorithm for
 /// perfect forward secrecy and future secrecy.
-#[wasm_bindgen]
 #[derive(Zeroize, ZeroizeOnDrop)]
 pub struct DoubleRatchet {
     /// Root key for chain derivation
     root_key: [u8; 32],
     
-    /// Sending chain key
-    sending_chain_key: [u8; 32],
+    /// Sending chain key, once a DH ratchet step has produced one
+    sending_chain_key: Option<[u8; 32]>,
     
-    /// Receiving chain key
-    receiving_chain_key: [u8; 32],
+    /// Receiving chain key, once the peer's ratchet key is known
+    receiving_chain_key: Option<[u8; 32]>,
     
     /// Sending message number
     sending_message_number: u32,
@@ -42,10 +59,17 @@ pub struct DoubleRatchet {
     
     /// Remote DH public key (if initialized)
     remote_dh_public: Option<[u8; 32]>,
+    
+    /// Padding applied before encryption
+    #[zeroize(skip)]
+    padding: PaddingScheme,
+    
+    /// Randomness for ratchet keys and nonces
+    #[zeroize(skip)]
+    rng: Arc<dyn CryptoRng>,
 }
 
 /// MLS Group state for secure group messaging
-#[wasm_bindgen]
 #[derive(Zeroize, ZeroizeOnDrop)]
 pub struct MLSGroup {
     /// Group ID
@@ -55,19 +79,30 @@ pub struct MLSGroup {
     /// Epoch number (increments on each change)
     epoch: u64,
     
-    /// Group secret tree
-    secret_tree: Vec<u8>,
+    /// Secret of the current epoch, replaced on every commit
+    epoch_secret: [u8; 32],
     
     /// Member count
     member_count: usize,
     
     /// Pending proposals
     pending_proposals: Vec<GroupProposal>,
+    
+    /// Encrypted group metadata extension for the current epoch
+    #[zeroize(skip)]
+    metadata: Option<GroupMetadataExtension>,
+    
+    /// Padding applied before encryption
+    #[zeroize(skip)]
+    padding: PaddingScheme,
+    
+    /// Randomness for group IDs, secrets and nonces
+    #[zeroize(skip)]
+    rng: Arc<dyn CryptoRng>,
 }
 
 /// A message envelope containing all metadata
-#[wasm_bindgen]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MessageEnvelope {
     /// Message ciphertext
     pub ciphertext: Vec<u8>,
@@ -83,8 +118,7 @@ pub struct MessageEnvelope {
 }
 
 /// Message header for ratchet synchronization
-#[wasm_bindgen]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MessageHeader {
     /// DH ratchet public key
     pub dh_public: Vec<u8>,
@@ -99,97 +133,105 @@ pub struct MessageHeader {
     pub sender_key_id: u64,
 }
 
+impl MessageHeader {
+    /// Canonical encoding, authenticated by the tag and the AEAD
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.dh_public.len() + 24);
+        bytes.extend_from_slice(&(self.dh_public.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.dh_public);
+        bytes.extend_from_slice(&self.message_number.to_be_bytes());
+        bytes.extend_from_slice(&self.previous_chain_length.to_be_bytes());
+        bytes.extend_from_slice(&self.sender_key_id.to_be_bytes());
+        bytes
+    }
+}
+
+/// Key derivation context for the epoch secret chain
+const EPOCH_SECRET_CONTEXT: &str = "forticomm-blackhole 2024 group epoch-secret v1";
+
+/// Key derivation context for the key sealing a commit to the current epoch
+const COMMIT_KEY_CONTEXT: &str = "forticomm-blackhole 2024 group commit v1";
+
+/// A commit as sent to the other members
+///
+/// The fresh commit secret and the added members are sealed under the
+/// epoch being left, so only its members can follow the group into the
+/// next one; the metadata extension is sealed under the new epoch.
+#[derive(Serialize, Deserialize)]
+struct GroupCommit {
+    group_id: String,
+    /// Epoch the commit leads to
+    epoch: u64,
+    nonce: [u8; 12],
+    /// Encrypted `CommitSecrets`
+    ciphertext: Vec<u8>,
+    /// Encoded `GroupMetadataExtension` for the new epoch
+    metadata: Option<Vec<u8>>,
+}
+
+/// The sealed part of a commit
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct CommitSecrets {
+    commit_secret: [u8; 32],
+    added: Vec<String>,
+}
+
+/// Group state handed to a member added by a commit
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct GroupWelcome {
+    group_id: String,
+    epoch: u64,
+    epoch_secret: [u8; 32],
+    member_count: usize,
+    #[zeroize(skip)]
+    metadata: Option<GroupMetadataExtension>,
+}
+
 /// Group proposal for MLS
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
 struct GroupProposal {
     proposal_type: ProposalType,
     member_id: String,
     key_package: Vec<u8>,
+    metadata: Option<GroupMetadata>,
 }
 
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
 enum ProposalType {
     Add,
-    Remove,
-    Update,
+    GroupContextExtensions,
 }
 
-#[wasm_bindgen]
 impl DoubleRatchet {
     /// Create a new Double Ratchet instance
-    #[wasm_bindgen(constructor)]
-    pub fn new(identity: &SingularityKey) -> Result<DoubleRatchet, JsValue> {
-        log::info!("🔑 Initializing Double Ratchet...");
-        
-        // Generate DH key pair
-        let (dh_private, dh_public) = Self::generate_dh_keypair()?;
-        
-        // Derive root key from identity
-        let root_key = Self::derive_root_key(identity)?;
-        
-        // Initialize chain keys from root
-        let sending_chain_key = Self::kdf_derive(&root_key, b"chain-send");
-        let receiving_chain_key = Self::kdf_derive(&root_key, b"chain-recv");
-        
-        log::info!("✅ Double Ratchet initialized");
-        
-        Ok(DoubleRatchet {
-            root_key,
-            sending_chain_key,
-            receiving_chain_key,
-            sending_message_number: 0,
-            receiving_message_number: 0,
-            previous_chain_length: 0,
-            dh_private,
-            dh_public,
-            remote_dh_public: None,
-        })
+    pub fn new(identity: &SingularityKey) -> Result<DoubleRatchet, BlackHoleError> {
+        Self::with_rng(identity, Arc::new(OsRandom))
     }
     
     /// Initialize as responder (with remote public key)
-    #[wasm_bindgen]
     pub fn initialize_responder(
         identity: &SingularityKey,
         remote_dh_public: &[u8],
-    ) -> Result<DoubleRatchet, JsValue> {
-        let mut ratchet = Self::new(identity)?;
-        
-        if remote_dh_public.len() != 32 {
-            return Err(JsValue::from_str("Invalid DH public key length"));
-        }
-        
-        let mut remote_key = [0u8; 32];
-        remote_key.copy_from_slice(remote_dh_public);
-        ratchet.remote_dh_public = Some(remote_key);
-        
-        // Perform initial DH and update root key
-        ratchet.dh_ratchet()?;
-        
-        Ok(ratchet)
+    ) -> Result<DoubleRatchet, BlackHoleError> {
+        Self::initialize_responder_with_rng(identity, remote_dh_public, Arc::new(OsRandom))
     }
     
     /// Encrypt a message
-    #[wasm_bindgen]
-    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<MessageEnvelope, JsValue> {
-        use aes_gcm::{Aes256Gcm, Key, Nonce};
-        use aes_gcm::aead::{Aead, KeyInit};
+    ///
+    /// The initiator of a session can only send once the responder's first
+    /// message has completed the DH ratchet step.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<MessageEnvelope, BlackHoleError> {
+        use aes_gcm::{Aes256Gcm, Key};
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
+        
+        let chain_key = self.sending_chain_key
+            .ok_or(BlackHoleError::InvalidState("Awaiting the peer's first message"))?;
         
         // Derive message key from chain key
-        let message_key = Self::kdf_derive(&self.sending_chain_key, b"message-key");
+        let message_key = Self::kdf_derive(&chain_key, b"message-key");
         
         // Update chain key
-        self.sending_chain_key = Self::kdf_derive(&self.sending_chain_key, b"chain-key");
-        
-        // Encrypt
-        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&message_key));
-        let nonce: [u8; 12] = rand::random();
-        
-        let ciphertext = cipher
-            .encrypt(Nonce::from_slice(&nonce), plaintext)
-            .map_err(|e| JsValue::from_str(&format!("Encryption failed: {:?}", e)))?;
-        
-        // Generate auth tag
-        let auth_tag = blake3::hash(&[&message_key[..], &ciphertext[..]].concat());
+        self.sending_chain_key = Some(Self::kdf_derive(&chain_key, b"chain-key"));
         
         let header = MessageHeader {
             dh_public: self.dh_public.to_vec(),
@@ -197,6 +239,20 @@ impl DoubleRatchet {
             previous_chain_length: self.previous_chain_length,
             sender_key_id: 0,
         };
+        let header_bytes = header.to_bytes();
+        
+        // Encrypt, binding the header as associated data
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&message_key));
+        let padded = padding::pad(plaintext, self.padding);
+        
+        let ciphertext = cipher
+            .encrypt(
+                &Self::message_nonce(&message_key),
+                Payload { msg: padded.as_ref(), aad: &header_bytes },
+            )
+            .map_err(|_| BlackHoleError::Encryption)?;
+        
+        let auth_tag = Self::auth_tag(&message_key, &header_bytes, &ciphertext);
         
         self.sending_message_number += 1;
         
@@ -209,106 +265,148 @@ impl DoubleRatchet {
     }
     
     /// Decrypt a message
-    #[wasm_bindgen]
-    pub fn decrypt(&mut self, envelope: &MessageEnvelope) -> Result<Vec<u8>, JsValue> {
-        use aes_gcm::{Aes256Gcm, Key, Nonce};
-        use aes_gcm::aead::{Aead, KeyInit};
+    ///
+    /// The DH ratchet step and the next chain key are derived into
+    /// temporaries and only committed once the tag, the AEAD and the padding
+    /// check out, so a forged or replayed envelope leaves the session as it
+    /// was.
+    pub fn decrypt(&mut self, envelope: &MessageEnvelope) -> Result<Vec<u8>, BlackHoleError> {
+        use aes_gcm::{Aes256Gcm, Key};
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
         
-        // Check if we need to perform DH ratchet
-        let remote_dh = envelope.header.dh_public.as_slice();
-        if self.remote_dh_public.is_none() || 
-           self.remote_dh_public.unwrap().to_vec() != remote_dh {
-            // New ratchet key from sender
-            let mut new_remote = [0u8; 32];
-            new_remote.copy_from_slice(remote_dh);
-            self.remote_dh_public = Some(new_remote);
-            self.dh_ratchet()?;
-        }
+        // A new ratchet key from the sender starts a new receiving chain
+        let remote_dh: [u8; 32] = key_array(&envelope.header.dh_public)?;
+        let new_chain = self.remote_dh_public != Some(remote_dh);
         
-        // Derive message key
-        let message_key = Self::kdf_derive(&self.receiving_chain_key, b"message-key");
+        let (root_key, chain_key, message_number) = if new_chain {
+            let (root_key, chain_key) = Self::root_step(&self.root_key, &self.dh_private, &remote_dh);
+            (root_key, chain_key, 0)
+        } else if envelope.header.message_number < self.receiving_message_number {
+            // Message keys of this chain have already been consumed
+            return Err(BlackHoleError::Replay);
+        } else {
+            let chain_key = self.receiving_chain_key.ok_or(BlackHoleError::Decryption)?;
+            (self.root_key, chain_key, self.receiving_message_number)
+        };
         
-        // Update chain key
-        self.receiving_chain_key = Self::kdf_derive(&self.receiving_chain_key, b"chain-key");
+        // Derive message key
+        let message_key = Self::kdf_derive(&chain_key, b"message-key");
         
-        // Verify auth tag
-        let computed_tag = blake3::hash(&[&message_key[..], &envelope.ciphertext[..]].concat());
-        if computed_tag.as_bytes() != &envelope.auth_tag[..] {
-            return Err(JsValue::from_str("Authentication tag verification failed"));
+        // Verify auth tag over header and ciphertext (blake3::Hash compares in constant time)
+        let header_bytes = envelope.header.to_bytes();
+        let computed_tag = Self::auth_tag(&message_key, &header_bytes, &envelope.ciphertext);
+        if computed_tag != blake3::Hash::from(envelope.auth_tag) {
+            return Err(BlackHoleError::Decryption);
         }
         
         // Decrypt
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&message_key));
-        let nonce = Nonce::from_slice(&[0u8; 12]); // In production, extract from ciphertext
         
-        let plaintext = cipher
-            .decrypt(nonce, envelope.ciphertext.as_ref())
-            .map_err(|e| JsValue::from_str(&format!("Decryption failed: {:?}", e)))?;
+        let mut plaintext = cipher
+            .decrypt(
+                &Self::message_nonce(&message_key),
+                Payload { msg: &envelope.ciphertext, aad: &header_bytes },
+            )
+            .map_err(|_| BlackHoleError::Decryption)?;
+        
+        if !padding::unpad_in_place(&mut plaintext) {
+            plaintext.zeroize();
+            return Err(BlackHoleError::InvalidPadding);
+        }
         
-        self.receiving_message_number += 1;
+        // The message is authentic: commit the new state
+        if new_chain {
+            self.root_key = root_key;
+            self.remote_dh_public = Some(remote_dh);
+            self.dh_ratchet(&remote_dh)?;
+        }
+        self.receiving_chain_key = Some(Self::kdf_derive(&chain_key, b"chain-key"));
+        self.receiving_message_number = message_number + 1;
         
         Ok(plaintext)
     }
     
     /// Get current DH public key for sharing
-    #[wasm_bindgen]
     pub fn get_dh_public(&self) -> Vec<u8> {
         self.dh_public.to_vec()
     }
     
-    fn dh_ratchet(&mut self) -> Result<(), JsValue> {
-        use x25519_dalek::{PublicKey, StaticSecret};
+    /// Select the padding scheme for outgoing messages
+    pub fn set_padding_scheme(&mut self, scheme: PaddingScheme) {
+        self.padding = scheme;
+    }
+    
+    /// Sending half of a DH ratchet step on a new ratchet key from the peer
+    ///
+    /// Runs once a message on the peer's new key has authenticated and its
+    /// receiving chain is in place: a fresh key pair, announced in our next
+    /// header, derives the sending chain.
+    fn dh_ratchet(&mut self, remote_key: &[u8; 32]) -> Result<(), BlackHoleError> {
+        // Generate new DH key pair
+        let (new_private, new_public) = Self::generate_dh_keypair(self.rng.as_ref())?;
+        self.dh_private = new_private;
+        self.dh_public = new_public;
         
-        if let Some(remote_key) = self.remote_dh_public {
-            // Generate new DH key pair
-            let (new_private, new_public) = Self::generate_dh_keypair()?;
-            
-            // Perform DH with remote key
-            let secret = StaticSecret::from(self.dh_private);
-            let remote = PublicKey::from(remote_key);
-            let shared = secret.diffie_hellman(&remote);
-            
-            // Update root key
-            self.root_key = Self::kdf_derive(shared.as_bytes(), b"root-key");
-            
-            // Update chain keys
-            self.sending_chain_key = Self::kdf_derive(&self.root_key, b"chain-send");
-            self.receiving_chain_key = Self::kdf_derive(&self.root_key, b"chain-recv");
-            
-            // Save new keys
-            self.dh_private = new_private;
-            self.dh_public = new_public;
-            
-            // Reset message numbers
-            self.previous_chain_length = self.sending_message_number;
-            self.sending_message_number = 0;
-            self.receiving_message_number = 0;
-        }
+        self.sending_chain_key = Some(self.ratchet_root(remote_key));
+        
+        // Reset message numbers
+        self.previous_chain_length = self.sending_message_number;
+        self.sending_message_number = 0;
         
         Ok(())
     }
     
-    fn generate_dh_keypair() -> Result<([u8; 32], [u8; 32]), JsValue> {
+    /// Mix DH with `remote_key` into the root key, returning a new chain key
+    fn ratchet_root(&mut self, remote_key: &[u8; 32]) -> [u8; 32] {
+        let (root_key, chain_key) = Self::root_step(&self.root_key, &self.dh_private, remote_key);
+        self.root_key = root_key;
+        
+        chain_key
+    }
+    
+    /// Root KDF step: the next root key and a new chain key
+    fn root_step(root_key: &[u8; 32], dh_private: &[u8; 32], remote_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+        use x25519_dalek::{PublicKey, StaticSecret};
+        
+        let secret = StaticSecret::from(*dh_private);
+        let shared = secret.diffie_hellman(&PublicKey::from(*remote_key));
+        
+        let mut input = [&root_key[..], shared.as_bytes()].concat();
+        let next_root = Self::kdf_derive(&input, b"root-key");
+        let chain_key = Self::kdf_derive(&input, b"chain-key");
+        input.zeroize();
+        
+        (next_root, chain_key)
+    }
+    
+    fn generate_dh_keypair(rng: &dyn CryptoRng) -> Result<([u8; 32], [u8; 32]), BlackHoleError> {
         use x25519_dalek::{StaticSecret, PublicKey};
-        use rand::rngs::OsRng;
         
-        let secret = StaticSecret::random_from_rng(OsRng);
+        let secret = StaticSecret::random_from_rng(RngAdapter(rng));
         let public = PublicKey::from(&secret);
         
         Ok((secret.to_bytes(), *public.as_bytes()))
     }
     
-    fn derive_root_key(identity: &SingularityKey) -> Result<[u8; 32], JsValue> {
-        // Derive root key from identity private key
-        let mut hasher = blake3::Hasher::new();
-        hasher.update(&identity.private);
-        hasher.update(b"forticomm-blackhole-root");
-        
-        let hash = hasher.finalize();
-        let mut key = [0u8; 32];
-        key.copy_from_slice(hash.as_bytes());
+    /// AES-GCM nonce of a message key
+    ///
+    /// Every message key encrypts exactly one message, so the nonce can be
+    /// derived from it instead of travelling with the ciphertext.
+    fn message_nonce(message_key: &[u8; 32]) -> aes_gcm::Nonce<aes_gcm::aes::cipher::consts::U12> {
+        let derived = Self::kdf_derive(message_key, b"message-nonce");
+        *aes_gcm::Nonce::from_slice(&derived[..12])
+    }
+    
+    /// Authentication tag over the encoded header and the ciphertext
+    fn auth_tag(message_key: &[u8; 32], header: &[u8], ciphertext: &[u8]) -> blake3::Hash {
+        let mut auth_key = Self::kdf_derive(message_key, b"auth-key");
+        let mut hasher = blake3::Hasher::new_keyed(&auth_key);
+        auth_key.zeroize();
         
-        Ok(key)
+        hasher.update(&(header.len() as u64).to_be_bytes());
+        hasher.update(header);
+        hasher.update(ciphertext);
+        hasher.finalize()
     }
     
     fn kdf_derive(key: &[u8], context: &[u8]) -> [u8; 32] {
@@ -323,49 +421,83 @@ impl DoubleRatchet {
     }
 }
 
-#[wasm_bindgen]
-impl MLSGroup {
-    /// Create a new MLS group
-    #[wasm_bindgen(constructor)]
-    pub fn new(identity: &SingularityKey) -> Result<MLSGroup, JsValue> {
-        log::info!("👥 Creating new MLS group...");
-        
-        let group_id = format!("mls-group-{}", hex::encode(&identity.fingerprint[..8]));
+impl DoubleRatchet {
+    /// Create a new Double Ratchet drawing ratchet keys from `rng`
+    ///
+    /// This is the initiator's side: share `get_dh_public()` with the peer,
+    /// whose first message starts both chains.
+    pub fn with_rng(_identity: &SingularityKey, rng: Arc<dyn CryptoRng>) -> Result<DoubleRatchet, BlackHoleError> {
+        log::info!("🔑 Initializing Double Ratchet...");
         
-        // Generate initial secret tree
-        let mut secret_tree = vec![0u8; 1024];
-        rand::fill(&mut secret_tree[..]);
+        // Generate DH key pair
+        let (dh_private, dh_public) = Self::generate_dh_keypair(rng.as_ref())?;
         
-        log::info!("✅ MLS group created: {}", group_id);
+        log::info!("✅ Double Ratchet initialized");
         
-        Ok(MLSGroup {
-            group_id,
-            epoch: 0,
-            secret_tree,
-            member_count: 1,
-            pending_proposals: Vec::new(),
+        // No secret is shared before the first DH ratchet step
+        Ok(DoubleRatchet {
+            root_key: [0u8; 32],
+            sending_chain_key: None,
+            receiving_chain_key: None,
+            sending_message_number: 0,
+            receiving_message_number: 0,
+            previous_chain_length: 0,
+            dh_private,
+            dh_public,
+            remote_dh_public: None,
+            padding: PaddingScheme::default(),
+            rng,
         })
     }
     
+    /// Initialize as responder, drawing ratchet keys from `rng`
+    ///
+    /// The responder sends first, on a chain from DH with the initiator's
+    /// ratchet key.
+    pub fn initialize_responder_with_rng(
+        identity: &SingularityKey,
+        remote_dh_public: &[u8],
+        rng: Arc<dyn CryptoRng>,
+    ) -> Result<DoubleRatchet, BlackHoleError> {
+        let remote_key: [u8; 32] = key_array(remote_dh_public)?;
+        
+        let mut ratchet = Self::with_rng(identity, rng)?;
+        ratchet.remote_dh_public = Some(remote_key);
+        
+        // Perform initial DH and update root key
+        ratchet.sending_chain_key = Some(ratchet.ratchet_root(&remote_key));
+        
+        Ok(ratchet)
+    }
+}
+
+impl MLSGroup {
+    /// Create a new MLS group
+    ///
+    /// The group ID is drawn from 32 random bytes. The creator's identity is
+    /// deliberately not mixed in, so groups created by the same user cannot
+    /// be linked by their IDs and any number of groups may be created.
+    pub fn new(identity: &SingularityKey) -> Result<MLSGroup, BlackHoleError> {
+        Self::with_rng(identity, Arc::new(OsRandom))
+    }
+    
     /// Get the group ID
-    #[wasm_bindgen]
     pub fn get_group_id(&self) -> String {
         self.group_id.clone()
     }
     
     /// Get current epoch
-    #[wasm_bindgen]
     pub fn get_epoch(&self) -> u64 {
         self.epoch
     }
     
     /// Add a member to the group
-    #[wasm_bindgen]
-    pub fn propose_add(&mut self, member_id: &str, key_package: &[u8]) -> Result<(), JsValue> {
+    pub fn propose_add(&mut self, member_id: &str, key_package: &[u8]) -> Result<(), BlackHoleError> {
         let proposal = GroupProposal {
             proposal_type: ProposalType::Add,
             member_id: member_id.to_string(),
             key_package: key_package.to_vec(),
+            metadata: None,
         };
         
         self.pending_proposals.push(proposal);
@@ -374,63 +506,207 @@ impl MLSGroup {
         Ok(())
     }
     
+    /// Propose new group metadata (name, description, avatar)
+    ///
+    /// The metadata is sealed under the next epoch's secret when committed.
+    pub fn propose_metadata(
+        &mut self,
+        name: &str,
+        description: &str,
+        avatar: Option<Vec<u8>>,
+    ) -> Result<(), BlackHoleError> {
+        let proposal = GroupProposal {
+            proposal_type: ProposalType::GroupContextExtensions,
+            member_id: String::new(),
+            key_package: Vec::new(),
+            metadata: Some(GroupMetadata::new(name, description, avatar)),
+        };
+        
+        self.pending_proposals.push(proposal);
+        log::info!("📋 Proposed new group metadata");
+        
+        Ok(())
+    }
+    
+    /// Get the decrypted group metadata for the current epoch
+    pub fn get_metadata(&self) -> Result<GroupMetadata, BlackHoleError> {
+        Ok(self.metadata()?.unwrap_or_default())
+    }
+    
+    /// Export the encrypted metadata extension to distribute with a commit
+    pub fn export_metadata_extension(&self) -> Result<Option<Vec<u8>>, BlackHoleError> {
+        self.metadata
+            .as_ref()
+            .map(|ext| ext.to_bytes())
+            .transpose()
+    }
+    
+    /// Apply a metadata extension received from another member
+    ///
+    /// The extension must be sealed for the current epoch and must not roll
+    /// the metadata version back.
+    pub fn apply_metadata_extension(&mut self, bytes: &[u8]) -> Result<(), BlackHoleError> {
+        let ext = GroupMetadataExtension::from_bytes(bytes)?;
+        
+        if ext.epoch != self.epoch {
+            return Err(BlackHoleError::StaleEpoch { current: self.epoch, received: ext.epoch });
+        }
+        
+        if let Some(current) = &self.metadata {
+            if ext.version < current.version {
+                return Err(BlackHoleError::Replay);
+            }
+        }
+        
+        // Authenticate before accepting
+        ext.open(&self.epoch_secret, &self.group_id)?;
+        
+        self.metadata = Some(ext);
+        Ok(())
+    }
+    
     /// Commit pending proposals
-    #[wasm_bindgen]
-    pub fn commit(&mut self) -> Result<Vec<u8>, JsValue> {
+    ///
+    /// Moves to the next epoch under a fresh secret and returns the commit
+    /// message for the other members to pass to [`MLSGroup::apply_commit`].
+    pub fn commit(&mut self) -> Result<Vec<u8>, BlackHoleError> {
         if self.pending_proposals.is_empty() {
-            return Err(JsValue::from_str("No pending proposals to commit"));
+            return Err(BlackHoleError::InvalidState("No pending proposals to commit"));
         }
         
+        // Carry the current metadata across the epoch change
+        let mut metadata = self.metadata()?;
+        let mut metadata_version = self.metadata.as_ref().map_or(0, |ext| ext.version);
+        let mut added = Vec::new();
+        
         // Apply all proposals
         for proposal in &self.pending_proposals {
             match proposal.proposal_type {
                 ProposalType::Add => {
-                    self.member_count += 1;
+                    added.push(proposal.member_id.clone());
                     log::info!("👤 Added member: {}", proposal.member_id);
                 }
-                ProposalType::Remove => {
-                    self.member_count = self.member_count.saturating_sub(1);
-                    log::info!("🚫 Removed member: {}", proposal.member_id);
-                }
-                ProposalType::Update => {
-                    log::info!("🔄 Updated member: {}", proposal.member_id);
+                ProposalType::GroupContextExtensions => {
+                    metadata = proposal.metadata.clone();
+                    metadata_version += 1;
+                    log::info!("🏷️ Updated group metadata");
                 }
             }
         }
         
-        // Clear proposals and increment epoch
+        // Seal the commit secret to the members of the epoch being left
+        let secrets = CommitSecrets { commit_secret: self.rng.random(), added };
+        let epoch = self.epoch + 1;
+        let nonce: [u8; 12] = self.rng.random();
+        let ciphertext = self.seal_commit(&secrets, epoch, &nonce)?;
+        
         self.pending_proposals.clear();
-        self.epoch += 1;
+        let next = self.next_epoch_secret(&secrets, epoch);
+        self.advance(&next, epoch, secrets.added.len());
         
-        // Generate commit message
-        let commit = format!("commit-epoch-{}", self.epoch);
+        // Re-seal the metadata under the new epoch
+        self.metadata = match metadata {
+            Some(metadata) => Some(GroupMetadataExtension::seal(
+                &metadata,
+                &self.epoch_secret,
+                &self.group_id,
+                self.epoch,
+                metadata_version,
+                self.rng.as_ref(),
+            )?),
+            None => None,
+        };
+        
+        let commit = GroupCommit {
+            group_id: self.group_id.clone(),
+            epoch,
+            nonce,
+            ciphertext,
+            metadata: self.export_metadata_extension()?,
+        };
         
         log::info!("✅ Committed to epoch {}", self.epoch);
         
-        Ok(commit.into_bytes())
+        serde_json::to_vec(&commit).map_err(BlackHoleError::serialization)
+    }
+    
+    /// Apply a commit made by another member, moving to its epoch
+    ///
+    /// Fails with `StaleEpoch` unless the commit leads to the next epoch,
+    /// and leaves the group untouched on any error.
+    pub fn apply_commit(&mut self, bytes: &[u8]) -> Result<(), BlackHoleError> {
+        let commit: GroupCommit = serde_json::from_slice(bytes)
+            .map_err(BlackHoleError::serialization)?;
+        
+        if commit.group_id != self.group_id {
+            return Err(BlackHoleError::UnknownGroup);
+        }
+        if commit.epoch != self.epoch + 1 {
+            return Err(BlackHoleError::StaleEpoch { current: self.epoch, received: commit.epoch });
+        }
+        
+        let secrets = self.open_commit(&commit)?;
+        let next = self.next_epoch_secret(&secrets, commit.epoch);
+        
+        // Check the metadata under the new epoch before switching to it
+        let metadata = commit.metadata
+            .as_deref()
+            .map(GroupMetadataExtension::from_bytes)
+            .transpose()?;
+        if let Some(ext) = &metadata {
+            if ext.epoch != commit.epoch {
+                return Err(BlackHoleError::StaleEpoch { current: commit.epoch, received: ext.epoch });
+            }
+            if self.metadata.as_ref().is_some_and(|current| ext.version < current.version) {
+                return Err(BlackHoleError::Replay);
+            }
+            ext.open(next.as_ref(), &self.group_id)?;
+        }
+        
+        self.advance(&next, commit.epoch, secrets.added.len());
+        self.metadata = metadata;
+        
+        log::info!("✅ Applied commit to epoch {}", self.epoch);
+        
+        Ok(())
+    }
+    
+    /// Group state for a member added in the current epoch
+    ///
+    /// Contains the epoch secret: send it to the new member only over an
+    /// end-to-end encrypted session, never through the relay in the clear.
+    pub fn welcome(&self) -> Result<Vec<u8>, BlackHoleError> {
+        let welcome = GroupWelcome {
+            group_id: self.group_id.clone(),
+            epoch: self.epoch,
+            epoch_secret: self.epoch_secret,
+            member_count: self.member_count,
+            metadata: self.metadata.clone(),
+        };
+        
+        serde_json::to_vec(&welcome).map_err(BlackHoleError::serialization)
     }
     
     /// Get member count
-    #[wasm_bindgen]
     pub fn get_member_count(&self) -> usize {
         self.member_count
     }
     
     /// Encrypt a group message
-    #[wasm_bindgen]
-    pub fn encrypt_group_message(&self, plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
-        // Derive group encryption key from secret tree
-        let key = blake3::hash(&self.secret_tree);
+    pub fn encrypt_group_message(&self, plaintext: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        // Derive group encryption key from the epoch secret
+        let key = blake3::hash(&self.epoch_secret);
         
         use aes_gcm::{Aes256Gcm, Key, Nonce};
         use aes_gcm::aead::{Aead, KeyInit};
         
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_bytes()));
-        let nonce: [u8; 12] = rand::random();
+        let nonce: [u8; 12] = self.rng.random();
+        let padded = padding::pad(plaintext, self.padding);
         
         let mut ciphertext = cipher
-            .encrypt(Nonce::from_slice(&nonce), plaintext)
-            .map_err(|e| JsValue::from_str(&format!("Group encryption failed: {:?}", e)))?;
+            .encrypt(Nonce::from_slice(&nonce), padded.as_ref())
+            .map_err(|_| BlackHoleError::Encryption)?;
         
         // Prepend nonce
         let mut result = nonce.to_vec();
@@ -438,23 +714,344 @@ impl MLSGroup {
         
         Ok(result)
     }
+    
+    /// Decrypt a group message
+    pub fn decrypt_group_message(&self, message: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        if message.len() < 12 {
+            return Err(BlackHoleError::Malformed("group message"));
+        }
+        
+        let key = blake3::hash(&self.epoch_secret);
+        
+        use aes_gcm::{Aes256Gcm, Key, Nonce};
+        use aes_gcm::aead::{Aead, KeyInit};
+        
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_bytes()));
+        let (nonce, ciphertext) = message.split_at(12);
+        
+        let mut plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| BlackHoleError::Decryption)?;
+        
+        if !padding::unpad_in_place(&mut plaintext) {
+            return Err(BlackHoleError::InvalidPadding);
+        }
+        
+        Ok(plaintext)
+    }
+    
+    /// Select the padding scheme for outgoing group messages
+    pub fn set_padding_scheme(&mut self, scheme: PaddingScheme) {
+        self.padding = scheme;
+    }
+}
+
+impl MLSGroup {
+    /// Create a new MLS group drawing its ID, secrets and nonces from `rng`
+    pub fn with_rng(_identity: &SingularityKey, rng: Arc<dyn CryptoRng>) -> Result<MLSGroup, BlackHoleError> {
+        log::info!("👥 Creating new MLS group...");
+        
+        let group_id = Self::generate_group_id(rng.as_ref());
+        
+        let epoch_secret = rng.random();
+        
+        log::info!("✅ MLS group created: {}", group_id);
+        
+        Ok(MLSGroup {
+            group_id,
+            epoch: 0,
+            epoch_secret,
+            member_count: 1,
+            pending_proposals: Vec::new(),
+            metadata: None,
+            padding: PaddingScheme::default(),
+            rng,
+        })
+    }
+    
+    /// Join a group from the welcome of the member who added us
+    pub fn join(identity: &SingularityKey, welcome: &[u8]) -> Result<MLSGroup, BlackHoleError> {
+        Self::join_with_rng(identity, welcome, Arc::new(OsRandom))
+    }
+    
+    /// Join a group, drawing nonces from `rng`
+    pub fn join_with_rng(_identity: &SingularityKey, welcome: &[u8], rng: Arc<dyn CryptoRng>) -> Result<MLSGroup, BlackHoleError> {
+        let welcome: GroupWelcome = serde_json::from_slice(welcome)
+            .map_err(BlackHoleError::serialization)?;
+        
+        let group = MLSGroup {
+            group_id: welcome.group_id.clone(),
+            epoch: welcome.epoch,
+            epoch_secret: welcome.epoch_secret,
+            member_count: welcome.member_count,
+            pending_proposals: Vec::new(),
+            metadata: welcome.metadata.clone(),
+            padding: PaddingScheme::default(),
+            rng,
+        };
+        
+        // The welcome's metadata must open under its epoch secret
+        group.metadata()?;
+        
+        log::info!("✅ Joined MLS group: {}", group.group_id);
+        
+        Ok(group)
+    }
+    
+    /// Move to `epoch` under its secret
+    fn advance(&mut self, epoch_secret: &[u8; 32], epoch: u64, added: usize) {
+        self.epoch_secret = *epoch_secret;
+        self.epoch = epoch;
+        self.member_count += added;
+    }
+    
+    /// `epoch_secret' = BLAKE3-derive(epoch_secret ‖ commit_secret ‖ group_id ‖ epoch)`
+    fn next_epoch_secret(&self, secrets: &CommitSecrets, epoch: u64) -> Zeroizing<[u8; 32]> {
+        let mut hasher = blake3::Hasher::new_derive_key(EPOCH_SECRET_CONTEXT);
+        hasher.update(&self.epoch_secret);
+        hasher.update(&secrets.commit_secret);
+        hasher.update(self.group_id.as_bytes());
+        hasher.update(&epoch.to_be_bytes());
+        
+        Zeroizing::new(*hasher.finalize().as_bytes())
+    }
+    
+    /// Key sealing commits out of the current epoch
+    fn commit_key(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(blake3::derive_key(COMMIT_KEY_CONTEXT, &self.epoch_secret))
+    }
+    
+    fn seal_commit(&self, secrets: &CommitSecrets, epoch: u64, nonce: &[u8; 12]) -> Result<Vec<u8>, BlackHoleError> {
+        use aes_gcm::{Aes256Gcm, Key, Nonce};
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
+        
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(secrets).map_err(BlackHoleError::serialization)?,
+        );
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(self.commit_key().as_ref()));
+        let aad = commit_associated_data(&self.group_id, epoch);
+        
+        cipher
+            .encrypt(Nonce::from_slice(nonce), Payload { msg: &plaintext, aad: &aad })
+            .map_err(|_| BlackHoleError::Encryption)
+    }
+    
+    fn open_commit(&self, commit: &GroupCommit) -> Result<CommitSecrets, BlackHoleError> {
+        use aes_gcm::{Aes256Gcm, Key, Nonce};
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
+        
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(self.commit_key().as_ref()));
+        let aad = commit_associated_data(&self.group_id, commit.epoch);
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(Nonce::from_slice(&commit.nonce), Payload { msg: &commit.ciphertext, aad: &aad })
+                .map_err(|_| BlackHoleError::Decryption)?,
+        );
+        
+        serde_json::from_slice(&plaintext).map_err(BlackHoleError::serialization)
+    }
+    
+    /// Generate a random, unlinkable group ID (hex of 32 random bytes)
+    fn generate_group_id(rng: &dyn CryptoRng) -> String {
+        let id: [u8; 32] = rng.random();
+        crate::hex::encode(&id)
+    }
+    
+    /// Decrypt the metadata of the current epoch, if any has been set
+    pub fn metadata(&self) -> Result<Option<GroupMetadata>, BlackHoleError> {
+        self.metadata
+            .as_ref()
+            .map(|ext| ext.open(&self.epoch_secret, &self.group_id))
+            .transpose()
+    }
+}
+
+/// Associated data binding a commit to its group and target epoch
+fn commit_associated_data(group_id: &str, epoch: u64) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(group_id.len() + 8);
+    aad.extend_from_slice(group_id.as_bytes());
+    aad.extend_from_slice(&epoch.to_be_bytes());
+    aad
+}
+
+/// Registry of the MLS groups held by one identity, keyed by group ID
+pub struct GroupRegistry {
+    groups: BTreeMap<String, MLSGroup>,
+    padding: PaddingScheme,
+    rng: Arc<dyn CryptoRng>,
+}
+
+impl Default for GroupRegistry {
+    fn default() -> Self {
+        Self::with_rng(Arc::new(OsRandom))
+    }
+}
+
+impl GroupRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        GroupRegistry::default()
+    }
+    
+    /// Create an empty registry whose groups draw randomness from `rng`
+    pub fn with_rng(rng: Arc<dyn CryptoRng>) -> Self {
+        GroupRegistry {
+            groups: BTreeMap::new(),
+            padding: PaddingScheme::default(),
+            rng,
+        }
+    }
+    
+    /// Create a new group and return its ID
+    pub fn create(&mut self, identity: &SingularityKey) -> Result<String, BlackHoleError> {
+        let mut group = MLSGroup::with_rng(identity, self.rng.clone())?;
+        group.set_padding_scheme(self.padding);
+        let group_id = group.get_group_id();
+        
+        if self.groups.contains_key(&group_id) {
+            return Err(BlackHoleError::InvalidState("Group ID collision"));
+        }
+        
+        self.groups.insert(group_id.clone(), group);
+        Ok(group_id)
+    }
+    
+    /// Join a group from a welcome and return its ID
+    pub fn join(&mut self, identity: &SingularityKey, welcome: &[u8]) -> Result<String, BlackHoleError> {
+        let mut group = MLSGroup::join_with_rng(identity, welcome, self.rng.clone())?;
+        group.set_padding_scheme(self.padding);
+        let group_id = group.get_group_id();
+        
+        if self.groups.contains_key(&group_id) {
+            return Err(BlackHoleError::InvalidState("Already a member of this group"));
+        }
+        
+        self.groups.insert(group_id.clone(), group);
+        Ok(group_id)
+    }
+    
+    /// Look up a group by ID
+    pub fn get(&self, group_id: &str) -> Result<&MLSGroup, BlackHoleError> {
+        self.groups
+            .get(group_id)
+            .ok_or(BlackHoleError::UnknownGroup)
+    }
+    
+    /// Look up a group by ID for modification
+    pub fn get_mut(&mut self, group_id: &str) -> Result<&mut MLSGroup, BlackHoleError> {
+        self.groups
+            .get_mut(group_id)
+            .ok_or(BlackHoleError::UnknownGroup)
+    }
+    
+    /// Remove a group, wiping its secrets
+    pub fn remove(&mut self, group_id: &str) -> bool {
+        match self.groups.remove(group_id) {
+            Some(mut group) => {
+                group.zeroize();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Select the padding scheme for all current and future groups
+    pub fn set_padding_scheme(&mut self, scheme: PaddingScheme) {
+        self.padding = scheme;
+        for group in self.groups.values_mut() {
+            group.set_padding_scheme(scheme);
+        }
+    }
+    
+    /// IDs of all registered groups
+    pub fn group_ids(&self) -> Vec<String> {
+        self.groups.keys().cloned().collect()
+    }
+    
+    /// Number of registered groups
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+    
+    /// Whether the registry holds no groups
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+impl Zeroize for GroupRegistry {
+    fn zeroize(&mut self) {
+        for group in self.groups.values_mut() {
+            group.zeroize();
+        }
+        self.groups.clear();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::SingularityKey;
+    use alloc::vec;
     
     #[test]
     fn test_double_ratchet_basic() {
-        let identity = SingularityKey::generate().unwrap();
-        let mut ratchet = DoubleRatchet::new(&identity).unwrap();
+        let alice = SingularityKey::generate().unwrap();
+        let bob = SingularityKey::generate().unwrap();
         
-        let plaintext = b"Hello, Black Hole!";
-        let envelope = ratchet.encrypt(plaintext).unwrap();
+        let mut initiator = DoubleRatchet::new(&alice).unwrap();
+        let mut responder = DoubleRatchet::initialize_responder(&bob, &initiator.get_dh_public()).unwrap();
+        assert_eq!(
+            initiator.encrypt(b"too early").unwrap_err(),
+            BlackHoleError::InvalidState("Awaiting the peer's first message"),
+        );
         
-        assert!(!envelope.ciphertext.is_empty());
+        let envelope = responder.encrypt(b"Hello, Black Hole!").unwrap();
         assert_eq!(envelope.header.message_number, 0);
+        assert_eq!(initiator.decrypt(&envelope).unwrap(), b"Hello, Black Hole!".to_vec());
+        assert_eq!(initiator.decrypt(&envelope), Err(BlackHoleError::Replay));
+        
+        // Every reply steps the DH ratchet
+        for round in 0..3u8 {
+            let reply = initiator.encrypt(&[round; 5]).unwrap();
+            assert_eq!(responder.decrypt(&reply).unwrap(), vec![round; 5]);
+            
+            let answer = responder.encrypt(&[round; 7]).unwrap();
+            assert_eq!(initiator.decrypt(&answer).unwrap(), vec![round; 7]);
+        }
+    }
+    
+    #[test]
+    fn test_forged_messages_leave_session_intact() {
+        let alice = SingularityKey::generate().unwrap();
+        let bob = SingularityKey::generate().unwrap();
+        
+        let mut initiator = DoubleRatchet::new(&alice).unwrap();
+        let mut responder = DoubleRatchet::initialize_responder(&bob, &initiator.get_dh_public()).unwrap();
+        
+        let first = responder.encrypt(b"first").unwrap();
+        let second = responder.encrypt(b"second").unwrap();
+        assert_eq!(initiator.decrypt(&first).unwrap(), b"first".to_vec());
+        
+        // A replay renumbered past the guard fails the tag
+        let mut renumbered = first.clone();
+        renumbered.header.message_number = 1;
+        assert_eq!(initiator.decrypt(&renumbered), Err(BlackHoleError::Decryption));
+        
+        // So does any other header change
+        let mut tampered = second.clone();
+        tampered.header.previous_chain_length = 9;
+        assert_eq!(initiator.decrypt(&tampered), Err(BlackHoleError::Decryption));
+        
+        // A forged ratchet key does not step the DH ratchet
+        let mut forged = second.clone();
+        forged.header.dh_public = DoubleRatchet::new(&bob).unwrap().get_dh_public();
+        assert_eq!(initiator.decrypt(&forged), Err(BlackHoleError::Decryption));
+        
+        // The genuine message still decrypts and the session carries on
+        assert_eq!(initiator.decrypt(&second).unwrap(), b"second".to_vec());
+        let reply = initiator.encrypt(b"reply").unwrap();
+        assert_eq!(responder.decrypt(&reply).unwrap(), b"reply".to_vec());
     }
     
     #[test]
@@ -474,4 +1071,180 @@ mod tests {
         assert_eq!(group.get_member_count(), 2);
         assert_eq!(group.get_epoch(), 1);
     }
+    
+    #[test]
+    fn test_group_message_padding() {
+        let identity = SingularityKey::generate().unwrap();
+        let mut group = MLSGroup::new(&identity).unwrap();
+        group.set_padding_scheme(PaddingScheme::PowerOfTwo);
+        
+        let short = group.encrypt_group_message(b"hi").unwrap();
+        let longer = group.encrypt_group_message(b"hello there").unwrap();
+        assert_eq!(short.len(), longer.len());
+        
+        assert_eq!(group.decrypt_group_message(&longer).unwrap(), b"hello there".to_vec());
+    }
+    
+    #[test]
+    fn test_mls_group_ids_are_unlinkable() {
+        let identity = SingularityKey::generate().unwrap();
+        let a = MLSGroup::new(&identity).unwrap();
+        let b = MLSGroup::new(&identity).unwrap();
+        
+        assert_eq!(a.get_group_id().len(), 64);
+        assert_ne!(a.get_group_id(), b.get_group_id());
+        assert!(!a.get_group_id().contains(&identity.fingerprint[..8]));
+    }
+    
+    #[test]
+    fn test_group_registry() {
+        let identity = SingularityKey::generate().unwrap();
+        let mut registry = GroupRegistry::new();
+        
+        let first = registry.create(&identity).unwrap();
+        let second = registry.create(&identity).unwrap();
+        assert_eq!(registry.len(), 2);
+        
+        registry.get_mut(&first).unwrap().propose_add("member-2", &[1, 2, 3]).unwrap();
+        registry.get_mut(&first).unwrap().commit().unwrap();
+        assert_eq!(registry.get(&first).unwrap().get_epoch(), 1);
+        assert_eq!(registry.get(&second).unwrap().get_epoch(), 0);
+        
+        assert!(registry.remove(&first));
+        assert!(!registry.remove(&first));
+        assert_eq!(registry.group_ids(), vec![second]);
+    }
+    
+    #[test]
+    fn test_mls_group_metadata_follows_epoch() {
+        let identity = SingularityKey::generate().unwrap();
+        let mut group = MLSGroup::new(&identity).unwrap();
+        assert!(group.metadata().unwrap().is_none());
+        
+        group.propose_metadata("Singularity", "Core team", None).unwrap();
+        group.commit().unwrap();
+        
+        let ext = GroupMetadataExtension::from_bytes(
+            &group.export_metadata_extension().unwrap().unwrap(),
+        ).unwrap();
+        assert_eq!(ext.epoch, 1);
+        assert_eq!(ext.version, 1);
+        
+        // A commit without metadata changes re-seals under the new epoch
+        group.propose_add("member-2", &[1, 2, 3, 4]).unwrap();
+        group.commit().unwrap();
+        
+        let ext = GroupMetadataExtension::from_bytes(
+            &group.export_metadata_extension().unwrap().unwrap(),
+        ).unwrap();
+        assert_eq!(ext.epoch, 2);
+        assert_eq!(ext.version, 1);
+        assert_eq!(group.metadata().unwrap().unwrap().name, "Singularity");
+    }
+    
+    #[test]
+    fn test_members_converge_on_commits() {
+        let alice = SingularityKey::generate().unwrap();
+        let bob = SingularityKey::generate().unwrap();
+        
+        let mut a = MLSGroup::new(&alice).unwrap();
+        a.propose_metadata("Singularity", "Core team", None).unwrap();
+        a.propose_add("bob", &[1, 2, 3, 4]).unwrap();
+        a.commit().unwrap();
+        
+        let mut b = MLSGroup::join(&bob, &a.welcome().unwrap()).unwrap();
+        assert_eq!(b.get_epoch(), 1);
+        assert_eq!(b.get_member_count(), 2);
+        assert_eq!(b.get_metadata().unwrap().name, "Singularity");
+        let before = a.encrypt_group_message(b"epoch one").unwrap();
+        
+        // Bob follows Alice's commit to the same metadata
+        let avatar = vec![0x89, b'P', b'N', b'G'];
+        a.propose_metadata("Event Horizon", "Renamed", Some(avatar.clone())).unwrap();
+        let commit = a.commit().unwrap();
+        assert!(!commit.windows(13).any(|window| window == b"Event Horizon"));
+        
+        b.apply_commit(&commit).unwrap();
+        assert_eq!(b.get_epoch(), 2);
+        assert_eq!(b.get_metadata().unwrap(), a.get_metadata().unwrap());
+        assert_eq!(b.get_metadata().unwrap().description, "Renamed");
+        assert_eq!(b.get_metadata().unwrap().avatar, Some(avatar));
+        
+        // The epoch secret moved on with the commit
+        let message = b.encrypt_group_message(b"hello").unwrap();
+        assert_eq!(a.decrypt_group_message(&message).unwrap(), b"hello".to_vec());
+        assert_eq!(b.decrypt_group_message(&before), Err(BlackHoleError::Decryption));
+        
+        // Replays and other groups' commits are refused
+        assert_eq!(b.apply_commit(&commit), Err(BlackHoleError::StaleEpoch { current: 2, received: 2 }));
+        let mut other = MLSGroup::new(&alice).unwrap();
+        other.propose_add("bob", &[1, 2, 3, 4]).unwrap();
+        assert_eq!(b.apply_commit(&other.commit().unwrap()), Err(BlackHoleError::UnknownGroup));
+        
+        // Without the epoch secret the next commit does not open
+        let mut forged: serde_json::Value = serde_json::from_slice(&a.welcome().unwrap()).unwrap();
+        forged["epoch_secret"] = serde_json::to_value([7u8; 32]).unwrap();
+        forged["metadata"] = serde_json::Value::Null;
+        let mut eve = MLSGroup::join(&bob, &serde_json::to_vec(&forged).unwrap()).unwrap();
+        
+        a.propose_add("carol", &[5, 6, 7, 8]).unwrap();
+        let next = a.commit().unwrap();
+        assert_eq!(eve.apply_commit(&next), Err(BlackHoleError::Decryption));
+        assert_eq!(eve.get_epoch(), 2);
+        
+        b.apply_commit(&next).unwrap();
+        assert_eq!(b.get_member_count(), 3);
+        assert_eq!(b.get_metadata().unwrap().name, "Event Horizon");
+    }
+    
+    #[test]
+    fn test_seeded_ratchet_transcript_is_reproducible() {
+        use crate::providers::SeededRng;
+        
+        let identity = SingularityKey::generate().unwrap();
+        let initiator = DoubleRatchet::with_rng(&identity, Arc::new(SeededRng::from_u64(4))).unwrap();
+        let responder = |seed| {
+            let rng = Arc::new(SeededRng::from_u64(seed));
+            DoubleRatchet::initialize_responder_with_rng(&identity, &initiator.get_dh_public(), rng).unwrap()
+        };
+        let (mut a, mut b) = (responder(5), responder(5));
+        assert_eq!(a.get_dh_public(), b.get_dh_public());
+        
+        let first = a.encrypt(b"known answer").unwrap();
+        let second = b.encrypt(b"known answer").unwrap();
+        assert_eq!(first.ciphertext, second.ciphertext);
+        assert_eq!(first.auth_tag, second.auth_tag);
+        
+        assert_eq!(
+            crate::hex::encode(&first.header.dh_public),
+            "861c35146b63dae4efd56e5f3435ef4ab4e81630c317b7f0fa52fc17e791da0c",
+        );
+        assert_eq!(
+            crate::hex::encode(&first.ciphertext),
+            "22ac7d19d635e20ad484f65a42949860a712a91edb9963fa5dad2018a5d5",
+        );
+    }
+    
+    #[test]
+    fn test_errors_are_typed() {
+        let identity = SingularityKey::generate().unwrap();
+        
+        assert!(matches!(
+            DoubleRatchet::initialize_responder(&identity, &[0u8; 31]),
+            Err(BlackHoleError::InvalidKeyLength { expected: 32, actual: 31 }),
+        ));
+        
+        let mut group = MLSGroup::new(&identity).unwrap();
+        group.propose_metadata("Singularity", "", None).unwrap();
+        group.commit().unwrap();
+        let stale = group.export_metadata_extension().unwrap().unwrap();
+        
+        group.propose_add("member-2", &[1, 2, 3, 4]).unwrap();
+        group.commit().unwrap();
+        assert_eq!(
+            group.apply_metadata_extension(&stale),
+            Err(BlackHoleError::StaleEpoch { current: 2, received: 1 }),
+        );
+        assert_eq!(group.commit(), Err(BlackHoleError::InvalidState("No pending proposals to commit")));
+    }
 }
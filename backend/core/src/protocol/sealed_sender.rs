@@ -0,0 +1,516 @@
+//! ✉️ Sealed Sender
+//!
+//! Hides the sender of a message from the relay. The outer envelope only
+//! carries the recipient's delivery address; the sender's identity key and a
+//! short-lived, server-issued sender certificate travel encrypted to the
+//! recipient's identity key.
+//!
+//! # Construction
+//!
+//! Identity keys are Ed25519 and are mapped to X25519 for the key agreement.
+//!
+//! ```text
+//! e, E          ephemeral X25519 key pair
+//! R             recipient identity key (X25519 form)
+//! S             sender identity key (X25519 form)
+//!
+//! chain, k_e    = KDF("ephemeral", R ‖ E ‖ DH(e, R))
+//! c_static      = AEAD(k_e, S_ed25519)
+//! k_s           = KDF("static", chain ‖ c_static ‖ DH(s, R))
+//! c_message     = AEAD(k_s, certificate ‖ content)
+//! ```
+//!
+//! The static DH authenticates the sender to the recipient, and the
+//! certificate binds that identity key to a fingerprint vouched for by the
+//! configured trust root.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+use crate::crypto::padding::{self, PaddingScheme};
+use crate::error::{key_array, BlackHoleError};
+use crate::providers::{CryptoRng, RngAdapter};
+use crate::SingularityKey;
+
+/// Domain separation context for the ephemeral layer
+const EPHEMERAL_KDF_CONTEXT: &str = "forticomm-blackhole 2024 sealed-sender ephemeral v1";
+
+/// Domain separation context for the static layer
+const STATIC_KDF_CONTEXT: &str = "forticomm-blackhole 2024 sealed-sender static v1";
+
+/// Prefix of the bytes signed by the certificate issuer
+const CERTIFICATE_SIGNING_PREFIX: &[u8] = b"forticomm-blackhole sender-certificate v1";
+
+/// The key that sender certificates must be signed with
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrustRoot {
+    /// Ed25519 public key of the certificate issuer
+    public_key: [u8; 32],
+}
+
+/// A short-lived certificate binding a sender's identity key to its fingerprint
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SenderCertificate {
+    /// Fingerprint of the sender's identity
+    pub sender_fingerprint: String,
+
+    /// Sender's Ed25519 identity public key
+    pub sender_identity_key: [u8; 32],
+
+    /// Expiry time (milliseconds since the Unix epoch)
+    pub expires_at: u64,
+
+    /// Issuer's Ed25519 signature over the fields above
+    pub signature: Vec<u8>,
+}
+
+/// A sealed envelope as seen by the relay
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SealedSenderEnvelope {
+    /// Delivery address of the recipient (the only routing information)
+    pub recipient_address: String,
+
+    /// Ephemeral X25519 public key
+    pub ephemeral_public: [u8; 32],
+
+    /// Sender identity key, encrypted under the ephemeral layer
+    pub encrypted_static: Vec<u8>,
+
+    /// Sender certificate and content, encrypted under the static layer
+    pub encrypted_message: Vec<u8>,
+}
+
+/// The result of opening a sealed envelope
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsealedMessage {
+    /// Fingerprint of the authenticated sender
+    pub sender_fingerprint: String,
+
+    /// Sender's Ed25519 identity public key
+    pub sender_identity_key: [u8; 32],
+
+    /// Decrypted content
+    pub content: Vec<u8>,
+}
+
+/// Inner plaintext of the static layer
+#[derive(Serialize, Deserialize)]
+struct SealedContent {
+    certificate: SenderCertificate,
+    content: Vec<u8>,
+}
+
+/// Key and nonce for one AEAD layer
+struct LayerKeys {
+    key: [u8; 32],
+    nonce: [u8; 12],
+}
+
+impl TrustRoot {
+    /// Create a trust root from the issuer's Ed25519 public key
+    pub fn new(public_key: &[u8]) -> Result<Self, BlackHoleError> {
+        let public_key: [u8; 32] = key_array(public_key)?;
+
+        ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+            .map_err(|_| BlackHoleError::InvalidKey)?;
+
+        Ok(TrustRoot { public_key })
+    }
+
+    /// Check that the certificate was signed by this trust root
+    fn verifies(&self, certificate: &SenderCertificate) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let Ok(verifying_key) = VerifyingKey::from_bytes(&self.public_key) else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_slice(&certificate.signature) else {
+            return false;
+        };
+
+        verifying_key
+            .verify(&certificate.signed_bytes(), &signature)
+            .is_ok()
+    }
+}
+
+impl SenderCertificate {
+    /// Whether the certificate is signed by `trust_root` and unexpired at `now`
+    pub fn is_valid(&self, trust_root: &TrustRoot, now: u64) -> bool {
+        now < self.expires_at && trust_root.verifies(self)
+    }
+
+    /// Whether the certificate was issued for `identity`
+    pub fn is_for(&self, identity: &SingularityKey) -> bool {
+        self.sender_identity_key == identity.public
+            && self.sender_fingerprint == identity.fingerprint
+    }
+
+    /// Bytes covered by the issuer's signature
+    pub(crate) fn signed_bytes(&self) -> Vec<u8> {
+        let fingerprint = self.sender_fingerprint.as_bytes();
+
+        let mut bytes = Vec::with_capacity(CERTIFICATE_SIGNING_PREFIX.len() + fingerprint.len() + 44);
+        bytes.extend_from_slice(CERTIFICATE_SIGNING_PREFIX);
+        bytes.extend_from_slice(&(fingerprint.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(fingerprint);
+        bytes.extend_from_slice(&self.sender_identity_key);
+        bytes.extend_from_slice(&self.expires_at.to_be_bytes());
+        bytes
+    }
+
+    /// Encode the certificate
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BlackHoleError> {
+        serde_json::to_vec(self)
+            .map_err(BlackHoleError::serialization)
+    }
+
+    /// Decode a certificate received from the server
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlackHoleError> {
+        serde_json::from_slice(bytes)
+            .map_err(BlackHoleError::serialization)
+    }
+}
+
+impl SealedSenderEnvelope {
+    /// Seal `content` from `sender` to the holder of `recipient_identity_key`
+    pub fn seal(
+        sender: &SingularityKey,
+        certificate: &SenderCertificate,
+        recipient_address: &str,
+        recipient_identity_key: &[u8],
+        content: &[u8],
+        rng: &dyn CryptoRng,
+    ) -> Result<Self, BlackHoleError> {
+        use x25519_dalek::{PublicKey, StaticSecret};
+
+        if !certificate.is_for(sender) {
+            return Err(BlackHoleError::InvalidCertificate);
+        }
+
+        let recipient = PublicKey::from(ed25519_public_to_x25519(recipient_identity_key)?);
+
+        // Ephemeral layer: hides the sender identity key
+        let ephemeral_secret = StaticSecret::random_from_rng(RngAdapter(rng));
+        let ephemeral_public = PublicKey::from(&ephemeral_secret);
+        let ephemeral_shared = ephemeral_secret.diffie_hellman(&recipient);
+
+        let (mut chain_key, ephemeral_keys) = ephemeral_layer_keys(
+            recipient.as_bytes(),
+            ephemeral_public.as_bytes(),
+            ephemeral_shared.as_bytes(),
+        );
+
+        let encrypted_static = aead_seal(&ephemeral_keys, &sender.public, recipient_address)?;
+
+        // Static layer: authenticates the sender and carries the certificate
+        let static_secret = StaticSecret::from(ed25519_private_to_x25519(&sender.private));
+        let static_shared = static_secret.diffie_hellman(&recipient);
+
+        let static_keys = static_layer_keys(&chain_key, &encrypted_static, static_shared.as_bytes());
+        chain_key.zeroize();
+
+        let mut serialized = serde_json::to_vec(&SealedContent {
+            certificate: certificate.clone(),
+            content: content.to_vec(),
+        })
+        .map_err(BlackHoleError::serialization)?;
+
+        let mut inner = padding::pad(&serialized, PaddingScheme::default());
+        serialized.zeroize();
+
+        let encrypted_message = aead_seal(&static_keys, &inner, recipient_address);
+        inner.zeroize();
+
+        Ok(SealedSenderEnvelope {
+            recipient_address: recipient_address.to_string(),
+            ephemeral_public: *ephemeral_public.as_bytes(),
+            encrypted_static,
+            encrypted_message: encrypted_message?,
+        })
+    }
+
+    /// Open an envelope addressed to `recipient`
+    ///
+    /// Fails unless the sender certificate is signed by `trust_root`, is
+    /// unexpired at `now`, and matches the DH-authenticated sender key.
+    pub fn open(
+        &self,
+        recipient: &SingularityKey,
+        trust_root: &TrustRoot,
+        now: u64,
+    ) -> Result<UnsealedMessage, BlackHoleError> {
+        use x25519_dalek::{PublicKey, StaticSecret};
+
+        let recipient_secret = StaticSecret::from(ed25519_private_to_x25519(&recipient.private));
+        let recipient_public = PublicKey::from(&recipient_secret);
+
+        // Ephemeral layer
+        let ephemeral_public = PublicKey::from(self.ephemeral_public);
+        let ephemeral_shared = recipient_secret.diffie_hellman(&ephemeral_public);
+
+        let (mut chain_key, ephemeral_keys) = ephemeral_layer_keys(
+            recipient_public.as_bytes(),
+            &self.ephemeral_public,
+            ephemeral_shared.as_bytes(),
+        );
+
+        let sender_identity_key: [u8; 32] =
+            aead_open(&ephemeral_keys, &self.encrypted_static, &self.recipient_address)?
+                .try_into()
+                .map_err(|_| BlackHoleError::Malformed("sealed sender envelope"))?;
+
+        // Static layer
+        let sender_public = PublicKey::from(ed25519_public_to_x25519(&sender_identity_key)?);
+        let static_shared = recipient_secret.diffie_hellman(&sender_public);
+
+        let static_keys = static_layer_keys(&chain_key, &self.encrypted_static, static_shared.as_bytes());
+        chain_key.zeroize();
+
+        let mut inner = aead_open(&static_keys, &self.encrypted_message, &self.recipient_address)?;
+        let sealed: Option<SealedContent> = padding::unpad(&inner)
+            .and_then(|serialized| serde_json::from_slice(serialized).ok());
+        inner.zeroize();
+
+        let SealedContent { certificate, content } = sealed
+            .ok_or(BlackHoleError::Malformed("sealed sender content"))?;
+
+        if certificate.sender_identity_key != sender_identity_key {
+            return Err(BlackHoleError::InvalidCertificate);
+        }
+
+        if !certificate.is_valid(trust_root, now) {
+            return Err(BlackHoleError::InvalidCertificate);
+        }
+
+        Ok(UnsealedMessage {
+            sender_fingerprint: certificate.sender_fingerprint,
+            sender_identity_key,
+            content,
+        })
+    }
+
+    /// Encode the envelope for the relay
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BlackHoleError> {
+        serde_json::to_vec(self)
+            .map_err(BlackHoleError::serialization)
+    }
+
+    /// Decode an envelope received from the relay
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlackHoleError> {
+        serde_json::from_slice(bytes)
+            .map_err(BlackHoleError::serialization)
+    }
+}
+
+impl Drop for LayerKeys {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.nonce.zeroize();
+    }
+}
+
+/// Derive the chain key and AEAD keys of the ephemeral layer
+fn ephemeral_layer_keys(
+    recipient_public: &[u8; 32],
+    ephemeral_public: &[u8; 32],
+    shared: &[u8; 32],
+) -> ([u8; 32], LayerKeys) {
+    let mut hasher = blake3::Hasher::new_derive_key(EPHEMERAL_KDF_CONTEXT);
+    hasher.update(recipient_public);
+    hasher.update(ephemeral_public);
+    hasher.update(shared);
+
+    let mut okm = [0u8; 76];
+    hasher.finalize_xof().fill(&mut okm);
+
+    let mut chain_key = [0u8; 32];
+    let mut keys = LayerKeys { key: [0u8; 32], nonce: [0u8; 12] };
+    chain_key.copy_from_slice(&okm[..32]);
+    keys.key.copy_from_slice(&okm[32..64]);
+    keys.nonce.copy_from_slice(&okm[64..]);
+    okm.zeroize();
+
+    (chain_key, keys)
+}
+
+/// Derive the AEAD keys of the static layer
+fn static_layer_keys(chain_key: &[u8; 32], encrypted_static: &[u8], shared: &[u8; 32]) -> LayerKeys {
+    let mut hasher = blake3::Hasher::new_derive_key(STATIC_KDF_CONTEXT);
+    hasher.update(chain_key);
+    hasher.update(encrypted_static);
+    hasher.update(shared);
+
+    let mut okm = [0u8; 44];
+    hasher.finalize_xof().fill(&mut okm);
+
+    let mut keys = LayerKeys { key: [0u8; 32], nonce: [0u8; 12] };
+    keys.key.copy_from_slice(&okm[..32]);
+    keys.nonce.copy_from_slice(&okm[32..]);
+    okm.zeroize();
+
+    keys
+}
+
+/// Encrypt one layer; keys are single-use, so the derived nonce never repeats
+fn aead_seal(keys: &LayerKeys, plaintext: &[u8], recipient_address: &str) -> Result<Vec<u8>, BlackHoleError> {
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&keys.key))
+        .encrypt(
+            Nonce::from_slice(&keys.nonce),
+            Payload { msg: plaintext, aad: recipient_address.as_bytes() },
+        )
+        .map_err(|_| BlackHoleError::Encryption)
+}
+
+/// Decrypt one layer
+fn aead_open(keys: &LayerKeys, ciphertext: &[u8], recipient_address: &str) -> Result<Vec<u8>, BlackHoleError> {
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&keys.key))
+        .decrypt(
+            Nonce::from_slice(&keys.nonce),
+            Payload { msg: ciphertext, aad: recipient_address.as_bytes() },
+        )
+        .map_err(|_| BlackHoleError::Decryption)
+}
+
+/// Map an Ed25519 public key to its X25519 (Montgomery) form
+fn ed25519_public_to_x25519(public_key: &[u8]) -> Result<[u8; 32], BlackHoleError> {
+    let public_key: [u8; 32] = key_array(public_key)?;
+
+    let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+        .map_err(|_| BlackHoleError::InvalidKey)?;
+
+    Ok(verifying_key.to_montgomery().to_bytes())
+}
+
+/// Map an Ed25519 private key to the matching X25519 scalar
+fn ed25519_private_to_x25519(private_key: &[u8; 32]) -> [u8; 32] {
+    ed25519_dalek::SigningKey::from_bytes(private_key).to_scalar_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use crate::providers::OsRandom;
+
+    /// Local stand-in for the server's certificate issuer
+    struct LocalIssuer {
+        signing_key: SigningKey,
+    }
+
+    impl LocalIssuer {
+        fn new() -> Self {
+            LocalIssuer { signing_key: SigningKey::generate(&mut rand_core::OsRng) }
+        }
+
+        fn trust_root(&self) -> TrustRoot {
+            TrustRoot::new(self.signing_key.verifying_key().as_bytes()).unwrap()
+        }
+
+        fn issue(&self, identity: &SingularityKey, expires_at: u64) -> SenderCertificate {
+            let mut certificate = SenderCertificate {
+                sender_fingerprint: identity.fingerprint.clone(),
+                sender_identity_key: identity.public,
+                expires_at,
+                signature: Vec::new(),
+            };
+            certificate.signature = self.signing_key.sign(&certificate.signed_bytes()).to_bytes().to_vec();
+            certificate
+        }
+    }
+
+    #[test]
+    fn test_sealed_sender_roundtrip() {
+        let issuer = LocalIssuer::new();
+        let alice = SingularityKey::generate().unwrap();
+        let bob = SingularityKey::generate().unwrap();
+        let certificate = issuer.issue(&alice, 2_000);
+
+        let envelope = SealedSenderEnvelope::seal(
+            &alice, &certificate, "bob-mailbox", &bob.public, b"through the horizon", &OsRandom,
+        ).unwrap();
+
+        let bytes = envelope.to_bytes().unwrap();
+        let received = SealedSenderEnvelope::from_bytes(&bytes).unwrap();
+        let opened = received.open(&bob, &issuer.trust_root(), 1_000).unwrap();
+
+        assert_eq!(opened.sender_fingerprint, alice.fingerprint);
+        assert_eq!(opened.sender_identity_key, alice.public);
+        assert_eq!(opened.content, b"through the horizon".to_vec());
+    }
+
+    #[test]
+    fn test_seeded_envelope_known_answer() {
+        use crate::providers::SeededRng;
+
+        let issuer = LocalIssuer::new();
+        let alice = SingularityKey::generate_with(&SeededRng::from_u64(1)).unwrap();
+        let bob = SingularityKey::generate_with(&SeededRng::from_u64(2)).unwrap();
+        let certificate = issuer.issue(&alice, 2_000);
+
+        let seal = || {
+            SealedSenderEnvelope::seal(
+                &alice, &certificate, "bob-mailbox", &bob.public, b"hello", &SeededRng::from_u64(3),
+            ).unwrap()
+        };
+        let envelope = seal();
+        assert_eq!(envelope.ephemeral_public, seal().ephemeral_public);
+        assert_eq!(
+            crate::hex::encode(&envelope.ephemeral_public),
+            "464a36fb5639c45dd9bbfbfa708f68df7d57c49675b6e690512c34f3ee6c0a0a",
+        );
+        assert_eq!(
+            crate::hex::encode(&envelope.encrypted_static),
+            "97b8bd1990f524dcd35c7617dbf66540c23bb69eb51637fb35d6a367092ab5dda7145a1748a5658d30b9602383123a38",
+        );
+    }
+
+    #[test]
+    fn test_envelope_hides_sender() {
+        let issuer = LocalIssuer::new();
+        let alice = SingularityKey::generate().unwrap();
+        let bob = SingularityKey::generate().unwrap();
+        let certificate = issuer.issue(&alice, 2_000);
+
+        let envelope = SealedSenderEnvelope::seal(
+            &alice, &certificate, "bob-mailbox", &bob.public, b"hello", &OsRandom,
+        ).unwrap();
+        let bytes = envelope.to_bytes().unwrap();
+
+        let fingerprint = alice.fingerprint.as_bytes();
+        assert!(!bytes.windows(fingerprint.len()).any(|w| w == fingerprint));
+
+        // The key appears neither in its JSON encoding nor inside any field
+        let encoded_key = serde_json::to_vec(&alice.public).unwrap();
+        assert!(!bytes.windows(encoded_key.len()).any(|w| w == encoded_key));
+
+        let decoded = SealedSenderEnvelope::from_bytes(&bytes).unwrap();
+        assert_ne!(decoded.ephemeral_public, alice.public);
+        for field in [&decoded.encrypted_static, &decoded.encrypted_message] {
+            assert!(!field.windows(32).any(|w| w == alice.public));
+        }
+    }
+
+    #[test]
+    fn test_certificate_validation() {
+        let issuer = LocalIssuer::new();
+        let impostor = LocalIssuer::new();
+        let alice = SingularityKey::generate().unwrap();
+        let certificate = issuer.issue(&alice, 2_000);
+
+        assert!(certificate.is_valid(&issuer.trust_root(), 1_999));
+        assert!(!certificate.is_valid(&issuer.trust_root(), 2_000));
+        assert!(!certificate.is_valid(&impostor.trust_root(), 1_000));
+
+        let mut forged = certificate.clone();
+        forged.expires_at = 10_000;
+        assert!(!forged.is_valid(&issuer.trust_root(), 5_000));
+    }
+}
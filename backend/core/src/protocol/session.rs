@@ -0,0 +1,623 @@
+//! 🗂️ Sessions and Disappearing Messages
+//!
+//! `SessionManager` owns one Double Ratchet per peer together with the local
+//! history of each conversation. Every stored message is sealed under its own
+//! random key, so expiring a message is a matter of destroying that key: once
+//! its deadline passes, the key and ciphertext are zeroized and any copy of
+//! the ciphertext left behind (backups, swap, old exports) is unreadable.
+//!
+//! ```text
+//! k_msg     ←$ {0,1}²⁵⁶
+//! stored    = AES-256-GCM(k_msg, content, aad = peer ‖ id)
+//! expires_at = stored_at + policy.seconds
+//! ```
+//!
+//! Message keys live in a key store apart from the history. An export never
+//! contains them in the clear: they are sealed under a device key drawn
+//! fresh for every export, which the client keeps in the platform keystore
+//! in place of the previous one. Once a new export replaces an old one, the
+//! old device key is gone and so are the keys of messages that expired in
+//! between.
+//!
+//! ```text
+//! k_dev     ←$ {0,1}²⁵⁶                      per export
+//! export    = history ‖ AES-256-GCM(k_dev, { k_msg })
+//! ```
+//!
+//! Deadlines are absolute timestamps taken from the injected `Clock`, so they
+//! survive an export/import across restarts and anything that expired while
+//! the client was closed is purged on import.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::crypto::padding::PaddingScheme;
+use crate::error::{key_array, BlackHoleError};
+use crate::providers::Providers;
+use super::content::{ExpiryPolicy, MessageContent};
+use super::{DoubleRatchet, MessageEnvelope};
+
+/// Associated data of the sealed key store in an export
+const KEY_STORE_AAD: &[u8] = b"BlackHole 2025 history keys";
+
+/// A message at rest, sealed under its own key
+///
+/// The key is held in the `KeyStore`, never next to the ciphertext.
+#[derive(Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct StoredMessage {
+    /// Local message ID, unique within the conversation
+    #[zeroize(skip)]
+    id: u64,
+
+    /// Whether we sent the message
+    #[zeroize(skip)]
+    outgoing: bool,
+
+    /// When the message was stored (ms since the Unix epoch)
+    #[zeroize(skip)]
+    stored_at: u64,
+
+    /// When the message must be destroyed, if it disappears
+    #[zeroize(skip)]
+    expires_at: Option<u64>,
+
+    /// Nonce for AES-GCM
+    nonce: [u8; 12],
+
+    /// Encrypted `MessageContent`
+    ciphertext: Vec<u8>,
+}
+
+/// Local history and timer of one conversation
+#[derive(Clone, Default, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct Conversation {
+    /// Timer applied to new messages
+    #[zeroize(skip)]
+    expiry: Option<ExpiryPolicy>,
+
+    /// Next local message ID
+    #[zeroize(skip)]
+    next_id: u64,
+
+    /// Stored messages, oldest first
+    messages: Vec<StoredMessage>,
+}
+
+/// Per-message storage keys by peer and message ID
+#[derive(Default, Serialize, Deserialize)]
+struct KeyStore(BTreeMap<String, BTreeMap<u64, [u8; 32]>>);
+
+/// The exported form of the history
+#[derive(Serialize, Deserialize)]
+struct SealedHistory {
+    /// Timers and encrypted messages
+    conversations: BTreeMap<String, Conversation>,
+
+    /// Nonce for AES-GCM
+    nonce: [u8; 12],
+
+    /// The key store, sealed under the device key
+    keys: Vec<u8>,
+}
+
+/// History exported for the client's storage
+#[derive(Clone, Serialize, Zeroize, ZeroizeOnDrop)]
+pub struct HistoryExport {
+    /// Timers and messages, with the message keys sealed under `device_key`
+    #[zeroize(skip)]
+    pub history: Vec<u8>,
+
+    /// Key unlocking this export, to keep in the platform keystore in place
+    /// of the previous one
+    pub device_key: [u8; 32],
+}
+
+/// A decrypted message from the local history
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct HistoryEntry {
+    /// Local message ID
+    pub id: u64,
+
+    /// Whether we sent the message
+    pub outgoing: bool,
+
+    /// When the message was stored (ms since the Unix epoch)
+    pub stored_at: u64,
+
+    /// When the message will be destroyed, if it disappears
+    pub expires_at: Option<u64>,
+
+    /// The message itself
+    pub content: MessageContent,
+}
+
+/// Per-peer ratchet sessions and conversation storage
+pub struct SessionManager {
+    /// Double Ratchet per peer
+    sessions: BTreeMap<String, DoubleRatchet>,
+
+    /// Conversation history per peer
+    conversations: BTreeMap<String, Conversation>,
+
+    /// Keys of the stored messages
+    keys: KeyStore,
+
+    /// Clock for timestamps and expiry, randomness for storage keys
+    providers: Providers,
+
+    /// Padding applied by every session
+    padding: PaddingScheme,
+}
+
+impl SessionManager {
+    /// Create an empty manager running against `providers`
+    pub fn new(providers: Providers) -> Self {
+        SessionManager {
+            sessions: BTreeMap::new(),
+            conversations: BTreeMap::new(),
+            keys: KeyStore::default(),
+            providers,
+            padding: PaddingScheme::default(),
+        }
+    }
+
+    /// Install the ratchet session for `peer`, replacing any existing one
+    pub fn add_session(&mut self, peer: &str, mut ratchet: DoubleRatchet) {
+        ratchet.set_padding_scheme(self.padding);
+        self.sessions.insert(peer.to_string(), ratchet);
+    }
+
+    /// Move every ratchet session out, leaving the history in place
+    pub fn take_sessions(&mut self) -> BTreeMap<String, DoubleRatchet> {
+        core::mem::take(&mut self.sessions)
+    }
+
+    /// Whether a ratchet session with `peer` exists
+    pub fn has_session(&self, peer: &str) -> bool {
+        self.sessions.contains_key(peer)
+    }
+
+    /// Drop the session and history of `peer`, wiping their secrets
+    pub fn remove_session(&mut self, peer: &str) -> bool {
+        let had_session = self.sessions.remove(peer).is_some();
+        let had_history = self.conversations.remove(peer).is_some();
+        self.keys.remove_peer(peer);
+
+        had_session || had_history
+    }
+
+    /// Peers with a session or stored history
+    pub fn peers(&self) -> Vec<String> {
+        let mut peers: Vec<String> = self.sessions.keys().chain(self.conversations.keys()).cloned().collect();
+        peers.sort();
+        peers.dedup();
+        peers
+    }
+
+    /// Select the padding scheme for every current and future session
+    pub fn set_padding_scheme(&mut self, scheme: PaddingScheme) {
+        self.padding = scheme;
+
+        for ratchet in self.sessions.values_mut() {
+            ratchet.set_padding_scheme(scheme);
+        }
+    }
+
+    /// Disappearing-message timer of the conversation with `peer`
+    pub fn expiry(&self, peer: &str) -> Option<ExpiryPolicy> {
+        self.conversations.get(peer).and_then(|c| c.expiry)
+    }
+
+    /// Change the timer locally, returning the content that tells the peer
+    pub fn set_expiry(&mut self, peer: &str, policy: Option<ExpiryPolicy>) -> MessageContent {
+        self.conversations.entry(peer.to_string()).or_default().expiry = policy;
+        MessageContent::ExpiryTimer { policy }
+    }
+
+    /// Encrypt content for `peer` and keep a local copy
+    ///
+    /// Texts without their own policy inherit the conversation timer.
+    pub fn encrypt(&mut self, peer: &str, content: MessageContent) -> Result<(u64, MessageEnvelope), BlackHoleError> {
+        let content = self.apply_timer(peer, content);
+        let now = self.providers.clock.now_ms();
+
+        let ratchet = self.sessions.get_mut(peer)
+            .ok_or(BlackHoleError::NoSession)?;
+
+        let mut encoded = content.encode()?;
+        let envelope = ratchet.encrypt(&encoded);
+        encoded.zeroize();
+
+        let mut envelope = envelope?;
+        envelope.timestamp = now;
+
+        let id = self.record(peer, true, &content)?;
+        Ok((id, envelope))
+    }
+
+    /// Decrypt a message from `peer` and store it
+    pub fn decrypt(&mut self, peer: &str, envelope: &MessageEnvelope) -> Result<(u64, MessageContent), BlackHoleError> {
+        let ratchet = self.sessions.get_mut(peer)
+            .ok_or(BlackHoleError::NoSession)?;
+
+        let mut plaintext = ratchet.decrypt(envelope)?;
+        let content = MessageContent::decode(&plaintext);
+        plaintext.zeroize();
+
+        let content = self.apply_timer(peer, content?);
+        let id = self.record(peer, false, &content)?;
+
+        Ok((id, content))
+    }
+
+    /// Store content exchanged with `peer` over any transport
+    ///
+    /// An `ExpiryTimer` updates the conversation timer. Returns the local ID.
+    pub fn record(&mut self, peer: &str, outgoing: bool, content: &MessageContent) -> Result<u64, BlackHoleError> {
+        use aes_gcm::{Aes256Gcm, Key, Nonce};
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+        let mut encoded = content.encode()?;
+        let now = self.providers.clock.now_ms();
+        let conversation = self.conversations.entry(peer.to_string()).or_default();
+
+        if let MessageContent::ExpiryTimer { policy } = content {
+            conversation.expiry = *policy;
+        }
+
+        let id = conversation.next_id;
+        conversation.next_id += 1;
+
+        let key: [u8; 32] = self.providers.rng.random();
+        let nonce: [u8; 12] = self.providers.rng.random();
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload { msg: &encoded, aad: &associated_data(peer, id) },
+            )
+            .map_err(|_| BlackHoleError::Encryption);
+        encoded.zeroize();
+
+        conversation.messages.push(StoredMessage {
+            id,
+            outgoing,
+            stored_at: now,
+            expires_at: content.expiry().map(|policy| policy.expires_at(now)),
+            nonce,
+            ciphertext: ciphertext?,
+        });
+        self.keys.0.entry(peer.to_string()).or_default().insert(id, key);
+
+        Ok(id)
+    }
+
+    /// Read one stored message, unless it has expired
+    pub fn message(&mut self, peer: &str, id: u64) -> Option<HistoryEntry> {
+        self.purge_expired();
+
+        let stored = self.conversations.get(peer)?
+            .messages.iter()
+            .find(|m| m.id == id)?;
+
+        open_message(peer, stored, self.keys.get(peer, id)?)
+    }
+
+    /// Read the unexpired history of the conversation with `peer`
+    pub fn history(&mut self, peer: &str) -> Vec<HistoryEntry> {
+        self.purge_expired();
+
+        let keys = &self.keys;
+        self.conversations.get(peer)
+            .map(|c| c.messages.iter().filter_map(|m| open_message(peer, m, keys.get(peer, m.id)?)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Destroy every message whose deadline has passed
+    ///
+    /// Returns the number of messages destroyed. Their keys are zeroized and
+    /// dropping a message zeroizes its ciphertext.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.providers.clock.now_ms();
+        let mut purged = 0;
+
+        for (peer, conversation) in self.conversations.iter_mut() {
+            let keys = &mut self.keys;
+            let before = conversation.messages.len();
+            conversation.messages.retain(|m| {
+                let live = m.expires_at.map_or(true, |deadline| deadline > now);
+                if !live {
+                    keys.remove(peer, m.id);
+                }
+                live
+            });
+            purged += before - conversation.messages.len();
+        }
+
+        if purged > 0 {
+            log::info!("🔥 Destroyed {} expired messages", purged);
+        }
+
+        purged
+    }
+
+    /// Earliest pending deadline, for scheduling the next purge
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.conversations.values()
+            .flat_map(|c| c.messages.iter())
+            .filter_map(|m| m.expires_at)
+            .min()
+    }
+
+    /// Export timers and history for persistence across restarts
+    ///
+    /// The message keys of unexpired messages are sealed under a fresh device
+    /// key. Store it in the platform keystore, replacing the key of the
+    /// previous export, so that older exports can no longer be opened.
+    pub fn export_history(&mut self) -> Result<HistoryExport, BlackHoleError> {
+        use aes_gcm::{Aes256Gcm, Key, Nonce};
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+        self.purge_expired();
+
+        let device_key: [u8; 32] = self.providers.rng.random();
+        let nonce: [u8; 12] = self.providers.rng.random();
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&device_key));
+
+        let mut keys = serde_json::to_vec(&self.keys).map_err(BlackHoleError::serialization)?;
+        let sealed = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &keys, aad: KEY_STORE_AAD })
+            .map_err(|_| BlackHoleError::Encryption);
+        keys.zeroize();
+
+        let history = SealedHistory {
+            conversations: self.conversations.clone(),
+            nonce,
+            keys: sealed?,
+        };
+
+        Ok(HistoryExport {
+            history: serde_json::to_vec(&history).map_err(BlackHoleError::serialization)?,
+            device_key,
+        })
+    }
+
+    /// Restore timers and history, destroying anything that expired meanwhile
+    ///
+    /// `device_key` is the key returned with the export.
+    pub fn import_history(&mut self, bytes: &[u8], device_key: &[u8]) -> Result<(), BlackHoleError> {
+        use aes_gcm::{Aes256Gcm, Key, Nonce};
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+        let device_key: [u8; 32] = key_array(device_key)?;
+        let history: SealedHistory = serde_json::from_slice(bytes)
+            .map_err(BlackHoleError::serialization)?;
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&device_key));
+        let mut keys = cipher
+            .decrypt(Nonce::from_slice(&history.nonce), Payload { msg: &history.keys, aad: KEY_STORE_AAD })
+            .map_err(|_| BlackHoleError::Decryption)?;
+        let store = serde_json::from_slice(&keys).map_err(BlackHoleError::serialization);
+        keys.zeroize();
+
+        self.keys = store?;
+        self.conversations = history.conversations;
+        self.purge_expired();
+
+        Ok(())
+    }
+
+    /// Give texts without their own policy the conversation timer
+    pub fn apply_timer(&self, peer: &str, content: MessageContent) -> MessageContent {
+        match content {
+            MessageContent::Text { body, mentions, quote, expiry: None } => MessageContent::Text {
+                body,
+                mentions,
+                quote,
+                expiry: self.expiry(peer),
+            },
+            content => content,
+        }
+    }
+}
+
+impl Zeroize for SessionManager {
+    fn zeroize(&mut self) {
+        // Dropping ratchets and stored messages zeroizes their keys
+        self.sessions.clear();
+        self.conversations.clear();
+        self.keys.zeroize();
+    }
+}
+
+impl KeyStore {
+    fn get(&self, peer: &str, id: u64) -> Option<&[u8; 32]> {
+        self.0.get(peer)?.get(&id)
+    }
+
+    fn remove(&mut self, peer: &str, id: u64) {
+        if let Some(mut key) = self.0.get_mut(peer).and_then(|keys| keys.remove(&id)) {
+            key.zeroize();
+        }
+    }
+
+    fn remove_peer(&mut self, peer: &str) {
+        if let Some(mut keys) = self.0.remove(peer) {
+            keys.values_mut().for_each(Zeroize::zeroize);
+        }
+    }
+}
+
+impl Zeroize for KeyStore {
+    fn zeroize(&mut self) {
+        for keys in self.0.values_mut() {
+            keys.values_mut().for_each(Zeroize::zeroize);
+        }
+        self.0.clear();
+    }
+}
+
+impl Drop for KeyStore {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Decrypt a stored message
+fn open_message(peer: &str, stored: &StoredMessage, key: &[u8; 32]) -> Option<HistoryEntry> {
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut encoded = cipher
+        .decrypt(
+            Nonce::from_slice(&stored.nonce),
+            Payload { msg: &stored.ciphertext, aad: &associated_data(peer, stored.id) },
+        )
+        .ok()?;
+
+    let content = MessageContent::decode(&encoded).ok();
+    encoded.zeroize();
+
+    Some(HistoryEntry {
+        id: stored.id,
+        outgoing: stored.outgoing,
+        stored_at: stored.stored_at,
+        expires_at: stored.expires_at,
+        content: content?,
+    })
+}
+
+/// Associated data binding a stored message to its conversation and ID
+fn associated_data(peer: &str, id: u64) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(peer.len() + 8);
+    aad.extend_from_slice(peer.as_bytes());
+    aad.extend_from_slice(&id.to_be_bytes());
+    aad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::sync::Arc;
+    use crate::providers::{FixedClock, SeededRng};
+    use crate::SingularityKey;
+
+    const PEER: &str = "a1b2c3";
+
+    fn manager() -> (Arc<FixedClock>, SessionManager) {
+        let clock = Arc::new(FixedClock::new(1_700_000_000_000));
+        let manager = SessionManager::new(providers(&clock));
+        (clock, manager)
+    }
+
+    fn providers(clock: &Arc<FixedClock>) -> Providers {
+        Providers::new(clock.clone(), Arc::new(SeededRng::from_u64(1)))
+    }
+
+    #[test]
+    fn test_messages_expire_with_conversation_timer() {
+        let (clock, mut manager) = manager();
+
+        let timer = manager.set_expiry(PEER, Some(ExpiryPolicy::new(60)));
+        assert_eq!(timer, MessageContent::ExpiryTimer { policy: Some(ExpiryPolicy::new(60)) });
+
+        let text = manager.apply_timer(PEER, MessageContent::text("gone in 60 seconds"));
+        let disappearing = manager.record(PEER, true, &text).unwrap();
+        manager.set_expiry(PEER, None);
+        let permanent = manager.record(PEER, false, &MessageContent::text("stays")).unwrap();
+
+        assert_eq!(manager.next_expiry(), Some(1_700_000_060_000));
+        assert!(manager.message(PEER, disappearing).is_some());
+
+        clock.advance(59_999);
+        assert_eq!(manager.purge_expired(), 0);
+
+        clock.advance(1);
+        assert!(manager.message(PEER, disappearing).is_none());
+        assert_eq!(manager.history(PEER).len(), 1);
+        assert_eq!(manager.history(PEER)[0].id, permanent);
+        assert_eq!(manager.next_expiry(), None);
+    }
+
+    #[test]
+    fn test_received_timer_update_applies_to_conversation() {
+        let (_, mut manager) = manager();
+
+        let update = MessageContent::ExpiryTimer { policy: Some(ExpiryPolicy::new(5)) };
+        manager.record(PEER, false, &update).unwrap();
+
+        assert_eq!(manager.expiry(PEER), Some(ExpiryPolicy::new(5)));
+        assert_eq!(
+            manager.apply_timer(PEER, MessageContent::text("hi")).expiry(),
+            Some(ExpiryPolicy::new(5)),
+        );
+    }
+
+    #[test]
+    fn test_timers_survive_restart() {
+        let (clock, mut manager) = manager();
+        manager.set_expiry(PEER, Some(ExpiryPolicy::new(3600)));
+
+        let text = manager.apply_timer(PEER, MessageContent::text("secret"));
+        let id = manager.record(PEER, true, &text).unwrap();
+        let exported = manager.export_history().unwrap();
+
+        // Restart shortly after: the message and timer are restored
+        clock.advance(1_000);
+        let mut restored = SessionManager::new(providers(&clock));
+        restored.import_history(&exported.history, &exported.device_key).unwrap();
+        assert_eq!(restored.expiry(PEER), Some(ExpiryPolicy::new(3600)));
+        assert_eq!(restored.message(PEER, id).unwrap().content, text);
+
+        // Restart after the deadline: the message is destroyed on import
+        clock.advance(3_600_000);
+        let mut restored = SessionManager::new(providers(&clock));
+        restored.import_history(&exported.history, &exported.device_key).unwrap();
+        assert!(restored.message(PEER, id).is_none());
+        assert!(restored.history(PEER).is_empty());
+    }
+
+    #[test]
+    fn test_expired_message_cannot_be_read() {
+        let (clock, mut alice) = manager();
+        let mut bob = SessionManager::new(providers(&clock));
+
+        let identity = SingularityKey::generate().unwrap();
+        let initiator = DoubleRatchet::new(&identity).unwrap();
+        let responder = DoubleRatchet::initialize_responder(&identity, &initiator.get_dh_public()).unwrap();
+        bob.add_session("alice", initiator);
+        alice.add_session(PEER, responder);
+
+        alice.set_expiry(PEER, Some(ExpiryPolicy::new(60)));
+        let (_, envelope) = alice.encrypt(PEER, MessageContent::text("burn after reading")).unwrap();
+        let (id, content) = bob.decrypt("alice", &envelope).unwrap();
+        assert_eq!(content.expiry(), Some(ExpiryPolicy::new(60)));
+        assert_eq!(bob.message("alice", id).unwrap().content, content);
+
+        let before = bob.export_history().unwrap();
+        assert!(!String::from_utf8_lossy(&before.history).contains("\"key\""));
+
+        clock.advance(60_000);
+        assert_eq!(bob.purge_expired(), 1);
+        assert!(bob.message("alice", id).is_none());
+        assert!(bob.keys.get("alice", id).is_none());
+
+        // The next export rotates the device key; the earlier export does not
+        // open under it, and the new one holds no key for the message
+        let after = bob.export_history().unwrap();
+        assert_ne!(after.device_key, before.device_key);
+
+        let mut restored = SessionManager::new(providers(&clock));
+        assert_eq!(
+            restored.import_history(&before.history, &after.device_key),
+            Err(BlackHoleError::Decryption),
+        );
+        restored.import_history(&after.history, &after.device_key).unwrap();
+        assert!(restored.keys.get("alice", id).is_none());
+        assert!(restored.history("alice").is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+//! ⏱️ Environment Providers
+//!
+//! Services the core needs from its host environment, behind traits so they
+//! can be swapped for deterministic implementations in native tests.
+//!
+//! - `Clock`: wall-clock time (`SystemClock`, `FixedClock`)
+//! - `CryptoRng`: cryptographic randomness (`OsRandom`, `SeededRng`)
+//!
+//! `Providers` bundles both and is threaded through `BlackHoleCore`, the
+//! Double Ratchet, MLS groups and the post-quantum key generator. A seeded
+//! bundle makes every key, nonce and timestamp reproducible, which is what
+//! known-answer transcripts are built from.
+//!
+//! Without the `std` feature there is no `SystemClock`; embedded targets
+//! supply their own `Clock` (an RTC, or a time received from the host) and
+//! register their hardware TRNG with `getrandom::register_custom_getrandom!`
+//! so that `OsRandom` can draw from it.
+
+use alloc::sync::Arc;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
+use spin::Mutex;
+
+/// Source of wall-clock time in milliseconds since the Unix epoch
+pub trait Clock: Send + Sync {
+    /// Current time in milliseconds since the Unix epoch
+    fn now_ms(&self) -> u64;
+}
+
+/// The host's wall clock
+///
+/// Uses `Date.now()` in the browser and the system time elsewhere.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn now_ms(&self) -> u64 {
+        js_sys::Date::now() as u64
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    fn now_ms(&self) -> u64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// Intended for tests that need to cross expiry deadlines deterministically.
+#[derive(Debug, Default)]
+pub struct FixedClock {
+    // A lock rather than an `AtomicU64`: 32-bit MCUs have no 64-bit atomics
+    now: Mutex<u64>,
+}
+
+impl FixedClock {
+    /// Create a clock frozen at `now_ms`
+    pub fn new(now_ms: u64) -> Self {
+        FixedClock { now: Mutex::new(now_ms) }
+    }
+
+    /// Jump to an absolute time
+    pub fn set(&self, now_ms: u64) {
+        *self.now.lock() = now_ms;
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, ms: u64) {
+        *self.now.lock() += ms;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_ms(&self) -> u64 {
+        *self.now.lock()
+    }
+}
+
+/// Source of cryptographically secure random bytes
+pub trait CryptoRng: Send + Sync {
+    /// Fill `dest` with random bytes
+    fn fill_bytes(&self, dest: &mut [u8]);
+}
+
+impl dyn CryptoRng + '_ {
+    /// Draw a random byte array
+    pub fn random<const N: usize>(&self) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        self.fill_bytes(&mut bytes);
+        bytes
+    }
+}
+
+/// The operating system's CSPRNG (`crypto.getRandomValues` in the browser)
+///
+/// On bare-metal targets this is whatever source the firmware registered
+/// with `getrandom`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsRandom;
+
+impl OsRandom {
+    /// Draw a random byte array
+    pub fn random<const N: usize>(&self) -> [u8; N] {
+        (self as &dyn CryptoRng).random()
+    }
+}
+
+impl CryptoRng for OsRandom {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        rand_core::OsRng.fill_bytes(dest);
+    }
+}
+
+/// Deterministic ChaCha20 generator for reproducible tests
+///
+/// Never use outside tests: anyone who knows the seed knows every key.
+pub struct SeededRng {
+    inner: Mutex<ChaCha20Rng>,
+}
+
+impl SeededRng {
+    /// Create a generator from a 32-byte seed
+    pub fn new(seed: [u8; 32]) -> Self {
+        SeededRng { inner: Mutex::new(ChaCha20Rng::from_seed(seed)) }
+    }
+
+    /// Create a generator from a short numeric seed
+    pub fn from_u64(seed: u64) -> Self {
+        SeededRng { inner: Mutex::new(ChaCha20Rng::seed_from_u64(seed)) }
+    }
+}
+
+impl CryptoRng for SeededRng {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        self.inner.lock().fill_bytes(dest);
+    }
+}
+
+/// Adapts a `CryptoRng` provider to the `rand_core` traits used by the
+/// curve libraries
+pub struct RngAdapter<'a>(pub &'a dyn CryptoRng);
+
+impl RngCore for RngAdapter<'_> {
+    fn next_u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.0.random())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.0.random())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl rand_core::CryptoRng for RngAdapter<'_> {}
+
+/// The clock and randomness a component runs against
+#[derive(Clone)]
+pub struct Providers {
+    /// Wall-clock time
+    pub clock: Arc<dyn Clock>,
+
+    /// Cryptographic randomness
+    pub rng: Arc<dyn CryptoRng>,
+}
+
+impl Providers {
+    /// Bundle a clock and a random number generator
+    pub fn new(clock: Arc<dyn Clock>, rng: Arc<dyn CryptoRng>) -> Self {
+        Providers { clock, rng }
+    }
+
+    /// The host's clock and CSPRNG
+    #[cfg(feature = "std")]
+    pub fn system() -> Self {
+        Self::new(Arc::new(SystemClock), Arc::new(OsRandom))
+    }
+
+    /// A frozen clock and a seeded generator, for reproducible runs
+    pub fn deterministic(seed: u64, now_ms: u64) -> Self {
+        Self::new(Arc::new(FixedClock::new(now_ms)), Arc::new(SeededRng::from_u64(seed)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for Providers {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_rng_is_reproducible() {
+        let a = SeededRng::from_u64(42);
+        let b = SeededRng::from_u64(42);
+        let c = SeededRng::from_u64(43);
+
+        let first: [u8; 32] = (&a as &dyn CryptoRng).random();
+        assert_eq!(first, (&b as &dyn CryptoRng).random());
+        assert_ne!(first, (&c as &dyn CryptoRng).random());
+
+        // The stream advances
+        assert_ne!(first, (&a as &dyn CryptoRng).random());
+    }
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = FixedClock::new(1_000);
+        clock.advance(500);
+        assert_eq!(clock.now_ms(), 1_500);
+
+        clock.set(42);
+        assert_eq!(clock.now_ms(), 42);
+    }
+}
@@ -0,0 +1,422 @@
+//! 🧵 Thread-Safe Core Handle
+//!
+//! `BlackHoleCore` takes `&mut self` and leaves synchronization to the
+//! caller. `SharedCore` is the `Send + Sync` handle native clients share
+//! between worker threads: cloning it is cheap, and every method takes
+//! `&self`, so it can be moved into a thread pool or an async runtime's
+//! blocking tasks.
+//!
+//! Each Double Ratchet session sits behind its own lock, so encryption to
+//! different peers runs in parallel while operations on one session are
+//! serialized and keep its message numbers in order. Identity, groups and
+//! the event horizon sit behind a read-write lock: proofs, group messages
+//! and attachments proceed concurrently, while key changes are exclusive.
+//!
+//! Locks are always taken in this order, which rules out deadlocks:
+//!
+//! ```text
+//! core → session table → one session → history
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use zeroize::Zeroize;
+
+use crate::{
+    BlackHoleCore,
+    BlackHoleError,
+    DoubleRatchet,
+    EncryptedAttachment,
+    EncryptedMessage,
+    ExpiryPolicy,
+    HistoryEntry,
+    HistoryExport,
+    MessageContent,
+    MessageEnvelope,
+    PaddingScheme,
+    Providers,
+    SessionManager,
+    UnsealedMessage,
+    ZKParameters,
+    ZKProof,
+};
+
+/// A ratchet session; `None` once ended or wiped
+type Session = Arc<Mutex<Option<DoubleRatchet>>>;
+
+/// A `Send + Sync` handle to a Black Hole identity
+#[derive(Clone)]
+pub struct SharedCore {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// Identity, groups, ZK and post-quantum keys, event horizon
+    core: RwLock<BlackHoleCore>,
+
+    /// Double Ratchet session per peer, each behind its own lock
+    sessions: RwLock<BTreeMap<String, Session>>,
+
+    /// Conversation history and expiry timers of every peer
+    history: Mutex<SessionManager>,
+
+    /// Clock for envelope timestamps
+    providers: Providers,
+}
+
+impl SharedCore {
+    /// Create a new identity proving with ceremony `parameters`
+    pub fn new(parameters: &ZKParameters) -> Result<SharedCore, BlackHoleError> {
+        Ok(BlackHoleCore::new(parameters)?.into())
+    }
+
+    /// Encrypt a message for `recipient` (see `BlackHoleCore::encrypt`)
+    pub fn encrypt(&self, recipient: &str, plaintext: &str) -> Result<EncryptedMessage, BlackHoleError> {
+        self.core_mut().encrypt(recipient, plaintext)
+    }
+
+    /// Decrypt a text message
+    pub fn decrypt(&self, encrypted: &EncryptedMessage) -> Result<String, BlackHoleError> {
+        self.core().decrypt(encrypted)
+    }
+
+    /// Encrypt structured content for `recipient`
+    pub fn encrypt_content(&self, recipient: &str, content: &MessageContent) -> Result<EncryptedMessage, BlackHoleError> {
+        self.core_mut().encrypt_content(recipient, content)
+    }
+
+    /// Decrypt a message into its structured content
+    pub fn decrypt_content(&self, encrypted: &EncryptedMessage) -> Result<MessageContent, BlackHoleError> {
+        self.core().decrypt_content(encrypted)
+    }
+
+    /// Start a Double Ratchet session with `peer`, replacing any existing one
+    ///
+    /// Pass the peer's ratchet key when responding to them. Returns our
+    /// ratchet public key to send to the peer.
+    pub fn start_session(&self, peer: &str, remote_dh_public: Option<Vec<u8>>) -> Result<Vec<u8>, BlackHoleError> {
+        let core = self.core();
+        let mut ratchet = core.new_ratchet(remote_dh_public)?;
+        ratchet.set_padding_scheme(core.event_horizon.padding);
+        let dh_public = ratchet.get_dh_public();
+
+        let session = Arc::new(Mutex::new(Some(ratchet)));
+        let replaced = write(&self.inner.sessions).insert(peer.to_string(), session);
+
+        // Wipe the replaced ratchet once in-flight operations on it finish
+        if let Some(replaced) = replaced {
+            lock(&replaced).take();
+        }
+
+        Ok(dh_public)
+    }
+
+    /// End the session with `peer`, wiping its keys and history
+    pub fn end_session(&self, peer: &str) -> bool {
+        let session = write(&self.inner.sessions).remove(peer);
+        let had_session = session.is_some_and(|session| lock(&session).take().is_some());
+        let had_history = self.conversations().remove_session(peer);
+
+        had_session || had_history
+    }
+
+    /// Encrypt structured content for `peer` over its ratchet session
+    ///
+    /// Blocks only while another thread is using the same session.
+    pub fn session_encrypt(&self, peer: &str, content: MessageContent) -> Result<MessageEnvelope, BlackHoleError> {
+        let session = self.session(peer)?;
+        let mut session = lock(&session);
+        let ratchet = session.as_mut().ok_or(BlackHoleError::NoSession)?;
+
+        let content = self.conversations().apply_timer(peer, content);
+        let now = self.inner.providers.clock.now_ms();
+
+        let mut encoded = content.encode()?;
+        let envelope = ratchet.encrypt(&encoded);
+        encoded.zeroize();
+
+        let mut envelope = envelope?;
+        envelope.timestamp = now;
+
+        self.conversations().record(peer, true, &content)?;
+        Ok(envelope)
+    }
+
+    /// Decrypt a ratchet message from `peer` into its structured content
+    pub fn session_decrypt(&self, peer: &str, envelope: &MessageEnvelope) -> Result<MessageContent, BlackHoleError> {
+        let session = self.session(peer)?;
+        let mut session = lock(&session);
+        let ratchet = session.as_mut().ok_or(BlackHoleError::NoSession)?;
+
+        let mut plaintext = ratchet.decrypt(envelope)?;
+        let content = MessageContent::decode(&plaintext);
+        plaintext.zeroize();
+
+        let mut conversations = self.conversations();
+        let content = conversations.apply_timer(peer, content?);
+        conversations.record(peer, false, &content)?;
+
+        Ok(content)
+    }
+
+    /// Set the disappearing-message timer for `peer` (`None` turns it off)
+    ///
+    /// Returns the encrypted timer update to deliver to the peer.
+    pub fn set_expiry_timer(&self, peer: &str, seconds: Option<u32>) -> Result<MessageEnvelope, BlackHoleError> {
+        let update = self.conversations().set_expiry(peer, seconds.map(ExpiryPolicy::new));
+        self.session_encrypt(peer, update)
+    }
+
+    /// Unexpired local history of the conversation with `peer`
+    pub fn history(&self, peer: &str) -> Vec<HistoryEntry> {
+        self.conversations().history(peer)
+    }
+
+    /// Destroy expired messages, returning how many were destroyed
+    pub fn purge_expired(&self) -> usize {
+        self.conversations().purge_expired()
+    }
+
+    /// Earliest pending expiry deadline (ms since the Unix epoch)
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.conversations().next_expiry()
+    }
+
+    /// Export timers and history, with the device key that unlocks them
+    pub fn export_history(&self) -> Result<HistoryExport, BlackHoleError> {
+        self.conversations().export_history()
+    }
+
+    /// Restore timers and history after a restart, given the export's device key
+    pub fn import_history(&self, bytes: &[u8], device_key: &[u8]) -> Result<(), BlackHoleError> {
+        self.conversations().import_history(bytes, device_key)
+    }
+
+    /// Configure the certificate issuer trusted for sealed sender
+    pub fn set_trust_root(&self, public_key: &[u8]) -> Result<(), BlackHoleError> {
+        self.core_mut().set_trust_root(public_key)
+    }
+
+    /// Install the sender certificate issued to us by the server
+    pub fn set_sender_certificate(&self, certificate: &[u8]) -> Result<(), BlackHoleError> {
+        self.core_mut().set_sender_certificate(certificate)
+    }
+
+    /// Seal content so that the relay only learns the recipient address
+    pub fn seal(
+        &self,
+        recipient_address: &str,
+        recipient_identity_key: &[u8],
+        content: &[u8],
+    ) -> Result<Vec<u8>, BlackHoleError> {
+        self.core().seal(recipient_address, recipient_identity_key, content)
+    }
+
+    /// Open a sealed envelope, authenticating the hidden sender
+    pub fn unseal(&self, envelope: &[u8]) -> Result<UnsealedMessage, BlackHoleError> {
+        self.core().unseal(envelope)
+    }
+
+    /// Encrypt a file as an attachment under a fresh random key
+    pub fn encrypt_attachment(
+        &self,
+        data: &[u8],
+        content_type: &str,
+        thumbnail: Option<Vec<u8>>,
+    ) -> Result<EncryptedAttachment, BlackHoleError> {
+        self.core().encrypt_attachment(data, content_type, thumbnail)
+    }
+
+    /// Verify a downloaded blob against its pointer and decrypt it
+    pub fn decrypt_attachment(&self, pointer: &[u8], blob: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        self.core().decrypt_attachment(pointer, blob)
+    }
+
+    /// Get the Ed25519 identity public key
+    pub fn get_identity_public_key(&self) -> Vec<u8> {
+        self.core().get_identity_public_key()
+    }
+
+    /// Get the public key fingerprint
+    pub fn get_fingerprint(&self) -> String {
+        self.core().get_fingerprint()
+    }
+
+    /// Get post-quantum public key for hybrid encryption
+    pub fn get_pq_public_key(&self) -> String {
+        self.core().get_pq_public_key()
+    }
+
+    /// Create a zero-knowledge proof of identity
+    pub fn prove_identity(&self) -> Result<ZKProof, BlackHoleError> {
+        self.core().prove_identity()
+    }
+
+    /// Verify a zero-knowledge proof
+    pub fn verify_identity(&self, proof: &ZKProof) -> Result<bool, BlackHoleError> {
+        self.core().verify_identity(proof)
+    }
+
+    /// Create a new MLS group and return its random group ID
+    pub fn create_group(&self) -> Result<String, BlackHoleError> {
+        self.core_mut().create_group()
+    }
+
+    /// List the IDs of all groups held by this identity
+    pub fn list_groups(&self) -> Vec<String> {
+        self.core().list_groups()
+    }
+
+    /// Propose adding a member to a group
+    pub fn group_propose_add(&self, group_id: &str, member_id: &str, key_package: &[u8]) -> Result<(), BlackHoleError> {
+        self.core_mut().group_propose_add(group_id, member_id, key_package)
+    }
+
+    /// Commit the pending proposals of a group
+    pub fn group_commit(&self, group_id: &str) -> Result<Vec<u8>, BlackHoleError> {
+        self.core_mut().group_commit(group_id)
+    }
+
+    /// Encrypt a message for a group
+    pub fn group_encrypt(&self, group_id: &str, plaintext: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        self.core().group_encrypt(group_id, plaintext)
+    }
+
+    /// Decrypt a message received in a group
+    pub fn group_decrypt(&self, group_id: &str, message: &[u8]) -> Result<Vec<u8>, BlackHoleError> {
+        self.core().group_decrypt(group_id, message)
+    }
+
+    /// Leave a group, wiping its secrets
+    pub fn leave_group(&self, group_id: &str) -> bool {
+        self.core_mut().leave_group(group_id)
+    }
+
+    /// Select the padding scheme for messages, groups and every session
+    pub fn set_padding_scheme(&self, scheme: PaddingScheme) {
+        let mut core = self.core_mut();
+        core.set_padding_scheme(scheme);
+
+        for session in read(&self.inner.sessions).values() {
+            if let Some(ratchet) = lock(session).as_mut() {
+                ratchet.set_padding_scheme(scheme);
+            }
+        }
+    }
+
+    /// Panic wipe - emergency deletion of all keys, including every session
+    pub fn panic_wipe(&self) {
+        let mut core = self.core_mut();
+        core.panic_wipe();
+
+        let mut sessions = write(&self.inner.sessions);
+        for session in sessions.values() {
+            lock(session).take();
+        }
+        sessions.clear();
+
+        self.conversations().zeroize();
+    }
+
+    /// Shared access to the core, for the C ABI's identity-level calls
+    pub(crate) fn core(&self) -> RwLockReadGuard<'_, BlackHoleCore> {
+        read(&self.inner.core)
+    }
+
+    fn core_mut(&self) -> RwLockWriteGuard<'_, BlackHoleCore> {
+        write(&self.inner.core)
+    }
+
+    fn conversations(&self) -> MutexGuard<'_, SessionManager> {
+        lock(&self.inner.history)
+    }
+
+    /// The session with `peer`, without holding the session table
+    fn session(&self, peer: &str) -> Result<Session, BlackHoleError> {
+        read(&self.inner.sessions).get(peer).cloned().ok_or(BlackHoleError::NoSession)
+    }
+}
+
+impl From<BlackHoleCore> for SharedCore {
+    /// Share an existing core, moving its sessions behind per-session locks
+    fn from(mut core: BlackHoleCore) -> Self {
+        let providers = core.providers.clone();
+        let mut history = std::mem::replace(&mut core.sessions, SessionManager::new(providers.clone()));
+
+        let sessions = history.take_sessions()
+            .into_iter()
+            .map(|(peer, ratchet)| (peer, Arc::new(Mutex::new(Some(ratchet)))))
+            .collect();
+
+        SharedCore {
+            inner: Arc::new(Inner {
+                core: RwLock::new(core),
+                sessions: RwLock::new(sessions),
+                history: Mutex::new(history),
+                providers,
+            }),
+        }
+    }
+}
+
+// A panic in one call must not brick the identity for every other thread,
+// so poisoned locks are recovered rather than propagated
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::setup::test_parameters;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    /// Bob's ratchet key, so that our sessions respond and may send first
+    fn bob() -> Option<Vec<u8>> {
+        Some(BlackHoleCore::new(test_parameters()).unwrap().start_session("alice", None).unwrap())
+    }
+
+    #[test]
+    fn test_handle_is_send_and_sync() {
+        assert_send_sync::<SharedCore>();
+        assert_send_sync::<BlackHoleCore>();
+    }
+
+    #[test]
+    fn test_existing_sessions_are_shared() {
+        let mut core = BlackHoleCore::new(test_parameters()).unwrap();
+        core.start_session("bob", bob()).unwrap();
+        core.session_encrypt("bob", MessageContent::text("before")).unwrap();
+
+        let shared = SharedCore::from(core);
+        let envelope = shared.session_encrypt("bob", MessageContent::text("after")).unwrap();
+
+        assert_eq!(envelope.header.message_number, 1);
+        assert_eq!(shared.history("bob").len(), 2);
+    }
+
+    #[test]
+    fn test_ended_session_rejects_messages() {
+        let shared = SharedCore::new(test_parameters()).unwrap();
+        shared.start_session("bob", bob()).unwrap();
+        shared.session_encrypt("bob", MessageContent::text("hi")).unwrap();
+
+        assert!(shared.end_session("bob"));
+        assert!(shared.history("bob").is_empty());
+        assert_eq!(
+            shared.session_encrypt("bob", MessageContent::text("hi")).unwrap_err(),
+            BlackHoleError::NoSession,
+        );
+    }
+}
@@ -1,16 +1,34 @@
 //! 🕸️ WebAssembly Bindings Module
 //!
 //! This module provides JavaScript-friendly wrappers for the cryptographic core.
+//! It is only compiled with the `wasm` feature; the `Js*` types and free
+//! functions here are the entire JavaScript surface, and the core stays
+//! plain Rust.
 
 use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::future_to_promise;
-use js_sys::Promise;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use js_sys::{Promise, Uint8Array};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::{
-    BlackHoleCore, EventHorizon, SingularityKey, EncryptedMessage,
-    crypto::{PostQuantumKeys, HybridEncryption, EncapsulationResult},
-    protocol::{DoubleRatchet, MLSGroup, MessageEnvelope},
-    zk::{ZKIdentity, ZKProof, ZKVerifier, RangeProof},
+    BlackHoleCore, BlackHoleError, SingularityKey,
+    crypto::{PostQuantumKeys, PaddingScheme},
+    crypto::bao::BaoError,
+    crypto::stream::{StreamDecryptor, StreamEncryptor, StreamError, HEADER_LEN},
+    error::key_array,
+    protocol::attachment::AttachmentError,
+    protocol::ContentError,
+    protocol::{AttachmentDownload, AttachmentPointer, DoubleRatchet, MLSGroup, MessageContent, MessageEnvelope},
+    providers::{CryptoRng, OsRandom},
+    zk::{ZKIdentity, ZKProof, ZKVerifier},
+    zk::credential::{Credential, CredentialRequest, CredentialResponse, IssuerKey, IssuerParameters, PendingCredential, Presentation},
+    zk::membership::{MembershipParameters, MembershipProof, MembershipTree, MembershipVerifier, NullifierLog},
+    zk::range::{Blinding, Commitment, RangeProof},
+    zk::rln::{Observation, RlnEpoch, RlnParameters, RlnProof, RlnVerifier, SlashingDetector},
+    zk::setup::ZKParameters,
+    zk::sigma::{IdentityKeyProof, ProofTranscript, SigmaProof, Statement, Witness},
 };
 
 /// Initialize the WASM module
@@ -20,6 +38,53 @@ pub fn start() {
     console_log::init_with_level(log::Level::Debug).ok();
     
     log::info!("🕳️ FortiComm Black Hole WASM module initialized");
+    log::info!("   \"The singularity is ready. Messages will be swallowed.\"");
+}
+
+/// Errors cross into JavaScript as their message
+impl From<BlackHoleError> for JsValue {
+    fn from(err: BlackHoleError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/// Module errors convert through `BlackHoleError`
+macro_rules! impl_into_js {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for JsValue {
+                fn from(err: $error) -> Self {
+                    BlackHoleError::from(err).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_into_js!(StreamError, AttachmentError, BaoError, ContentError);
+
+/// Padding scheme applied before encryption
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsPaddingScheme {
+    /// Only the marker byte (no length hiding)
+    None = 0,
+    
+    /// Padmé padding
+    Padme = 1,
+    
+    /// Bucketed power-of-two padding
+    PowerOfTwo = 2,
+}
+
+impl From<JsPaddingScheme> for PaddingScheme {
+    fn from(scheme: JsPaddingScheme) -> Self {
+        match scheme {
+            JsPaddingScheme::None => PaddingScheme::None,
+            JsPaddingScheme::Padme => PaddingScheme::Padme,
+            JsPaddingScheme::PowerOfTwo => PaddingScheme::PowerOfTwo,
+        }
+    }
 }
 
 /// JavaScript-friendly wrapper for BlackHoleCore
@@ -30,41 +95,229 @@ pub struct JsBlackHoleCore {
 
 #[wasm_bindgen]
 impl JsBlackHoleCore {
+    /// Create a core with a fresh identity, proving with the ceremony's
+    /// encoded ZK parameters
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Result<JsBlackHoleCore, JsValue> {
-        BlackHoleCore::new().map(|inner| JsBlackHoleCore { inner })
+    pub fn new(parameters: &[u8]) -> Result<JsBlackHoleCore, JsValue> {
+        let parameters = ZKParameters::from_bytes(parameters)?;
+        
+        Ok(JsBlackHoleCore { inner: BlackHoleCore::new(&parameters)? })
     }
     
+    /// Encrypt a text message for a recipient's identity key
     #[wasm_bindgen]
     pub fn encrypt(&mut self, recipient: &str, plaintext: &str) -> Result<JsValue, JsValue> {
-        self.inner.encrypt(recipient, plaintext)
+        to_js(&self.inner.encrypt(recipient, plaintext)?)
     }
     
+    /// Decrypt a message produced by `encrypt`
     #[wasm_bindgen]
     pub fn decrypt(&self, ciphertext: JsValue) -> Result<String, JsValue> {
-        self.inner.decrypt(ciphertext)
+        Ok(self.inner.decrypt(&from_js(ciphertext)?)?)
+    }
+    
+    /// Encrypt a `MessageContent` object for a recipient's identity key
+    #[wasm_bindgen]
+    pub fn encrypt_content(&mut self, recipient: &str, content: JsValue) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.encrypt_content(recipient, &from_js(content)?)?)
     }
     
+    /// Decrypt a message produced by `encrypt_content`
+    #[wasm_bindgen]
+    pub fn decrypt_content(&self, ciphertext: JsValue) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.decrypt_content(&from_js(ciphertext)?)?)
+    }
+    
+    /// Start a ratchet session and return our ratchet public key
+    #[wasm_bindgen]
+    pub fn start_session(&mut self, peer: &str, remote_dh_public: Option<Vec<u8>>) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.start_session(peer, remote_dh_public)?)
+    }
+    
+    /// End a session, returning whether one existed
+    #[wasm_bindgen]
+    pub fn end_session(&mut self, peer: &str) -> bool {
+        self.inner.end_session(peer)
+    }
+    
+    /// Encrypt a `MessageContent` object in a peer's session
+    #[wasm_bindgen]
+    pub fn session_encrypt(&mut self, peer: &str, content: JsValue) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.session_encrypt(peer, from_js(content)?)?)
+    }
+    
+    /// Decrypt an envelope received in a peer's session
+    #[wasm_bindgen]
+    pub fn session_decrypt(&mut self, peer: &str, envelope: JsValue) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.session_decrypt(peer, &from_js(envelope)?)?)
+    }
+    
+    /// Set or clear a session's disappearing-message timer
+    #[wasm_bindgen]
+    pub fn set_expiry_timer(&mut self, peer: &str, seconds: Option<u32>) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.set_expiry_timer(peer, seconds)?)
+    }
+    
+    /// Messages kept for a peer, oldest first
+    #[wasm_bindgen]
+    pub fn history(&mut self, peer: &str) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.history(peer))
+    }
+    
+    /// Drop expired messages, returning how many were removed
+    #[wasm_bindgen]
+    pub fn purge_expired(&mut self) -> usize {
+        self.inner.purge_expired()
+    }
+    
+    /// Earliest pending expiry deadline (ms since the Unix epoch)
+    #[wasm_bindgen]
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.inner.next_expiry()
+    }
+    
+    /// Export the history sealed under a fresh device key
+    #[wasm_bindgen]
+    pub fn export_history(&mut self) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.export_history()?)
+    }
+    
+    /// Import a history exported with `export_history`
+    #[wasm_bindgen]
+    pub fn import_history(&mut self, bytes: &[u8], device_key: &[u8]) -> Result<(), JsValue> {
+        Ok(self.inner.import_history(bytes, device_key)?)
+    }
+    
+    /// Fingerprint of the identity key
     #[wasm_bindgen]
     pub fn get_fingerprint(&self) -> String {
         self.inner.get_fingerprint()
     }
     
+    /// Public identity key bytes
+    #[wasm_bindgen]
+    pub fn get_identity_public_key(&self) -> Vec<u8> {
+        self.inner.get_identity_public_key()
+    }
+    
+    /// Encrypt an attachment, returning its pointer and blob
+    #[wasm_bindgen]
+    pub fn encrypt_attachment(
+        &self,
+        data: &[u8],
+        content_type: &str,
+        thumbnail: Option<Vec<u8>>,
+    ) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.encrypt_attachment(data, content_type, thumbnail)?)
+    }
+    
+    /// Decrypt an attachment blob with its pointer
+    #[wasm_bindgen]
+    pub fn decrypt_attachment(&self, pointer: &[u8], blob: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.decrypt_attachment(pointer, blob)?)
+    }
+    
+    /// Set the server key that signs sender certificates
+    #[wasm_bindgen]
+    pub fn set_trust_root(&mut self, public_key: &[u8]) -> Result<(), JsValue> {
+        Ok(self.inner.set_trust_root(public_key)?)
+    }
+    
+    /// Set the certificate attached to sealed envelopes
+    #[wasm_bindgen]
+    pub fn set_sender_certificate(&mut self, certificate: &[u8]) -> Result<(), JsValue> {
+        Ok(self.inner.set_sender_certificate(certificate)?)
+    }
+    
+    /// Seal content so that only the recipient learns the sender
+    #[wasm_bindgen]
+    pub fn seal(
+        &self,
+        recipient_address: &str,
+        recipient_identity_key: &[u8],
+        content: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.seal(recipient_address, recipient_identity_key, content)?)
+    }
+    
+    /// Open a sealed envelope and check the sender certificate
+    #[wasm_bindgen]
+    pub fn unseal(&self, envelope: &[u8]) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.unseal(envelope)?)
+    }
+    
+    /// Prove knowledge of the identity key
     #[wasm_bindgen]
     pub fn prove_identity(&self) -> Result<JsValue, JsValue> {
-        self.inner.prove_identity()
+        to_js(&self.inner.prove_identity()?)
     }
     
+    /// Verify an identity proof
     #[wasm_bindgen]
     pub fn verify_identity(&self, proof_js: JsValue) -> Result<bool, JsValue> {
-        self.inner.verify_identity(proof_js)
+        Ok(self.inner.verify_identity(&from_js(proof_js)?)?)
+    }
+    
+    /// Prove ownership of the identity key, bound to a context
+    #[wasm_bindgen]
+    pub fn prove_identity_key(&self, context: &[u8]) -> Vec<u8> {
+        self.inner.prove_identity_key(context).to_bytes().to_vec()
+    }
+    
+    /// Create a group, returning its ID
+    #[wasm_bindgen]
+    pub fn create_group(&mut self) -> Result<String, JsValue> {
+        Ok(self.inner.create_group()?)
+    }
+    
+    /// IDs of the groups we belong to
+    #[wasm_bindgen]
+    pub fn list_groups(&self) -> Vec<String> {
+        self.inner.list_groups()
     }
     
+    /// Propose adding a member to a group
+    #[wasm_bindgen]
+    pub fn group_propose_add(
+        &mut self,
+        group_id: &str,
+        member_id: &str,
+        key_package: &[u8],
+    ) -> Result<(), JsValue> {
+        Ok(self.inner.group_propose_add(group_id, member_id, key_package)?)
+    }
+    
+    /// Commit pending proposals, returning the commit message
+    #[wasm_bindgen]
+    pub fn group_commit(&mut self, group_id: &str) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.group_commit(group_id)?)
+    }
+    
+    /// Encrypt a message for a group
+    #[wasm_bindgen]
+    pub fn group_encrypt(&self, group_id: &str, plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.group_encrypt(group_id, plaintext)?)
+    }
+    
+    /// Leave a group, returning whether we were a member
+    #[wasm_bindgen]
+    pub fn leave_group(&mut self, group_id: &str) -> bool {
+        self.inner.leave_group(group_id)
+    }
+    
+    /// Set the padding applied before encryption
+    #[wasm_bindgen]
+    pub fn set_padding_scheme(&mut self, scheme: JsPaddingScheme) {
+        self.inner.set_padding_scheme(scheme.into());
+    }
+    
+    /// Post-quantum public key as hex
     #[wasm_bindgen]
     pub fn get_pq_public_key(&self) -> String {
         self.inner.get_pq_public_key()
     }
     
+    /// Wipe every key, session and message
     #[wasm_bindgen]
     pub fn panic_wipe(&mut self) {
         self.inner.panic_wipe();
@@ -79,12 +332,15 @@ pub struct JsDoubleRatchet {
 
 #[wasm_bindgen]
 impl JsDoubleRatchet {
+    /// Start a session as the initiator
     #[wasm_bindgen(constructor)]
     pub fn new(identity: &JsSingularityKey) -> Result<JsDoubleRatchet, JsValue> {
         DoubleRatchet::new(&identity.inner)
             .map(|inner| JsDoubleRatchet { inner })
+            .map_err(JsValue::from)
     }
     
+    /// Start a session as the responder to a peer's ratchet key
     #[wasm_bindgen]
     pub fn initialize_responder(
         identity: &JsSingularityKey,
@@ -92,29 +348,34 @@ impl JsDoubleRatchet {
     ) -> Result<JsDoubleRatchet, JsValue> {
         DoubleRatchet::initialize_responder(&identity.inner, remote_dh_public)
             .map(|inner| JsDoubleRatchet { inner })
+            .map_err(JsValue::from)
     }
     
+    /// Encrypt a message, returning the envelope
     #[wasm_bindgen]
     pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<JsValue, JsValue> {
-        self.inner.encrypt(plaintext)
-            .and_then(|envelope| {
-                serde_wasm_bindgen::to_value(&envelope)
-                    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
-            })
+        to_js(&self.inner.encrypt(plaintext)?)
     }
     
+    /// Decrypt an envelope from the peer
     #[wasm_bindgen]
     pub fn decrypt(&mut self, envelope_js: JsValue) -> Result<Vec<u8>, JsValue> {
-        let envelope: MessageEnvelope = serde_wasm_bindgen::from_value(envelope_js)
-            .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+        let envelope: MessageEnvelope = from_js(envelope_js)?;
         
-        self.inner.decrypt(&envelope)
+        Ok(self.inner.decrypt(&envelope)?)
     }
     
+    /// Current ratchet public key
     #[wasm_bindgen]
     pub fn get_dh_public(&self) -> Vec<u8> {
         self.inner.get_dh_public()
     }
+    
+    /// Set the padding applied before encryption
+    #[wasm_bindgen]
+    pub fn set_padding_scheme(&mut self, scheme: JsPaddingScheme) {
+        self.inner.set_padding_scheme(scheme.into());
+    }
 }
 
 /// JavaScript-friendly wrapper for MLSGroup
@@ -125,40 +386,109 @@ pub struct JsMLSGroup {
 
 #[wasm_bindgen]
 impl JsMLSGroup {
+    /// Create a group with us as its only member
     #[wasm_bindgen(constructor)]
     pub fn new(identity: &JsSingularityKey) -> Result<JsMLSGroup, JsValue> {
         MLSGroup::new(&identity.inner)
             .map(|inner| JsMLSGroup { inner })
+            .map_err(JsValue::from)
+    }
+    
+    /// Join a group from the welcome of the member who added us
+    #[wasm_bindgen]
+    pub fn join(identity: &JsSingularityKey, welcome: &[u8]) -> Result<JsMLSGroup, JsValue> {
+        MLSGroup::join(&identity.inner, welcome)
+            .map(|inner| JsMLSGroup { inner })
+            .map_err(JsValue::from)
     }
     
+    /// Group ID
     #[wasm_bindgen]
     pub fn get_group_id(&self) -> String {
         self.inner.get_group_id()
     }
     
+    /// Current epoch
     #[wasm_bindgen]
     pub fn get_epoch(&self) -> u64 {
         self.inner.get_epoch()
     }
     
+    /// Propose adding a member
     #[wasm_bindgen]
     pub fn propose_add(&mut self, member_id: &str, key_package: &[u8]) -> Result<(), JsValue> {
-        self.inner.propose_add(member_id, key_package)
+        Ok(self.inner.propose_add(member_id, key_package)?)
     }
     
+    /// Commit pending proposals, returning the commit message
     #[wasm_bindgen]
     pub fn commit(&mut self) -> Result<Vec<u8>, JsValue> {
-        self.inner.commit()
+        Ok(self.inner.commit()?)
     }
     
+    /// Apply a commit made by another member
+    #[wasm_bindgen]
+    pub fn apply_commit(&mut self, commit: &[u8]) -> Result<(), JsValue> {
+        Ok(self.inner.apply_commit(commit)?)
+    }
+    
+    /// Group state for members added by the last commit; send it encrypted
+    #[wasm_bindgen]
+    pub fn welcome(&self) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.welcome()?)
+    }
+    
+    /// Number of members
     #[wasm_bindgen]
     pub fn get_member_count(&self) -> usize {
         self.inner.get_member_count()
     }
     
+    /// Encrypt a message for the group
     #[wasm_bindgen]
     pub fn encrypt_group_message(&self, plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
-        self.inner.encrypt_group_message(plaintext)
+        Ok(self.inner.encrypt_group_message(plaintext)?)
+    }
+    
+    /// Decrypt a message sent to the group
+    #[wasm_bindgen]
+    pub fn decrypt_group_message(&self, message: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.decrypt_group_message(message)?)
+    }
+    
+    /// Set the padding applied before encryption
+    #[wasm_bindgen]
+    pub fn set_padding_scheme(&mut self, scheme: JsPaddingScheme) {
+        self.inner.set_padding_scheme(scheme.into());
+    }
+    
+    /// Propose new group metadata
+    #[wasm_bindgen]
+    pub fn propose_metadata(
+        &mut self,
+        name: &str,
+        description: &str,
+        avatar: Option<Vec<u8>>,
+    ) -> Result<(), JsValue> {
+        Ok(self.inner.propose_metadata(name, description, avatar)?)
+    }
+    
+    /// Current group metadata
+    #[wasm_bindgen]
+    pub fn get_metadata(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.get_metadata()?)
+    }
+    
+    /// Metadata encoded as a group context extension, if set
+    #[wasm_bindgen]
+    pub fn export_metadata_extension(&self) -> Result<Option<Vec<u8>>, JsValue> {
+        Ok(self.inner.export_metadata_extension()?)
+    }
+    
+    /// Apply metadata from a group context extension
+    #[wasm_bindgen]
+    pub fn apply_metadata_extension(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        Ok(self.inner.apply_metadata_extension(bytes)?)
     }
 }
 
@@ -170,33 +500,572 @@ pub struct JsZKIdentity {
 
 #[wasm_bindgen]
 impl JsZKIdentity {
+    /// Derive a zero-knowledge identity from an identity key
     #[wasm_bindgen(constructor)]
     pub fn new(identity: &JsSingularityKey) -> Result<JsZKIdentity, JsValue> {
         ZKIdentity::new(&identity.inner)
             .map(|inner| JsZKIdentity { inner })
+            .map_err(JsValue::from)
     }
     
+    /// Prove knowledge of the identity secret
     #[wasm_bindgen]
     pub fn prove(&self) -> Result<JsValue, JsValue> {
-        self.inner.prove()
-            .and_then(|proof| {
-                serde_wasm_bindgen::to_value(&proof)
-                    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
-            })
+        to_js(&self.inner.prove()?)
     }
     
+    /// Verify an identity proof
     #[wasm_bindgen]
     pub fn verify(&self, proof_js: JsValue) -> Result<bool, JsValue> {
-        let proof: ZKProof = serde_wasm_bindgen::from_value(proof_js)
-            .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+        let proof: ZKProof = from_js(proof_js)?;
         
-        self.inner.verify(&proof)
+        Ok(self.inner.verify(&proof)?)
     }
     
+    /// Public key bytes
     #[wasm_bindgen]
     pub fn get_public_key(&self) -> Vec<u8> {
         self.inner.get_public_key()
     }
+    
+    /// Identity commitment bytes
+    #[wasm_bindgen]
+    pub fn get_commitment(&self) -> Vec<u8> {
+        self.inner.get_commitment()
+    }
+    
+    /// Verifier for this identity's proofs
+    #[wasm_bindgen]
+    pub fn verifier(&self) -> JsZKVerifier {
+        JsZKVerifier { inner: self.inner.verifier() }
+    }
+    
+    /// Serialized verifying key
+    #[wasm_bindgen]
+    pub fn verifying_key(&self) -> Vec<u8> {
+        self.inner.verifying_key()
+    }
+    
+    /// Prove membership in a group without revealing which member
+    #[wasm_bindgen]
+    pub fn prove_membership(
+        &self,
+        parameters: &JsMembershipParameters,
+        group: &JsMembershipTree,
+        external_nullifier: &[u8],
+        signal: &[u8],
+    ) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.prove_membership(&parameters.inner, &group.inner, external_nullifier, signal)?)
+    }
+    
+    /// Nullifier for an external nullifier
+    #[wasm_bindgen]
+    pub fn nullifier(&self, external_nullifier: &[u8]) -> Vec<u8> {
+        self.inner.nullifier(external_nullifier)
+    }
+    
+    /// Prove a message is within an epoch's rate limit
+    #[wasm_bindgen]
+    pub fn prove_rate_limited(
+        &self,
+        parameters: &JsRlnParameters,
+        group: &JsMembershipTree,
+        epoch: &JsRlnEpoch,
+        message_id: u16,
+        signal: &[u8],
+    ) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.prove_rate_limited(&parameters.inner, &group.inner, &epoch.inner, message_id, signal)?)
+    }
+    
+    /// Start requesting a credential from an issuer
+    #[wasm_bindgen]
+    pub fn request_credential(&self) -> Result<JsPendingCredential, JsValue> {
+        Ok(JsPendingCredential { inner: self.inner.request_credential()? })
+    }
+    
+    /// Present a credential, disclosing only the chosen attributes
+    #[wasm_bindgen]
+    pub fn present_credential(
+        &self,
+        credential_js: JsValue,
+        parameters_js: JsValue,
+        disclose: Vec<usize>,
+        scope: &[u8],
+        context: &[u8],
+    ) -> Result<JsValue, JsValue> {
+        let credential: Credential = from_js(credential_js)?;
+        let parameters: IssuerParameters = from_js(parameters_js)?;
+        
+        to_js(&self.inner.present_credential(&credential, &parameters, &disclose, scope, context)?)
+    }
+}
+
+/// JavaScript-friendly wrapper for PendingCredential
+#[wasm_bindgen]
+pub struct JsPendingCredential {
+    inner: PendingCredential,
+}
+
+#[wasm_bindgen]
+impl JsPendingCredential {
+    /// Request to send to the issuer
+    #[wasm_bindgen]
+    pub fn request(&self) -> Result<JsValue, JsValue> {
+        to_js(self.inner.request())
+    }
+    
+    /// Unblind the issuer's response into a credential
+    #[wasm_bindgen]
+    pub fn finish(self, parameters_js: JsValue, response_js: JsValue) -> Result<JsValue, JsValue> {
+        let parameters: IssuerParameters = from_js(parameters_js)?;
+        let response: CredentialResponse = from_js(response_js)?;
+        
+        to_js(&self.inner.finish(&parameters, &response)?)
+    }
+}
+
+/// JavaScript-friendly wrapper for IssuerKey
+///
+/// Attributes are passed as an array of byte arrays; the key is exported
+/// as a plain object and must be stored as a secret.
+#[wasm_bindgen]
+pub struct JsIssuerKey {
+    inner: IssuerKey,
+}
+
+#[wasm_bindgen]
+impl JsIssuerKey {
+    /// Generate a key for credentials with this many attributes
+    #[wasm_bindgen(constructor)]
+    pub fn generate(attributes: usize) -> JsIssuerKey {
+        JsIssuerKey { inner: IssuerKey::generate(attributes, &OsRandom) }
+    }
+    
+    /// Import a key exported with `export`
+    #[wasm_bindgen]
+    pub fn import(key_js: JsValue) -> Result<JsIssuerKey, JsValue> {
+        Ok(JsIssuerKey { inner: from_js(key_js)? })
+    }
+    
+    /// Export the key as a plain object
+    #[wasm_bindgen]
+    pub fn export(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.inner)
+    }
+    
+    /// Public parameters that holders need
+    #[wasm_bindgen]
+    pub fn parameters(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.parameters())
+    }
+    
+    /// Issue a credential over the given attributes
+    #[wasm_bindgen]
+    pub fn issue(&self, request_js: JsValue, attributes_js: JsValue) -> Result<JsValue, JsValue> {
+        let request: CredentialRequest = from_js(request_js)?;
+        let attributes: Vec<Vec<u8>> = from_js(attributes_js)?;
+        let attributes: Vec<&[u8]> = attributes.iter().map(Vec::as_slice).collect();
+        
+        to_js(&self.inner.issue(&request, &attributes, &OsRandom)?)
+    }
+    
+    /// Verify a credential presentation
+    #[wasm_bindgen]
+    pub fn verify_presentation(&self, presentation_js: JsValue, scope: &[u8], context: &[u8]) -> Result<bool, JsValue> {
+        let presentation: Presentation = from_js(presentation_js)?;
+        
+        Ok(self.inner.verify_presentation(&presentation, scope, context)?)
+    }
+}
+
+/// JavaScript-friendly wrapper for ZKVerifier
+#[wasm_bindgen]
+pub struct JsZKVerifier {
+    inner: ZKVerifier,
+}
+
+#[wasm_bindgen]
+impl JsZKVerifier {
+    /// Load a verifier from a serialized verifying key
+    #[wasm_bindgen]
+    pub fn from_verifying_key(bytes: &[u8]) -> Result<JsZKVerifier, JsValue> {
+        ZKVerifier::from_verifying_key(bytes)
+            .map(|inner| JsZKVerifier { inner })
+            .map_err(JsValue::from)
+    }
+    
+    /// Identifier of the verifying key
+    #[wasm_bindgen]
+    pub fn key_id(&self) -> Vec<u8> {
+        self.inner.key_id().to_vec()
+    }
+    
+    /// Verify an identity proof against a commitment
+    #[wasm_bindgen]
+    pub fn verify_proof(&self, proof_js: JsValue, commitment: &[u8]) -> Result<bool, JsValue> {
+        let proof: ZKProof = from_js(proof_js)?;
+        
+        Ok(self.inner.verify_proof(&proof, commitment)?)
+    }
+}
+
+/// JavaScript-friendly wrapper for MembershipTree
+#[wasm_bindgen]
+pub struct JsMembershipTree {
+    inner: MembershipTree,
+}
+
+#[wasm_bindgen]
+impl JsMembershipTree {
+    /// Create an empty group
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsMembershipTree {
+        JsMembershipTree { inner: MembershipTree::new() }
+    }
+    
+    /// Add an identity commitment, returning its index
+    #[wasm_bindgen]
+    pub fn insert(&mut self, commitment: &[u8]) -> Result<usize, JsValue> {
+        Ok(self.inner.insert(commitment)?)
+    }
+    
+    /// Remove the member at an index
+    #[wasm_bindgen]
+    pub fn remove(&mut self, index: usize) -> Result<(), JsValue> {
+        Ok(self.inner.remove(index)?)
+    }
+    
+    /// Index of an identity commitment
+    #[wasm_bindgen]
+    pub fn index_of(&self, commitment: &[u8]) -> Option<usize> {
+        self.inner.index_of(commitment)
+    }
+    
+    /// Current Merkle root
+    #[wasm_bindgen]
+    pub fn root(&self) -> Vec<u8> {
+        self.inner.root()
+    }
+}
+
+impl Default for JsMembershipTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// JavaScript-friendly wrapper for MembershipParameters
+#[wasm_bindgen]
+pub struct JsMembershipParameters {
+    inner: MembershipParameters,
+}
+
+#[wasm_bindgen]
+impl JsMembershipParameters {
+    /// Load parameters, checking they match the circuit
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<JsMembershipParameters, JsValue> {
+        MembershipParameters::from_bytes(bytes)
+            .map(|inner| JsMembershipParameters { inner })
+            .map_err(JsValue::from)
+    }
+    
+    /// Serialized verifying key
+    #[wasm_bindgen]
+    pub fn verifying_key(&self) -> Vec<u8> {
+        self.inner.verifying_key()
+    }
+    
+    /// Identifier of the verifying key
+    #[wasm_bindgen]
+    pub fn key_id(&self) -> Vec<u8> {
+        self.inner.key_id().to_vec()
+    }
+}
+
+/// JavaScript-friendly wrapper for MembershipVerifier
+#[wasm_bindgen]
+pub struct JsMembershipVerifier {
+    inner: MembershipVerifier,
+}
+
+#[wasm_bindgen]
+impl JsMembershipVerifier {
+    /// Load a verifier from a serialized verifying key
+    #[wasm_bindgen]
+    pub fn from_verifying_key(bytes: &[u8]) -> Result<JsMembershipVerifier, JsValue> {
+        MembershipVerifier::from_verifying_key(bytes)
+            .map(|inner| JsMembershipVerifier { inner })
+            .map_err(JsValue::from)
+    }
+    
+    /// Identifier of the verifying key
+    #[wasm_bindgen]
+    pub fn key_id(&self) -> Vec<u8> {
+        self.inner.key_id().to_vec()
+    }
+    
+    /// Verify a membership proof against a root
+    #[wasm_bindgen]
+    pub fn verify(&self, proof_js: JsValue, root: &[u8], external_nullifier: &[u8], signal: &[u8]) -> Result<bool, JsValue> {
+        let proof: MembershipProof = from_js(proof_js)?;
+        
+        Ok(self.inner.verify(&proof, root, external_nullifier, signal)?)
+    }
+}
+
+/// JavaScript-friendly wrapper for NullifierLog
+#[wasm_bindgen]
+pub struct JsNullifierLog {
+    inner: NullifierLog,
+}
+
+#[wasm_bindgen]
+impl JsNullifierLog {
+    /// Create an empty log
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsNullifierLog {
+        JsNullifierLog { inner: NullifierLog::new() }
+    }
+    
+    /// Record a proof's nullifier, rejecting reuse
+    #[wasm_bindgen]
+    pub fn record(&mut self, proof_js: JsValue) -> Result<(), JsValue> {
+        let proof: MembershipProof = from_js(proof_js)?;
+        
+        Ok(self.inner.record(&proof)?)
+    }
+}
+
+impl Default for JsNullifierLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// JavaScript-friendly wrapper for RlnEpoch
+#[wasm_bindgen]
+pub struct JsRlnEpoch {
+    inner: RlnEpoch,
+}
+
+#[wasm_bindgen]
+impl JsRlnEpoch {
+    /// Epoch with an explicit number
+    #[wasm_bindgen(constructor)]
+    pub fn new(scope: &[u8], epoch: u64, limit: u16) -> JsRlnEpoch {
+        JsRlnEpoch { inner: RlnEpoch::new(scope, epoch, limit) }
+    }
+    
+    /// Epoch containing a time in milliseconds
+    #[wasm_bindgen]
+    pub fn at(scope: &[u8], now_ms: u64, length_ms: u64, limit: u16) -> JsRlnEpoch {
+        JsRlnEpoch { inner: RlnEpoch::at(scope, now_ms, length_ms, limit) }
+    }
+    
+    /// Epoch number
+    #[wasm_bindgen]
+    pub fn epoch(&self) -> u64 {
+        self.inner.epoch
+    }
+}
+
+/// JavaScript-friendly wrapper for RlnParameters
+#[wasm_bindgen]
+pub struct JsRlnParameters {
+    inner: RlnParameters,
+}
+
+#[wasm_bindgen]
+impl JsRlnParameters {
+    /// Load parameters, checking they match the circuit
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<JsRlnParameters, JsValue> {
+        RlnParameters::from_bytes(bytes)
+            .map(|inner| JsRlnParameters { inner })
+            .map_err(JsValue::from)
+    }
+    
+    /// Serialized verifying key
+    #[wasm_bindgen]
+    pub fn verifying_key(&self) -> Vec<u8> {
+        self.inner.verifying_key()
+    }
+    
+    /// Identifier of the verifying key
+    #[wasm_bindgen]
+    pub fn key_id(&self) -> Vec<u8> {
+        self.inner.key_id().to_vec()
+    }
+}
+
+/// JavaScript-friendly wrapper for RlnVerifier
+#[wasm_bindgen]
+pub struct JsRlnVerifier {
+    inner: RlnVerifier,
+}
+
+#[wasm_bindgen]
+impl JsRlnVerifier {
+    /// Load a verifier from a serialized verifying key
+    #[wasm_bindgen]
+    pub fn from_verifying_key(bytes: &[u8]) -> Result<JsRlnVerifier, JsValue> {
+        RlnVerifier::from_verifying_key(bytes)
+            .map(|inner| JsRlnVerifier { inner })
+            .map_err(JsValue::from)
+    }
+    
+    /// Identifier of the verifying key
+    #[wasm_bindgen]
+    pub fn key_id(&self) -> Vec<u8> {
+        self.inner.key_id().to_vec()
+    }
+    
+    /// Verify a rate-limit proof for an epoch
+    #[wasm_bindgen]
+    pub fn verify(&self, proof_js: JsValue, root: &[u8], epoch: &JsRlnEpoch, signal: &[u8]) -> Result<bool, JsValue> {
+        let proof: RlnProof = from_js(proof_js)?;
+        
+        Ok(self.inner.verify(&proof, root, &epoch.inner, signal)?)
+    }
+}
+
+/// JavaScript-friendly wrapper for SlashingDetector
+///
+/// `observe` returns `undefined` for an acceptable message and the
+/// sender's identity commitment when a message exceeds the rate limit.
+#[wasm_bindgen]
+pub struct JsSlashingDetector {
+    inner: SlashingDetector,
+}
+
+#[wasm_bindgen]
+impl JsSlashingDetector {
+    /// Create an empty detector
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsSlashingDetector {
+        JsSlashingDetector { inner: SlashingDetector::new() }
+    }
+    
+    /// Observe a proof, returning the commitment of a spammer
+    #[wasm_bindgen]
+    pub fn observe(&mut self, proof_js: JsValue) -> Result<Option<Vec<u8>>, JsValue> {
+        let proof: RlnProof = from_js(proof_js)?;
+        
+        match self.inner.observe(&proof)? {
+            Observation::Fresh | Observation::Replayed => Ok(None),
+            Observation::Spam(slashed) => Ok(Some(slashed.commitment)),
+        }
+    }
+    
+    /// Drop state for a finished epoch
+    #[wasm_bindgen]
+    pub fn forget(&mut self, epoch: &JsRlnEpoch) {
+        self.inner.forget(&epoch.inner);
+    }
+}
+
+impl Default for JsSlashingDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// JavaScript-friendly wrapper for RangeProof
+///
+/// Commitments and blindings are 32-byte arrays; several of them are
+/// passed concatenated.
+#[wasm_bindgen]
+pub struct JsRangeProof;
+
+#[wasm_bindgen]
+impl JsRangeProof {
+    /// Fresh random blinding
+    #[wasm_bindgen]
+    pub fn random_blinding() -> Vec<u8> {
+        Blinding::random(&OsRandom).to_bytes().to_vec()
+    }
+    
+    /// Commit to a value
+    #[wasm_bindgen]
+    pub fn commit(value: u64, blinding: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(Commitment::new(value, &Blinding::from_bytes(blinding)?).to_bytes().to_vec())
+    }
+    
+    /// Add two commitments
+    #[wasm_bindgen]
+    pub fn add_commitments(a: &[u8], b: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok((Commitment::from_bytes(a)? + Commitment::from_bytes(b)?).to_bytes().to_vec())
+    }
+    
+    /// Prove a committed value fits in `bits` bits
+    #[wasm_bindgen]
+    pub fn prove(value: u64, blinding: &[u8], bits: usize) -> Result<Vec<u8>, JsValue> {
+        let (proof, _) = RangeProof::prove(value, &Blinding::from_bytes(blinding)?, bits, &OsRandom)?;
+        
+        Ok(proof.to_bytes())
+    }
+    
+    /// Verify a range proof
+    #[wasm_bindgen]
+    pub fn verify(proof: &[u8], commitment: &[u8], bits: usize) -> Result<bool, JsValue> {
+        Ok(RangeProof::from_bytes(proof)?.verify(&Commitment::from_bytes(commitment)?, bits)?)
+    }
+    
+    /// Prove several committed values at once
+    #[wasm_bindgen]
+    pub fn prove_multiple(values: Vec<u64>, blindings: &[u8], bits: usize) -> Result<Vec<u8>, JsValue> {
+        let blindings = split_32(blindings)?
+            .map(Blinding::from_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        let (proof, _) = RangeProof::prove_multiple(&values, &blindings, bits, &OsRandom)?;
+        
+        Ok(proof.to_bytes())
+    }
+    
+    /// Verify an aggregated range proof
+    #[wasm_bindgen]
+    pub fn verify_multiple(proof: &[u8], commitments: &[u8], bits: usize) -> Result<bool, JsValue> {
+        let commitments = split_32(commitments)?
+            .map(Commitment::from_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(RangeProof::from_bytes(proof)?.verify_multiple(&commitments, bits)?)
+    }
+}
+
+/// JavaScript-friendly wrapper for SigmaProof and IdentityKeyProof
+///
+/// Statements, witnesses and proofs are plain objects; identity key proofs
+/// are 64-byte arrays.
+#[wasm_bindgen]
+pub struct JsSigmaProof;
+
+#[wasm_bindgen]
+impl JsSigmaProof {
+    /// Prove knowledge of a witness for a statement
+    #[wasm_bindgen]
+    pub fn prove(statement_js: JsValue, witness_js: JsValue, context: &[u8]) -> Result<JsValue, JsValue> {
+        let statement: Statement = from_js(statement_js)?;
+        let witness: Witness = from_js(witness_js)?;
+        
+        to_js(&SigmaProof::prove(&statement, &witness, &mut ProofTranscript::new(context), &OsRandom)?)
+    }
+    
+    /// Verify a proof for a statement
+    #[wasm_bindgen]
+    pub fn verify(statement_js: JsValue, proof_js: JsValue, context: &[u8]) -> Result<bool, JsValue> {
+        let statement: Statement = from_js(statement_js)?;
+        let proof: SigmaProof = from_js(proof_js)?;
+        
+        Ok(proof.verify(&statement, &mut ProofTranscript::new(context))?)
+    }
+    
+    /// Verify an identity key proof
+    #[wasm_bindgen]
+    pub fn verify_identity_key(proof: &[u8], public_key: &[u8], context: &[u8]) -> Result<bool, JsValue> {
+        Ok(IdentityKeyProof::from_bytes(proof)?.verify(public_key, &mut ProofTranscript::new(context))?)
+    }
 }
 
 /// JavaScript-friendly wrapper for PostQuantumKeys
@@ -207,44 +1076,48 @@ pub struct JsPostQuantumKeys {
 
 #[wasm_bindgen]
 impl JsPostQuantumKeys {
+    /// Generate a post-quantum key pair
     #[wasm_bindgen(constructor)]
     pub fn generate() -> Result<JsPostQuantumKeys, JsValue> {
         PostQuantumKeys::generate()
             .map(|inner| JsPostQuantumKeys { inner })
+            .map_err(JsValue::from)
     }
     
+    /// KEM public key as hex
     #[wasm_bindgen]
     pub fn get_public_key_hex(&self) -> String {
         self.inner.get_public_key_hex()
     }
     
+    /// Signature public key as hex
     #[wasm_bindgen]
     pub fn get_sig_public_key_hex(&self) -> String {
         self.inner.get_sig_public_key_hex()
     }
     
+    /// Encapsulate a shared secret for this key
     #[wasm_bindgen]
     pub fn encapsulate(&self) -> Result<JsValue, JsValue> {
-        self.inner.encapsulate()
-            .and_then(|result| {
-                serde_wasm_bindgen::to_value(&result)
-                    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
-            })
+        to_js(&self.inner.encapsulate()?)
     }
     
+    /// Recover a shared secret from a ciphertext
     #[wasm_bindgen]
     pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
-        self.inner.decapsulate(ciphertext)
+        Ok(self.inner.decapsulate(ciphertext)?)
     }
     
+    /// Sign a message
     #[wasm_bindgen]
     pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JsValue> {
-        self.inner.sign(message)
+        Ok(self.inner.sign(message)?)
     }
     
+    /// Verify a signature
     #[wasm_bindgen]
     pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<bool, JsValue> {
-        self.inner.verify(message, signature)
+        Ok(self.inner.verify(message, signature)?)
     }
 }
 
@@ -256,11 +1129,244 @@ pub struct JsSingularityKey {
 
 #[wasm_bindgen]
 impl JsSingularityKey {
+    /// Generate an identity key
     #[wasm_bindgen(constructor)]
     pub fn generate() -> Result<JsSingularityKey, JsValue> {
         SingularityKey::generate()
             .map(|inner| JsSingularityKey { inner })
+            .map_err(JsValue::from)
+    }
+}
+
+/// JavaScript-friendly wrapper for StreamEncryptor
+#[wasm_bindgen]
+pub struct JsStreamEncryptor {
+    inner: StreamEncryptor,
+}
+
+#[wasm_bindgen]
+impl JsStreamEncryptor {
+    /// Start encrypting under a 32-byte key
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8]) -> Result<JsStreamEncryptor, JsValue> {
+        Ok(JsStreamEncryptor { inner: StreamEncryptor::new(&stream_key(key)?, &OsRandom) })
+    }
+    
+    /// Header to send before the first chunk
+    #[wasm_bindgen]
+    pub fn header(&self) -> Vec<u8> {
+        self.inner.header().to_vec()
+    }
+    
+    /// Encrypt the next piece of plaintext
+    #[wasm_bindgen]
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.update(chunk)?)
+    }
+    
+    /// Seal the final chunk
+    #[wasm_bindgen]
+    pub fn finish(&mut self) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.finalize()?)
+    }
+}
+
+/// JavaScript-friendly wrapper for StreamDecryptor
+#[wasm_bindgen]
+pub struct JsStreamDecryptor {
+    inner: StreamDecryptor,
+}
+
+#[wasm_bindgen]
+impl JsStreamDecryptor {
+    /// Start decrypting a stream from its header
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8], header: &[u8]) -> Result<JsStreamDecryptor, JsValue> {
+        Ok(JsStreamDecryptor { inner: StreamDecryptor::new(&stream_key(key)?, header)? })
+    }
+    
+    /// Decrypt the next piece of ciphertext
+    #[wasm_bindgen]
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.update(chunk)?)
+    }
+    
+    /// Check and open the final chunk
+    #[wasm_bindgen]
+    pub fn finish(&mut self) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.finalize()?)
+    }
+}
+
+/// JavaScript-friendly wrapper for AttachmentDownload
+#[wasm_bindgen]
+pub struct JsAttachmentDownload {
+    inner: AttachmentDownload,
+}
+
+#[wasm_bindgen]
+impl JsAttachmentDownload {
+    /// Start a verified download from a pointer and outboard
+    #[wasm_bindgen(constructor)]
+    pub fn new(pointer: &[u8], outboard: &[u8]) -> Result<JsAttachmentDownload, JsValue> {
+        let pointer = AttachmentPointer::from_bytes(pointer)?;
+        Ok(JsAttachmentDownload { inner: AttachmentDownload::new(&pointer, outboard)? })
+    }
+    
+    /// Blob offset to resume the download from
+    #[wasm_bindgen]
+    pub fn position(&self) -> u64 {
+        self.inner.position()
+    }
+    
+    /// Verify and decrypt the next piece of the blob
+    #[wasm_bindgen]
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.push(chunk)?)
+    }
+    
+    /// Verify a range of the blob without decrypting it
+    #[wasm_bindgen]
+    pub fn verify_range(&self, offset: u64, data: &[u8]) -> Result<(), JsValue> {
+        Ok(self.inner.verify_range(offset, data)?)
+    }
+    
+    /// Check the download is complete and return the rest
+    #[wasm_bindgen]
+    pub fn finish(&mut self) -> Result<Vec<u8>, JsValue> {
+        Ok(self.inner.finalize()?)
+    }
+}
+
+/// Encode a `MessageContent` object for `DoubleRatchet` or group encryption
+#[wasm_bindgen]
+pub fn encode_message_content(content: JsValue) -> Result<Vec<u8>, JsValue> {
+    let content: MessageContent = from_js(content)?;
+    
+    Ok(content.encode()?)
+}
+
+/// Decode a decrypted payload into a `MessageContent` object
+#[wasm_bindgen]
+pub fn decode_message_content(bytes: &[u8]) -> Result<JsValue, JsValue> {
+    to_js(&MessageContent::decode(bytes)?)
+}
+
+/// Encrypt a `ReadableStream` chunk by chunk
+///
+/// `sink` is called with a `Uint8Array` for the header and for every sealed
+/// chunk as soon as it is available. The returned promise resolves once the
+/// final chunk has been emitted.
+#[wasm_bindgen]
+pub fn encrypt_readable_stream(
+    key: &[u8],
+    stream: web_sys::ReadableStream,
+    sink: js_sys::Function,
+) -> Promise {
+    let key = stream_key(key);
+    
+    future_to_promise(async move {
+        let mut encryptor = StreamEncryptor::new(&key?, &OsRandom);
+        emit(&sink, &encryptor.header())?;
+        
+        let reader: web_sys::ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+        while let Some(chunk) = read_chunk(&reader).await? {
+            emit(&sink, &encryptor.update(&chunk)?)?;
+        }
+        
+        emit(&sink, &encryptor.finalize()?)?;
+        Ok(JsValue::UNDEFINED)
+    })
+}
+
+/// Decrypt a `ReadableStream` produced by `encrypt_readable_stream`
+///
+/// `sink` receives the plaintext as it is authenticated. The promise rejects
+/// if any chunk is tampered with, reordered, or the stream is truncated.
+#[wasm_bindgen]
+pub fn decrypt_readable_stream(
+    key: &[u8],
+    stream: web_sys::ReadableStream,
+    sink: js_sys::Function,
+) -> Promise {
+    let key = stream_key(key);
+    
+    future_to_promise(async move {
+        let key = key?;
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        let mut decryptor: Option<StreamDecryptor> = None;
+        
+        let reader: web_sys::ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+        while let Some(chunk) = read_chunk(&reader).await? {
+            let mut body = &chunk[..];
+            
+            // The first bytes of the stream carry the header
+            if decryptor.is_none() {
+                let needed = (HEADER_LEN - header.len()).min(body.len());
+                header.extend_from_slice(&body[..needed]);
+                body = &body[needed..];
+                
+                if header.len() < HEADER_LEN {
+                    continue;
+                }
+                decryptor = Some(StreamDecryptor::new(&key, &header)?);
+            }
+            
+            if let Some(decryptor) = decryptor.as_mut() {
+                emit(&sink, &decryptor.update(body)?)?;
+            }
+        }
+        
+        let mut decryptor = decryptor.ok_or(StreamError::Truncated)?;
+        emit(&sink, &decryptor.finalize()?)?;
+        Ok(JsValue::UNDEFINED)
+    })
+}
+
+fn stream_key(key: &[u8]) -> Result<[u8; 32], JsValue> {
+    Ok(key_array(key)?)
+}
+
+/// Convert a core value into a plain JavaScript object
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(value)
+        .map_err(BlackHoleError::serialization)?)
+}
+
+/// Split concatenated 32-byte values
+fn split_32(bytes: &[u8]) -> Result<core::slice::ChunksExact<'_, u8>, BlackHoleError> {
+    if bytes.len() % 32 != 0 {
+        return Err(BlackHoleError::InvalidKeyLength { expected: 32, actual: bytes.len() % 32 });
+    }
+    
+    Ok(bytes.chunks_exact(32))
+}
+
+/// Convert a JavaScript object into a core value
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsValue> {
+    Ok(serde_wasm_bindgen::from_value(value)
+        .map_err(BlackHoleError::serialization)?)
+}
+
+/// Read the next chunk from a stream reader, or `None` once it is done
+async fn read_chunk(reader: &web_sys::ReadableStreamDefaultReader) -> Result<Option<Vec<u8>>, JsValue> {
+    let result = JsFuture::from(reader.read()).await?;
+    
+    let done = js_sys::Reflect::get(&result, &JsValue::from_str("done"))?;
+    if done.is_truthy() {
+        return Ok(None);
+    }
+    
+    let value = js_sys::Reflect::get(&result, &JsValue::from_str("value"))?;
+    Ok(Some(value.dyn_into::<Uint8Array>()?.to_vec()))
+}
+
+/// Hand bytes to a JavaScript sink, skipping empty outputs
+fn emit(sink: &js_sys::Function, bytes: &[u8]) -> Result<(), JsValue> {
+    if !bytes.is_empty() {
+        sink.call1(&JsValue::NULL, &Uint8Array::from(bytes))?;
     }
+    Ok(())
 }
 
 /// Utility functions for JavaScript
@@ -279,7 +1385,7 @@ impl Utils {
     #[wasm_bindgen]
     pub fn random_bytes(length: usize) -> Vec<u8> {
         let mut bytes = vec![0u8; length];
-        rand::fill(&mut bytes[..]);
+        OsRandom.fill_bytes(&mut bytes);
         bytes
     }
     
@@ -301,16 +1407,17 @@ impl Utils {
 
 /// Async key generation for non-blocking UI
 #[wasm_bindgen]
-pub fn generate_keys_async() -> Promise {
-    future_to_promise(async {
+pub fn generate_keys_async(parameters: Vec<u8>) -> Promise {
+    future_to_promise(async move {
         // Simulate async work
         wasm_bindgen_futures::JsFuture::from(js_sys::Promise::new(&mut |resolve, _| {
             resolve.call0(&JsValue::NULL).unwrap();
         })).await.unwrap();
         
-        BlackHoleCore::new()
+        ZKParameters::from_bytes(&parameters)
+            .and_then(|parameters| BlackHoleCore::new(&parameters))
             .map(|core| JsValue::from(JsBlackHoleCore { inner: core }))
-            .map_err(|e| e)
+            .map_err(JsValue::from)
     })
 }
 
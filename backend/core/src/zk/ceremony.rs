@@ -0,0 +1,816 @@
+//! 🕯️ Phase-2 Ceremony
+//!
+//! A multi-party computation for the circuit-specific part of the Groth16
+//! setup, so no single party ever knows δ. Participants take turns: each
+//! picks a secret `d`, multiplies δ by it and divides the δ-scaled queries
+//! by it, then destroys `d`. The final δ is the product of every secret, so
+//! the parameters are sound as long as one participant was honest.
+//!
+//! ```text
+//! δ' = δ · d        H' = H / d        L' = L / d
+//! ```
+//!
+//! Every contribution is recorded in a `Transcript` with a proof of
+//! knowledge of `d`: a random `s` with `s·d` in G1, and `r·d` for a point
+//! `r` hashed into G2 from everything that came before, so a contribution
+//! cannot be chosen to cancel an earlier one. The verifier replays the
+//! whole chain from the initial to the final parameters using pairings
+//! only; intermediate parameter files are not needed.
+//!
+//! Only δ is protected. α, β and τ come from a powers-of-tau ("phase 1")
+//! ceremony, whose output is imported as [`PowersOfTau`]; the initial
+//! parameters are derived from it deterministically with γ = δ = 1, so
+//! whoever starts the ceremony learns no secret and anyone can re-derive
+//! them to check: `Transcript::verify` does so before replaying the chain.
+//! The parameters are sound as long as one participant of
+//! each phase was honest.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use zeroize::Zeroize;
+use crate::error::BlackHoleError;
+use crate::providers::{CryptoRng, RngAdapter};
+use super::membership::MembershipParameters;
+use super::rln::RlnParameters;
+use super::setup::ZKParameters;
+
+/// Key derivation context for the transcript hash chain
+const TRANSCRIPT_CONTEXT: &str = "BlackHole 2025 ZK ceremony transcript";
+
+/// Key derivation context for the proof-of-knowledge base point
+const BASE_POINT_CONTEXT: &str = "BlackHole 2025 ZK ceremony base point";
+
+/// Key derivation context for the batched query check
+const CHALLENGE_CONTEXT: &str = "BlackHole 2025 ZK ceremony challenge";
+
+/// Key derivation context for the powers-of-tau consistency check
+const POWERS_CONTEXT: &str = "BlackHole 2025 ZK powers of tau challenge";
+
+/// Errors raised while contributing to or verifying a ceremony
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CeremonyError {
+    /// The parameters are not the latest in the transcript
+    #[error("Parameters do not match the ceremony transcript")]
+    WrongParameters,
+
+    /// A contribution's proof of knowledge or δ update is invalid
+    #[error("Invalid ceremony contribution {0}")]
+    InvalidContribution(usize),
+
+    /// The final parameters are not the initial ones updated by the transcript
+    #[error("Final parameters do not follow from the ceremony transcript")]
+    InvalidResult,
+
+    /// The transcript could not be decoded
+    #[error("Malformed ceremony transcript")]
+    Malformed,
+
+    /// The powers of tau could not be decoded or are not powers of one τ
+    #[error("Invalid powers of tau")]
+    InvalidPowers,
+
+    /// The circuit needs a larger phase-1 ceremony
+    #[error("The circuit needs {0} powers of tau")]
+    TooFewPowers(usize),
+}
+
+/// One participant's update, with its proof of knowledge
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution {
+    /// δ·G1 after the update
+    delta_g1: G1Affine,
+    /// δ·G2 after the update
+    delta_g2: G2Affine,
+    /// Random point chosen by the participant
+    s: G1Affine,
+    /// `s·d`
+    s_delta: G1Affine,
+    /// `r·d` for the base point `r` hashed from the transcript
+    r_delta: G2Affine,
+}
+
+/// The record of a ceremony: the initial parameters and every contribution
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Transcript {
+    /// BLAKE3 hash of the encoded initial parameters
+    initial: [u8; 32],
+    /// Contributions in order
+    contributions: Vec<Contribution>,
+}
+
+impl Transcript {
+    /// Start a ceremony from initial parameters
+    pub fn new(initial: &ZKParameters) -> Self {
+        Transcript {
+            initial: *blake3::hash(&initial.to_bytes()).as_bytes(),
+            contributions: Vec::new(),
+        }
+    }
+
+    /// Decode a transcript
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CeremonyError> {
+        Transcript::deserialize_compressed(bytes).map_err(|_| CeremonyError::Malformed)
+    }
+
+    /// Encode the transcript
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes)
+            .expect("serializing into a Vec cannot fail");
+        bytes
+    }
+
+    /// Number of contributions so far
+    pub fn len(&self) -> usize {
+        self.contributions.len()
+    }
+
+    /// Whether nobody has contributed yet
+    pub fn is_empty(&self) -> bool {
+        self.contributions.is_empty()
+    }
+
+    /// Hash of the transcript so far, which participants publish to attest
+    /// their contribution
+    pub fn hash(&self) -> [u8; 32] {
+        self.contributions.iter().fold(self.initial, chain)
+    }
+
+    /// Contribute fresh randomness to the latest parameters
+    ///
+    /// Updates `parameters` in place, records the contribution and returns
+    /// the new transcript hash.
+    pub fn contribute(&mut self, parameters: &mut ZKParameters, rng: &dyn CryptoRng) -> Result<[u8; 32], CeremonyError> {
+        let latest = match self.contributions.last() {
+            Some(last) => {
+                let key = parameters.proving_key();
+                key.delta_g1 == last.delta_g1 && key.vk.delta_g2 == last.delta_g2
+            }
+            None => blake3::hash(&parameters.to_bytes()).as_bytes() == &self.initial,
+        };
+        if !latest {
+            return Err(CeremonyError::WrongParameters);
+        }
+
+        log::info!("🕯️ Contributing to ceremony (contribution {})...", self.len() + 1);
+
+        let mut rng = RngAdapter(rng);
+        let mut d = nonzero_scalar(&mut rng);
+        let mut x = nonzero_scalar(&mut rng);
+
+        let s = (G1Affine::generator() * x).into_affine();
+        let s_delta = (s * d).into_affine();
+        let r = base_point(&self.hash(), &s, &s_delta);
+        let r_delta = (r * d).into_affine();
+
+        let key = parameters.proving_key_mut();
+        rescale(key, d);
+
+        self.contributions.push(Contribution {
+            delta_g1: key.delta_g1,
+            delta_g2: key.vk.delta_g2,
+            s,
+            s_delta,
+            r_delta,
+        });
+
+        d.zeroize();
+        x.zeroize();
+
+        log::info!("✅ Contribution recorded");
+
+        Ok(self.hash())
+    }
+
+    /// Check the whole chain for `circuit` from `powers` to `result`
+    ///
+    /// The initial parameters are derived again from the phase-1 powers, so
+    /// a transcript started from any other parameters, such as a local
+    /// setup whose δ someone knows, is rejected. Returns the transcript hash
+    /// after each contribution, for comparing against the hashes
+    /// participants published.
+    pub fn verify(&self, powers: &PowersOfTau, circuit: Circuit, result: &ZKParameters) -> Result<Vec<[u8; 32]>, BlackHoleError> {
+        let initial = circuit.initial_parameters(powers)?;
+
+        Ok(self.replay(&initial, result)?)
+    }
+
+    /// Check the chain from `initial` to `result`
+    fn replay(&self, initial: &ZKParameters, result: &ZKParameters) -> Result<Vec<[u8; 32]>, CeremonyError> {
+        log::info!("🔍 Verifying ceremony transcript ({} contributions)...", self.len());
+
+        if blake3::hash(&initial.to_bytes()).as_bytes() != &self.initial {
+            return Err(CeremonyError::WrongParameters);
+        }
+
+        let before = initial.proving_key();
+        let after = result.proving_key();
+        // δ·G1 and δ·G2 must agree; compare against β in both groups
+        if !same_ratio((before.beta_g1, before.delta_g1), (before.vk.beta_g2, before.vk.delta_g2)) {
+            return Err(CeremonyError::WrongParameters);
+        }
+
+        let mut hashes = Vec::with_capacity(self.len());
+        let mut hash = self.initial;
+        let (mut delta_g1, mut delta_g2) = (before.delta_g1, before.vk.delta_g2);
+
+        for (index, contribution) in self.contributions.iter().enumerate() {
+            let r = base_point(&hash, &contribution.s, &contribution.s_delta);
+
+            let valid = !contribution.s.is_zero()
+                // Knows d: s → s·d is the same step as r → r·d
+                && same_ratio((contribution.s, contribution.s_delta), (r, contribution.r_delta))
+                // δ was multiplied by that same d, in both groups
+                && same_ratio((delta_g1, contribution.delta_g1), (r, contribution.r_delta))
+                && same_ratio((delta_g1, contribution.delta_g1), (delta_g2, contribution.delta_g2));
+            if !valid {
+                return Err(CeremonyError::InvalidContribution(index));
+            }
+
+            hash = chain(hash, contribution);
+            hashes.push(hash);
+            (delta_g1, delta_g2) = (contribution.delta_g1, contribution.delta_g2);
+        }
+
+        if !follows(before, after, delta_g1, delta_g2, &hash) {
+            return Err(CeremonyError::InvalidResult);
+        }
+
+        log::info!("✅ Ceremony transcript verified");
+
+        Ok(hashes)
+    }
+}
+
+/// Circuits a ceremony can be run for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Circuit {
+    /// Identity proofs (`ZKIdentity`)
+    Identity,
+
+    /// Group membership proofs (`MembershipParameters`)
+    Membership,
+
+    /// Rate-limiting nullifiers (`RlnParameters`)
+    Rln,
+}
+
+impl Circuit {
+    /// Every circuit
+    pub const ALL: [Circuit; 3] = [Circuit::Identity, Circuit::Membership, Circuit::Rln];
+
+    /// Name of the circuit on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Circuit::Identity => "identity",
+            Circuit::Membership => "membership",
+            Circuit::Rln => "rln",
+        }
+    }
+
+    /// Look a circuit up by its name
+    pub fn from_name(name: &str) -> Option<Circuit> {
+        Self::ALL.into_iter().find(|circuit| circuit.name() == name)
+    }
+
+    /// The parameters every ceremony for this circuit over `powers` starts from
+    pub fn initial_parameters(self, powers: &PowersOfTau) -> Result<ZKParameters, BlackHoleError> {
+        match self {
+            Circuit::Identity => ZKParameters::from_powers_of_tau(powers),
+            Circuit::Membership => MembershipParameters::from_powers_of_tau(powers).map(Into::into),
+            Circuit::Rln => RlnParameters::from_powers_of_tau(powers).map(Into::into),
+        }
+    }
+}
+
+/// The output of a powers-of-tau ("phase 1") ceremony for `n` powers
+///
+/// Only the consistency of the powers is checked on import, not the phase-1
+/// transcript: verify that with the phase-1 ceremony's own tools before
+/// trusting the file.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PowersOfTau {
+    /// τ^i·G1 for i < 2n - 1
+    tau_g1: Vec<G1Affine>,
+    /// τ^i·G2 for i < n
+    tau_g2: Vec<G2Affine>,
+    /// α·τ^i·G1 for i < n
+    alpha_tau_g1: Vec<G1Affine>,
+    /// β·τ^i·G1 for i < n
+    beta_tau_g1: Vec<G1Affine>,
+    /// β·G2
+    beta_g2: G2Affine,
+}
+
+impl PowersOfTau {
+    /// Powers of a τ, α and β drawn locally
+    ///
+    /// Whoever knows the randomness can forge proofs under every key
+    /// derived from these powers; for tests and local development only.
+    pub fn generate_insecure(size: usize, rng: &dyn CryptoRng) -> Result<Self, CeremonyError> {
+        if size < 2 || !size.is_power_of_two() {
+            return Err(CeremonyError::InvalidPowers);
+        }
+
+        let mut rng = RngAdapter(rng);
+        let mut tau = nonzero_scalar(&mut rng);
+        let mut alpha = nonzero_scalar(&mut rng);
+        let mut beta = nonzero_scalar(&mut rng);
+
+        let mut powers = Vec::with_capacity(2 * size - 1);
+        let mut power = Fr::one();
+        for _ in 0..2 * size - 1 {
+            powers.push(power);
+            power *= tau;
+        }
+
+        let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+        let scaled_g1 = |factor: Fr, count: usize| {
+            let points: Vec<G1Projective> = powers[..count].iter().map(|power| g1 * (*power * factor)).collect();
+            G1Projective::normalize_batch(&points)
+        };
+        let tau_g2: Vec<G2Projective> = powers[..size].iter().map(|power| g2 * power).collect();
+
+        let result = PowersOfTau {
+            tau_g1: scaled_g1(Fr::one(), 2 * size - 1),
+            tau_g2: G2Projective::normalize_batch(&tau_g2),
+            alpha_tau_g1: scaled_g1(alpha, size),
+            beta_tau_g1: scaled_g1(beta, size),
+            beta_g2: (g2 * beta).into_affine(),
+        };
+
+        powers.zeroize();
+        tau.zeroize();
+        alpha.zeroize();
+        beta.zeroize();
+
+        Ok(result)
+    }
+
+    /// Decode powers of tau, checking they are consistent
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CeremonyError> {
+        let powers = PowersOfTau::deserialize_compressed(bytes).map_err(|_| CeremonyError::InvalidPowers)?;
+        if !powers.is_consistent() {
+            return Err(CeremonyError::InvalidPowers);
+        }
+
+        Ok(powers)
+    }
+
+    /// Encode the powers of tau
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes)
+            .expect("serializing into a Vec cannot fail");
+        bytes
+    }
+
+    /// Largest evaluation domain these powers can seed a circuit for
+    pub fn size(&self) -> usize {
+        self.tau_g2.len()
+    }
+
+    /// Whether every list is a geometric sequence in the same τ
+    ///
+    /// Each chain is checked with one pairing equation over a random
+    /// combination of its consecutive pairs.
+    fn is_consistent(&self) -> bool {
+        let size = self.size();
+        let shaped = size >= 2
+            && size.is_power_of_two()
+            && self.tau_g1.len() == 2 * size - 1
+            && self.alpha_tau_g1.len() == size
+            && self.beta_tau_g1.len() == size
+            && !self.tau_g1[0].is_zero()
+            && !self.tau_g1[1].is_zero()
+            && !self.tau_g2[0].is_zero()
+            && !self.alpha_tau_g1[0].is_zero()
+            && !self.beta_tau_g1[0].is_zero();
+        if !shaped {
+            return false;
+        }
+
+        let mut challenge = blake3::Hasher::new_derive_key(POWERS_CONTEXT)
+            .update(blake3::hash(&self.to_bytes()).as_bytes())
+            .finalize_xof();
+        let weights: Vec<Fr> = (0..self.tau_g1.len() - 1)
+            .map(|_| {
+                let mut wide = [0u8; 64];
+                challenge.fill(&mut wide);
+                Fr::from_le_bytes_mod_order(&wide)
+            })
+            .collect();
+
+        // Σ w_i·P_i and Σ w_i·P_{i+1}, which differ by τ if every step does
+        let pairs_g1 = |points: &[G1Affine]| {
+            let count = points.len() - 1;
+            (
+                G1Projective::msm_unchecked(&points[..count], &weights[..count]).into_affine(),
+                G1Projective::msm_unchecked(&points[1..], &weights[..count]).into_affine(),
+            )
+        };
+        let count = size - 1;
+        let pairs_g2 = (
+            G2Projective::msm_unchecked(&self.tau_g2[..count], &weights[..count]).into_affine(),
+            G2Projective::msm_unchecked(&self.tau_g2[1..], &weights[..count]).into_affine(),
+        );
+        let tau_step_g1 = (self.tau_g1[0], self.tau_g1[1]);
+        let tau_step_g2 = (self.tau_g2[0], self.tau_g2[1]);
+
+        same_ratio(pairs_g1(&self.tau_g1), tau_step_g2)
+            && same_ratio(tau_step_g1, pairs_g2)
+            && same_ratio(pairs_g1(&self.alpha_tau_g1), tau_step_g2)
+            && same_ratio(pairs_g1(&self.beta_tau_g1), tau_step_g2)
+            && same_ratio((self.tau_g1[0], self.beta_tau_g1[0]), (self.tau_g2[0], self.beta_g2))
+    }
+
+    /// Initial phase-2 parameters for a circuit, with γ = δ = 1
+    ///
+    /// Evaluates the circuit's QAP at τ in the exponent the way the Groth16
+    /// generator does with a known τ, using the Lagrange basis the inverse
+    /// FFT gives from the powers.
+    pub(crate) fn specialize(&self, circuit: impl ConstraintSynthesizer<Fr>) -> Result<ProvingKey<Bn254>, BlackHoleError> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Setup);
+        circuit.generate_constraints(cs.clone()).map_err(|_| BlackHoleError::Proof)?;
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or(BlackHoleError::Proof)?;
+
+        let constraints = cs.num_constraints();
+        let instance = cs.num_instance_variables();
+        let variables = instance + cs.num_witness_variables();
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(constraints + instance).ok_or(BlackHoleError::Proof)?;
+        let size = domain.size();
+        if size > self.size() {
+            return Err(CeremonyError::TooFewPowers(size).into());
+        }
+
+        // L_i(τ) in each group, for every point of the domain
+        let lagrange_g1 = |points: &[G1Affine]| {
+            let points: Vec<G1Projective> = points[..size].iter().map(|point| point.into_group()).collect();
+            G1Projective::normalize_batch(&domain.ifft(&points))
+        };
+        let tau = lagrange_g1(&self.tau_g1);
+        let alpha_tau = lagrange_g1(&self.alpha_tau_g1);
+        let beta_tau = lagrange_g1(&self.beta_tau_g1);
+        let tau_g2: Vec<G2Projective> = self.tau_g2[..size].iter().map(|point| point.into_group()).collect();
+        let tau_g2 = G2Projective::normalize_batch(&domain.ifft(&tau_g2));
+
+        // Each variable's column of the constraint matrices, as (row, coefficient)
+        let columns = |matrix: &[Vec<(Fr, usize)>]| {
+            let mut columns = vec![Vec::new(); variables];
+            for (row, terms) in matrix.iter().enumerate() {
+                for &(coefficient, index) in terms {
+                    columns[index].push((row, coefficient));
+                }
+            }
+            columns
+        };
+        let (mut a, b, c) = (columns(&matrices.a), columns(&matrices.b), columns(&matrices.c));
+
+        // The reduction appends a constraint binding each public input
+        for (index, column) in a.iter_mut().take(instance).enumerate() {
+            column.push((constraints + index, Fr::one()));
+        }
+
+        let a_query: Vec<G1Projective> = a.iter().map(|column| combine(&tau, column)).collect();
+        let b_g1_query: Vec<G1Projective> = b.iter().map(|column| combine(&tau, column)).collect();
+        let b_g2_query: Vec<G2Projective> = b.iter().map(|column| combine(&tau_g2, column)).collect();
+        // β·A_i(τ) + α·B_i(τ) + C_i(τ)
+        let abc: Vec<G1Projective> = (0..variables)
+            .map(|index| {
+                combine::<G1Projective>(&beta_tau, &a[index])
+                    + combine::<G1Projective>(&alpha_tau, &b[index])
+                    + combine::<G1Projective>(&tau, &c[index])
+            })
+            .collect();
+
+        // τ^i·Z(τ) = τ^(n+i) - τ^i for the vanishing polynomial X^n - 1
+        let h_query: Vec<G1Projective> = (0..size - 1)
+            .map(|i| self.tau_g1[size + i].into_group() - self.tau_g1[i])
+            .collect();
+
+        let (g1, g2) = (self.tau_g1[0], self.tau_g2[0]);
+        let vk = VerifyingKey {
+            alpha_g1: self.alpha_tau_g1[0],
+            beta_g2: self.beta_g2,
+            gamma_g2: g2,
+            delta_g2: g2,
+            gamma_abc_g1: G1Projective::normalize_batch(&abc[..instance]),
+        };
+
+        Ok(ProvingKey {
+            vk,
+            beta_g1: self.beta_tau_g1[0],
+            delta_g1: g1,
+            a_query: G1Projective::normalize_batch(&a_query),
+            b_g1_query: G1Projective::normalize_batch(&b_g1_query),
+            b_g2_query: G2Projective::normalize_batch(&b_g2_query),
+            h_query: G1Projective::normalize_batch(&h_query),
+            l_query: G1Projective::normalize_batch(&abc[instance..]),
+        })
+    }
+}
+
+/// `Σ coefficient·points[row]` over one column of the constraint matrices
+fn combine<G: VariableBaseMSM<ScalarField = Fr>>(points: &[G::MulBase], column: &[(usize, Fr)]) -> G {
+    let (bases, scalars): (Vec<G::MulBase>, Vec<Fr>) = column.iter()
+        .map(|&(row, coefficient)| (points[row], coefficient))
+        .unzip();
+    G::msm_unchecked(&bases, &scalars)
+}
+
+/// Whether `after` is `before` with δ replaced and the δ-scaled queries rescaled
+fn follows(before: &ProvingKey<Bn254>, after: &ProvingKey<Bn254>, delta_g1: G1Affine, delta_g2: G2Affine, hash: &[u8; 32]) -> bool {
+    let unchanged = after.delta_g1 == delta_g1
+        && after.vk.delta_g2 == delta_g2
+        && after.vk.alpha_g1 == before.vk.alpha_g1
+        && after.vk.beta_g2 == before.vk.beta_g2
+        && after.vk.gamma_g2 == before.vk.gamma_g2
+        && after.vk.gamma_abc_g1 == before.vk.gamma_abc_g1
+        && after.beta_g1 == before.beta_g1
+        && after.a_query == before.a_query
+        && after.b_g1_query == before.b_g1_query
+        && after.b_g2_query == before.b_g2_query
+        && after.h_query.len() == before.h_query.len()
+        && after.l_query.len() == before.l_query.len();
+    if !unchanged {
+        return false;
+    }
+
+    // H·δ and L·δ are unchanged; check a random combination of each
+    let mut challenge = blake3::Hasher::new_derive_key(CHALLENGE_CONTEXT)
+        .update(hash)
+        .update(blake3::hash(&serialize_key(after)).as_bytes())
+        .finalize_xof();
+    let weights: Vec<Fr> = (0..before.h_query.len().max(before.l_query.len()))
+        .map(|_| {
+            let mut wide = [0u8; 64];
+            challenge.fill(&mut wide);
+            Fr::from_le_bytes_mod_order(&wide)
+        })
+        .collect();
+
+    [(&before.h_query, &after.h_query), (&before.l_query, &after.l_query)]
+        .into_iter()
+        .all(|(old, new)| {
+            let old = G1Projective::msm_unchecked(old, &weights[..old.len()]).into_affine();
+            let new = G1Projective::msm_unchecked(new, &weights[..new.len()]).into_affine();
+            same_ratio((new, old), (before.vk.delta_g2, delta_g2))
+        })
+}
+
+/// Multiply δ by `d` and divide the δ-scaled queries by it
+fn rescale(key: &mut ProvingKey<Bn254>, d: Fr) {
+    let inverse = d.inverse().expect("contribution scalar is non-zero");
+
+    key.delta_g1 = (key.delta_g1 * d).into_affine();
+    key.vk.delta_g2 = (key.vk.delta_g2 * d).into_affine();
+
+    for query in [&mut key.h_query, &mut key.l_query] {
+        let scaled: Vec<G1Projective> = query.iter().map(|point| *point * inverse).collect();
+        *query = G1Projective::normalize_batch(&scaled);
+    }
+}
+
+/// Whether `b/a` in G1 equals `d/c` in G2, i.e. `e(a, d) = e(b, c)`
+fn same_ratio((a, b): (G1Affine, G1Affine), (c, d): (G2Affine, G2Affine)) -> bool {
+    Bn254::pairing(a, d) == Bn254::pairing(b, c)
+}
+
+/// Next link of the transcript hash chain
+fn chain(hash: [u8; 32], contribution: &Contribution) -> [u8; 32] {
+    let mut encoded = Vec::with_capacity(contribution.compressed_size());
+    contribution.serialize_compressed(&mut encoded)
+        .expect("serializing into a Vec cannot fail");
+
+    *blake3::Hasher::new_derive_key(TRANSCRIPT_CONTEXT)
+        .update(&hash)
+        .update(&encoded)
+        .finalize()
+        .as_bytes()
+}
+
+/// Hash the transcript and the new `s` pair to a point in G2 nobody knows
+/// the discrete logarithm of
+///
+/// Try-and-increment: draw x coordinates from the XOF until one is on the
+/// curve, then clear the cofactor to land in the prime-order subgroup.
+fn base_point(hash: &[u8; 32], s: &G1Affine, s_delta: &G1Affine) -> G2Affine {
+    let mut seed = Vec::new();
+    seed.extend_from_slice(hash);
+    s.serialize_compressed(&mut seed).expect("serializing into a Vec cannot fail");
+    s_delta.serialize_compressed(&mut seed).expect("serializing into a Vec cannot fail");
+
+    let mut reader = blake3::Hasher::new_derive_key(BASE_POINT_CONTEXT)
+        .update(&seed)
+        .finalize_xof();
+
+    loop {
+        let mut wide = [0u8; 129];
+        reader.fill(&mut wide);
+
+        let x = Fq2::new(
+            Fq::from_le_bytes_mod_order(&wide[..64]),
+            Fq::from_le_bytes_mod_order(&wide[64..128]),
+        );
+        if let Some(point) = G2Affine::get_point_from_x_unchecked(x, wide[128] & 1 == 1) {
+            let point = point.clear_cofactor();
+            if !point.is_zero() {
+                return point;
+            }
+        }
+    }
+}
+
+fn nonzero_scalar(rng: &mut RngAdapter<'_>) -> Fr {
+    loop {
+        let scalar = Fr::rand(rng);
+        if !scalar.is_zero() {
+            return scalar;
+        }
+    }
+}
+
+fn serialize_key(key: &ProvingKey<Bn254>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(key.compressed_size());
+    key.serialize_compressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::SeededRng;
+    use crate::zk::ZKIdentity;
+    use crate::SingularityKey;
+    use alloc::sync::Arc;
+
+    /// Domain size of the identity circuit
+    const IDENTITY_POWERS: usize = 256;
+
+    fn phase1(seed: u64) -> PowersOfTau {
+        PowersOfTau::generate_insecure(IDENTITY_POWERS, &SeededRng::from_u64(seed)).unwrap()
+    }
+
+    fn ceremony(participants: u64) -> (PowersOfTau, ZKParameters, ZKParameters, Transcript) {
+        let powers = phase1(0);
+        let initial = ZKParameters::from_powers_of_tau(&powers).unwrap();
+        let mut parameters = initial.clone();
+        let mut transcript = Transcript::new(&initial);
+
+        for participant in 1..=participants {
+            transcript.contribute(&mut parameters, &SeededRng::from_u64(participant)).unwrap();
+        }
+
+        (powers, initial, parameters, transcript)
+    }
+
+    #[test]
+    fn test_ceremony_chain_verifies() {
+        let (powers, _, result, transcript) = ceremony(3);
+
+        let decoded = Transcript::from_bytes(&transcript.to_bytes()).unwrap();
+        let hashes = decoded.verify(&powers, Circuit::Identity, &result).unwrap();
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(hashes.last(), Some(&transcript.hash()));
+
+        // Proofs made with the ceremony parameters verify under its key
+        let identity = ZKIdentity::with_parameters(&SingularityKey::generate().unwrap(), &result, Arc::new(SeededRng::from_u64(9)));
+        let verifier = crate::zk::ZKVerifier::from_verifying_key(&result.verifying_key()).unwrap();
+        assert!(verifier.verify_proof(&identity.prove().unwrap(), &identity.get_commitment()).unwrap());
+    }
+
+    #[test]
+    fn test_contributions_must_chain() {
+        let (powers, initial, mut result, mut transcript) = ceremony(1);
+
+        // Contributing to stale parameters is refused
+        let mut stale = initial.clone();
+        assert_eq!(
+            transcript.contribute(&mut stale, &SeededRng::from_u64(5)),
+            Err(CeremonyError::WrongParameters),
+        );
+
+        // A forged record that leaves δ where it was does not verify
+        let mut lazy = transcript.clone();
+        let mut skipped = lazy.contributions[0].clone();
+        skipped.delta_g1 = initial.proving_key().delta_g1;
+        skipped.delta_g2 = initial.proving_key().vk.delta_g2;
+        lazy.contributions.push(skipped);
+        assert_eq!(
+            lazy.verify(&powers, Circuit::Identity, &result),
+            Err(CeremonyError::InvalidContribution(1).into()),
+        );
+
+        // Nor does a replayed proof of knowledge
+        transcript.contribute(&mut result, &SeededRng::from_u64(6)).unwrap();
+        let mut replayed = transcript.clone();
+        let first = replayed.contributions[0].clone();
+        let second = &mut replayed.contributions[1];
+        (second.s, second.s_delta, second.r_delta) = (first.s, first.s_delta, first.r_delta);
+        assert_eq!(
+            replayed.verify(&powers, Circuit::Identity, &result),
+            Err(CeremonyError::InvalidContribution(1).into()),
+        );
+    }
+
+    #[test]
+    fn test_tampered_result_is_rejected() {
+        let (powers, initial, result, transcript) = ceremony(2);
+
+        // Keeping δ but leaving H unscaled
+        let mut tampered = result.clone();
+        tampered.proving_key_mut().h_query = initial.proving_key().h_query.clone();
+        assert_eq!(
+            transcript.verify(&powers, Circuit::Identity, &tampered),
+            Err(CeremonyError::InvalidResult.into()),
+        );
+
+        // Swapping in another phase 1's α
+        let other = phase1(42);
+        let mut tampered = result.clone();
+        tampered.proving_key_mut().vk.alpha_g1 = ZKParameters::from_powers_of_tau(&other).unwrap().proving_key().vk.alpha_g1;
+        assert_eq!(
+            transcript.verify(&powers, Circuit::Identity, &tampered),
+            Err(CeremonyError::InvalidResult.into()),
+        );
+
+        // The chain must start from the parameters these powers give
+        assert_eq!(
+            transcript.verify(&other, Circuit::Identity, &result),
+            Err(CeremonyError::WrongParameters.into()),
+        );
+    }
+
+    #[test]
+    fn test_locally_seeded_ceremony_is_rejected() {
+        // Whoever ran this setup knows δ and every other secret
+        let initial = ZKParameters::generate(&SeededRng::from_u64(0)).unwrap();
+        let mut result = initial.clone();
+        let mut transcript = Transcript::new(&initial);
+        transcript.contribute(&mut result, &SeededRng::from_u64(1)).unwrap();
+
+        assert_eq!(transcript.replay(&initial, &result).unwrap().len(), 1);
+        assert_eq!(
+            transcript.verify(&phase1(0), Circuit::Identity, &result),
+            Err(CeremonyError::WrongParameters.into()),
+        );
+    }
+
+    #[test]
+    fn test_ceremony_from_powers_of_tau() {
+        let powers = phase1(0);
+        let imported = PowersOfTau::from_bytes(&powers.to_bytes()).unwrap();
+        assert_eq!(imported.size(), IDENTITY_POWERS);
+
+        // γ = δ = 1 until the first contribution
+        let initial = ZKParameters::from_powers_of_tau(&imported).unwrap();
+        let key = initial.proving_key();
+        assert_eq!(key.vk.gamma_g2, powers.tau_g2[0]);
+        assert_eq!(key.vk.delta_g2, powers.tau_g2[0]);
+        assert_eq!(key.delta_g1, powers.tau_g1[0]);
+
+        let mut result = initial.clone();
+        let mut transcript = Transcript::new(&initial);
+        transcript.contribute(&mut result, &SeededRng::from_u64(1)).unwrap();
+        assert_eq!(transcript.verify(&imported, Circuit::Identity, &result).unwrap().len(), 1);
+
+        let identity = ZKIdentity::with_parameters(&SingularityKey::generate().unwrap(), &result, Arc::new(SeededRng::from_u64(9)));
+        let verifier = crate::zk::ZKVerifier::from_verifying_key(&result.verifying_key()).unwrap();
+        assert!(verifier.verify_proof(&identity.prove().unwrap(), &identity.get_commitment()).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_powers_of_tau_are_rejected() {
+        let powers = PowersOfTau::generate_insecure(IDENTITY_POWERS / 2, &SeededRng::from_u64(0)).unwrap();
+
+        // Powers that are not a geometric sequence
+        let mut broken = powers.clone();
+        broken.tau_g1.swap(2, 3);
+        assert_eq!(PowersOfTau::from_bytes(&broken.to_bytes()), Err(CeremonyError::InvalidPowers));
+
+        // β in G2 that does not match β in G1
+        let mut broken = powers.clone();
+        broken.beta_g2 = broken.tau_g2[1];
+        assert_eq!(PowersOfTau::from_bytes(&broken.to_bytes()), Err(CeremonyError::InvalidPowers));
+
+        // A phase 1 too small for the circuit
+        assert_eq!(
+            ZKParameters::from_powers_of_tau(&powers).map(|_| ()),
+            Err(BlackHoleError::Ceremony(CeremonyError::TooFewPowers(IDENTITY_POWERS))),
+        );
+        assert_eq!(PowersOfTau::generate_insecure(100, &SeededRng::from_u64(0)), Err(CeremonyError::InvalidPowers));
+    }
+}
@@ -0,0 +1,200 @@
+//! 🧮 Identity Circuit
+//!
+//! The R1CS statement behind `ZKIdentity`: knowledge of a secret whose
+//! Poseidon hash is the public identity commitment.
+//!
+//! ```text
+//! public:  c
+//! witness: s
+//! enforce: Poseidon(s) = c
+//! ```
+//!
+//! Poseidon runs over the BN254 scalar field as a sponge of width 3
+//! (capacity 1, rate 2) with x⁵ S-boxes, 8 full and 57 partial rounds.
+//! Round constants and the MDS matrix come from the Grain LFSR of the
+//! Poseidon paper. The native hashes and their gadgets share the same round
+//! schedule, so a commitment computed natively is exactly the value the
+//! circuit enforces. Two-element hashes fill the whole rate and are used
+//! for Merkle nodes and nullifiers (see [`super::membership`]).
+
+use alloc::vec::Vec;
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig};
+use ark_ff::{Field, PrimeField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+/// Sponge width: one capacity element and two rate elements
+const WIDTH: usize = 3;
+
+/// S-box exponent
+const ALPHA: u64 = 5;
+
+/// Full rounds, split evenly around the partial rounds
+const FULL_ROUNDS: usize = 8;
+
+/// Partial rounds, with an S-box on the first element only
+const PARTIAL_ROUNDS: usize = 57;
+
+/// Poseidon parameters for the identity commitment
+pub fn poseidon_config() -> PoseidonConfig<Fr> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+        Fr::MODULUS_BIT_SIZE as u64,
+        WIDTH - 1,
+        FULL_ROUNDS as u64,
+        PARTIAL_ROUNDS as u64,
+        0,
+    );
+
+    PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, WIDTH - 1, 1)
+}
+
+/// Poseidon hash of one field element: absorb into the rate, permute, squeeze
+pub fn poseidon_hash(config: &PoseidonConfig<Fr>, input: Fr) -> Fr {
+    permute(config, [Fr::ZERO, input, Fr::ZERO])[1]
+}
+
+/// Poseidon hash of two field elements, filling the rate
+pub fn poseidon_hash_two(config: &PoseidonConfig<Fr>, left: Fr, right: Fr) -> Fr {
+    permute(config, [Fr::ZERO, left, right])[1]
+}
+
+/// In-circuit [`poseidon_hash`]
+pub(crate) fn poseidon_gadget(config: &PoseidonConfig<Fr>, input: &FpVar<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+    let state = permute_gadget(config, [FpVar::zero(), input.clone(), FpVar::zero()])?;
+    Ok(state[1].clone())
+}
+
+/// In-circuit [`poseidon_hash_two`]
+pub(crate) fn poseidon_two_gadget(config: &PoseidonConfig<Fr>, left: &FpVar<Fr>, right: &FpVar<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+    let state = permute_gadget(config, [FpVar::zero(), left.clone(), right.clone()])?;
+    Ok(state[1].clone())
+}
+
+fn permute(config: &PoseidonConfig<Fr>, mut state: [Fr; WIDTH]) -> [Fr; WIDTH] {
+    for round in 0..FULL_ROUNDS + PARTIAL_ROUNDS {
+        for (element, constant) in state.iter_mut().zip(&config.ark[round]) {
+            *element += constant;
+        }
+
+        let s_boxes = if is_full_round(round) { WIDTH } else { 1 };
+        for element in &mut state[..s_boxes] {
+            *element = element.pow([ALPHA]);
+        }
+
+        state = core::array::from_fn(|i| {
+            state.iter().zip(&config.mds[i]).map(|(element, entry)| *element * entry).sum()
+        });
+    }
+
+    state
+}
+
+fn permute_gadget(config: &PoseidonConfig<Fr>, mut state: [FpVar<Fr>; WIDTH]) -> Result<[FpVar<Fr>; WIDTH], SynthesisError> {
+    for round in 0..FULL_ROUNDS + PARTIAL_ROUNDS {
+        for (element, constant) in state.iter_mut().zip(&config.ark[round]) {
+            *element += *constant;
+        }
+
+        let s_boxes = if is_full_round(round) { WIDTH } else { 1 };
+        for element in &mut state[..s_boxes] {
+            // x⁵ in three constraints
+            let square = element.square()?;
+            *element = square.square()? * &*element;
+        }
+
+        let mixed: Vec<FpVar<Fr>> = (0..WIDTH)
+            .map(|i| {
+                state.iter()
+                    .zip(&config.mds[i])
+                    .fold(FpVar::zero(), |sum, (element, entry)| sum + element * *entry)
+            })
+            .collect();
+        state.clone_from_slice(&mixed);
+    }
+
+    Ok(state)
+}
+
+/// Statement: the witness secret hashes to the public commitment
+#[derive(Clone)]
+pub struct IdentityCircuit<'a> {
+    config: &'a PoseidonConfig<Fr>,
+    secret: Option<Fr>,
+    commitment: Option<Fr>,
+}
+
+impl<'a> IdentityCircuit<'a> {
+    /// The circuit shape without assignments, for key generation
+    pub fn blank(config: &'a PoseidonConfig<Fr>) -> Self {
+        IdentityCircuit { config, secret: None, commitment: None }
+    }
+
+    /// The circuit with a full assignment, for proving
+    pub fn new(config: &'a PoseidonConfig<Fr>, secret: Fr, commitment: Fr) -> Self {
+        IdentityCircuit { config, secret: Some(secret), commitment: Some(commitment) }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for IdentityCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let commitment = FpVar::new_input(cs.clone(), || {
+            self.commitment.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let secret = FpVar::new_witness(cs, || {
+            self.secret.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        poseidon_gadget(self.config, &secret)?.enforce_equal(&commitment)
+    }
+}
+
+fn is_full_round(round: usize) -> bool {
+    !(FULL_ROUNDS / 2..FULL_ROUNDS / 2 + PARTIAL_ROUNDS).contains(&round)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
+    use ark_crypto_primitives::sponge::CryptographicSponge;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_hash_matches_reference_sponge() {
+        let config = poseidon_config();
+        let input = Fr::from(1234567u64);
+
+        let mut sponge = PoseidonSponge::new(&config);
+        sponge.absorb(&input);
+        let expected: Fr = sponge.squeeze_field_elements(1)[0];
+
+        assert_eq!(poseidon_hash(&config, input), expected);
+
+        let right = Fr::from(7654321u64);
+        let mut sponge = PoseidonSponge::new(&config);
+        sponge.absorb(&input);
+        sponge.absorb(&right);
+        let expected: Fr = sponge.squeeze_field_elements(1)[0];
+
+        assert_eq!(poseidon_hash_two(&config, input, right), expected);
+    }
+
+    #[test]
+    fn test_circuit_accepts_only_the_preimage() {
+        let config = poseidon_config();
+        let secret = Fr::from(42u64);
+        let commitment = poseidon_hash(&config, secret);
+
+        let cs = ConstraintSystem::new_ref();
+        IdentityCircuit::new(&config, secret, commitment).generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let cs = ConstraintSystem::new_ref();
+        IdentityCircuit::new(&config, secret + Fr::ONE, commitment).generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
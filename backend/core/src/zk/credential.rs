@@ -0,0 +1,782 @@
+//! 🎫 Anonymous Credentials
+//!
+//! Keyed-verification credentials (Chase, Meiklejohn and Zaverucha): a
+//! server issues credentials on attributes such as "verified phone" or
+//! "paid subscriber" and later verifies them with its own key, while the
+//! holder presents them without being linkable to the issuance or to
+//! other presentations.
+//!
+//! A credential is an algebraic MAC on Ristretto,
+//!
+//! ```text
+//! (U, V = (w + Σ xᵢ·mᵢ)·U)
+//! ```
+//!
+//! keyed by the issuer's `(w, x₀, …, xₙ)`. `m₀` is a holder secret derived
+//! from the [`ZKIdentity`] and `m₁…mₙ` are the attributes asserted by the
+//! issuer. The holder secret reaches the issuer only encrypted, and the
+//! issuer computes `V` under the encryption; it proves it used the key
+//! behind its published [`IssuerParameters`], so it cannot tag holders
+//! with per-user keys.
+//!
+//! To present, the holder re-randomizes `(U, V)`, commits to the hidden
+//! attributes and proves with a [`sigma`](super::sigma) proof that the
+//! commitments open to a valid MAC. Chosen attributes are disclosed and
+//! the rest stay hidden. Each presentation carries a tag `m₀·H(scope)`
+//! that repeats for the same holder and scope, so servers can admit a
+//! holder to a group once or count its messages per epoch, and that is
+//! unlinkable across scopes.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use bulletproofs::PedersenGens;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use super::sigma::{Equation, ProofTranscript, SigmaProof, Statement, Witness};
+use super::{field_to_bytes, ZKIdentity};
+use crate::error::BlackHoleError;
+use crate::providers::{CryptoRng, RngAdapter};
+
+/// Key derivation context for the holder secret
+const HOLDER_CONTEXT: &str = "BlackHole 2025 credential holder secret";
+
+/// Hash context mapping attributes to scalars
+const ATTRIBUTE_CONTEXT: &str = "BlackHole 2025 credential attribute";
+
+/// Hash context mapping scopes to group elements
+const SCOPE_CONTEXT: &str = "BlackHole 2025 credential scope";
+
+/// Transcript context of the holder's request proof
+const REQUEST_CONTEXT: &[u8] = b"BlackHole 2025 credential request";
+
+/// Transcript context of the issuer's proof
+const ISSUANCE_CONTEXT: &[u8] = b"BlackHole 2025 credential issuance";
+
+/// Transcript context of presentation proofs
+const PRESENTATION_CONTEXT: &[u8] = b"BlackHole 2025 credential presentation";
+
+/// An issuer's secret key
+#[derive(Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct IssuerKey {
+    w: Scalar,
+    w_blinding: Scalar,
+
+    /// One per attribute, the holder secret first
+    x: Vec<Scalar>,
+}
+
+/// An issuer's public parameters, published to holders
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuerParameters {
+    /// `w·G + w̃·H`
+    commitment: RistrettoPoint,
+
+    /// `Xᵢ = xᵢ·H`, the holder secret first
+    points: Vec<RistrettoPoint>,
+}
+
+/// A holder's request for a credential, sent to the issuer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CredentialRequest {
+    /// ElGamal public key `D = d·G`
+    key: RistrettoPoint,
+
+    /// Encryption of the holder secret, `(E₁, E₂) = (r·G, m₀·G + r·D)`
+    ciphertext: (RistrettoPoint, RistrettoPoint),
+
+    proof: SigmaProof,
+}
+
+/// The holder's side of a request, kept until the issuer responds
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct PendingCredential {
+    #[zeroize(skip)]
+    request: CredentialRequest,
+
+    /// ElGamal secret key `d`
+    key: Scalar,
+}
+
+/// The issuer's response to a [`CredentialRequest`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CredentialResponse {
+    attributes: Vec<Vec<u8>>,
+    u: RistrettoPoint,
+
+    /// `T = b·X₀`, tying the encrypted MAC to the holder secret's key
+    t: RistrettoPoint,
+
+    /// Encryption of `V` under the request key
+    ciphertext: (RistrettoPoint, RistrettoPoint),
+
+    proof: SigmaProof,
+}
+
+/// An anonymous credential, held alongside the [`ZKIdentity`] it was issued to
+#[derive(Clone, Debug, Serialize, Deserialize, Zeroize)]
+pub struct Credential {
+    attributes: Vec<Vec<u8>>,
+    u: RistrettoPoint,
+    v: RistrettoPoint,
+}
+
+/// Credentials held by a [`ZKIdentity`], by issuer ID, with the issuer
+/// parameters they verify under
+#[derive(Default)]
+pub(super) struct CredentialStore(BTreeMap<[u8; 32], (IssuerParameters, Credential)>);
+
+impl Zeroize for CredentialStore {
+    fn zeroize(&mut self) {
+        self.0.values_mut().for_each(|(_, credential)| credential.zeroize());
+        self.0.clear();
+    }
+}
+
+/// A presentation of a credential, disclosing some of its attributes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Presentation {
+    /// Issuer-asserted attributes, `None` where hidden
+    attributes: Vec<Option<Vec<u8>>>,
+    u: RistrettoPoint,
+
+    /// `C_V = V + r·G`
+    v_commitment: RistrettoPoint,
+
+    /// `Cⱼ = mⱼ·U + zⱼ·H` for each hidden attribute, the holder secret first
+    commitments: Vec<RistrettoPoint>,
+
+    /// `m₀·H(scope)`
+    tag: RistrettoPoint,
+
+    proof: SigmaProof,
+}
+
+impl IssuerKey {
+    /// A fresh key for credentials with `attributes` issuer-asserted attributes
+    pub fn generate(attributes: usize, rng: &dyn CryptoRng) -> Self {
+        let rng = &mut RngAdapter(rng);
+
+        IssuerKey {
+            w: Scalar::random(rng),
+            w_blinding: Scalar::random(rng),
+            x: (0..=attributes).map(|_| Scalar::random(rng)).collect(),
+        }
+    }
+
+    /// The public parameters holders verify issuance against
+    pub fn parameters(&self) -> IssuerParameters {
+        let h = blinding_base();
+
+        IssuerParameters {
+            commitment: RistrettoPoint::mul_base(&self.w) + self.w_blinding * h,
+            points: self.x.iter().map(|x| x * h).collect(),
+        }
+    }
+
+    /// Issue a credential on `attributes` for a holder's request
+    ///
+    /// Fails with `InvalidCredential` when the request proof does not
+    /// verify.
+    pub fn issue(
+        &self,
+        request: &CredentialRequest,
+        attributes: &[&[u8]],
+        rng: &dyn CryptoRng,
+    ) -> Result<CredentialResponse, BlackHoleError> {
+        if attributes.len() + 1 != self.x.len() {
+            return Err(BlackHoleError::Malformed("credential attributes"));
+        }
+
+        let statement = request_statement(request.key, request.ciphertext);
+        if !request.proof.verify(&statement, &mut ProofTranscript::new(REQUEST_CONTEXT))? {
+            return Err(BlackHoleError::InvalidCredential);
+        }
+
+        log::info!("🎫 Issuing credential...");
+
+        let adapter = &mut RngAdapter(rng);
+        let b = Zeroizing::new(Scalar::random(adapter));
+        let s = Zeroizing::new(Scalar::random(adapter));
+        let t = Zeroizing::new(*b * self.x[0]);
+
+        // Enc(V) = (s·G + t·E₁, s·D + (w + Σᵢ₌₁ xᵢ·mᵢ)·U + t·E₂) decrypts to
+        // V, since t·(E₂ − d·E₁) = b·x₀·m₀·G = x₀·m₀·U
+        let u = RistrettoPoint::mul_base(&b);
+        let values: Vec<Scalar> = attributes.iter().map(|attribute| attribute_scalar(attribute)).collect();
+        let clear = self.w + self.x[1..].iter().zip(&values).map(|(x, m)| x * m).sum::<Scalar>();
+        let (e1, e2) = request.ciphertext;
+        let ciphertext = (RistrettoPoint::mul_base(&s) + *t * e1, *s * request.key + clear * u + *t * e2);
+        let t_point = *t * blinding_base();
+
+        let mut secrets = Zeroizing::new(vec![self.w, self.w_blinding]);
+        secrets.extend_from_slice(&self.x);
+        secrets.extend([*b, *s, *t]);
+
+        let proof = SigmaProof::prove(
+            &issuance_statement(&self.parameters(), request, &values, u, t_point, ciphertext),
+            &Witness::Linear(secrets.to_vec()),
+            &mut ProofTranscript::new(ISSUANCE_CONTEXT),
+            rng,
+        )?;
+
+        log::info!("✅ Credential issued");
+
+        Ok(CredentialResponse {
+            attributes: attributes.iter().map(|attribute| attribute.to_vec()).collect(),
+            u,
+            t: t_point,
+            ciphertext,
+            proof,
+        })
+    }
+
+    /// Verify a presentation made for `scope` and `context`
+    ///
+    /// Returns `Ok(false)` for a presentation that does not verify; the
+    /// caller checks the disclosed attributes and the tag.
+    pub fn verify_presentation(&self, presentation: &Presentation, scope: &[u8], context: &[u8]) -> Result<bool, BlackHoleError> {
+        log::info!("🔍 Verifying credential presentation...");
+
+        let hidden = hidden_indices(&presentation.attributes);
+        if presentation.attributes.len() + 1 != self.x.len() || presentation.commitments.len() != hidden.len() {
+            return Err(BlackHoleError::Malformed("credential presentation"));
+        }
+        if presentation.u.is_identity() {
+            return Ok(false);
+        }
+
+        // Z = w·U + Σ xⱼ·Cⱼ + Σ xᵢ·mᵢ·U − C_V over hidden j and disclosed i,
+        // which the holder knows as Σ zⱼ·Xⱼ − r·G
+        let disclosed: Scalar = presentation.attributes
+            .iter()
+            .zip(&self.x[1..])
+            .filter_map(|(attribute, x)| attribute.as_ref().map(|m| x * attribute_scalar(m)))
+            .sum();
+        let committed: RistrettoPoint = hidden
+            .iter()
+            .zip(&presentation.commitments)
+            .map(|(&index, commitment)| self.x[index] * commitment)
+            .sum();
+        let z = (self.w + disclosed) * presentation.u + committed - presentation.v_commitment;
+
+        let valid = presentation.proof.verify(
+            &presentation_statement(&self.parameters(), presentation.u, &presentation.commitments, presentation.tag, &hidden, z, scope),
+            &mut presentation_transcript(scope, context),
+        )?;
+
+        log::info!("✅ Credential presentation verification: {}", valid);
+
+        Ok(valid)
+    }
+}
+
+impl IssuerParameters {
+    /// Number of issuer-asserted attributes
+    pub fn attributes(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    /// ID of the issuer: the BLAKE3 hash of its compressed points
+    pub fn id(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        for point in core::iter::once(&self.commitment).chain(&self.points) {
+            hasher.update(point.compress().as_bytes());
+        }
+        *hasher.finalize().as_bytes()
+    }
+}
+
+impl PendingCredential {
+    /// The request to send to the issuer
+    pub fn request(&self) -> &CredentialRequest {
+        &self.request
+    }
+
+    /// Decrypt the issuer's response into a credential
+    ///
+    /// Fails with `InvalidCredential` when the issuer did not use the key
+    /// behind `parameters`.
+    pub fn finish(self, parameters: &IssuerParameters, response: &CredentialResponse) -> Result<Credential, BlackHoleError> {
+        if response.attributes.len() + 1 != parameters.points.len() {
+            return Err(BlackHoleError::Malformed("credential attributes"));
+        }
+
+        let values: Vec<Scalar> = response.attributes.iter().map(|attribute| attribute_scalar(attribute)).collect();
+        let statement = issuance_statement(parameters, &self.request, &values, response.u, response.t, response.ciphertext);
+        if response.u.is_identity() || !response.proof.verify(&statement, &mut ProofTranscript::new(ISSUANCE_CONTEXT))? {
+            return Err(BlackHoleError::InvalidCredential);
+        }
+
+        let (e1, e2) = response.ciphertext;
+
+        Ok(Credential {
+            attributes: response.attributes.clone(),
+            u: response.u,
+            v: e2 - self.key * e1,
+        })
+    }
+}
+
+impl Credential {
+    /// The issuer-asserted attributes
+    pub fn attributes(&self) -> &[Vec<u8>] {
+        &self.attributes
+    }
+}
+
+impl Presentation {
+    /// A disclosed attribute, or `None` if it is hidden or out of range
+    pub fn attribute(&self, index: usize) -> Option<&[u8]> {
+        self.attributes.get(index)?.as_deref()
+    }
+
+    /// The holder's pseudonym for the scope (32 bytes)
+    ///
+    /// Equal for every presentation by the same holder in the same scope.
+    pub fn tag(&self) -> [u8; 32] {
+        self.tag.compress().to_bytes()
+    }
+}
+
+impl ZKIdentity {
+    /// Start a request for a credential
+    ///
+    /// The request carries the holder secret encrypted to a one-time key;
+    /// the returned state decrypts the issuer's response.
+    pub fn request_credential(&self) -> Result<PendingCredential, BlackHoleError> {
+        let rng = &mut RngAdapter(self.rng.as_ref());
+        let secret = self.holder_secret();
+        let key = Zeroizing::new(Scalar::random(rng));
+        let r = Zeroizing::new(Scalar::random(rng));
+
+        let public_key = RistrettoPoint::mul_base(&key);
+        let ciphertext = (RistrettoPoint::mul_base(&r), RistrettoPoint::mul_base(&secret) + *r * public_key);
+        let proof = SigmaProof::prove(
+            &request_statement(public_key, ciphertext),
+            &Witness::Linear(vec![*key, *r, *secret]),
+            &mut ProofTranscript::new(REQUEST_CONTEXT),
+            self.rng.as_ref(),
+        )?;
+
+        Ok(PendingCredential {
+            request: CredentialRequest { key: public_key, ciphertext, proof },
+            key: *key,
+        })
+    }
+
+    /// Present `credential` for `scope`, disclosing the attributes at `disclose`
+    ///
+    /// `scope` determines the tag; `context` binds the presentation to a
+    /// request or message so it cannot be replayed elsewhere.
+    pub fn present_credential(
+        &self,
+        credential: &Credential,
+        parameters: &IssuerParameters,
+        disclose: &[usize],
+        scope: &[u8],
+        context: &[u8],
+    ) -> Result<Presentation, BlackHoleError> {
+        if credential.attributes.len() + 1 != parameters.points.len() {
+            return Err(BlackHoleError::Malformed("credential attributes"));
+        }
+        if disclose.iter().any(|&index| index >= credential.attributes.len()) {
+            return Err(BlackHoleError::InvalidState("no attribute at that index"));
+        }
+
+        log::info!("🎫 Presenting credential...");
+
+        let rng = &mut RngAdapter(self.rng.as_ref());
+        let secret = self.holder_secret();
+        let a = Scalar::random(rng);
+        let r = Zeroizing::new(Scalar::random(rng));
+        let u = a * credential.u;
+
+        let attributes: Vec<Option<Vec<u8>>> = credential.attributes
+            .iter()
+            .enumerate()
+            .map(|(i, attribute)| disclose.contains(&i).then(|| attribute.clone()))
+            .collect();
+
+        // Values and blindings of the hidden attributes, holder secret first
+        let mut values = Zeroizing::new(vec![*secret]);
+        values.extend(
+            attributes
+                .iter()
+                .zip(&credential.attributes)
+                .filter(|(disclosed, _)| disclosed.is_none())
+                .map(|(_, attribute)| attribute_scalar(attribute)),
+        );
+        let blindings: Zeroizing<Vec<Scalar>> = Zeroizing::new(values.iter().map(|_| Scalar::random(rng)).collect());
+
+        let commitments: Vec<RistrettoPoint> = values
+            .iter()
+            .zip(blindings.iter())
+            .map(|(m, z)| m * u + z * blinding_base())
+            .collect();
+        let tag = *secret * scope_point(scope);
+
+        let hidden = hidden_indices(&attributes);
+        let z = hidden
+            .iter()
+            .zip(blindings.iter())
+            .map(|(&index, blinding)| blinding * parameters.points[index])
+            .sum::<RistrettoPoint>()
+            - RistrettoPoint::mul_base(&r);
+
+        let mut secrets = Zeroizing::new(Vec::with_capacity(2 * values.len() + 1));
+        for (m, z) in values.iter().zip(blindings.iter()) {
+            secrets.extend([*m, *z]);
+        }
+        secrets.push(*r);
+
+        let proof = SigmaProof::prove(
+            &presentation_statement(parameters, u, &commitments, tag, &hidden, z, scope),
+            &Witness::Linear(secrets.to_vec()),
+            &mut presentation_transcript(scope, context),
+            self.rng.as_ref(),
+        )?;
+
+        log::info!("✅ Credential presented");
+
+        Ok(Presentation {
+            attributes,
+            u,
+            v_commitment: a * credential.v + RistrettoPoint::mul_base(&r),
+            commitments,
+            tag,
+            proof,
+        })
+    }
+
+    /// Hold a credential issued under `parameters`
+    ///
+    /// Replaces any credential held from the same issuer. Returns the
+    /// issuer ID the credential is held under.
+    pub fn store_credential(&mut self, parameters: IssuerParameters, credential: Credential) -> Result<[u8; 32], BlackHoleError> {
+        if credential.attributes.len() + 1 != parameters.points.len() {
+            return Err(BlackHoleError::Malformed("credential attributes"));
+        }
+
+        let issuer = parameters.id();
+        self.credentials.0.insert(issuer, (parameters, credential));
+        Ok(issuer)
+    }
+
+    /// The credential held from an issuer, with the parameters it verifies under
+    pub fn credential(&self, issuer: &[u8; 32]) -> Option<(&Credential, &IssuerParameters)> {
+        self.credentials.0.get(issuer).map(|(parameters, credential)| (credential, parameters))
+    }
+
+    /// IDs of the issuers this identity holds credentials from
+    pub fn credential_issuers(&self) -> impl Iterator<Item = &[u8; 32]> {
+        self.credentials.0.keys()
+    }
+
+    /// Stop holding the credential from an issuer, returning it
+    pub fn remove_credential(&mut self, issuer: &[u8; 32]) -> Option<Credential> {
+        self.credentials.0.remove(issuer).map(|(_, credential)| credential)
+    }
+
+    /// Present the credential held from `issuer` (see [`present_credential`](Self::present_credential))
+    pub fn present_held_credential(
+        &self,
+        issuer: &[u8; 32],
+        disclose: &[usize],
+        scope: &[u8],
+        context: &[u8],
+    ) -> Result<Presentation, BlackHoleError> {
+        let (credential, parameters) = self.credential(issuer)
+            .ok_or(BlackHoleError::InvalidState("no credential from that issuer"))?;
+
+        self.present_credential(credential, parameters, disclose, scope, context)
+    }
+
+    /// The credential attribute `m₀` bound to this identity
+    fn holder_secret(&self) -> Zeroizing<Scalar> {
+        Zeroizing::new(hash_to_scalar(HOLDER_CONTEXT, &field_to_bytes(&self.secret)))
+    }
+}
+
+/// `D = d·G`, `E₁ = r·G` and `E₂ = m₀·G + r·D` over the secrets `[d, r, m₀]`
+fn request_statement(key: RistrettoPoint, (e1, e2): (RistrettoPoint, RistrettoPoint)) -> Statement {
+    let g = RISTRETTO_BASEPOINT_POINT;
+
+    Statement::Linear {
+        secrets: 3,
+        equations: vec![
+            Equation { point: key, terms: vec![(0, g)] },
+            Equation { point: e1, terms: vec![(1, g)] },
+            Equation { point: e2, terms: vec![(2, g), (1, key)] },
+        ],
+    }
+}
+
+/// The issuer's statement over the secrets `[w, w̃, x₀, …, xₙ, b, s, t]`
+///
+/// Shows that the key is the one behind `parameters`, that `U = b·G` and
+/// `t = b·x₀`, and that the encrypted `V` is the MAC on `values`.
+fn issuance_statement(
+    parameters: &IssuerParameters,
+    request: &CredentialRequest,
+    values: &[Scalar],
+    u: RistrettoPoint,
+    t_point: RistrettoPoint,
+    (v1, v2): (RistrettoPoint, RistrettoPoint),
+) -> Statement {
+    let (g, h) = (RISTRETTO_BASEPOINT_POINT, blinding_base());
+    let n = parameters.points.len();
+    let (b, s, t) = (n + 2, n + 3, n + 4);
+    let (e1, e2) = request.ciphertext;
+
+    let mut equations = vec![Equation { point: parameters.commitment, terms: vec![(0, g), (1, h)] }];
+    equations.extend(
+        parameters.points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| Equation { point: *point, terms: vec![(i + 2, h)] }),
+    );
+
+    let mut v_terms = vec![(s, request.key), (0, u), (t, e2)];
+    v_terms.extend(values.iter().enumerate().map(|(i, m)| (i + 3, m * u)));
+
+    equations.extend([
+        Equation { point: u, terms: vec![(b, g)] },
+        Equation { point: t_point, terms: vec![(b, parameters.points[0])] },
+        Equation { point: t_point, terms: vec![(t, h)] },
+        Equation { point: v1, terms: vec![(s, g), (t, e1)] },
+        Equation { point: v2, terms: v_terms },
+    ]);
+
+    Statement::Linear { secrets: n + 5, equations }
+}
+
+/// The holder's statement over `[mⱼ, zⱼ]` for each hidden attribute, then `r`
+///
+/// Shows that the commitments open to the hidden attributes, that
+/// `Z = Σ zⱼ·Xⱼ − r·G`, and that the tag is `m₀·H(scope)`.
+fn presentation_statement(
+    parameters: &IssuerParameters,
+    u: RistrettoPoint,
+    commitments: &[RistrettoPoint],
+    tag: RistrettoPoint,
+    hidden: &[usize],
+    z: RistrettoPoint,
+    scope: &[u8],
+) -> Statement {
+    let (g, h) = (RISTRETTO_BASEPOINT_POINT, blinding_base());
+    let r = 2 * hidden.len();
+
+    let mut equations: Vec<Equation> = commitments
+        .iter()
+        .enumerate()
+        .map(|(j, commitment)| Equation { point: *commitment, terms: vec![(2 * j, u), (2 * j + 1, h)] })
+        .collect();
+
+    let mut z_terms: Vec<(usize, RistrettoPoint)> = hidden
+        .iter()
+        .enumerate()
+        .map(|(j, &index)| (2 * j + 1, parameters.points[index]))
+        .collect();
+    z_terms.push((r, -g));
+
+    equations.push(Equation { point: z, terms: z_terms });
+    equations.push(Equation { point: tag, terms: vec![(0, scope_point(scope))] });
+
+    Statement::Linear { secrets: r + 1, equations }
+}
+
+/// Indices in the key of the hidden attributes, the holder secret first
+fn hidden_indices(attributes: &[Option<Vec<u8>>]) -> Vec<usize> {
+    let mut hidden = vec![0];
+    hidden.extend(
+        attributes
+            .iter()
+            .enumerate()
+            .filter(|(_, attribute)| attribute.is_none())
+            .map(|(i, _)| i + 1),
+    );
+    hidden
+}
+
+fn presentation_transcript(scope: &[u8], context: &[u8]) -> ProofTranscript {
+    let mut transcript = ProofTranscript::new(PRESENTATION_CONTEXT);
+    transcript.append_message(b"scope", scope);
+    transcript.append_message(b"context", context);
+    transcript
+}
+
+/// The second generator `H`, shared with range proof commitments
+fn blinding_base() -> RistrettoPoint {
+    PedersenGens::default().B_blinding
+}
+
+fn attribute_scalar(attribute: &[u8]) -> Scalar {
+    hash_to_scalar(ATTRIBUTE_CONTEXT, attribute)
+}
+
+fn scope_point(scope: &[u8]) -> RistrettoPoint {
+    RistrettoPoint::from_uniform_bytes(&wide_hash(SCOPE_CONTEXT, scope))
+}
+
+/// Hash to a uniform scalar, reducing 64 bytes modulo the group order
+fn hash_to_scalar(context: &str, bytes: &[u8]) -> Scalar {
+    let mut wide = wide_hash(context, bytes);
+    let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.zeroize();
+    scalar
+}
+
+fn wide_hash(context: &str, bytes: &[u8]) -> [u8; 64] {
+    let mut wide = [0u8; 64];
+    blake3::Hasher::new_derive_key(context)
+        .update(bytes)
+        .finalize_xof()
+        .fill(&mut wide);
+    wide
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::setup::ZKParameters;
+    use crate::providers::SeededRng;
+    use crate::SingularityKey;
+    use alloc::sync::Arc;
+
+    fn holder(seed: u64, parameters: &ZKParameters) -> ZKIdentity {
+        ZKIdentity::with_parameters(&SingularityKey::generate().unwrap(), parameters, Arc::new(SeededRng::from_u64(seed)))
+    }
+
+    fn issue(issuer: &IssuerKey, identity: &ZKIdentity, attributes: &[&[u8]]) -> Credential {
+        let pending = identity.request_credential().unwrap();
+        let response = issuer.issue(pending.request(), attributes, &SeededRng::from_u64(9)).unwrap();
+
+        pending.finish(&issuer.parameters(), &response).unwrap()
+    }
+
+    #[test]
+    fn test_selective_disclosure() {
+        let identity_parameters = ZKParameters::generate(&SeededRng::from_u64(1)).unwrap();
+        let alice = holder(2, &identity_parameters);
+        let issuer = IssuerKey::generate(2, &SeededRng::from_u64(3));
+        let parameters = issuer.parameters();
+
+        let credential = issue(&issuer, &alice, &[b"verified-phone", b"expires:2026-12-31"]);
+        assert_eq!(credential.attributes()[0], b"verified-phone");
+
+        let presentation = alice.present_credential(&credential, &parameters, &[0], b"group-1", b"join").unwrap();
+        let presentation: Presentation = serde_json::from_slice(&serde_json::to_vec(&presentation).unwrap()).unwrap();
+        assert!(issuer.verify_presentation(&presentation, b"group-1", b"join").unwrap());
+        assert_eq!(presentation.attribute(0), Some(&b"verified-phone"[..]));
+        assert_eq!(presentation.attribute(1), None);
+
+        // Bound to its scope and context
+        assert!(!issuer.verify_presentation(&presentation, b"group-2", b"join").unwrap());
+        assert!(!issuer.verify_presentation(&presentation, b"group-1", b"leave").unwrap());
+
+        // Disclosed attributes cannot be swapped
+        let mut forged = presentation.clone();
+        forged.attributes[0] = Some(b"paid-subscriber".to_vec());
+        assert!(!issuer.verify_presentation(&forged, b"group-1", b"join").unwrap());
+
+        // Only the issuer's key verifies
+        let other = IssuerKey::generate(2, &SeededRng::from_u64(4));
+        assert!(!other.verify_presentation(&presentation, b"group-1", b"join").unwrap());
+
+        // Everything disclosed, or nothing
+        for disclose in [&[0, 1][..], &[]] {
+            let presentation = alice.present_credential(&credential, &parameters, disclose, b"group-1", b"").unwrap();
+            assert!(issuer.verify_presentation(&presentation, b"group-1", b"").unwrap());
+        }
+        assert!(alice.present_credential(&credential, &parameters, &[2], b"group-1", b"").is_err());
+    }
+
+    #[test]
+    fn test_tags_are_stable_per_scope_and_unlinkable() {
+        let identity_parameters = ZKParameters::generate(&SeededRng::from_u64(5)).unwrap();
+        let alice = holder(6, &identity_parameters);
+        let bob = holder(7, &identity_parameters);
+        let issuer = IssuerKey::generate(1, &SeededRng::from_u64(8));
+        let parameters = issuer.parameters();
+
+        let first = issue(&issuer, &alice, &[b"paid-subscriber"]);
+        let second = issue(&issuer, &alice, &[b"paid-subscriber"]);
+        let present = |identity: &ZKIdentity, credential: &Credential, scope: &[u8]| {
+            identity.present_credential(credential, &parameters, &[0], scope, b"").unwrap()
+        };
+
+        // Two presentations share nothing but the tag, even across credentials
+        let (a, b) = (present(&alice, &first, b"epoch-1"), present(&alice, &second, b"epoch-1"));
+        assert_eq!(a.tag(), b.tag());
+        assert_ne!(a.u, b.u);
+        assert_ne!(a.commitments, b.commitments);
+        assert_ne!(a.tag(), present(&alice, &first, b"epoch-2").tag());
+
+        // Another holder cannot present Alice's credential
+        let stolen = bob.present_credential(&first, &parameters, &[0], b"epoch-1", b"").unwrap();
+        assert!(!issuer.verify_presentation(&stolen, b"epoch-1", b"").unwrap());
+        assert!(issuer.verify_presentation(&a, b"epoch-1", b"").unwrap());
+    }
+
+    #[test]
+    fn test_issuance_is_checked_on_both_sides() {
+        let identity_parameters = ZKParameters::generate(&SeededRng::from_u64(10)).unwrap();
+        let alice = holder(11, &identity_parameters);
+        let issuer = IssuerKey::generate(1, &SeededRng::from_u64(12));
+
+        // A request whose ciphertext was swapped is refused
+        let pending = alice.request_credential().unwrap();
+        let mut request = pending.request().clone();
+        request.ciphertext.1 += RISTRETTO_BASEPOINT_POINT;
+        assert_eq!(issuer.issue(&request, &[b"verified-phone"], &SeededRng::from_u64(13)).unwrap_err(), BlackHoleError::InvalidCredential);
+        assert!(issuer.issue(pending.request(), &[], &SeededRng::from_u64(13)).is_err());
+
+        // A response under a key other than the published one is refused
+        let tagging = IssuerKey::generate(1, &SeededRng::from_u64(14));
+        let response = tagging.issue(pending.request(), &[b"verified-phone"], &SeededRng::from_u64(15)).unwrap();
+        assert_eq!(pending.finish(&issuer.parameters(), &response).unwrap_err(), BlackHoleError::InvalidCredential);
+    }
+
+    #[test]
+    fn test_credentials_are_held_by_issuer() {
+        let identity_parameters = ZKParameters::generate(&SeededRng::from_u64(16)).unwrap();
+        let mut alice = holder(17, &identity_parameters);
+        let phone = IssuerKey::generate(1, &SeededRng::from_u64(18));
+        let subscription = IssuerKey::generate(2, &SeededRng::from_u64(19));
+
+        let credential = issue(&phone, &alice, &[b"verified-phone"]);
+        assert!(alice.store_credential(subscription.parameters(), credential.clone()).is_err());
+        let phone_id = alice.store_credential(phone.parameters(), credential).unwrap();
+        assert_eq!(phone_id, phone.parameters().id());
+
+        let credential = issue(&subscription, &alice, &[b"paid-subscriber", b"expires:2026-12-31"]);
+        let subscription_id = alice.store_credential(subscription.parameters(), credential).unwrap();
+        assert_ne!(phone_id, subscription_id);
+        assert_eq!(alice.credential_issuers().count(), 2);
+
+        let (held, parameters) = alice.credential(&subscription_id).unwrap();
+        assert_eq!(held.attributes()[0], b"paid-subscriber");
+        assert_eq!(parameters, &subscription.parameters());
+
+        let presentation = alice.present_held_credential(&subscription_id, &[0], b"group-1", b"join").unwrap();
+        assert!(subscription.verify_presentation(&presentation, b"group-1", b"join").unwrap());
+
+        // A new credential from the same issuer replaces the old one
+        let renewed = issue(&phone, &alice, &[b"verified-phone"]);
+        alice.store_credential(phone.parameters(), renewed).unwrap();
+        assert_eq!(alice.credential_issuers().count(), 2);
+
+        assert!(alice.remove_credential(&phone_id).is_some());
+        assert!(alice.credential(&phone_id).is_none());
+        assert!(alice.present_held_credential(&phone_id, &[], b"group-1", b"").is_err());
+
+        // Wiping the identity drops its credentials
+        alice.zeroize();
+        assert_eq!(alice.credential_issuers().count(), 0);
+    }
+}
@@ -0,0 +1,672 @@
+//! 👥 Anonymous Group Membership
+//!
+//! Semaphore-style proofs of "I am one of these identities" that do not
+//! say which one. Members are the Poseidon commitments of their
+//! `ZKIdentity`, kept as the leaves of a fixed-depth Merkle tree whose
+//! nodes are two-element Poseidon hashes; a group is named by its root.
+//!
+//! ```text
+//! public:  root, nullifier, external nullifier e, signal hash m
+//! witness: s, Merkle path
+//! enforce: Poseidon(s) is a leaf under root
+//!          nullifier = Poseidon(e, s)
+//! ```
+//!
+//! The external nullifier names what is being signalled on (a poll, a
+//! channel, an epoch) and the signal is the message itself, so a proof
+//! cannot be moved to another topic or message. A member's nullifier is
+//! the same every time they signal on one external nullifier and
+//! unlinkable across external nullifiers, so a `NullifierLog` catches a
+//! member signalling twice without learning who they are.
+//!
+//! Membership proofs use their own keys (`MembershipParameters`), which
+//! come from a setup like the identity keys and can go through the same
+//! [`super::ceremony`].
+
+use alloc::collections::BTreeSet;
+use alloc::{vec, vec::Vec};
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::select::CondSelectGadget;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use serde::{Deserialize, Serialize};
+use crate::error::BlackHoleError;
+use crate::providers::{CryptoRng, RngAdapter};
+use super::ceremony::PowersOfTau;
+use super::circuit::{poseidon_config, poseidon_gadget, poseidon_hash_two, poseidon_two_gadget};
+use super::setup::{self, ZKParameters};
+use super::{field_to_bytes, ZKIdentity};
+
+/// Depth of the membership tree: groups of up to 2²⁰ members
+pub const TREE_DEPTH: usize = 20;
+
+/// Public inputs: root, nullifier, external nullifier, signal hash
+const PUBLIC_INPUTS: usize = 4;
+
+/// Membership proof format version
+const MEMBERSHIP_PROOF_VERSION: u32 = 1;
+
+/// Key derivation context for external nullifiers
+const EXTERNAL_NULLIFIER_CONTEXT: &str = "BlackHole 2025 ZK external nullifier";
+
+/// Key derivation context for signal hashes
+const SIGNAL_CONTEXT: &str = "BlackHole 2025 ZK signal";
+
+/// Identity commitments of a group, as a Poseidon Merkle tree
+///
+/// Leaves are filled left to right; removed members leave a zero leaf so
+/// the other members keep their positions.
+#[derive(Clone)]
+pub struct MembershipTree {
+    /// Nodes present at each level, leaves first; missing nodes are zero subtrees
+    layers: Vec<Vec<Fr>>,
+    /// Root of an empty subtree at each level
+    zeros: Vec<Fr>,
+    /// Poseidon parameters of the tree
+    poseidon: PoseidonConfig<Fr>,
+}
+
+/// A Semaphore-style membership proof
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MembershipProof {
+    /// Proof data (Groth16 format)
+    pub proof_data: Vec<u8>,
+
+    /// Root of the group the prover is a member of
+    pub root: Vec<u8>,
+
+    /// Nullifier of the prover for this external nullifier
+    pub nullifier: Vec<u8>,
+
+    /// External nullifier the proof signals on
+    pub external_nullifier: Vec<u8>,
+
+    /// Proof version
+    pub version: u32,
+
+    /// ID of the verifying key the proof was made for
+    pub key_id: Vec<u8>,
+}
+
+/// Proving key (which embeds its verifying key) for membership proofs
+#[derive(Clone)]
+pub struct MembershipParameters(ZKParameters);
+
+/// Membership proof verifier
+pub struct MembershipVerifier {
+    /// Verifying key, prepared for pairing checks
+    verifying_key: PreparedVerifyingKey<Bn254>,
+
+    /// ID of the verifying key
+    key_id: [u8; 32],
+}
+
+/// Nullifiers already seen, per external nullifier
+#[derive(Default)]
+pub struct NullifierLog {
+    seen: BTreeSet<(Vec<u8>, Vec<u8>)>,
+}
+
+/// Public inputs of the membership circuit
+#[derive(Clone, Copy)]
+struct Statement {
+    root: Fr,
+    nullifier: Fr,
+    external_nullifier: Fr,
+    signal_hash: Fr,
+}
+
+/// Statement: a member of the tree signals on the external nullifier
+#[derive(Clone)]
+pub struct MembershipCircuit<'a> {
+    config: &'a PoseidonConfig<Fr>,
+    secret: Option<Fr>,
+    index: Option<usize>,
+    siblings: Option<Vec<Fr>>,
+    statement: Option<Statement>,
+}
+
+impl MembershipTree {
+    /// An empty group
+    pub fn new() -> Self {
+        let poseidon = poseidon_config();
+
+        let mut zeros = Vec::with_capacity(TREE_DEPTH + 1);
+        zeros.push(Fr::zero());
+        for level in 0..TREE_DEPTH {
+            zeros.push(poseidon_hash_two(&poseidon, zeros[level], zeros[level]));
+        }
+
+        MembershipTree {
+            layers: vec![Vec::new(); TREE_DEPTH + 1],
+            zeros,
+            poseidon,
+        }
+    }
+
+    /// Number of leaves used, including removed members
+    pub fn len(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    /// Whether no member was ever added
+    pub fn is_empty(&self) -> bool {
+        self.layers[0].is_empty()
+    }
+
+    /// Add an identity commitment, returning its leaf index
+    pub fn insert(&mut self, commitment: &[u8]) -> Result<usize, BlackHoleError> {
+        let leaf = decode_field(commitment, "identity commitment")?;
+        if self.len() == 1 << TREE_DEPTH {
+            return Err(BlackHoleError::InvalidState("membership tree is full"));
+        }
+
+        let index = self.len();
+        self.layers[0].push(leaf);
+        self.update(index);
+
+        Ok(index)
+    }
+
+    /// Remove the member at `index`
+    pub fn remove(&mut self, index: usize) -> Result<(), BlackHoleError> {
+        let leaf = self.layers[0].get_mut(index)
+            .ok_or(BlackHoleError::InvalidState("no member at that index"))?;
+        *leaf = Fr::zero();
+        self.update(index);
+
+        Ok(())
+    }
+
+    /// Leaf index of an identity commitment
+    pub fn index_of(&self, commitment: &[u8]) -> Option<usize> {
+        let leaf = decode_field(commitment, "identity commitment").ok()?;
+        self.position(&leaf)
+    }
+
+    /// Root naming the current members (32 bytes, compressed)
+    pub fn root(&self) -> Vec<u8> {
+        field_to_bytes(&self.root_field())
+    }
+
+    pub(super) fn position(&self, leaf: &Fr) -> Option<usize> {
+        if leaf.is_zero() {
+            return None;
+        }
+        self.layers[0].iter().position(|member| member == leaf)
+    }
+
+    /// Siblings from the leaf at `index` up to the root
+    pub(super) fn path(&self, index: usize) -> Vec<Fr> {
+        (0..TREE_DEPTH).map(|level| self.node(level, (index >> level) ^ 1)).collect()
+    }
+
+    pub(super) fn root_field(&self) -> Fr {
+        self.node(TREE_DEPTH, 0)
+    }
+
+    fn node(&self, level: usize, index: usize) -> Fr {
+        self.layers[level].get(index).copied().unwrap_or(self.zeros[level])
+    }
+
+    /// Recompute the nodes above the leaf at `index`
+    fn update(&mut self, index: usize) {
+        for level in 0..TREE_DEPTH {
+            let parent = index >> (level + 1);
+            let node = poseidon_hash_two(
+                &self.poseidon,
+                self.node(level, parent << 1),
+                self.node(level, (parent << 1) | 1),
+            );
+
+            let layer = &mut self.layers[level + 1];
+            if parent == layer.len() {
+                layer.push(node);
+            } else {
+                layer[parent] = node;
+            }
+        }
+    }
+}
+
+impl Default for MembershipTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MembershipParameters {
+    /// Run a setup for the membership circuit, discarding its randomness afterwards
+    pub fn generate(rng: &dyn CryptoRng) -> Result<MembershipParameters, BlackHoleError> {
+        ZKParameters::setup(MembershipCircuit::blank(&poseidon_config()), rng).map(MembershipParameters)
+    }
+
+    /// Initial ceremony parameters for the membership circuit, derived from the
+    /// output of a powers-of-tau ceremony
+    pub fn from_powers_of_tau(powers: &PowersOfTau) -> Result<MembershipParameters, BlackHoleError> {
+        ZKParameters::specialize(MembershipCircuit::blank(&poseidon_config()), powers).map(MembershipParameters)
+    }
+
+    /// Load parameters, checking they are for the membership circuit
+    pub fn from_bytes(bytes: &[u8]) -> Result<MembershipParameters, BlackHoleError> {
+        ZKParameters::from_bytes(bytes)?.try_into()
+    }
+
+    /// Compressed encoding of the proving key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    /// Compressed encoding of the verifying key, to publish to verifiers
+    pub fn verifying_key(&self) -> Vec<u8> {
+        self.0.verifying_key()
+    }
+
+    /// ID of the verifying key (see [`setup::key_id`])
+    pub fn key_id(&self) -> [u8; 32] {
+        self.0.key_id()
+    }
+
+    /// A verifier for proofs made with these parameters
+    pub fn verifier(&self) -> MembershipVerifier {
+        MembershipVerifier {
+            verifying_key: ark_groth16::prepare_verifying_key(&self.0.proving_key().vk),
+            key_id: self.key_id(),
+        }
+    }
+}
+
+impl TryFrom<ZKParameters> for MembershipParameters {
+    type Error = BlackHoleError;
+
+    /// Accept parameters from a ceremony, checking their circuit shape
+    fn try_from(parameters: ZKParameters) -> Result<Self, Self::Error> {
+        if parameters.proving_key().vk.gamma_abc_g1.len() != PUBLIC_INPUTS + 1 {
+            return Err(BlackHoleError::Malformed("membership proving key"));
+        }
+
+        Ok(MembershipParameters(parameters))
+    }
+}
+
+impl From<MembershipParameters> for ZKParameters {
+    fn from(parameters: MembershipParameters) -> Self {
+        parameters.0
+    }
+}
+
+impl ZKIdentity {
+    /// Prove membership of a group and signal on an external nullifier
+    ///
+    /// Proves: "my commitment is in the group with this root, and this is
+    /// my nullifier for `external_nullifier`", bound to `signal`, without
+    /// revealing which member is signalling.
+    pub fn prove_membership(
+        &self,
+        parameters: &MembershipParameters,
+        group: &MembershipTree,
+        external_nullifier: &[u8],
+        signal: &[u8],
+    ) -> Result<MembershipProof, BlackHoleError> {
+        log::info!("👥 Generating membership proof...");
+
+        let index = group.position(&self.commitment)
+            .ok_or(BlackHoleError::InvalidState("not a member of the group"))?;
+
+        let external_nullifier = hash_to_field(EXTERNAL_NULLIFIER_CONTEXT, external_nullifier);
+        let statement = Statement {
+            root: group.root_field(),
+            nullifier: poseidon_hash_two(&self.poseidon, external_nullifier, self.secret),
+            external_nullifier,
+            signal_hash: hash_to_field(SIGNAL_CONTEXT, signal),
+        };
+
+        let circuit = MembershipCircuit::new(&self.poseidon, self.secret, index, group.path(index), statement);
+        let proof = Groth16::<Bn254>::prove(parameters.0.proving_key(), circuit, &mut RngAdapter(self.rng.as_ref()))
+            .map_err(|_| BlackHoleError::Proof)?;
+
+        let mut proof_data = Vec::new();
+        proof.serialize_compressed(&mut proof_data)
+            .map_err(BlackHoleError::serialization)?;
+
+        log::info!("✅ Membership proof generated ({} bytes)", proof_data.len());
+
+        Ok(MembershipProof {
+            proof_data,
+            root: field_to_bytes(&statement.root),
+            nullifier: field_to_bytes(&statement.nullifier),
+            external_nullifier: field_to_bytes(&statement.external_nullifier),
+            version: MEMBERSHIP_PROOF_VERSION,
+            key_id: parameters.key_id().to_vec(),
+        })
+    }
+
+    /// The nullifier this identity reveals when signalling on `external_nullifier`
+    pub fn nullifier(&self, external_nullifier: &[u8]) -> Vec<u8> {
+        let external_nullifier = hash_to_field(EXTERNAL_NULLIFIER_CONTEXT, external_nullifier);
+        field_to_bytes(&poseidon_hash_two(&self.poseidon, external_nullifier, self.secret))
+    }
+}
+
+impl MembershipVerifier {
+    /// Load a verifier from an encoded verifying key
+    ///
+    /// Check its [`MembershipVerifier::key_id`] against a pinned ID before
+    /// trusting its proofs.
+    pub fn from_verifying_key(bytes: &[u8]) -> Result<MembershipVerifier, BlackHoleError> {
+        let verifying_key = setup::decode_verifying_key(bytes)?;
+        if verifying_key.gamma_abc_g1.len() != PUBLIC_INPUTS + 1 {
+            return Err(BlackHoleError::Malformed("membership verifying key"));
+        }
+
+        Ok(MembershipVerifier {
+            verifying_key: ark_groth16::prepare_verifying_key(&verifying_key),
+            key_id: setup::key_id(bytes),
+        })
+    }
+
+    /// ID of the verifying key
+    pub fn key_id(&self) -> [u8; 32] {
+        self.key_id
+    }
+
+    /// Verify a membership proof for a group root, external nullifier and signal
+    ///
+    /// Returns `Ok(false)` for a well-formed proof that does not verify or
+    /// is for another group, topic or signal, and an error when the proof
+    /// cannot be decoded or was made for a different verifying key. Record
+    /// verified proofs in a [`NullifierLog`] to catch double signals.
+    pub fn verify(&self, proof: &MembershipProof, root: &[u8], external_nullifier: &[u8], signal: &[u8]) -> Result<bool, BlackHoleError> {
+        log::info!("🔍 Verifying membership proof...");
+
+        if proof.version != MEMBERSHIP_PROOF_VERSION {
+            return Err(BlackHoleError::Malformed("proof version"));
+        }
+
+        if proof.key_id != self.key_id {
+            return Err(BlackHoleError::UntrustedParameters);
+        }
+
+        let external_nullifier = hash_to_field(EXTERNAL_NULLIFIER_CONTEXT, external_nullifier);
+        if proof.root != root || proof.external_nullifier != field_to_bytes(&external_nullifier) {
+            return Ok(false);
+        }
+
+        let statement = Statement {
+            root: decode_field(root, "group root")?,
+            nullifier: decode_field(&proof.nullifier, "nullifier")?,
+            external_nullifier,
+            signal_hash: hash_to_field(SIGNAL_CONTEXT, signal),
+        };
+        let groth16_proof = Proof::<Bn254>::deserialize_compressed(&proof.proof_data[..])
+            .map_err(|_| BlackHoleError::Malformed("proof"))?;
+
+        let valid = Groth16::<Bn254>::verify_with_processed_vk(&self.verifying_key, &statement.inputs(), &groth16_proof)
+            .map_err(|_| BlackHoleError::Malformed("proof"))?;
+
+        log::info!("✅ Membership proof verification: {}", valid);
+
+        Ok(valid)
+    }
+}
+
+impl NullifierLog {
+    /// An empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the proof's nullifier was already recorded
+    pub fn contains(&self, proof: &MembershipProof) -> bool {
+        self.seen.contains(&(proof.external_nullifier.clone(), proof.nullifier.clone()))
+    }
+
+    /// Record a verified proof's nullifier
+    ///
+    /// Fails with `DuplicateNullifier` when the same member already
+    /// signalled on this external nullifier.
+    pub fn record(&mut self, proof: &MembershipProof) -> Result<(), BlackHoleError> {
+        if !self.seen.insert((proof.external_nullifier.clone(), proof.nullifier.clone())) {
+            return Err(BlackHoleError::DuplicateNullifier);
+        }
+
+        Ok(())
+    }
+}
+
+impl Statement {
+    fn inputs(&self) -> [Fr; PUBLIC_INPUTS] {
+        [self.root, self.nullifier, self.external_nullifier, self.signal_hash]
+    }
+}
+
+impl<'a> MembershipCircuit<'a> {
+    /// The circuit shape without assignments, for key generation
+    pub fn blank(config: &'a PoseidonConfig<Fr>) -> Self {
+        MembershipCircuit { config, secret: None, index: None, siblings: None, statement: None }
+    }
+
+    fn new(config: &'a PoseidonConfig<Fr>, secret: Fr, index: usize, siblings: Vec<Fr>, statement: Statement) -> Self {
+        MembershipCircuit {
+            config,
+            secret: Some(secret),
+            index: Some(index),
+            siblings: Some(siblings),
+            statement: Some(statement),
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for MembershipCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let statement = self.statement.map(|statement| statement.inputs());
+        let inputs = inputs_gadget(cs.clone(), statement.as_ref().map(|inputs| &inputs[..]), PUBLIC_INPUTS)?;
+        let [root, nullifier, external_nullifier, signal_hash] = &inputs[..] else {
+            unreachable!("the statement has {PUBLIC_INPUTS} inputs");
+        };
+        let secret = FpVar::new_witness(cs.clone(), || {
+            self.secret.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let leaf = poseidon_gadget(self.config, &secret)?;
+        let node = root_gadget(cs, self.config, leaf, self.index, self.siblings.as_deref())?;
+        node.enforce_equal(root)?;
+
+        poseidon_two_gadget(self.config, external_nullifier, &secret)?.enforce_equal(nullifier)?;
+
+        // An unconstrained input could be swapped after proving; squaring
+        // the signal hash ties it to the proof
+        let _ = signal_hash.square()?;
+
+        Ok(())
+    }
+}
+
+/// Allocate `count` public inputs, assigned from `values` when proving
+pub(super) fn inputs_gadget(cs: ConstraintSystemRef<Fr>, values: Option<&[Fr]>, count: usize) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+    (0..count)
+        .map(|i| {
+            FpVar::new_input(cs.clone(), || {
+                values.map(|values| values[i]).ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect()
+}
+
+/// In-circuit root of the tree above `leaf`, from its index and siblings
+///
+/// The index bits say whether the node is a right child at each level.
+pub(super) fn root_gadget(
+    cs: ConstraintSystemRef<Fr>,
+    config: &PoseidonConfig<Fr>,
+    leaf: FpVar<Fr>,
+    index: Option<usize>,
+    siblings: Option<&[Fr]>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut node = leaf;
+    for level in 0..TREE_DEPTH {
+        let is_right = Boolean::new_witness(cs.clone(), || {
+            index.map(|index| (index >> level) & 1 == 1).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let sibling = FpVar::new_witness(cs.clone(), || {
+            siblings.map(|siblings| siblings[level]).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let left = FpVar::conditionally_select(&is_right, &sibling, &node)?;
+        let right = FpVar::conditionally_select(&is_right, &node, &sibling)?;
+        node = poseidon_two_gadget(config, &left, &right)?;
+    }
+
+    Ok(node)
+}
+
+/// Hash arbitrary bytes to a uniform field element
+pub(super) fn hash_to_field(context: &str, bytes: &[u8]) -> Fr {
+    let mut wide = [0u8; 64];
+    blake3::Hasher::new_derive_key(context)
+        .update(bytes)
+        .finalize_xof()
+        .fill(&mut wide);
+
+    Fr::from_le_bytes_mod_order(&wide)
+}
+
+pub(super) fn decode_field(bytes: &[u8], what: &'static str) -> Result<Fr, BlackHoleError> {
+    Fr::deserialize_compressed(bytes).map_err(|_| BlackHoleError::Malformed(what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::SeededRng;
+    use crate::SingularityKey;
+    use alloc::sync::Arc;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn member(seed: u64, parameters: &ZKParameters) -> ZKIdentity {
+        ZKIdentity::with_parameters(&SingularityKey::generate().unwrap(), parameters, Arc::new(SeededRng::from_u64(seed)))
+    }
+
+    #[test]
+    fn test_tree_paths_reach_the_root() {
+        let config = poseidon_config();
+        let mut tree = MembershipTree::new();
+        let empty = tree.root();
+
+        let leaves: Vec<Fr> = (1..=5u64).map(Fr::from).collect();
+        for leaf in &leaves {
+            tree.insert(&field_to_bytes(leaf)).unwrap();
+        }
+        assert_ne!(tree.root(), empty);
+        assert_eq!(tree.index_of(&field_to_bytes(&leaves[3])), Some(3));
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let root = tree.path(index).iter().enumerate().fold(*leaf, |node, (level, sibling)| {
+                if (index >> level) & 1 == 1 {
+                    poseidon_hash_two(&config, *sibling, node)
+                } else {
+                    poseidon_hash_two(&config, node, *sibling)
+                }
+            });
+            assert_eq!(field_to_bytes(&root), tree.root());
+        }
+
+        // Removing a member changes the root but not the others' positions
+        let before = tree.root();
+        tree.remove(1).unwrap();
+        assert_ne!(tree.root(), before);
+        assert_eq!(tree.index_of(&field_to_bytes(&leaves[1])), None);
+        assert_eq!(tree.index_of(&field_to_bytes(&leaves[4])), Some(4));
+        assert!(tree.remove(5).is_err());
+    }
+
+    #[test]
+    fn test_circuit_rejects_non_members() {
+        let config = poseidon_config();
+        let secret = Fr::from(42u64);
+        let mut tree = MembershipTree::new();
+        tree.insert(&field_to_bytes(&Fr::from(7u64))).unwrap();
+        tree.insert(&field_to_bytes(&super::super::circuit::poseidon_hash(&config, secret))).unwrap();
+
+        let external_nullifier = Fr::from(9u64);
+        let statement = Statement {
+            root: tree.root_field(),
+            nullifier: poseidon_hash_two(&config, external_nullifier, secret),
+            external_nullifier,
+            signal_hash: Fr::from(3u64),
+        };
+
+        let cs = ConstraintSystem::new_ref();
+        MembershipCircuit::new(&config, secret, 1, tree.path(1), statement).generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // Someone else's leaf
+        let cs = ConstraintSystem::new_ref();
+        MembershipCircuit::new(&config, secret, 0, tree.path(0), statement).generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // A nullifier for another topic
+        let cs = ConstraintSystem::new_ref();
+        let shifted = Statement { external_nullifier: Fr::from(10u64), ..statement };
+        MembershipCircuit::new(&config, secret, 1, tree.path(1), shifted).generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_membership_proofs_are_anonymous_and_single_use() {
+        let identity_parameters = ZKParameters::generate(&SeededRng::from_u64(1)).unwrap();
+        let parameters = MembershipParameters::generate(&SeededRng::from_u64(2)).unwrap();
+        let verifier = MembershipVerifier::from_verifying_key(&parameters.verifying_key()).unwrap();
+
+        let alice = member(3, &identity_parameters);
+        let bob = member(4, &identity_parameters);
+        let mallory = member(5, &identity_parameters);
+
+        let mut group = MembershipTree::new();
+        group.insert(&alice.get_commitment()).unwrap();
+        group.insert(&bob.get_commitment()).unwrap();
+        let root = group.root();
+
+        let proof = alice.prove_membership(&parameters, &group, b"poll-1", b"yes").unwrap();
+        assert_eq!(proof.proof_data.len(), 128);
+        assert_eq!(proof.nullifier, alice.nullifier(b"poll-1"));
+        assert!(verifier.verify(&proof, &root, b"poll-1", b"yes").unwrap());
+
+        // Bound to the signal, topic and group
+        assert!(!verifier.verify(&proof, &root, b"poll-1", b"no").unwrap());
+        assert!(!verifier.verify(&proof, &root, b"poll-2", b"yes").unwrap());
+        let mut other = group.clone();
+        other.insert(&mallory.get_commitment()).unwrap();
+        assert!(!verifier.verify(&proof, &other.root(), b"poll-1", b"yes").unwrap());
+
+        // Non-members cannot prove, and forged nullifiers do not verify
+        assert!(mallory.prove_membership(&parameters, &group, b"poll-1", b"yes").is_err());
+        let forged = MembershipProof { nullifier: mallory.nullifier(b"poll-1"), ..proof.clone() };
+        assert!(!verifier.verify(&forged, &root, b"poll-1", b"yes").unwrap());
+
+        // A second signal on the same topic reveals the same nullifier
+        let mut log = NullifierLog::new();
+        log.record(&proof).unwrap();
+        let again = alice.prove_membership(&parameters, &group, b"poll-1", b"no").unwrap();
+        assert!(verifier.verify(&again, &root, b"poll-1", b"no").unwrap());
+        assert!(log.contains(&again));
+        assert_eq!(log.record(&again), Err(BlackHoleError::DuplicateNullifier));
+
+        // Other members and other topics are unaffected
+        log.record(&bob.prove_membership(&parameters, &group, b"poll-1", b"no").unwrap()).unwrap();
+        log.record(&alice.prove_membership(&parameters, &group, b"poll-2", b"yes").unwrap()).unwrap();
+
+        // Identity keys are not membership keys
+        assert!(MembershipVerifier::from_verifying_key(&identity_parameters.verifying_key()).is_err());
+        assert!(MembershipParameters::try_from(identity_parameters).is_err());
+    }
+}
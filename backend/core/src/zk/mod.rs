@@ -10,40 +10,118 @@
 //!
 //! ## Groth16 Protocol
 //!
-//! The Groth16 protocol provides succinct proofs (~200 bytes) with fast
-//! verification (~2ms on modern hardware).
+//! The Groth16 protocol provides succinct proofs (128 bytes compressed on
+//! BN254) verified with three pairings:
 //!
-//! ## Circuit Construction
+//! ```text
+//! e(A, B) = e(α, β) · e(Σ xᵢ·ICᵢ, γ) · e(C, δ)
+//! ```
 //!
-//! Statements are converted to arithmetic circuits over finite fields:
-//! - Constraint: A · B = C
-//! - Where A, B, C are linear combinations of witness variables
+//! ## Identity Statement
+//!
+//! A `ZKIdentity` derives a field secret `s` from the identity private key
+//! and publishes the commitment `c = Poseidon(s)`. A proof shows knowledge
+//! of `s` for `c` (see [`circuit`]) without revealing `s` or the key.
+//!
+//! Proving and verifying keys come from a circuit-specific setup (see
+//! [`setup`]); whoever ran the setup could forge proofs, so third parties
+//! must only trust verifying keys from a setup they trust, such as a
+//! multi-party [`ceremony`]. Proofs name the
+//! key they were made for, and verifiers refuse proofs for any other key.
+//!
+//! ## Group Membership
+//!
+//! The same commitments can be collected into a Merkle tree, and an
+//! identity can prove it is one of the leaves without saying which one,
+//! revealing only a nullifier that repeats if it signals twice on the same
+//! topic (see [`membership`]). Rate-limiting nullifiers ([`rln`]) build on
+//! this to cap anonymous messages per epoch: exceeding the cap reveals
+//! the sender's identity secret.
+//!
+//! ## Confidential Amounts
+//!
+//! Amounts are hidden in Pedersen commitments on Ristretto, with
+//! Bulletproofs range proofs that the committed values are in range (see
+//! [`range`]). These need no trusted setup.
+//!
+//! ## Sigma Protocols
+//!
+//! Knowledge of discrete logarithms, such as owning a key or two keys
+//! sharing a secret, is shown with Schnorr-style proofs that compose with
+//! AND and OR (see [`sigma`]). On top of them, servers issue anonymous
+//! [`credential`]s that identities present without being linked, for
+//! group admission and rate limits.
 
-use wasm_bindgen::prelude::*;
+pub mod ceremony;
+pub mod circuit;
+pub mod credential;
+pub mod membership;
+pub mod range;
+pub mod rln;
+pub mod setup;
+pub mod sigma;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::error::BlackHoleError;
+use crate::providers::{CryptoRng, OsRandom, RngAdapter};
 use crate::SingularityKey;
+use circuit::{poseidon_config, poseidon_hash, IdentityCircuit};
+use credential::CredentialStore;
+use setup::ZKParameters;
+
+/// Proof format version: Groth16 over BN254 with a Poseidon commitment
+const PROOF_VERSION: u32 = 2;
+
+/// Key derivation context for the identity secret
+const SECRET_CONTEXT: &str = "BlackHole 2025 ZK identity secret";
 
 /// A zero-knowledge identity
 ///
 /// Allows proving ownership of an identity without revealing the private key.
-#[wasm_bindgen]
 #[derive(Zeroize, ZeroizeOnDrop)]
 pub struct ZKIdentity {
-    /// Secret key (witness)
-    secret_key: [u8; 32],
+    /// Secret field element (witness)
+    secret: Fr,
     
-    /// Public key (public input)
+    /// Ed25519 public key of the identity
     #[zeroize(skip)]
     public_key: [u8; 32],
     
-    /// Proving key (generated during setup)
+    /// Poseidon commitment to the secret (public input)
+    #[zeroize(skip)]
+    commitment: Fr,
+    
+    /// Poseidon parameters of the circuit
+    #[zeroize(skip)]
+    poseidon: PoseidonConfig<Fr>,
+    
+    /// Proving and verifying keys from the setup
     #[zeroize(skip)]
-    proving_key: ZKProvingKey,
+    parameters: ZKParameters,
+    
+    /// ID of the verifying key
+    #[zeroize(skip)]
+    key_id: [u8; 32],
+    
+    /// Randomness for setup and proof blinding
+    #[zeroize(skip)]
+    rng: Arc<dyn CryptoRng>,
+    
+    /// Anonymous credentials issued to this identity
+    credentials: CredentialStore,
 }
 
 /// A zero-knowledge proof
-#[wasm_bindgen]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ZKProof {
     /// Proof data (Groth16 format)
     pub proof_data: Vec<u8>,
@@ -56,321 +134,193 @@ pub struct ZKProof {
     
     /// Proof version
     pub version: u32,
+    
+    /// ID of the verifying key the proof was made for
+    pub key_id: Vec<u8>,
 }
 
 /// Zero-knowledge proof verifier
-#[wasm_bindgen]
 pub struct ZKVerifier {
-    /// Verifying key
-    verifying_key: ZKVerifyingKey,
-}
-
-/// Proving key for zk-SNARKs
-#[derive(Clone, Zeroize, ZeroizeOnDrop)]
-struct ZKProvingKey {
-    /// Key data
-    data: Vec<u8>,
-}
-
-/// Verifying key for zk-SNARKs
-#[derive(Clone, Zeroize, ZeroizeOnDrop)]
-struct ZKVerifyingKey {
-    /// Key data
-    data: Vec<u8>,
-}
-
-/// Represents an arithmetic circuit for ZK proofs
-struct ZKCircuit {
-    /// Number of constraints
-    num_constraints: usize,
-    
-    /// Number of variables
-    num_variables: usize,
+    /// Verifying key, prepared for pairing checks
+    verifying_key: PreparedVerifyingKey<Bn254>,
     
-    /// Constraint matrices (A, B, C)
-    a_matrix: Vec<Vec<(usize, Vec<u8>)>>,
-    b_matrix: Vec<Vec<(usize, Vec<u8>)>>,
-    c_matrix: Vec<Vec<(usize, Vec<u8>)>>,
+    /// ID of the verifying key
+    key_id: [u8; 32],
 }
 
-#[wasm_bindgen]
 impl ZKIdentity {
     /// Create a new ZK identity
-    #[wasm_bindgen(constructor)]
-    pub fn new(identity: &SingularityKey) -> Result<ZKIdentity, JsValue> {
-        log::info!("🎭 Initializing ZK identity...");
+    pub fn new(identity: &SingularityKey) -> Result<ZKIdentity, BlackHoleError> {
+        Self::with_rng(identity, Arc::new(OsRandom))
+    }
+    
+    /// Create a new ZK identity, running a local setup and proving against `rng`
+    ///
+    /// Only verifiers that trust this identity's own setup can check its
+    /// proofs; use [`ZKIdentity::with_parameters`] for shared parameters.
+    pub fn with_rng(identity: &SingularityKey, rng: Arc<dyn CryptoRng>) -> Result<ZKIdentity, BlackHoleError> {
+        let parameters = ZKParameters::generate(rng.as_ref())?;
         
-        // Generate proving key
-        let proving_key = Self::generate_proving_key()?;
+        Ok(Self::with_parameters(identity, &parameters, rng))
+    }
+    
+    /// Create a new ZK identity proving with existing setup parameters
+    pub fn with_parameters(identity: &SingularityKey, parameters: &ZKParameters, rng: Arc<dyn CryptoRng>) -> ZKIdentity {
+        log::info!("🎭 Initializing ZK identity...");
         
-        let mut secret_key = [0u8; 32];
-        secret_key.copy_from_slice(&identity.private);
+        let poseidon = poseidon_config();
+        let secret = derive_secret(&identity.private);
+        let commitment = poseidon_hash(&poseidon, secret);
         
         let mut public_key = [0u8; 32];
         public_key.copy_from_slice(&identity.public);
         
         log::info!("✅ ZK identity initialized");
         
-        Ok(ZKIdentity {
-            secret_key,
+        ZKIdentity {
+            secret,
             public_key,
-            proving_key,
-        })
+            commitment,
+            poseidon,
+            key_id: parameters.key_id(),
+            parameters: parameters.clone(),
+            rng,
+            credentials: CredentialStore::default(),
+        }
     }
     
     /// Generate a zero-knowledge proof of identity
     ///
-    /// Proves: "I know the private key corresponding to this public key"
-    /// without revealing the private key.
-    #[wasm_bindgen]
-    pub fn prove(&self) -> Result<ZKProof, JsValue> {
+    /// Proves: "I know the secret behind this identity commitment"
+    /// without revealing the secret or the private key.
+    pub fn prove(&self) -> Result<ZKProof, BlackHoleError> {
         log::info!("🔐 Generating ZK proof of identity...");
         
-        // Construct the circuit
-        let circuit = self.build_identity_circuit()?;
-        
-        // Generate witness
-        let witness = self.generate_witness()?;
-        
-        // Create proof using Groth16
-        let proof_data = self.groth16_prove(&circuit, &witness)?;
+        let circuit = IdentityCircuit::new(&self.poseidon, self.secret, self.commitment);
+        let proof = Groth16::<Bn254>::prove(self.parameters.proving_key(), circuit, &mut RngAdapter(self.rng.as_ref()))
+            .map_err(|_| BlackHoleError::Proof)?;
         
-        // Public inputs (just the public key hash)
-        let public_inputs = blake3::hash(&self.public_key).as_bytes().to_vec();
+        let mut proof_data = Vec::new();
+        proof.serialize_compressed(&mut proof_data)
+            .map_err(BlackHoleError::serialization)?;
         
         log::info!("✅ ZK proof generated ({} bytes)", proof_data.len());
         
         Ok(ZKProof {
             proof_data,
-            public_inputs,
+            public_inputs: self.get_commitment(),
             timestamp: 0, // Will be set by caller
-            version: 1,
+            version: PROOF_VERSION,
+            key_id: self.key_id.to_vec(),
         })
     }
     
-    /// Verify a zero-knowledge proof
-    #[wasm_bindgen]
-    pub fn verify(&self, proof: &ZKProof) -> Result<bool, JsValue> {
-        log::info!("🔍 Verifying ZK proof...");
-        
-        // Verify proof format
-        if proof.proof_data.len() < 192 {
-            return Err(JsValue::from_str("Proof too short"));
-        }
-        
-        // Verify public inputs match
-        let expected_public = blake3::hash(&self.public_key).as_bytes().to_vec();
-        if proof.public_inputs != expected_public {
-            return Ok(false);
-        }
-        
-        // Perform Groth16 verification
-        let valid = self.groth16_verify(proof)?;
-        
-        log::info!("✅ ZK proof verification: {}", valid);
-        
-        Ok(valid)
-    }
-    
-    /// Get the public key
-    #[wasm_bindgen]
-    pub fn get_public_key(&self) -> Vec<u8> {
-        self.public_key.to_vec()
+    /// Verify a zero-knowledge proof for this identity's commitment
+    pub fn verify(&self, proof: &ZKProof) -> Result<bool, BlackHoleError> {
+        self.verifier().verify_proof(proof, &self.get_commitment())
     }
     
-    fn build_identity_circuit(&self) -> Result<ZKCircuit, JsValue> {
-        // Build a circuit that proves knowledge of private key
-        // Circuit: public_key = hash(private_key)
-        
-        let num_constraints = 256; // Simplified
-        let num_variables = 512;
-        
-        // Initialize constraint matrices (simplified representation)
-        let a_matrix = vec![Vec::new(); num_constraints];
-        let b_matrix = vec![Vec::new(); num_constraints];
-        let c_matrix = vec![Vec::new(); num_constraints];
-        
-        Ok(ZKCircuit {
-            num_constraints,
-            num_variables,
-            a_matrix,
-            b_matrix,
-            c_matrix,
-        })
-    }
-    
-    fn generate_witness(&self) -> Result<Vec<u8>, JsValue> {
-        // Witness contains the secret key and intermediate values
-        let mut witness = Vec::with_capacity(1024);
-        
-        // Add secret key to witness
-        witness.extend_from_slice(&self.secret_key);
-        
-        // Add public key to witness
-        witness.extend_from_slice(&self.public_key);
-        
-        // Pad to expected size
-        witness.resize(1024, 0);
-        
-        Ok(witness)
+    /// A verifier for proofs made with this identity's keys
+    pub fn verifier(&self) -> ZKVerifier {
+        ZKVerifier {
+            verifying_key: ark_groth16::prepare_verifying_key(&self.parameters.proving_key().vk),
+            key_id: self.key_id,
+        }
     }
     
-    fn groth16_prove(
-        &self,
-        _circuit: &ZKCircuit,
-        witness: &[u8],
-    ) -> Result<Vec<u8>, JsValue> {
-        // In production, use ark-groth16
-        // For now, simulate with a hash-based proof
-        
-        let mut proof = Vec::with_capacity(192);
-        
-        // Proof components (A, B, C in G1/G2)
-        let a = blake3::hash(&[&self.proving_key.data[..], witness, b"A"].concat());
-        let b = blake3::hash(&[&self.proving_key.data[..], witness, b"B"].concat());
-        let c = blake3::hash(&[&self.proving_key.data[..], witness, b"C"].concat());
-        
-        proof.extend_from_slice(a.as_bytes());
-        proof.extend_from_slice(b.as_bytes());
-        proof.extend_from_slice(c.as_bytes());
-        
-        // Add some padding to reach expected size
-        proof.resize(192, 0);
-        
-        Ok(proof)
+    /// Encoded verifying key, for verifiers that trust this identity's setup
+    pub fn verifying_key(&self) -> Vec<u8> {
+        self.parameters.verifying_key()
     }
     
-    fn groth16_verify(&self, proof: &ZKProof) -> Result<bool, JsValue> {
-        // In production, use ark-groth16 verification
-        // For now, simulate verification
-        
-        // Basic format check
-        if proof.proof_data.len() != 192 {
-            return Ok(false);
-        }
-        
-        // Verify proof components are non-zero
-        let has_non_zero = proof.proof_data.iter().any(|&b| b != 0);
-        
-        Ok(has_non_zero)
+    /// Get the public key
+    pub fn get_public_key(&self) -> Vec<u8> {
+        self.public_key.to_vec()
     }
     
-    fn generate_proving_key() -> Result<ZKProvingKey, JsValue> {
-        // In production, this would be the trusted setup output
-        // For now, generate deterministic key material
-        
-        let mut key_data = vec![0u8; 4096];
-        
-        // Deterministic generation for reproducibility
-        for i in 0..key_data.len() {
-            key_data[i] = ((i * 7 + 13) % 256) as u8;
-        }
-        
-        Ok(ZKProvingKey { data: key_data })
+    /// Get the Poseidon identity commitment (32 bytes, compressed)
+    pub fn get_commitment(&self) -> Vec<u8> {
+        field_to_bytes(&self.commitment)
     }
 }
 
-#[wasm_bindgen]
 impl ZKVerifier {
-    /// Create a new ZK verifier
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> Result<ZKVerifier, JsValue> {
-        let verifying_key = Self::generate_verifying_key()?;
-        
-        Ok(ZKVerifier { verifying_key })
-    }
-    
-    /// Verify a proof without knowing the identity
-    #[wasm_bindgen]
-    pub fn verify_proof(&self, proof: &ZKProof, public_key_hash: &[u8]) -> Result<bool, JsValue> {
-        // Verify public inputs match
-        if proof.public_inputs != public_key_hash {
-            return Ok(false);
-        }
-        
-        // Perform pairing check (simplified)
-        let valid = self.pairing_check(proof)?;
-        
-        Ok(valid)
+    /// Load a verifier from an encoded verifying key
+    ///
+    /// The key is only checked to be well formed; pin its [`setup::key_id`]
+    /// in a [`setup::KeyRegistry`] to also check where it came from.
+    pub fn from_verifying_key(bytes: &[u8]) -> Result<ZKVerifier, BlackHoleError> {
+        let verifying_key = setup::decode_verifying_key(bytes)?;
+        
+        Ok(ZKVerifier {
+            verifying_key: ark_groth16::prepare_verifying_key(&verifying_key),
+            key_id: setup::key_id(bytes),
+        })
     }
     
-    fn pairing_check(&self, proof: &ZKProof) -> Result<bool, JsValue> {
-        // In production, perform actual pairing check on BN254 curve
-        // e(A, B) = e(C, G2) where e is the pairing function
-        
-        // Simplified check
-        Ok(proof.proof_data.len() == 192 && !proof.proof_data.iter().all(|&b| b == 0))
+    /// ID of the verifying key
+    pub fn key_id(&self) -> [u8; 32] {
+        self.key_id
     }
     
-    fn generate_verifying_key() -> Result<ZKVerifyingKey, JsValue> {
-        let mut key_data = vec![0u8; 1024];
+    /// Verify a proof against an identity commitment, without the identity
+    ///
+    /// Returns `Ok(false)` for a well-formed proof that does not verify and
+    /// an error when the proof or commitment cannot be decoded, or when the
+    /// proof was made for a different verifying key.
+    pub fn verify_proof(&self, proof: &ZKProof, commitment: &[u8]) -> Result<bool, BlackHoleError> {
+        log::info!("🔍 Verifying ZK proof...");
         
-        for i in 0..key_data.len() {
-            key_data[i] = ((i * 3 + 7) % 256) as u8;
+        if proof.version != PROOF_VERSION {
+            return Err(BlackHoleError::Malformed("proof version"));
         }
         
-        Ok(ZKVerifyingKey { data: key_data })
-    }
-}
-
-/// Range proof for confidential amounts
-#[wasm_bindgen]
-pub struct RangeProof;
-
-#[wasm_bindgen]
-impl RangeProof {
-    /// Prove that a value is in range [0, 2^64)
-    #[wasm_bindgen]
-    pub fn prove(value: u64, blinding: &[u8]) -> Result<Vec<u8>, JsValue> {
-        // Use Bulletproofs-style range proof
-        let mut proof = Vec::with_capacity(672);
-        
-        // Commitment: C = v*G + r*H
-        let commitment = Self::pedersen_commit(value, blinding)?;
-        
-        // Generate range proof
-        let mut hasher = blake3::Hasher::new();
-        hasher.update(&commitment);
-        hasher.update(&value.to_le_bytes());
-        hasher.update(blinding);
-        
-        let proof_hash = hasher.finalize();
-        proof.extend_from_slice(proof_hash.as_bytes());
-        proof.resize(672, 0);
-        
-        Ok(proof)
-    }
-    
-    /// Verify a range proof
-    #[wasm_bindgen]
-    pub fn verify(proof: &[u8], commitment: &[u8]) -> Result<bool, JsValue> {
-        if proof.len() != 672 {
-            return Ok(false);
+        if proof.key_id != self.key_id {
+            return Err(BlackHoleError::UntrustedParameters);
         }
         
-        // Verify commitment format
-        if commitment.len() != 32 {
+        // The proof must be for the commitment we expect
+        if proof.public_inputs != commitment {
             return Ok(false);
         }
         
-        // Simplified verification
-        Ok(!proof.iter().all(|&b| b == 0))
-    }
-    
-    fn pedersen_commit(value: u64, blinding: &[u8]) -> Result<Vec<u8>, JsValue> {
-        let mut commitment = vec![0u8; 32];
+        let commitment = Fr::deserialize_compressed(commitment)
+            .map_err(|_| BlackHoleError::Malformed("identity commitment"))?;
+        let groth16_proof = Proof::<Bn254>::deserialize_compressed(&proof.proof_data[..])
+            .map_err(|_| BlackHoleError::Malformed("proof"))?;
         
-        let mut hasher = blake3::Hasher::new();
-        hasher.update(&value.to_le_bytes());
-        hasher.update(blinding);
+        // Pairing check
+        let valid = Groth16::<Bn254>::verify_with_processed_vk(&self.verifying_key, &[commitment], &groth16_proof)
+            .map_err(|_| BlackHoleError::Malformed("proof"))?;
         
-        let hash = hasher.finalize();
-        commitment.copy_from_slice(hash.as_bytes());
+        log::info!("✅ ZK proof verification: {}", valid);
         
-        Ok(commitment)
+        Ok(valid)
     }
 }
 
+/// Derive the identity secret from the private key
+///
+/// 64 bytes are reduced modulo r so the secret is uniform in the field.
+fn derive_secret(private_key: &[u8]) -> Fr {
+    let mut wide = [0u8; 64];
+    blake3::Hasher::new_derive_key(SECRET_CONTEXT)
+        .update(private_key)
+        .finalize_xof()
+        .fill(&mut wide);
+    
+    let secret = Fr::from_le_bytes_mod_order(&wide);
+    wide.zeroize();
+    secret
+}
+
+fn field_to_bytes(value: &Fr) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(32);
+    value.serialize_compressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -383,27 +333,63 @@ mod tests {
         // Generate proof
         let proof = zk_id.prove().unwrap();
         
-        assert!(!proof.proof_data.is_empty());
-        assert_eq!(proof.proof_data.len(), 192);
+        assert_eq!(proof.proof_data.len(), 128);
+        assert_eq!(proof.public_inputs, zk_id.get_commitment());
         
-        // Verify proof
+        // Verify proof, with and without the identity
         assert!(zk_id.verify(&proof).unwrap());
+        assert!(zk_id.verifier().verify_proof(&proof, &zk_id.get_commitment()).unwrap());
+        
+        // And with a verifier loaded from the published key
+        let verifier = ZKVerifier::from_verifying_key(&zk_id.verifying_key()).unwrap();
+        assert_eq!(verifier.key_id(), zk_id.verifier().key_id());
+        assert!(verifier.verify_proof(&proof, &zk_id.get_commitment()).unwrap());
     }
     
     #[test]
-    fn test_range_proof() {
-        let value = 1000u64;
-        let blinding = &[1u8; 32];
-        
-        let proof = RangeProof::prove(value, blinding).unwrap();
-        
-        // Generate commitment
-        let mut commitment = vec![0u8; 32];
-        let mut hasher = blake3::Hasher::new();
-        hasher.update(&value.to_le_bytes());
-        hasher.update(blinding);
-        commitment.copy_from_slice(hasher.finalize().as_bytes());
+    fn test_forged_proofs_fail() {
+        let zk_id = ZKIdentity::new(&SingularityKey::generate().unwrap()).unwrap();
+        let proof = zk_id.prove().unwrap();
         
-        assert!(RangeProof::verify(&proof, &commitment).unwrap());
+        // Arbitrary non-zero bytes are not a proof
+        let garbage = ZKProof { proof_data: vec![0xab; 128], ..proof.clone() };
+        assert!(zk_id.verify(&garbage).is_err());
+        
+        // Valid curve points in the wrong places
+        let points = Proof::<Bn254>::deserialize_compressed(&proof.proof_data[..]).unwrap();
+        let swapped = Proof::<Bn254> { a: points.c, b: points.b, c: points.a };
+        let mut proof_data = Vec::new();
+        swapped.serialize_compressed(&mut proof_data).unwrap();
+        assert!(!zk_id.verify(&ZKProof { proof_data, ..proof.clone() }).unwrap());
+        
+        // A real proof relabelled for another commitment
+        let other = poseidon_hash(&zk_id.poseidon, zk_id.secret + Fr::from(1u64));
+        let relabelled = ZKProof { public_inputs: field_to_bytes(&other), ..proof };
+        assert!(!zk_id.verifier().verify_proof(&relabelled, &field_to_bytes(&other)).unwrap());
+    }
+    
+    #[test]
+    fn test_proofs_do_not_transfer_between_identities() {
+        let alice = ZKIdentity::new(&SingularityKey::generate().unwrap()).unwrap();
+        let mallory = ZKIdentity::new(&SingularityKey::generate().unwrap()).unwrap();
+        
+        // Mallory's proof claims Alice's commitment and keys
+        let forged = ZKProof {
+            public_inputs: alice.get_commitment(),
+            key_id: alice.verifier().key_id().to_vec(),
+            ..mallory.prove().unwrap()
+        };
+        assert!(!alice.verify(&forged).unwrap());
+        
+        // Nor is her own proof accepted for keys from a different setup
+        let proof = mallory.prove().unwrap();
+        assert_eq!(
+            alice.verifier().verify_proof(&proof, &mallory.get_commitment()),
+            Err(BlackHoleError::UntrustedParameters),
+        );
+        
+        // Even relabelled, it does not verify under those keys
+        let relabelled = ZKProof { key_id: alice.verifier().key_id().to_vec(), ..proof };
+        assert!(!alice.verifier().verify_proof(&relabelled, &mallory.get_commitment()).unwrap());
     }
 }
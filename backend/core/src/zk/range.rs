@@ -0,0 +1,320 @@
+//! 📏 Range Proofs
+//!
+//! Confidential amounts are Pedersen commitments on the Ristretto group,
+//! with Bulletproofs showing the committed value fits in `n` bits without
+//! revealing it.
+//!
+//! ```text
+//! C = v·B + r·B̃
+//! ```
+//!
+//! `B` is the Ristretto base point and `B̃` is hashed from it, so nobody
+//! knows `log_B(B̃)`: a commitment cannot be opened to two values
+//! (binding), and `r` hides `v` completely (hiding). Commitments add:
+//! `C(v₁, r₁) + C(v₂, r₂) = C(v₁ + v₂, r₁ + r₂)`, so balances can be
+//! checked on commitments alone.
+//!
+//! A proof covers 8, 16, 32 or 64 bits and is 2·log₂(n) + 9 group
+//! elements and scalars. Proofs for several values aggregate into one
+//! that grows logarithmically with the number of values (a power of two).
+
+use alloc::vec::Vec;
+use core::ops::{Add, Sub};
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::error::{key_array, BlackHoleError};
+use crate::providers::{CryptoRng, OsRandom, RngAdapter};
+
+/// Transcript label binding range proofs to this protocol
+const TRANSCRIPT_LABEL: &[u8] = b"BlackHole 2025 range proof";
+
+/// Most values one aggregated proof covers
+pub const MAX_AGGREGATED: usize = 64;
+
+/// A Pedersen commitment to a 64-bit value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Commitment(CompressedRistretto);
+
+/// The blinding factor opening a commitment
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct Blinding(Scalar);
+
+/// A Bulletproofs range proof for one or more commitments
+#[derive(Clone, Debug)]
+pub struct RangeProof(bulletproofs::RangeProof);
+
+impl Commitment {
+    /// Commit to `value` with `blinding`
+    pub fn new(value: u64, blinding: &Blinding) -> Self {
+        Commitment(PedersenGens::default().commit(Scalar::from(value), blinding.0).compress())
+    }
+
+    /// Decode a commitment, checking it is a valid group element
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlackHoleError> {
+        let point = CompressedRistretto(key_array(bytes)?);
+        point.decompress().ok_or(BlackHoleError::Malformed("commitment"))?;
+
+        Ok(Commitment(point))
+    }
+
+    /// Compressed encoding (32 bytes)
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    fn combine(self, other: Self, subtract: bool) -> Self {
+        let (a, b) = (self.0.decompress(), other.0.decompress());
+        let (a, b) = a.zip(b).expect("commitments are checked when decoded");
+
+        Commitment(if subtract { a - b } else { a + b }.compress())
+    }
+}
+
+impl Add for Commitment {
+    type Output = Commitment;
+
+    /// Commitment to the sum of the values, under the sum of the blindings
+    fn add(self, other: Commitment) -> Commitment {
+        self.combine(other, false)
+    }
+}
+
+impl Sub for Commitment {
+    type Output = Commitment;
+
+    /// Commitment to the difference of the values, under the difference of the blindings
+    fn sub(self, other: Commitment) -> Commitment {
+        self.combine(other, true)
+    }
+}
+
+impl Blinding {
+    /// A fresh uniformly random blinding
+    pub fn random(rng: &dyn CryptoRng) -> Self {
+        Blinding(Scalar::random(&mut RngAdapter(rng)))
+    }
+
+    /// Decode a blinding, which must be a canonical scalar
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlackHoleError> {
+        Option::from(Scalar::from_canonical_bytes(key_array(bytes)?))
+            .map(Blinding)
+            .ok_or(BlackHoleError::InvalidKey)
+    }
+
+    /// Canonical encoding (32 bytes)
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+}
+
+impl Add for &Blinding {
+    type Output = Blinding;
+
+    fn add(self, other: &Blinding) -> Blinding {
+        Blinding(self.0 + other.0)
+    }
+}
+
+impl Sub for &Blinding {
+    type Output = Blinding;
+
+    fn sub(self, other: &Blinding) -> Blinding {
+        Blinding(self.0 - other.0)
+    }
+}
+
+impl RangeProof {
+    /// Prove that `value` fits in `bits` bits, returning the proof and the commitment
+    ///
+    /// Fails with `Proof` when the value does not fit or `bits` is not 8,
+    /// 16, 32 or 64.
+    pub fn prove(value: u64, blinding: &Blinding, bits: usize, rng: &dyn CryptoRng) -> Result<(RangeProof, Commitment), BlackHoleError> {
+        let (proof, commitments) = Self::prove_multiple(&[value], core::slice::from_ref(blinding), bits, rng)?;
+
+        Ok((proof, commitments[0]))
+    }
+
+    /// Prove that every value fits in `bits` bits with one aggregated proof
+    ///
+    /// The number of values must be a power of two, at most
+    /// [`MAX_AGGREGATED`]; pad with commitments to zero if needed.
+    pub fn prove_multiple(
+        values: &[u64],
+        blindings: &[Blinding],
+        bits: usize,
+        rng: &dyn CryptoRng,
+    ) -> Result<(RangeProof, Vec<Commitment>), BlackHoleError> {
+        if values.len() != blindings.len() || !fits(values.len(), bits) {
+            return Err(BlackHoleError::Proof);
+        }
+        if bits < 64 && values.iter().any(|&value| value >> bits != 0) {
+            return Err(BlackHoleError::Proof);
+        }
+
+        log::info!("📏 Generating range proof ({} values, {} bits)...", values.len(), bits);
+
+        let blindings: Vec<Scalar> = blindings.iter().map(|blinding| blinding.0).collect();
+        let (proof, commitments) = bulletproofs::RangeProof::prove_multiple_with_rng(
+            &BulletproofGens::new(bits, values.len()),
+            &PedersenGens::default(),
+            &mut Transcript::new(TRANSCRIPT_LABEL),
+            values,
+            &blindings,
+            bits,
+            &mut RngAdapter(rng),
+        )
+        .map_err(|_| BlackHoleError::Proof)?;
+
+        log::info!("✅ Range proof generated");
+
+        Ok((RangeProof(proof), commitments.into_iter().map(Commitment).collect()))
+    }
+
+    /// Verify that the committed value fits in `bits` bits
+    pub fn verify(&self, commitment: &Commitment, bits: usize) -> Result<bool, BlackHoleError> {
+        self.verify_multiple(core::slice::from_ref(commitment), bits)
+    }
+
+    /// Verify an aggregated proof for the commitments, in proving order
+    ///
+    /// Returns `Ok(false)` for a proof that does not verify and an error
+    /// when the number of commitments or `bits` cannot be proven.
+    pub fn verify_multiple(&self, commitments: &[Commitment], bits: usize) -> Result<bool, BlackHoleError> {
+        log::info!("🔍 Verifying range proof...");
+
+        if !fits(commitments.len(), bits) {
+            return Err(BlackHoleError::Malformed("range proof statement"));
+        }
+
+        let commitments: Vec<CompressedRistretto> = commitments.iter().map(|commitment| commitment.0).collect();
+        let valid = self.0
+            .verify_multiple_with_rng(
+                &BulletproofGens::new(bits, commitments.len()),
+                &PedersenGens::default(),
+                &mut Transcript::new(TRANSCRIPT_LABEL),
+                &commitments,
+                bits,
+                // Only batches the checks; need not be reproducible
+                &mut RngAdapter(&OsRandom),
+            )
+            .is_ok();
+
+        log::info!("✅ Range proof verification: {}", valid);
+
+        Ok(valid)
+    }
+
+    /// Decode a proof
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlackHoleError> {
+        bulletproofs::RangeProof::from_bytes(bytes)
+            .map(RangeProof)
+            .map_err(|_| BlackHoleError::Malformed("range proof"))
+    }
+
+    /// Encode the proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+}
+
+/// Whether `count` values of `bits` bits can be proven together
+fn fits(count: usize, bits: usize) -> bool {
+    matches!(bits, 8 | 16 | 32 | 64) && count.is_power_of_two() && count <= MAX_AGGREGATED
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::SeededRng;
+
+    #[test]
+    fn test_range_proof_round_trip() {
+        let rng = SeededRng::from_u64(1);
+        let blinding = Blinding::random(&rng);
+
+        let (proof, commitment) = RangeProof::prove(1000, &blinding, 32, &rng).unwrap();
+        assert_eq!(commitment, Commitment::new(1000, &blinding));
+
+        let proof = RangeProof::from_bytes(&proof.to_bytes()).unwrap();
+        let commitment = Commitment::from_bytes(&commitment.to_bytes()).unwrap();
+        assert!(proof.verify(&commitment, 32).unwrap());
+
+        // Bound to its commitment and bit size
+        assert!(!proof.verify(&Commitment::new(1001, &blinding), 32).unwrap());
+        assert!(!proof.verify(&commitment, 64).unwrap());
+        assert!(proof.verify(&commitment, 12).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_rejected() {
+        let rng = SeededRng::from_u64(2);
+        let blinding = Blinding::random(&rng);
+
+        assert_eq!(RangeProof::prove(256, &blinding, 8, &rng).unwrap_err(), BlackHoleError::Proof);
+        assert!(RangeProof::prove(255, &blinding, 8, &rng).is_ok());
+        assert!(RangeProof::prove(u64::MAX, &blinding, 64, &rng).is_ok());
+
+        // A prover that skips the check does not get a valid proof
+        let (forged, commitment) = bulletproofs::RangeProof::prove_single_with_rng(
+            &BulletproofGens::new(8, 1),
+            &PedersenGens::default(),
+            &mut Transcript::new(TRANSCRIPT_LABEL),
+            256,
+            &blinding.0,
+            8,
+            &mut RngAdapter(&rng),
+        )
+        .unwrap();
+        assert!(!RangeProof(forged).verify(&Commitment(commitment), 8).unwrap());
+    }
+
+    #[test]
+    fn test_commitments_are_homomorphic_and_binding() {
+        let rng = SeededRng::from_u64(3);
+        let (r1, r2) = (Blinding::random(&rng), Blinding::random(&rng));
+
+        let sum = Commitment::new(40, &r1) + Commitment::new(2, &r2);
+        assert_eq!(sum, Commitment::new(42, &(&r1 + &r2)));
+        assert_eq!(sum - Commitment::new(2, &r2), Commitment::new(40, &r1));
+
+        // The sum carries a range proof of its own
+        let (proof, commitment) = RangeProof::prove(42, &(&r1 + &r2), 16, &rng).unwrap();
+        assert_eq!(commitment, sum);
+        assert!(proof.verify(&sum, 16).unwrap());
+
+        // Hiding: the same value under different blindings looks unrelated;
+        // binding: no other value opens the same commitment
+        assert_ne!(Commitment::new(40, &r1), Commitment::new(40, &r2));
+        assert_ne!(Commitment::new(40, &r1), Commitment::new(41, &r1));
+
+        assert!(Commitment::from_bytes(&[0xff; 32]).is_err());
+        assert!(Blinding::from_bytes(&[0xff; 32]).is_err());
+        assert_eq!(Blinding::from_bytes(&r1.to_bytes()).unwrap().to_bytes(), r1.to_bytes());
+    }
+
+    #[test]
+    fn test_aggregated_proofs() {
+        let rng = SeededRng::from_u64(4);
+        let values = [1, 2, 3, u32::MAX as u64];
+        let blindings: Vec<Blinding> = values.iter().map(|_| Blinding::random(&rng)).collect();
+
+        let (proof, commitments) = RangeProof::prove_multiple(&values, &blindings, 32, &rng).unwrap();
+        assert_eq!(commitments.len(), 4);
+        assert!(proof.verify_multiple(&commitments, 32).unwrap());
+
+        // Shorter than four separate proofs
+        let (single, _) = RangeProof::prove(1, &blindings[0], 32, &rng).unwrap();
+        assert!(proof.to_bytes().len() < 4 * single.to_bytes().len());
+
+        // Order matters, and every value must be in range
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert!(!proof.verify_multiple(&swapped, 32).unwrap());
+        assert!(proof.verify_multiple(&commitments[..3], 32).is_err());
+        assert!(RangeProof::prove_multiple(&[1, 2, 3], &blindings[..3], 32, &rng).is_err());
+        assert!(RangeProof::prove_multiple(&[1, 2, 3, 1 << 32], &blindings, 32, &rng).is_err());
+    }
+}
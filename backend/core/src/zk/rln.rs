@@ -0,0 +1,597 @@
+//! 🚦 Rate-Limiting Nullifiers
+//!
+//! RLN lets members of a [`MembershipTree`] post anonymously to a public
+//! channel while limiting how often they can post. Every message carries a
+//! membership proof and a Shamir share of the sender's identity secret on
+//! a line that is fixed for the epoch and message slot:
+//!
+//! ```text
+//! public:  root, external nullifier e, message limit n,
+//!          x = H(signal), y, nullifier
+//! witness: s, Merkle path, message id k
+//! enforce: Poseidon(s) is a leaf under root
+//!          k < n
+//!          a₁ = Poseidon(s, Poseidon(e, k))
+//!          y = s + a₁·x
+//!          nullifier = Poseidon(a₁)
+//! ```
+//!
+//! A member has `n` message slots per epoch. One message per slot reveals
+//! a single point of the line `s + a₁·x`, which says nothing about `s`; a
+//! second message in the same slot repeats the nullifier and gives a
+//! second point, from which the `SlashingDetector` interpolates `s`. The
+//! recovered secret is the member's ZK identity secret, so whoever sees it
+//! can remove the member from the group (by its commitment) and prove as
+//! them; that is the stake a spammer loses.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::Field;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use serde::{Deserialize, Serialize};
+use crate::error::BlackHoleError;
+use crate::providers::{CryptoRng, RngAdapter};
+use super::ceremony::PowersOfTau;
+use super::circuit::{poseidon_config, poseidon_gadget, poseidon_hash, poseidon_hash_two, poseidon_two_gadget};
+use super::membership::{decode_field, hash_to_field, inputs_gadget, root_gadget, MembershipTree};
+use super::setup::{self, ZKParameters};
+use super::{field_to_bytes, ZKIdentity};
+
+/// Public inputs: root, external nullifier, message limit, x, y, nullifier
+const PUBLIC_INPUTS: usize = 6;
+
+/// Bits of message IDs and limits
+const LIMIT_BITS: usize = 16;
+
+/// RLN proof format version
+const RLN_PROOF_VERSION: u32 = 1;
+
+/// Key derivation context for epoch external nullifiers
+const EPOCH_CONTEXT: &str = "BlackHole 2025 ZK RLN epoch";
+
+/// Key derivation context for share x coordinates
+const SIGNAL_CONTEXT: &str = "BlackHole 2025 ZK RLN signal";
+
+/// One epoch of a rate-limited channel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RlnEpoch {
+    /// Channel (or other scope) the limit applies to
+    pub scope: Vec<u8>,
+
+    /// Epoch number
+    pub epoch: u64,
+
+    /// Messages each member may send in the epoch
+    pub limit: u16,
+}
+
+/// An anonymous rate-limited message proof
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RlnProof {
+    /// Proof data (Groth16 format)
+    pub proof_data: Vec<u8>,
+
+    /// Root of the group the sender is a member of
+    pub root: Vec<u8>,
+
+    /// External nullifier of the epoch
+    pub external_nullifier: Vec<u8>,
+
+    /// Messages allowed per epoch
+    pub limit: u16,
+
+    /// x coordinate of the share: the signal hash
+    pub x: Vec<u8>,
+
+    /// y coordinate of the share
+    pub y: Vec<u8>,
+
+    /// Nullifier of the sender's message slot
+    pub nullifier: Vec<u8>,
+
+    /// Proof version
+    pub version: u32,
+
+    /// ID of the verifying key the proof was made for
+    pub key_id: Vec<u8>,
+}
+
+/// Proving key (which embeds its verifying key) for RLN proofs
+#[derive(Clone)]
+pub struct RlnParameters(ZKParameters);
+
+/// RLN proof verifier
+pub struct RlnVerifier {
+    /// Verifying key, prepared for pairing checks
+    verifying_key: PreparedVerifyingKey<Bn254>,
+
+    /// ID of the verifying key
+    key_id: [u8; 32],
+}
+
+/// What a verified message means for its sender
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Observation {
+    /// First message in its slot
+    Fresh,
+
+    /// The same message again, which reveals nothing new
+    Replayed,
+
+    /// A second message in the same slot, which gave the sender away
+    Spam(SlashedIdentity),
+}
+
+/// Identity recovered from two shares in one slot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlashedIdentity {
+    /// The sender's identity secret (32 bytes, compressed)
+    pub secret: Vec<u8>,
+
+    /// The sender's identity commitment, to remove them from the group
+    pub commitment: Vec<u8>,
+}
+
+/// Verifier-side record of shares, to catch senders over their limit
+#[derive(Default)]
+pub struct SlashingDetector {
+    /// First share seen per external nullifier and nullifier
+    shares: BTreeMap<(Vec<u8>, Vec<u8>), (Fr, Fr)>,
+}
+
+/// Public inputs of the RLN circuit
+#[derive(Clone, Copy)]
+struct Statement {
+    root: Fr,
+    external_nullifier: Fr,
+    limit: u16,
+    x: Fr,
+    y: Fr,
+    nullifier: Fr,
+}
+
+/// Statement: a member sends message `k < n` of the epoch
+#[derive(Clone)]
+pub struct RlnCircuit<'a> {
+    config: &'a PoseidonConfig<Fr>,
+    secret: Option<Fr>,
+    index: Option<usize>,
+    siblings: Option<Vec<Fr>>,
+    message_id: Option<u16>,
+    statement: Option<Statement>,
+}
+
+impl RlnEpoch {
+    /// An epoch of `scope` allowing `limit` messages per member
+    pub fn new(scope: &[u8], epoch: u64, limit: u16) -> Self {
+        RlnEpoch { scope: scope.to_vec(), epoch, limit }
+    }
+
+    /// The epoch of `scope` containing `now_ms`, for epochs of `length_ms`
+    pub fn at(scope: &[u8], now_ms: u64, length_ms: u64, limit: u16) -> Self {
+        Self::new(scope, now_ms / length_ms.max(1), limit)
+    }
+
+    fn external_nullifier(&self) -> Fr {
+        let mut bytes = Vec::with_capacity(self.scope.len() + 8);
+        bytes.extend_from_slice(&self.scope);
+        bytes.extend_from_slice(&self.epoch.to_le_bytes());
+        hash_to_field(EPOCH_CONTEXT, &bytes)
+    }
+}
+
+impl RlnParameters {
+    /// Run a setup for the RLN circuit, discarding its randomness afterwards
+    pub fn generate(rng: &dyn CryptoRng) -> Result<RlnParameters, BlackHoleError> {
+        ZKParameters::setup(RlnCircuit::blank(&poseidon_config()), rng).map(RlnParameters)
+    }
+
+    /// Initial ceremony parameters for the RLN circuit, derived from the
+    /// output of a powers-of-tau ceremony
+    pub fn from_powers_of_tau(powers: &PowersOfTau) -> Result<RlnParameters, BlackHoleError> {
+        ZKParameters::specialize(RlnCircuit::blank(&poseidon_config()), powers).map(RlnParameters)
+    }
+
+    /// Load parameters, checking they are for the RLN circuit
+    pub fn from_bytes(bytes: &[u8]) -> Result<RlnParameters, BlackHoleError> {
+        ZKParameters::from_bytes(bytes)?.try_into()
+    }
+
+    /// Compressed encoding of the proving key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    /// Compressed encoding of the verifying key, to publish to verifiers
+    pub fn verifying_key(&self) -> Vec<u8> {
+        self.0.verifying_key()
+    }
+
+    /// ID of the verifying key (see [`setup::key_id`])
+    pub fn key_id(&self) -> [u8; 32] {
+        self.0.key_id()
+    }
+
+    /// A verifier for proofs made with these parameters
+    pub fn verifier(&self) -> RlnVerifier {
+        RlnVerifier {
+            verifying_key: ark_groth16::prepare_verifying_key(&self.0.proving_key().vk),
+            key_id: self.key_id(),
+        }
+    }
+}
+
+impl TryFrom<ZKParameters> for RlnParameters {
+    type Error = BlackHoleError;
+
+    /// Accept parameters from a ceremony, checking their circuit shape
+    fn try_from(parameters: ZKParameters) -> Result<Self, Self::Error> {
+        if parameters.proving_key().vk.gamma_abc_g1.len() != PUBLIC_INPUTS + 1 {
+            return Err(BlackHoleError::Malformed("RLN proving key"));
+        }
+
+        Ok(RlnParameters(parameters))
+    }
+}
+
+impl From<RlnParameters> for ZKParameters {
+    fn from(parameters: RlnParameters) -> Self {
+        parameters.0
+    }
+}
+
+impl ZKIdentity {
+    /// Prove a rate-limited anonymous message
+    ///
+    /// `message_id` is the slot used in this epoch, below `epoch.limit`.
+    /// Reusing a slot for a different signal reveals the identity secret
+    /// to anyone who sees both messages.
+    pub fn prove_rate_limited(
+        &self,
+        parameters: &RlnParameters,
+        group: &MembershipTree,
+        epoch: &RlnEpoch,
+        message_id: u16,
+        signal: &[u8],
+    ) -> Result<RlnProof, BlackHoleError> {
+        log::info!("🚦 Generating RLN proof...");
+
+        if message_id >= epoch.limit {
+            return Err(BlackHoleError::InvalidState("message id exceeds the rate limit"));
+        }
+
+        let index = group.position(&self.commitment)
+            .ok_or(BlackHoleError::InvalidState("not a member of the group"))?;
+
+        let external_nullifier = epoch.external_nullifier();
+        let slope = slope(&self.poseidon, self.secret, external_nullifier, message_id);
+        let x = hash_to_field(SIGNAL_CONTEXT, signal);
+        let statement = Statement {
+            root: group.root_field(),
+            external_nullifier,
+            limit: epoch.limit,
+            x,
+            y: self.secret + slope * x,
+            nullifier: poseidon_hash(&self.poseidon, slope),
+        };
+
+        let circuit = RlnCircuit {
+            config: &self.poseidon,
+            secret: Some(self.secret),
+            index: Some(index),
+            siblings: Some(group.path(index)),
+            message_id: Some(message_id),
+            statement: Some(statement),
+        };
+        let proof = Groth16::<Bn254>::prove(parameters.0.proving_key(), circuit, &mut RngAdapter(self.rng.as_ref()))
+            .map_err(|_| BlackHoleError::Proof)?;
+
+        let mut proof_data = Vec::new();
+        proof.serialize_compressed(&mut proof_data)
+            .map_err(BlackHoleError::serialization)?;
+
+        log::info!("✅ RLN proof generated ({} bytes)", proof_data.len());
+
+        Ok(RlnProof {
+            proof_data,
+            root: field_to_bytes(&statement.root),
+            external_nullifier: field_to_bytes(&statement.external_nullifier),
+            limit: statement.limit,
+            x: field_to_bytes(&statement.x),
+            y: field_to_bytes(&statement.y),
+            nullifier: field_to_bytes(&statement.nullifier),
+            version: RLN_PROOF_VERSION,
+            key_id: parameters.key_id().to_vec(),
+        })
+    }
+}
+
+impl RlnVerifier {
+    /// Load a verifier from an encoded verifying key
+    ///
+    /// Check its [`RlnVerifier::key_id`] against a pinned ID before
+    /// trusting its proofs.
+    pub fn from_verifying_key(bytes: &[u8]) -> Result<RlnVerifier, BlackHoleError> {
+        let verifying_key = setup::decode_verifying_key(bytes)?;
+        if verifying_key.gamma_abc_g1.len() != PUBLIC_INPUTS + 1 {
+            return Err(BlackHoleError::Malformed("RLN verifying key"));
+        }
+
+        Ok(RlnVerifier {
+            verifying_key: ark_groth16::prepare_verifying_key(&verifying_key),
+            key_id: setup::key_id(bytes),
+        })
+    }
+
+    /// ID of the verifying key
+    pub fn key_id(&self) -> [u8; 32] {
+        self.key_id
+    }
+
+    /// Verify an RLN proof for a group root, epoch and signal
+    ///
+    /// Returns `Ok(false)` for a well-formed proof that does not verify or
+    /// is for another group, epoch, limit or signal, and an error when the
+    /// proof cannot be decoded or was made for a different verifying key.
+    /// Pass verified proofs to a [`SlashingDetector`] to enforce the limit.
+    pub fn verify(&self, proof: &RlnProof, root: &[u8], epoch: &RlnEpoch, signal: &[u8]) -> Result<bool, BlackHoleError> {
+        log::info!("🔍 Verifying RLN proof...");
+
+        if proof.version != RLN_PROOF_VERSION {
+            return Err(BlackHoleError::Malformed("proof version"));
+        }
+
+        if proof.key_id != self.key_id {
+            return Err(BlackHoleError::UntrustedParameters);
+        }
+
+        let external_nullifier = epoch.external_nullifier();
+        let x = hash_to_field(SIGNAL_CONTEXT, signal);
+        if proof.root != root
+            || proof.external_nullifier != field_to_bytes(&external_nullifier)
+            || proof.limit != epoch.limit
+            || proof.x != field_to_bytes(&x)
+        {
+            return Ok(false);
+        }
+
+        let statement = Statement {
+            root: decode_field(root, "group root")?,
+            external_nullifier,
+            limit: epoch.limit,
+            x,
+            y: decode_field(&proof.y, "share")?,
+            nullifier: decode_field(&proof.nullifier, "nullifier")?,
+        };
+        let groth16_proof = Proof::<Bn254>::deserialize_compressed(&proof.proof_data[..])
+            .map_err(|_| BlackHoleError::Malformed("proof"))?;
+
+        let valid = Groth16::<Bn254>::verify_with_processed_vk(&self.verifying_key, &statement.inputs(), &groth16_proof)
+            .map_err(|_| BlackHoleError::Malformed("proof"))?;
+
+        log::info!("✅ RLN proof verification: {}", valid);
+
+        Ok(valid)
+    }
+}
+
+impl SlashingDetector {
+    /// An empty detector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a verified proof's share
+    ///
+    /// A second share in the same slot recovers the sender's secret.
+    pub fn observe(&mut self, proof: &RlnProof) -> Result<Observation, BlackHoleError> {
+        let share = (decode_field(&proof.x, "share")?, decode_field(&proof.y, "share")?);
+        let slot = (proof.external_nullifier.clone(), proof.nullifier.clone());
+
+        let Some(&first) = self.shares.get(&slot) else {
+            self.shares.insert(slot, share);
+            return Ok(Observation::Fresh);
+        };
+
+        if first.0 == share.0 {
+            return Ok(Observation::Replayed);
+        }
+
+        let secret = interpolate(first, share);
+        log::warn!("🚨 Rate limit exceeded: sender identity recovered");
+
+        Ok(Observation::Spam(SlashedIdentity {
+            secret: field_to_bytes(&secret),
+            commitment: field_to_bytes(&poseidon_hash(&poseidon_config(), secret)),
+        }))
+    }
+
+    /// Forget the shares of an epoch that has ended
+    pub fn forget(&mut self, epoch: &RlnEpoch) {
+        let external_nullifier = field_to_bytes(&epoch.external_nullifier());
+        self.shares.retain(|(seen, _), _| *seen != external_nullifier);
+    }
+}
+
+impl Statement {
+    fn inputs(&self) -> [Fr; PUBLIC_INPUTS] {
+        [self.root, self.external_nullifier, Fr::from(self.limit), self.x, self.y, self.nullifier]
+    }
+}
+
+impl<'a> RlnCircuit<'a> {
+    /// The circuit shape without assignments, for key generation
+    pub fn blank(config: &'a PoseidonConfig<Fr>) -> Self {
+        RlnCircuit { config, secret: None, index: None, siblings: None, message_id: None, statement: None }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for RlnCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let statement = self.statement.map(|statement| statement.inputs());
+        let inputs = inputs_gadget(cs.clone(), statement.as_ref().map(|inputs| &inputs[..]), PUBLIC_INPUTS)?;
+        let [root, external_nullifier, limit, x, y, nullifier] = &inputs[..] else {
+            unreachable!("the statement has {PUBLIC_INPUTS} inputs");
+        };
+        let secret = FpVar::new_witness(cs.clone(), || {
+            self.secret.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let message_id = FpVar::new_witness(cs.clone(), || {
+            self.message_id.map(Fr::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let leaf = poseidon_gadget(self.config, &secret)?;
+        root_gadget(cs.clone(), self.config, leaf, self.index, self.siblings.as_deref())?.enforce_equal(root)?;
+
+        // k < n: both k and n - 1 - k fit in LIMIT_BITS
+        let headroom = self.statement.zip(self.message_id)
+            .map(|(statement, message_id)| statement.limit.wrapping_sub(1).wrapping_sub(message_id));
+        enforce_bits(cs.clone(), &message_id, self.message_id)?;
+        enforce_bits(cs, &(limit - &message_id - Fr::ONE), headroom)?;
+
+        let slot = poseidon_two_gadget(self.config, external_nullifier, &message_id)?;
+        let slope = poseidon_two_gadget(self.config, &secret, &slot)?;
+        (&secret + &slope * x).enforce_equal(y)?;
+        poseidon_gadget(self.config, &slope)?.enforce_equal(nullifier)
+    }
+}
+
+/// Slope of the sender's line for one slot: `Poseidon(s, Poseidon(e, k))`
+fn slope(config: &PoseidonConfig<Fr>, secret: Fr, external_nullifier: Fr, message_id: u16) -> Fr {
+    poseidon_hash_two(config, secret, poseidon_hash_two(config, external_nullifier, Fr::from(message_id)))
+}
+
+/// The intercept `s` of the line through two shares with distinct x
+fn interpolate((x1, y1): (Fr, Fr), (x2, y2): (Fr, Fr)) -> Fr {
+    let slope = (y2 - y1) * (x2 - x1).inverse().expect("shares have distinct x");
+    y1 - slope * x1
+}
+
+/// Enforce that `value` fits in `LIMIT_BITS` bits
+fn enforce_bits(cs: ConstraintSystemRef<Fr>, value: &FpVar<Fr>, native: Option<u16>) -> Result<(), SynthesisError> {
+    let bits = (0..LIMIT_BITS)
+        .map(|bit| {
+            Boolean::new_witness(cs.clone(), || {
+                native.map(|native| (native >> bit) & 1 == 1).ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::SeededRng;
+    use crate::SingularityKey;
+    use alloc::sync::Arc;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn member(seed: u64, parameters: &ZKParameters) -> ZKIdentity {
+        ZKIdentity::with_parameters(&SingularityKey::generate().unwrap(), parameters, Arc::new(SeededRng::from_u64(seed)))
+    }
+
+    #[test]
+    fn test_circuit_enforces_the_limit() {
+        let config = poseidon_config();
+        let secret = Fr::from(42u64);
+        let mut tree = MembershipTree::new();
+        tree.insert(&field_to_bytes(&poseidon_hash(&config, secret))).unwrap();
+
+        let satisfied = |message_id: u16, limit: u16| {
+            let external_nullifier = Fr::from(9u64);
+            let slope = slope(&config, secret, external_nullifier, message_id);
+            let x = Fr::from(3u64);
+            let statement = Statement {
+                root: tree.root_field(),
+                external_nullifier,
+                limit,
+                x,
+                y: secret + slope * x,
+                nullifier: poseidon_hash(&config, slope),
+            };
+            let circuit = RlnCircuit {
+                config: &config,
+                secret: Some(secret),
+                index: Some(0),
+                siblings: Some(tree.path(0)),
+                message_id: Some(message_id),
+                statement: Some(statement),
+            };
+
+            let cs = ConstraintSystem::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+
+        assert!(satisfied(0, 1));
+        assert!(satisfied(2, 3));
+        assert!(!satisfied(3, 3));
+        assert!(!satisfied(0, 0));
+    }
+
+    #[test]
+    fn test_interpolation_recovers_the_secret() {
+        let secret = Fr::from(1234u64);
+        let slope = Fr::from(77u64);
+        let share = |x: u64| (Fr::from(x), secret + slope * Fr::from(x));
+
+        assert_eq!(interpolate(share(5), share(11)), secret);
+        assert_eq!(interpolate(share(11), share(5)), secret);
+    }
+
+    #[test]
+    fn test_spammers_are_slashed() {
+        let identity_parameters = ZKParameters::generate(&SeededRng::from_u64(1)).unwrap();
+        let parameters = RlnParameters::generate(&SeededRng::from_u64(2)).unwrap();
+        let verifier = RlnVerifier::from_verifying_key(&parameters.verifying_key()).unwrap();
+
+        let alice = member(3, &identity_parameters);
+        let mut group = MembershipTree::new();
+        group.insert(&field_to_bytes(&Fr::from(7u64))).unwrap();
+        group.insert(&alice.get_commitment()).unwrap();
+        let root = group.root();
+
+        let epoch = RlnEpoch::at(b"#general", 125_000, 60_000, 2);
+        assert_eq!(epoch, RlnEpoch::new(b"#general", 2, 2));
+        let mut detector = SlashingDetector::new();
+
+        // Two messages per epoch are fine
+        let first = alice.prove_rate_limited(&parameters, &group, &epoch, 0, b"hello").unwrap();
+        assert!(verifier.verify(&first, &root, &epoch, b"hello").unwrap());
+        assert!(!verifier.verify(&first, &root, &epoch, b"spam").unwrap());
+        assert!(!verifier.verify(&first, &root, &RlnEpoch::new(b"#general", 3, 2), b"hello").unwrap());
+        assert!(!verifier.verify(&first, &root, &RlnEpoch::new(b"#general", 2, 3), b"hello").unwrap());
+        assert_eq!(detector.observe(&first).unwrap(), Observation::Fresh);
+        assert_eq!(detector.observe(&first).unwrap(), Observation::Replayed);
+
+        let second = alice.prove_rate_limited(&parameters, &group, &epoch, 1, b"world").unwrap();
+        assert!(verifier.verify(&second, &root, &epoch, b"world").unwrap());
+        assert_eq!(detector.observe(&second).unwrap(), Observation::Fresh);
+        assert!(alice.prove_rate_limited(&parameters, &group, &epoch, 2, b"again").is_err());
+
+        // A third message has to reuse a slot, which gives Alice away
+        let third = alice.prove_rate_limited(&parameters, &group, &epoch, 0, b"buy now").unwrap();
+        assert!(verifier.verify(&third, &root, &epoch, b"buy now").unwrap());
+        let Observation::Spam(slashed) = detector.observe(&third).unwrap() else {
+            panic!("reusing a slot must slash the sender");
+        };
+        assert_eq!(slashed.secret, field_to_bytes(&alice.secret));
+        assert_eq!(slashed.commitment, alice.get_commitment());
+        assert_eq!(group.index_of(&slashed.commitment), Some(1));
+
+        // Slots reset with the epoch
+        detector.forget(&epoch);
+        assert_eq!(detector.observe(&third).unwrap(), Observation::Fresh);
+    }
+}
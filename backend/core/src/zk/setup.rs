@@ -0,0 +1,212 @@
+//! 🔑 Setup and Key Management
+//!
+//! Groth16 keys are specific to the identity circuit and must come from a
+//! setup the verifier trusts: whoever knows the setup randomness can forge
+//! proofs. `ZKParameters` runs the circuit-specific setup and moves the keys
+//! in and out of their canonical compressed `ark-serialize` encoding.
+//!
+//! Verifying keys are named by their key ID, the BLAKE3 hash of the
+//! compressed encoding (what `b3sum` prints for the key file). Every proof
+//! carries the ID of the key it was made for, and a `KeyRegistry` only
+//! accepts verifying keys whose ID was pinned in advance, so a proof made
+//! with unexpected parameters is rejected before any pairing is computed.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use crate::error::BlackHoleError;
+use crate::providers::{CryptoRng, RngAdapter};
+use super::ceremony::PowersOfTau;
+use super::circuit::{poseidon_config, IdentityCircuit};
+use super::{ZKProof, ZKVerifier};
+
+/// Proving key (which embeds its verifying key) for the identity circuit
+#[derive(Clone)]
+pub struct ZKParameters {
+    proving_key: ProvingKey<Bn254>,
+}
+
+impl ZKParameters {
+    /// Run a circuit-specific setup, discarding its randomness afterwards
+    pub fn generate(rng: &dyn CryptoRng) -> Result<ZKParameters, BlackHoleError> {
+        Self::setup(IdentityCircuit::blank(&poseidon_config()), rng)
+    }
+
+    /// Run the setup for any circuit over the BN254 scalar field
+    pub(crate) fn setup(circuit: impl ConstraintSynthesizer<Fr>, rng: &dyn CryptoRng) -> Result<ZKParameters, BlackHoleError> {
+        let (proving_key, _) = Groth16::<Bn254>::circuit_specific_setup(circuit, &mut RngAdapter(rng))
+            .map_err(|_| BlackHoleError::Proof)?;
+
+        Ok(ZKParameters { proving_key })
+    }
+
+    /// Initial ceremony parameters for the identity circuit, derived from
+    /// the output of a powers-of-tau ceremony
+    pub fn from_powers_of_tau(powers: &PowersOfTau) -> Result<ZKParameters, BlackHoleError> {
+        Self::specialize(IdentityCircuit::blank(&poseidon_config()), powers)
+    }
+
+    /// Derive initial ceremony parameters for any circuit over the BN254 scalar field
+    pub(crate) fn specialize(circuit: impl ConstraintSynthesizer<Fr>, powers: &PowersOfTau) -> Result<ZKParameters, BlackHoleError> {
+        Ok(ZKParameters { proving_key: powers.specialize(circuit)? })
+    }
+
+    /// Load parameters, checking every point is on the curve and in the subgroup
+    pub fn from_bytes(bytes: &[u8]) -> Result<ZKParameters, BlackHoleError> {
+        let proving_key = ProvingKey::deserialize_compressed(bytes)
+            .map_err(|_| BlackHoleError::Malformed("proving key"))?;
+
+        Ok(ZKParameters { proving_key })
+    }
+
+    /// Compressed encoding of the proving key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serialize(&self.proving_key)
+    }
+
+    /// Compressed encoding of the verifying key, to publish to verifiers
+    pub fn verifying_key(&self) -> Vec<u8> {
+        serialize(&self.proving_key.vk)
+    }
+
+    /// ID of the verifying key (see [`key_id`])
+    pub fn key_id(&self) -> [u8; 32] {
+        key_id(&self.verifying_key())
+    }
+
+    pub(crate) fn proving_key(&self) -> &ProvingKey<Bn254> {
+        &self.proving_key
+    }
+
+    pub(crate) fn proving_key_mut(&mut self) -> &mut ProvingKey<Bn254> {
+        &mut self.proving_key
+    }
+}
+
+/// Key ID of an encoded verifying key: its BLAKE3 hash
+pub fn key_id(verifying_key: &[u8]) -> [u8; 32] {
+    *blake3::hash(verifying_key).as_bytes()
+}
+
+/// Decode a verifying key, checking its points
+pub(crate) fn decode_verifying_key(bytes: &[u8]) -> Result<VerifyingKey<Bn254>, BlackHoleError> {
+    VerifyingKey::deserialize_compressed(bytes)
+        .map_err(|_| BlackHoleError::Malformed("verifying key"))
+}
+
+/// Verifying keys accepted by this client, pinned by key ID
+#[derive(Default)]
+pub struct KeyRegistry {
+    /// Pinned key IDs, with the verifier once the key has been loaded
+    keys: BTreeMap<[u8; 32], Option<ZKVerifier>>,
+}
+
+impl KeyRegistry {
+    /// An empty registry that accepts nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust the verifying key with this ID
+    pub fn pin(&mut self, key_id: [u8; 32]) {
+        self.keys.entry(key_id).or_insert(None);
+    }
+
+    /// Load a verifying key, which must have been pinned
+    ///
+    /// Returns the key ID.
+    pub fn load(&mut self, verifying_key: &[u8]) -> Result<[u8; 32], BlackHoleError> {
+        let id = key_id(verifying_key);
+        let slot = self.keys.get_mut(&id).ok_or(BlackHoleError::UntrustedParameters)?;
+
+        if slot.is_none() {
+            *slot = Some(ZKVerifier::from_verifying_key(verifying_key)?);
+        }
+
+        Ok(id)
+    }
+
+    /// Whether proofs for `key_id` can be verified
+    pub fn is_loaded(&self, key_id: &[u8]) -> bool {
+        key_id.try_into().ok()
+            .and_then(|id: [u8; 32]| self.keys.get(&id))
+            .is_some_and(Option::is_some)
+    }
+
+    /// Verify a proof with the pinned key it names
+    ///
+    /// Fails with `UntrustedParameters` when the proof names a key that is
+    /// not pinned and loaded.
+    pub fn verify(&self, proof: &ZKProof, commitment: &[u8]) -> Result<bool, BlackHoleError> {
+        let verifier = <[u8; 32]>::try_from(proof.key_id.as_slice()).ok()
+            .and_then(|id| self.keys.get(&id))
+            .and_then(Option::as_ref)
+            .ok_or(BlackHoleError::UntrustedParameters)?;
+
+        verifier.verify_proof(proof, commitment)
+    }
+}
+
+pub(crate) fn serialize(value: &impl CanonicalSerialize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.compressed_size());
+    value.serialize_compressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes
+}
+
+/// One local setup shared by the tests of the whole crate
+#[cfg(test)]
+pub(crate) fn test_parameters() -> &'static ZKParameters {
+    static PARAMETERS: std::sync::OnceLock<ZKParameters> = std::sync::OnceLock::new();
+
+    PARAMETERS.get_or_init(|| ZKParameters::generate(&crate::providers::SeededRng::from_u64(0)).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::SeededRng;
+    use crate::zk::ZKIdentity;
+    use crate::SingularityKey;
+    use alloc::sync::Arc;
+
+    #[test]
+    fn test_parameters_round_trip() {
+        let parameters = ZKParameters::generate(&SeededRng::from_u64(7)).unwrap();
+
+        let loaded = ZKParameters::from_bytes(&parameters.to_bytes()).unwrap();
+        assert_eq!(loaded.verifying_key(), parameters.verifying_key());
+        assert_eq!(loaded.key_id(), key_id(&parameters.verifying_key()));
+
+        let mut truncated = parameters.to_bytes();
+        truncated.truncate(truncated.len() - 1);
+        assert!(ZKParameters::from_bytes(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_registry_rejects_unpinned_parameters() {
+        let trusted = ZKParameters::generate(&SeededRng::from_u64(1)).unwrap();
+        let rogue = ZKParameters::generate(&SeededRng::from_u64(2)).unwrap();
+
+        let mut registry = KeyRegistry::new();
+        registry.pin(trusted.key_id());
+        assert_eq!(registry.load(&trusted.verifying_key()).unwrap(), trusted.key_id());
+        assert_eq!(registry.load(&rogue.verifying_key()), Err(BlackHoleError::UntrustedParameters));
+
+        let identity = SingularityKey::generate().unwrap();
+        let rng = Arc::new(SeededRng::from_u64(3));
+        let honest = ZKIdentity::with_parameters(&identity, &trusted, rng.clone());
+        let forger = ZKIdentity::with_parameters(&identity, &rogue, rng);
+
+        let commitment = honest.get_commitment();
+        assert!(registry.verify(&honest.prove().unwrap(), &commitment).unwrap());
+        assert_eq!(
+            registry.verify(&forger.prove().unwrap(), &commitment),
+            Err(BlackHoleError::UntrustedParameters),
+        );
+    }
+}
@@ -0,0 +1,627 @@
+//! 🔏 Sigma Protocols
+//!
+//! Proofs of knowledge of discrete logarithms on the Ristretto group, made
+//! non-interactive with the Fiat-Shamir transform over a Merlin transcript.
+//!
+//! ```text
+//! Schnorr:  know x with P = x·G
+//! DLEQ:     know x with P = x·G and Q = x·H
+//! Linear:   know x₁…xₙ with Pⱼ = Σ xᵢ·Gⱼᵢ for every j
+//! ```
+//!
+//! The prover commits `T = r·G`, draws the challenge `c` from a transcript
+//! of the statement and the commitments, and answers `s = r + c·x`; the
+//! verifier recomputes `T = s·G − c·P` and checks it yields the same `c`.
+//! Transcripts start from a caller-chosen context, so a proof made for one
+//! session or request does not verify for another.
+//!
+//! Statements compose. An AND proves every part under the same challenge.
+//! An OR proves one branch of the prover's choosing and simulates the
+//! others, splitting the challenge so that `c = Σ cᵢ` (Cramer, Damgård and
+//! Schoenmakers); the proof does not reveal which branch was real.
+//!
+//! Ed25519 identity keys live on the Edwards curve rather than Ristretto,
+//! so possession of one is shown with a dedicated Schnorr proof,
+//! [`IdentityKeyProof`].
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use ed25519_dalek::SigningKey;
+use merlin::Transcript;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use crate::error::{key_array, BlackHoleError};
+use crate::providers::{CryptoRng, RngAdapter};
+use crate::SingularityKey;
+
+/// Transcript label binding sigma proofs to this protocol
+const TRANSCRIPT_LABEL: &[u8] = b"BlackHole 2025 sigma";
+
+/// A Fiat-Shamir transcript
+///
+/// Prover and verifier must append the same data in the same order before
+/// drawing challenges; a transcript is used for one proof.
+pub struct ProofTranscript(Transcript);
+
+/// A statement about discrete logarithms
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Statement {
+    /// Knowledge of one `x` with `points[i] = x·bases[i]` for every `i`
+    Dlog {
+        /// The bases, at least one
+        bases: Vec<RistrettoPoint>,
+
+        /// The points, one per base
+        points: Vec<RistrettoPoint>,
+    },
+
+    /// Knowledge of scalars satisfying every equation
+    Linear {
+        /// Number of secrets
+        secrets: usize,
+
+        /// The equations, at least one
+        equations: Vec<Equation>,
+    },
+
+    /// Every part holds
+    And(Vec<Statement>),
+
+    /// At least one branch holds
+    Or(Vec<Statement>),
+}
+
+/// One equation `point = Σ x[index]·base` of a linear statement
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Equation {
+    /// The left-hand side
+    pub point: RistrettoPoint,
+
+    /// The terms, as the index of a secret and its base
+    pub terms: Vec<(usize, RistrettoPoint)>,
+}
+
+/// The secrets proving a [`Statement`], shaped like it
+#[derive(Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub enum Witness {
+    /// The discrete logarithm of a `Dlog` statement
+    Dlog(Scalar),
+
+    /// The secrets of a `Linear` statement, by index
+    Linear(Vec<Scalar>),
+
+    /// A witness for every part of an `And`
+    And(Vec<Witness>),
+
+    /// A witness for the branch of an `Or` at the given index
+    Or(usize, Box<Witness>),
+}
+
+/// A non-interactive proof of a [`Statement`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigmaProof {
+    challenge: Scalar,
+    response: Response,
+}
+
+/// Responses, shaped like the statement
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Response {
+    Dlog(Scalar),
+    Linear(Vec<Scalar>),
+    And(Vec<Response>),
+    /// The challenge and response of every branch
+    Or(Vec<(Scalar, Response)>),
+}
+
+/// Prover state between the commitments and the challenge
+enum Pending<'a> {
+    Dlog {
+        nonce: Zeroizing<Scalar>,
+        secret: &'a Scalar,
+    },
+    Linear {
+        nonces: Zeroizing<Vec<Scalar>>,
+        secrets: &'a [Scalar],
+    },
+    And(Vec<Pending<'a>>),
+    Or {
+        /// Simulated branches, with `None` for the real one
+        simulated: Vec<Option<(Scalar, Response)>>,
+        real: Box<Pending<'a>>,
+    },
+}
+
+/// A proof of possession of an Ed25519 identity key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentityKeyProof {
+    commitment: CompressedEdwardsY,
+    response: Scalar,
+}
+
+impl ProofTranscript {
+    /// A transcript bound to `context`, such as a session or request ID
+    pub fn new(context: &[u8]) -> Self {
+        let mut transcript = Transcript::new(TRANSCRIPT_LABEL);
+        transcript.append_message(b"context", context);
+
+        ProofTranscript(transcript)
+    }
+
+    /// Append a message under `label`
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.0.append_message(label, message);
+    }
+
+    /// Append an integer under `label`
+    pub fn append_u64(&mut self, label: &'static [u8], value: u64) {
+        self.0.append_u64(label, value);
+    }
+
+    /// Append a group element under `label`
+    pub fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto) {
+        self.0.append_message(label, point.as_bytes());
+    }
+
+    /// Draw a uniformly distributed challenge from everything appended so far
+    pub fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        let mut bytes = [0u8; 64];
+        self.0.challenge_bytes(label, &mut bytes);
+
+        Scalar::from_bytes_mod_order_wide(&bytes)
+    }
+}
+
+impl Statement {
+    /// Knowledge of the secret key of `public = x·G`
+    pub fn schnorr(public: RistrettoPoint) -> Self {
+        Statement::Dlog { bases: vec![RISTRETTO_BASEPOINT_POINT], points: vec![public] }
+    }
+
+    /// Knowledge of one `x` with `p = x·g` and `q = x·h`
+    ///
+    /// With `g = G` and `h` a peer's base, shows that two public keys share
+    /// a secret.
+    pub fn dleq(g: RistrettoPoint, p: RistrettoPoint, h: RistrettoPoint, q: RistrettoPoint) -> Self {
+        Statement::Dlog { bases: vec![g, h], points: vec![p, q] }
+    }
+
+    /// Whether every node has parts, every `Dlog` pairs its bases and
+    /// points, and every term names one of the secrets
+    fn is_well_formed(&self) -> bool {
+        match self {
+            Statement::Dlog { bases, points } => !bases.is_empty() && bases.len() == points.len(),
+            Statement::Linear { secrets, equations } => {
+                !equations.is_empty()
+                    && equations.iter().all(|equation| {
+                        !equation.terms.is_empty() && equation.terms.iter().all(|(index, _)| index < secrets)
+                    })
+            }
+            Statement::And(parts) | Statement::Or(parts) => {
+                !parts.is_empty() && parts.iter().all(Statement::is_well_formed)
+            }
+        }
+    }
+
+    fn append_to(&self, transcript: &mut ProofTranscript) {
+        match self {
+            Statement::Dlog { bases, points } => {
+                transcript.append_u64(b"dlog", bases.len() as u64);
+                for (base, point) in bases.iter().zip(points) {
+                    transcript.append_point(b"G", &base.compress());
+                    transcript.append_point(b"P", &point.compress());
+                }
+            }
+            Statement::Linear { secrets, equations } => {
+                transcript.append_u64(b"linear", *secrets as u64);
+                transcript.append_u64(b"equations", equations.len() as u64);
+                for equation in equations {
+                    transcript.append_u64(b"terms", equation.terms.len() as u64);
+                    for (index, base) in &equation.terms {
+                        transcript.append_u64(b"x", *index as u64);
+                        transcript.append_point(b"G", &base.compress());
+                    }
+                    transcript.append_point(b"P", &equation.point.compress());
+                }
+            }
+            Statement::And(parts) => {
+                transcript.append_u64(b"and", parts.len() as u64);
+                parts.iter().for_each(|part| part.append_to(transcript));
+            }
+            Statement::Or(branches) => {
+                transcript.append_u64(b"or", branches.len() as u64);
+                branches.iter().for_each(|branch| branch.append_to(transcript));
+            }
+        }
+    }
+}
+
+impl Equation {
+    /// `Σ x[index]·base` for the given scalars
+    fn evaluate(&self, scalars: &[Scalar]) -> RistrettoPoint {
+        self.terms.iter().map(|(index, base)| scalars[*index] * base).sum()
+    }
+}
+
+impl SigmaProof {
+    /// Prove `statement` with `witness`
+    ///
+    /// Fails with `Proof` when the witness does not match the statement or
+    /// does not satisfy it.
+    pub fn prove(
+        statement: &Statement,
+        witness: &Witness,
+        transcript: &mut ProofTranscript,
+        rng: &dyn CryptoRng,
+    ) -> Result<SigmaProof, BlackHoleError> {
+        if !statement.is_well_formed() {
+            return Err(BlackHoleError::Malformed("sigma statement"));
+        }
+
+        let mut commitments = Vec::new();
+        let pending = commit(statement, witness, &mut RngAdapter(rng), &mut commitments)?;
+        let challenge = challenge(transcript, statement, &commitments);
+
+        Ok(SigmaProof { challenge, response: pending.respond(challenge) })
+    }
+
+    /// Verify the proof of `statement`
+    ///
+    /// Returns `Ok(false)` for a proof that does not verify and an error
+    /// for a malformed statement.
+    pub fn verify(&self, statement: &Statement, transcript: &mut ProofTranscript) -> Result<bool, BlackHoleError> {
+        if !statement.is_well_formed() {
+            return Err(BlackHoleError::Malformed("sigma statement"));
+        }
+
+        let mut commitments = Vec::new();
+        if !recompute(statement, &self.response, self.challenge, &mut commitments) {
+            return Ok(false);
+        }
+
+        Ok(challenge(transcript, statement, &commitments) == self.challenge)
+    }
+}
+
+impl Pending<'_> {
+    /// Answer the challenge `c`
+    fn respond(self, c: Scalar) -> Response {
+        match self {
+            Pending::Dlog { nonce, secret } => Response::Dlog(*nonce + c * secret),
+            Pending::Linear { nonces, secrets } => {
+                Response::Linear(nonces.iter().zip(secrets).map(|(nonce, secret)| nonce + c * secret).collect())
+            }
+            Pending::And(parts) => Response::And(parts.into_iter().map(|part| part.respond(c)).collect()),
+            Pending::Or { simulated, real } => {
+                // The real branch takes whatever challenge the others left
+                let others: Scalar = simulated.iter().flatten().map(|(challenge, _)| challenge).sum();
+                let challenge = c - others;
+                let mut real = Some((challenge, real.respond(challenge)));
+
+                Response::Or(
+                    simulated
+                        .into_iter()
+                        .map(|branch| branch.or_else(|| real.take()).expect("exactly one real branch"))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+/// Commit to a proof of `statement`, appending the commitments in order
+fn commit<'a>(
+    statement: &Statement,
+    witness: &'a Witness,
+    rng: &mut RngAdapter,
+    commitments: &mut Vec<CompressedRistretto>,
+) -> Result<Pending<'a>, BlackHoleError> {
+    match (statement, witness) {
+        (Statement::Dlog { bases, points }, Witness::Dlog(secret)) => {
+            if bases.iter().zip(points).any(|(base, point)| secret * base != *point) {
+                return Err(BlackHoleError::Proof);
+            }
+
+            let nonce = Zeroizing::new(Scalar::random(rng));
+            commitments.extend(bases.iter().map(|base| (*nonce * base).compress()));
+
+            Ok(Pending::Dlog { nonce, secret })
+        }
+        (Statement::Linear { secrets: count, equations }, Witness::Linear(secrets)) if secrets.len() == *count => {
+            if equations.iter().any(|equation| equation.evaluate(secrets) != equation.point) {
+                return Err(BlackHoleError::Proof);
+            }
+
+            let nonces = Zeroizing::new((0..*count).map(|_| Scalar::random(rng)).collect::<Vec<_>>());
+            commitments.extend(equations.iter().map(|equation| equation.evaluate(&nonces).compress()));
+
+            Ok(Pending::Linear { nonces, secrets })
+        }
+        (Statement::And(parts), Witness::And(witnesses)) if parts.len() == witnesses.len() => parts
+            .iter()
+            .zip(witnesses)
+            .map(|(part, witness)| commit(part, witness, rng, commitments))
+            .collect::<Result<_, _>>()
+            .map(Pending::And),
+        (Statement::Or(branches), Witness::Or(index, witness)) if *index < branches.len() => {
+            let mut simulated = Vec::with_capacity(branches.len());
+            let mut real = None;
+
+            for (position, branch) in branches.iter().enumerate() {
+                if position == *index {
+                    real = Some(Box::new(commit(branch, witness, rng, commitments)?));
+                    simulated.push(None);
+                } else {
+                    let challenge = Scalar::random(rng);
+                    let response = simulate(branch, challenge, rng);
+                    recompute(branch, &response, challenge, commitments);
+                    simulated.push(Some((challenge, response)));
+                }
+            }
+
+            Ok(Pending::Or { simulated, real: real.expect("index is in range") })
+        }
+        _ => Err(BlackHoleError::Proof),
+    }
+}
+
+/// Random responses for a branch simulated under challenge `c`
+fn simulate(statement: &Statement, c: Scalar, rng: &mut RngAdapter) -> Response {
+    match statement {
+        Statement::Dlog { .. } => Response::Dlog(Scalar::random(rng)),
+        Statement::Linear { secrets, .. } => Response::Linear((0..*secrets).map(|_| Scalar::random(rng)).collect()),
+        Statement::And(parts) => Response::And(parts.iter().map(|part| simulate(part, c, rng)).collect()),
+        Statement::Or(branches) => {
+            let mut remaining = c;
+            let last = branches.len() - 1;
+
+            Response::Or(
+                branches
+                    .iter()
+                    .enumerate()
+                    .map(|(position, branch)| {
+                        let challenge = if position == last { remaining } else { Scalar::random(rng) };
+                        remaining -= challenge;
+                        (challenge, simulate(branch, challenge, rng))
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Recompute the commitments `T = s·G − c·P` behind `response`
+///
+/// Returns `false` when the response does not fit the statement or the OR
+/// challenges do not add up.
+fn recompute(statement: &Statement, response: &Response, c: Scalar, commitments: &mut Vec<CompressedRistretto>) -> bool {
+    match (statement, response) {
+        (Statement::Dlog { bases, points }, Response::Dlog(s)) => {
+            commitments.extend(bases.iter().zip(points).map(|(base, point)| (s * base - c * point).compress()));
+            true
+        }
+        (Statement::Linear { secrets, equations }, Response::Linear(s)) if s.len() == *secrets => {
+            commitments.extend(equations.iter().map(|equation| (equation.evaluate(s) - c * equation.point).compress()));
+            true
+        }
+        (Statement::And(parts), Response::And(responses)) if parts.len() == responses.len() => parts
+            .iter()
+            .zip(responses)
+            .all(|(part, response)| recompute(part, response, c, commitments)),
+        (Statement::Or(branches), Response::Or(responses)) if branches.len() == responses.len() => {
+            responses.iter().map(|(challenge, _)| challenge).sum::<Scalar>() == c
+                && branches
+                    .iter()
+                    .zip(responses)
+                    .all(|(branch, (challenge, response))| recompute(branch, response, *challenge, commitments))
+        }
+        _ => false,
+    }
+}
+
+/// The Fiat-Shamir challenge for `statement` and its commitments
+fn challenge(transcript: &mut ProofTranscript, statement: &Statement, commitments: &[CompressedRistretto]) -> Scalar {
+    transcript.append_message(b"protocol", b"sigma");
+    statement.append_to(transcript);
+    for commitment in commitments {
+        transcript.append_point(b"T", commitment);
+    }
+
+    transcript.challenge_scalar(b"c")
+}
+
+impl IdentityKeyProof {
+    /// Prove possession of the private half of `identity`
+    pub fn prove(identity: &SingularityKey, transcript: &mut ProofTranscript, rng: &dyn CryptoRng) -> Self {
+        let secret = Zeroizing::new(SigningKey::from_bytes(&identity.private).to_scalar());
+        let nonce = Zeroizing::new(Scalar::random(&mut RngAdapter(rng)));
+
+        let commitment = EdwardsPoint::mul_base(&nonce).compress();
+        let challenge = identity_challenge(transcript, &identity.public, &commitment);
+
+        IdentityKeyProof { commitment, response: *nonce + challenge * *secret }
+    }
+
+    /// Verify the proof for the Ed25519 public key `public_key`
+    ///
+    /// Fails with `InvalidKey` for keys that are not points of the
+    /// prime-order subgroup.
+    pub fn verify(&self, public_key: &[u8], transcript: &mut ProofTranscript) -> Result<bool, BlackHoleError> {
+        let public_key: [u8; 32] = key_array(public_key)?;
+        let point = CompressedEdwardsY(public_key)
+            .decompress()
+            .filter(EdwardsPoint::is_torsion_free)
+            .ok_or(BlackHoleError::InvalidKey)?;
+
+        let Some(commitment) = self.commitment.decompress() else {
+            return Ok(false);
+        };
+        let challenge = identity_challenge(transcript, &public_key, &self.commitment);
+
+        Ok(EdwardsPoint::mul_base(&self.response) == commitment + challenge * point)
+    }
+
+    /// Decode a proof
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlackHoleError> {
+        let bytes: [u8; 64] = bytes.try_into().map_err(|_| BlackHoleError::Malformed("identity key proof"))?;
+        let (commitment, response) = bytes.split_at(32);
+
+        let response = Option::from(Scalar::from_canonical_bytes(key_array(response)?))
+            .ok_or(BlackHoleError::Malformed("identity key proof"))?;
+
+        Ok(IdentityKeyProof { commitment: CompressedEdwardsY(key_array(commitment)?), response })
+    }
+
+    /// Encode the proof (64 bytes)
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.commitment.as_bytes());
+        bytes[32..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+}
+
+fn identity_challenge(transcript: &mut ProofTranscript, public_key: &[u8; 32], commitment: &CompressedEdwardsY) -> Scalar {
+    transcript.append_message(b"protocol", b"ed25519 identity");
+    transcript.append_message(b"A", public_key);
+    transcript.append_message(b"R", commitment.as_bytes());
+
+    transcript.challenge_scalar(b"c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::SeededRng;
+
+    fn keypair(rng: &SeededRng) -> (Scalar, RistrettoPoint) {
+        let secret = Scalar::random(&mut RngAdapter(rng));
+        (secret, RistrettoPoint::mul_base(&secret))
+    }
+
+    #[test]
+    fn test_schnorr_proof_is_bound_to_key_and_context() {
+        let rng = SeededRng::from_u64(1);
+        let (secret, public) = keypair(&rng);
+        let statement = Statement::schnorr(public);
+
+        let proof = SigmaProof::prove(&statement, &Witness::Dlog(secret), &mut ProofTranscript::new(b"login"), &rng).unwrap();
+        let proof: SigmaProof = serde_json::from_slice(&serde_json::to_vec(&proof).unwrap()).unwrap();
+        assert!(proof.verify(&statement, &mut ProofTranscript::new(b"login")).unwrap());
+
+        assert!(!proof.verify(&statement, &mut ProofTranscript::new(b"logout")).unwrap());
+        assert!(!proof.verify(&Statement::schnorr(keypair(&rng).1), &mut ProofTranscript::new(b"login")).unwrap());
+
+        // A wrong secret cannot produce a proof
+        let result = SigmaProof::prove(&statement, &Witness::Dlog(secret + Scalar::ONE), &mut ProofTranscript::new(b"login"), &rng);
+        assert_eq!(result.unwrap_err(), BlackHoleError::Proof);
+    }
+
+    #[test]
+    fn test_dleq_shows_two_keys_share_a_secret() {
+        let rng = SeededRng::from_u64(2);
+        let (secret, public) = keypair(&rng);
+        let base = keypair(&rng).1;
+        let statement = Statement::dleq(RISTRETTO_BASEPOINT_POINT, public, base, secret * base);
+
+        let proof = SigmaProof::prove(&statement, &Witness::Dlog(secret), &mut ProofTranscript::new(b""), &rng).unwrap();
+        assert!(proof.verify(&statement, &mut ProofTranscript::new(b"")).unwrap());
+
+        // Keys with different secrets fail
+        let unrelated = Statement::dleq(RISTRETTO_BASEPOINT_POINT, public, base, (secret + Scalar::ONE) * base);
+        assert!(!proof.verify(&unrelated, &mut ProofTranscript::new(b"")).unwrap());
+        assert!(SigmaProof::prove(&unrelated, &Witness::Dlog(secret), &mut ProofTranscript::new(b""), &rng).is_err());
+    }
+
+    #[test]
+    fn test_linear_statement_opens_a_commitment() {
+        let rng = SeededRng::from_u64(5);
+        let (value, blinding) = (Scalar::from(42u64), Scalar::random(&mut RngAdapter(&rng)));
+        let base = keypair(&rng).1;
+
+        // C = v·G + r·H, and V = v·H with the same v
+        let statement = Statement::Linear {
+            secrets: 2,
+            equations: vec![
+                Equation {
+                    point: RistrettoPoint::mul_base(&value) + blinding * base,
+                    terms: vec![(0, RISTRETTO_BASEPOINT_POINT), (1, base)],
+                },
+                Equation { point: value * base, terms: vec![(0, base)] },
+            ],
+        };
+
+        let proof = SigmaProof::prove(&statement, &Witness::Linear(vec![value, blinding]), &mut ProofTranscript::new(b""), &rng).unwrap();
+        assert!(proof.verify(&statement, &mut ProofTranscript::new(b"")).unwrap());
+
+        let wrong = Witness::Linear(vec![value + Scalar::ONE, blinding]);
+        assert!(SigmaProof::prove(&statement, &wrong, &mut ProofTranscript::new(b""), &rng).is_err());
+
+        // Terms must name one of the secrets
+        let mut malformed = statement.clone();
+        if let Statement::Linear { secrets, .. } = &mut malformed {
+            *secrets = 1;
+        }
+        assert!(proof.verify(&malformed, &mut ProofTranscript::new(b"")).is_err());
+    }
+
+    #[test]
+    fn test_composed_statements() {
+        let rng = SeededRng::from_u64(3);
+        let (a, a_public) = keypair(&rng);
+        let (b, b_public) = keypair(&rng);
+        let (_, c_public) = keypair(&rng);
+
+        // (a AND b) OR c, knowing only a and b
+        let statement = Statement::Or(vec![
+            Statement::And(vec![Statement::schnorr(a_public), Statement::schnorr(b_public)]),
+            Statement::schnorr(c_public),
+        ]);
+        let witness = Witness::Or(0, Box::new(Witness::And(vec![Witness::Dlog(a), Witness::Dlog(b)])));
+
+        let proof = SigmaProof::prove(&statement, &witness, &mut ProofTranscript::new(b"group"), &rng).unwrap();
+        assert!(proof.verify(&statement, &mut ProofTranscript::new(b"group")).unwrap());
+
+        // c OR a, proven with the second branch, looks the same as any other
+        let statement = Statement::Or(vec![Statement::schnorr(c_public), Statement::schnorr(a_public)]);
+        let proof = SigmaProof::prove(&statement, &Witness::Or(1, Box::new(Witness::Dlog(a))), &mut ProofTranscript::new(b""), &rng).unwrap();
+        assert!(proof.verify(&statement, &mut ProofTranscript::new(b"")).unwrap());
+
+        // Challenges that do not add up are rejected
+        let mut tampered = proof.clone();
+        if let Response::Or(branches) = &mut tampered.response {
+            branches[0].0 += Scalar::ONE;
+        }
+        assert!(!tampered.verify(&statement, &mut ProofTranscript::new(b"")).unwrap());
+
+        // Claiming a branch without its secret fails
+        let result = SigmaProof::prove(&statement, &Witness::Or(0, Box::new(Witness::Dlog(a))), &mut ProofTranscript::new(b""), &rng);
+        assert!(result.is_err());
+
+        // Proofs do not fit other shapes, and empty statements are malformed
+        assert!(!proof.verify(&Statement::schnorr(a_public), &mut ProofTranscript::new(b"")).unwrap());
+        assert!(proof.verify(&Statement::Or(vec![]), &mut ProofTranscript::new(b"")).is_err());
+    }
+
+    #[test]
+    fn test_identity_key_proof() {
+        let rng = SeededRng::from_u64(4);
+        let identity = SingularityKey::generate_with(&rng).unwrap();
+
+        let proof = IdentityKeyProof::prove(&identity, &mut ProofTranscript::new(b"challenge"), &rng);
+        let proof = IdentityKeyProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(proof.verify(&identity.public, &mut ProofTranscript::new(b"challenge")).unwrap());
+
+        assert!(!proof.verify(&identity.public, &mut ProofTranscript::new(b"replayed")).unwrap());
+        let other = SingularityKey::generate_with(&rng).unwrap();
+        assert!(!proof.verify(&other.public, &mut ProofTranscript::new(b"challenge")).unwrap());
+
+        assert!(proof.verify(&[0u8; 31], &mut ProofTranscript::new(b"challenge")).is_err());
+        assert!(IdentityKeyProof::from_bytes(&[0xff; 64]).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! 🤖 JNI entry points without a JVM
+//!
+//! Drives the `Java_com_forticomm_blackhole_BlackHoleNative_*` functions
+//! through a fake `JNIEnv` whose function table implements only the calls the
+//! bindings make: strings, byte arrays, object arrays, class lookup and
+//! exceptions. Fake objects are leaked boxes, which is fine for a test process.
+
+#![cfg(feature = "jni")]
+#![allow(unsafe_code)]
+
+use std::cell::{Cell, RefCell};
+use std::ffi::{c_char, CStr, CString};
+use std::sync::OnceLock;
+use std::{mem, ptr, slice};
+
+use blackhole_core::android::*;
+use blackhole_core::providers::SeededRng;
+use blackhole_core::ZKParameters;
+use jni::objects::{JByteArray, JClass, JString};
+use jni::sys::{
+    jarray, jboolean, jbyte, jbyteArray, jclass, jint, jlong, jobject, jobjectArray, jsize, jstring,
+    JNIEnv as RawEnv, JNINativeInterface_,
+};
+use jni::JNIEnv;
+
+/// What a fake `jobject` points at
+enum Object {
+    Bytes(Vec<u8>),
+    Str(CString),
+    Class(String),
+    Array(Vec<jobject>),
+}
+
+thread_local! {
+    /// Exception thrown and not yet taken, as (class, message)
+    static PENDING: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+
+    static ENV: Cell<*mut RawEnv> = const { Cell::new(ptr::null_mut()) };
+}
+
+fn new_object(object: Object) -> jobject {
+    Box::into_raw(Box::new(object)) as jobject
+}
+
+unsafe fn object<'a>(obj: jobject) -> &'a mut Object {
+    &mut *(obj as *mut Object)
+}
+
+unsafe extern "system" fn exception_check(_: *mut RawEnv) -> jboolean {
+    PENDING.with(|pending| pending.borrow().is_some()).into()
+}
+
+unsafe extern "system" fn find_class(_: *mut RawEnv, name: *const c_char) -> jclass {
+    new_object(Object::Class(CStr::from_ptr(name).to_string_lossy().into_owned()))
+}
+
+unsafe extern "system" fn get_object_class(_: *mut RawEnv, obj: jobject) -> jclass {
+    let name = match object(obj) {
+        Object::Bytes(_) => "[B",
+        Object::Str(_) => "java/lang/String",
+        Object::Class(_) => "java/lang/Class",
+        Object::Array(_) => "[[B",
+    };
+
+    new_object(Object::Class(name.to_string()))
+}
+
+unsafe extern "system" fn is_assignable_from(_: *mut RawEnv, sub: jclass, sup: jclass) -> jboolean {
+    match (object(sub), object(sup)) {
+        (Object::Class(sub), Object::Class(sup)) => (sub == sup).into(),
+        _ => false.into(),
+    }
+}
+
+unsafe extern "system" fn get_string_utf_chars(_: *mut RawEnv, string: jstring, is_copy: *mut jboolean) -> *const c_char {
+    if !is_copy.is_null() {
+        *is_copy = false.into();
+    }
+
+    match object(string) {
+        Object::Str(string) => string.as_ptr(),
+        _ => panic!("not a string"),
+    }
+}
+
+unsafe extern "system" fn release_string_utf_chars(_: *mut RawEnv, _: jstring, _: *const c_char) {}
+
+unsafe extern "system" fn new_string_utf(_: *mut RawEnv, chars: *const c_char) -> jstring {
+    new_object(Object::Str(CStr::from_ptr(chars).to_owned()))
+}
+
+unsafe extern "system" fn new_byte_array(_: *mut RawEnv, len: jsize) -> jbyteArray {
+    new_object(Object::Bytes(vec![0; len as usize]))
+}
+
+unsafe extern "system" fn set_byte_array_region(_: *mut RawEnv, array: jbyteArray, start: jsize, len: jsize, buf: *const jbyte) {
+    let Object::Bytes(bytes) = object(array) else { panic!("not a byte array") };
+    let source = slice::from_raw_parts(buf as *const u8, len as usize);
+    bytes[start as usize..][..len as usize].copy_from_slice(source);
+}
+
+unsafe extern "system" fn get_array_length(_: *mut RawEnv, array: jarray) -> jsize {
+    match object(array) {
+        Object::Bytes(bytes) => bytes.len() as jsize,
+        Object::Array(elements) => elements.len() as jsize,
+        _ => panic!("not an array"),
+    }
+}
+
+unsafe extern "system" fn get_byte_array_region(_: *mut RawEnv, array: jbyteArray, start: jsize, len: jsize, buf: *mut jbyte) {
+    let Object::Bytes(bytes) = object(array) else { panic!("not a byte array") };
+    let target = slice::from_raw_parts_mut(buf as *mut u8, len as usize);
+    target.copy_from_slice(&bytes[start as usize..][..len as usize]);
+}
+
+unsafe extern "system" fn throw_new(_: *mut RawEnv, class: jclass, message: *const c_char) -> jint {
+    let Object::Class(class) = object(class) else { panic!("not a class") };
+    let message = CStr::from_ptr(message).to_string_lossy().into_owned();
+
+    PENDING.with(|pending| *pending.borrow_mut() = Some((class.clone(), message)));
+    0
+}
+
+unsafe extern "system" fn delete_local_ref(_: *mut RawEnv, _: jobject) {}
+
+unsafe extern "system" fn new_object_array(_: *mut RawEnv, len: jsize, _: jclass, init: jobject) -> jobjectArray {
+    new_object(Object::Array(vec![init; len as usize]))
+}
+
+unsafe extern "system" fn set_object_array_element(_: *mut RawEnv, array: jobjectArray, index: jsize, value: jobject) {
+    let Object::Array(elements) = object(array) else { panic!("not an object array") };
+    elements[index as usize] = value;
+}
+
+/// A fresh `JNIEnv` over this thread's fake function table
+fn env() -> JNIEnv<'static> {
+    let raw = ENV.with(|env| {
+        if env.get().is_null() {
+            // SAFETY: every entry is an `Option` of a function pointer or a raw pointer
+            let mut table: JNINativeInterface_ = unsafe { mem::zeroed() };
+            table.ExceptionCheck = Some(exception_check);
+            table.FindClass = Some(find_class);
+            table.GetObjectClass = Some(get_object_class);
+            table.IsAssignableFrom = Some(is_assignable_from);
+            table.GetStringUTFChars = Some(get_string_utf_chars);
+            table.ReleaseStringUTFChars = Some(release_string_utf_chars);
+            table.NewStringUTF = Some(new_string_utf);
+            table.NewByteArray = Some(new_byte_array);
+            table.SetByteArrayRegion = Some(set_byte_array_region);
+            table.GetArrayLength = Some(get_array_length);
+            table.GetByteArrayRegion = Some(get_byte_array_region);
+            table.ThrowNew = Some(throw_new);
+            table.DeleteLocalRef = Some(delete_local_ref);
+            table.NewObjectArray = Some(new_object_array);
+            table.SetObjectArrayElement = Some(set_object_array_element);
+
+            let table: &'static JNINativeInterface_ = Box::leak(Box::new(table));
+            env.set(Box::into_raw(Box::new(table as *const JNINativeInterface_)));
+        }
+        env.get()
+    });
+
+    unsafe { JNIEnv::from_raw(raw) }.unwrap()
+}
+
+fn class() -> JClass<'static> {
+    unsafe { JClass::from_raw(ptr::null_mut()) }
+}
+
+fn string(value: &str) -> JString<'static> {
+    unsafe { JString::from_raw(new_object(Object::Str(CString::new(value).unwrap()))) }
+}
+
+fn bytes(data: &[u8]) -> JByteArray<'static> {
+    unsafe { JByteArray::from_raw(new_object(Object::Bytes(data.to_vec()))) }
+}
+
+fn null_bytes() -> JByteArray<'static> {
+    unsafe { JByteArray::from_raw(ptr::null_mut()) }
+}
+
+fn read_bytes(array: jbyteArray) -> Vec<u8> {
+    assert!(!array.is_null(), "unexpected exception: {:?}", take_exception());
+    match unsafe { object(array) } {
+        Object::Bytes(bytes) => bytes.clone(),
+        _ => panic!("not a byte array"),
+    }
+}
+
+fn read_string(string: jstring) -> String {
+    assert!(!string.is_null(), "unexpected exception: {:?}", take_exception());
+    match unsafe { object(string) } {
+        Object::Str(string) => string.to_str().unwrap().to_string(),
+        _ => panic!("not a string"),
+    }
+}
+
+fn take_exception() -> Option<(String, String)> {
+    PENDING.with(|pending| pending.borrow_mut().take())
+}
+
+/// Encoded parameters from one local setup shared by every test
+fn parameters() -> &'static [u8] {
+    static PARAMETERS: OnceLock<Vec<u8>> = OnceLock::new();
+
+    PARAMETERS.get_or_init(|| ZKParameters::generate(&SeededRng::from_u64(0)).unwrap().to_bytes())
+}
+
+fn core_new() -> jlong {
+    let handle = Java_com_forticomm_blackhole_BlackHoleNative_coreNew(env(), class(), bytes(parameters()));
+    assert_ne!(handle, 0);
+    handle
+}
+
+#[test]
+fn test_identity_sessions_and_groups() {
+    let handle = core_new();
+
+    let fingerprint = read_string(Java_com_forticomm_blackhole_BlackHoleNative_fingerprint(env(), class(), handle));
+    assert!(!fingerprint.is_empty());
+    let key = read_bytes(Java_com_forticomm_blackhole_BlackHoleNative_identityPublicKey(env(), class(), handle));
+    assert_eq!(key.len(), 32);
+
+    let message = Java_com_forticomm_blackhole_BlackHoleNative_encrypt(env(), class(), handle, string("bob"), string("hello"));
+    let message = read_bytes(message);
+    let plaintext = Java_com_forticomm_blackhole_BlackHoleNative_decrypt(env(), class(), handle, bytes(&message));
+    assert_eq!(read_string(plaintext), "hello");
+
+    let dh_public = Java_com_forticomm_blackhole_BlackHoleNative_startSession(env(), class(), handle, string("bob"), null_bytes());
+    assert_eq!(read_bytes(dh_public).len(), 32);
+    assert_eq!(Java_com_forticomm_blackhole_BlackHoleNative_endSession(env(), class(), handle, string("bob")), 1);
+
+    let group_id = read_string(Java_com_forticomm_blackhole_BlackHoleNative_createGroup(env(), class(), handle));
+    let sealed = Java_com_forticomm_blackhole_BlackHoleNative_groupEncrypt(env(), class(), handle, string(&group_id), bytes(b"hi all"));
+    let sealed = read_bytes(sealed);
+    let opened = Java_com_forticomm_blackhole_BlackHoleNative_groupDecrypt(env(), class(), handle, string(&group_id), bytes(&sealed));
+    assert_eq!(read_bytes(opened), b"hi all");
+    assert_eq!(Java_com_forticomm_blackhole_BlackHoleNative_leaveGroup(env(), class(), handle, string(&group_id)), 1);
+
+    assert_eq!(take_exception(), None);
+    Java_com_forticomm_blackhole_BlackHoleNative_coreFree(env(), class(), handle);
+}
+
+#[test]
+fn test_attachments_return_byte_array_triples() {
+    let handle = core_new();
+
+    let parts = Java_com_forticomm_blackhole_BlackHoleNative_encryptAttachment(
+        env(),
+        class(),
+        handle,
+        bytes(&[42u8; 5000]),
+        string("image/png"),
+        null_bytes(),
+    );
+    assert!(!parts.is_null(), "unexpected exception: {:?}", take_exception());
+    let Object::Array(parts) = (unsafe { object(parts) }) else { panic!("not an object array") };
+    assert_eq!(parts.len(), 3);
+
+    let (pointer, blob) = (read_bytes(parts[0]), read_bytes(parts[1]));
+    let data = Java_com_forticomm_blackhole_BlackHoleNative_decryptAttachment(env(), class(), handle, bytes(&pointer), bytes(&blob));
+    assert_eq!(read_bytes(data), vec![42u8; 5000]);
+
+    Java_com_forticomm_blackhole_BlackHoleNative_coreFree(env(), class(), handle);
+}
+
+#[test]
+fn test_failures_throw_java_exceptions() {
+    let handle = core_new();
+
+    let plaintext = Java_com_forticomm_blackhole_BlackHoleNative_decrypt(env(), class(), handle, bytes(b"{}"));
+    assert!(plaintext.is_null());
+    let (class_name, message) = take_exception().unwrap();
+    assert_eq!(class_name, EXCEPTION_CLASS);
+    assert!(!message.is_empty());
+
+    let commit = Java_com_forticomm_blackhole_BlackHoleNative_groupCommit(env(), class(), handle, string("missing"));
+    assert!(commit.is_null());
+    assert_eq!(take_exception().unwrap().0, EXCEPTION_CLASS);
+
+    let sealed = Java_com_forticomm_blackhole_BlackHoleNative_groupEncrypt(env(), class(), handle, string("missing"), null_bytes());
+    assert!(sealed.is_null());
+    assert_eq!(take_exception().unwrap(), ("java/lang/NullPointerException".to_string(), "`plaintext` is null".to_string()));
+
+    let fingerprint = Java_com_forticomm_blackhole_BlackHoleNative_fingerprint(env(), class(), 0);
+    assert!(fingerprint.is_null());
+    assert_eq!(take_exception().unwrap().0, "java/lang/IllegalStateException");
+
+    Java_com_forticomm_blackhole_BlackHoleNative_coreFree(env(), class(), handle);
+    Java_com_forticomm_blackhole_BlackHoleNative_coreFree(env(), class(), 0);
+}
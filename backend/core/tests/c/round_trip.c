@@ -0,0 +1,133 @@
+/* Round trip through the C ABI, run by tests/c_abi.rs with the path of the
+ * encoded ZK parameters as its argument */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "blackhole_core.h"
+
+#define CHECK(cond)                                                      \
+    do {                                                                 \
+        if (!(cond)) {                                                   \
+            fprintf(stderr, "%s:%d: check failed: %s (%s)\n", __FILE__,  \
+                    __LINE__, #cond, err.message ? err.message : "");    \
+            return 1;                                                    \
+        }                                                                \
+    } while (0)
+
+/* Read a whole file; NULL on failure */
+static uint8_t *read_file(const char *path, size_t *len) {
+    FILE *file = fopen(path, "rb");
+    if (file == NULL) {
+        return NULL;
+    }
+
+    uint8_t *data = NULL;
+    long size = -1;
+    if (fseek(file, 0, SEEK_END) == 0 && (size = ftell(file)) >= 0 && fseek(file, 0, SEEK_SET) == 0) {
+        data = malloc(size > 0 ? (size_t)size : 1);
+    }
+    if (data != NULL && fread(data, 1, (size_t)size, file) != (size_t)size) {
+        free(data);
+        data = NULL;
+    }
+
+    fclose(file);
+    *len = (size_t)size;
+    return data;
+}
+
+int main(int argc, char **argv) {
+    VaultError err = {0};
+    const char *text = "hello from C";
+
+    CHECK(argc == 2);
+    size_t parameters_len = 0;
+    uint8_t *parameters = read_file(argv[1], &parameters_len);
+    CHECK(parameters != NULL);
+
+    VaultCore *core = vault_core_new(parameters, parameters_len, &err);
+    CHECK(core != NULL);
+    free(parameters);
+
+    /* Ask for the size first, then collect the parked output */
+    size_t message_len = 0;
+    CHECK(vault_core_encrypt(core, "bob", (const uint8_t *)text, strlen(text),
+                             NULL, &message_len, &err) == VAULT_ERROR_BUFFER_TOO_SMALL);
+    CHECK(err.code == VAULT_ERROR_BUFFER_TOO_SMALL && message_len > 0);
+
+    uint8_t *message = malloc(message_len);
+    CHECK(vault_take_output(message, &message_len, &err) == 0);
+
+    uint8_t plaintext[64];
+    size_t plaintext_len = sizeof(plaintext);
+    CHECK(vault_core_decrypt(core, message, message_len, plaintext, &plaintext_len, &err) == 0);
+    CHECK(plaintext_len == strlen(text) && memcmp(plaintext, text, plaintext_len) == 0);
+
+    /* Failures populate the error */
+    message[0] ^= 0xff;
+    CHECK(vault_core_decrypt(core, message, message_len, plaintext, &plaintext_len, &err) != 0);
+    CHECK(err.code != 0 && err.message != NULL);
+    vault_error_free(&err);
+    CHECK(err.code == 0 && err.message == NULL);
+    free(message);
+
+    VaultGroup *group = vault_group_new(core, &err);
+    CHECK(group != NULL);
+
+    uint8_t sealed[256];
+    size_t sealed_len = sizeof(sealed);
+    CHECK(vault_group_encrypt(group, (const uint8_t *)text, strlen(text), sealed, &sealed_len, &err) == 0);
+
+    plaintext_len = sizeof(plaintext);
+    CHECK(vault_group_decrypt(group, sealed, sealed_len, plaintext, &plaintext_len, &err) == 0);
+    CHECK(plaintext_len == strlen(text) && memcmp(plaintext, text, plaintext_len) == 0);
+
+    VaultSession *session = vault_session_new(core, NULL, 0, &err);
+    CHECK(session != NULL);
+
+    uint8_t dh_public[32];
+    size_t dh_public_len = sizeof(dh_public);
+    CHECK(vault_session_dh_public(session, dh_public, &dh_public_len, &err) == 0);
+    CHECK(dh_public_len == 32);
+
+    /* Attachments: encrypt, read the three parts back, decrypt the blob */
+    const char *file = "attachment bytes from C";
+    VaultAttachment *attachment = vault_core_encrypt_attachment(
+        core, (const uint8_t *)file, strlen(file), "text/plain", NULL, 0, &err);
+    CHECK(attachment != NULL);
+
+    uint8_t pointer[512];
+    size_t pointer_len = sizeof(pointer);
+    CHECK(vault_attachment_pointer(attachment, pointer, &pointer_len, &err) == 0);
+
+    size_t blob_len = 0;
+    CHECK(vault_attachment_blob(attachment, NULL, &blob_len, &err) == VAULT_ERROR_BUFFER_TOO_SMALL);
+    uint8_t *blob = malloc(blob_len);
+    CHECK(vault_take_output(blob, &blob_len, &err) == 0);
+
+    uint8_t outboard[64];
+    size_t outboard_len = sizeof(outboard);
+    CHECK(vault_attachment_outboard(attachment, outboard, &outboard_len, &err) == 0);
+
+    plaintext_len = sizeof(plaintext);
+    CHECK(vault_core_decrypt_attachment(core, pointer, pointer_len, blob, blob_len,
+                                        plaintext, &plaintext_len, &err) == 0);
+    CHECK(plaintext_len == strlen(file) && memcmp(plaintext, file, plaintext_len) == 0);
+
+    blob[0] ^= 0xff;
+    CHECK(vault_core_decrypt_attachment(core, pointer, pointer_len, blob, blob_len,
+                                        plaintext, &plaintext_len, &err) != 0);
+    CHECK(err.code != 0 && err.message != NULL);
+    vault_error_free(&err);
+    free(blob);
+    vault_attachment_free(attachment);
+
+    vault_session_free(session);
+    vault_group_free(group);
+    vault_core_free(core);
+
+    printf("C ABI round trip ok (blackhole-core %s)\n", vault_version());
+    return 0;
+}
@@ -0,0 +1,70 @@
+//! 🔗 C ABI round trip
+//!
+//! Compiles `tests/c/round_trip.c` against the header generated by the build
+//! script with the platform C compiler, links it to the core's cdylib and
+//! runs it, so both the header and the exported symbols are exercised from C.
+
+#![cfg(all(feature = "ffi", target_os = "linux"))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use blackhole_core::providers::OsRandom;
+use blackhole_core::ZKParameters;
+
+/// Directory holding the cdylib built alongside this test
+fn library_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("no test executable path");
+    let deps = exe.parent().expect("test executable has no directory");
+
+    deps.ancestors()
+        .take(2)
+        .find(|dir| dir.join("libblackhole_core.so").exists())
+        .expect("libblackhole_core.so was not built")
+        .to_path_buf()
+}
+
+#[test]
+fn test_round_trip_through_c_abi() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = library_dir();
+    let exe = lib_dir.join("c_abi_round_trip");
+
+    let compiler = cc::Build::new()
+        .cargo_metadata(false)
+        .opt_level(0)
+        .host(env!("TARGET"))
+        .target(env!("TARGET"))
+        .get_compiler();
+
+    let status = compiler
+        .to_command()
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror"])
+        .arg("-I")
+        .arg(env!("BLACKHOLE_CORE_INCLUDE_DIR"))
+        .arg(manifest_dir.join("tests/c/round_trip.c"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-lblackhole_core")
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "round_trip.c does not compile against the header");
+
+    // Stands in for the ceremony parameters an app ships with
+    let parameters = lib_dir.join("c_abi_parameters");
+    fs::write(&parameters, ZKParameters::generate(&OsRandom).unwrap().to_bytes()).unwrap();
+
+    let output = Command::new(&exe)
+        .arg(&parameters)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .expect("failed to run the C program");
+    assert!(
+        output.status.success(),
+        "C round trip failed: {}",
+        String::from_utf8_lossy(&output.stderr),
+    );
+}
@@ -0,0 +1,70 @@
+//! 🕯️ Ceremony command-line round trip
+//!
+//! Runs `blackhole-ceremony` over files the way participants would: one
+//! initialization from phase-1 powers of tau, two contributions, then a
+//! verification of the chain that exports the verifying key.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use blackhole_core::providers::SeededRng;
+use blackhole_core::zk::ceremony::PowersOfTau;
+use blackhole_core::zk::setup::key_id;
+
+fn ceremony(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_blackhole-ceremony"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_ceremony_over_files() {
+    let dir = std::env::temp_dir().join(format!("blackhole-ceremony-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    // Stands in for the output of a public phase-1 ceremony
+    let powers = PowersOfTau::generate_insecure(256, &SeededRng::from_u64(0)).unwrap();
+    fs::write(dir.join("phase1"), powers.to_bytes()).unwrap();
+
+    // There is no way to start without phase 1
+    assert!(!ceremony(&dir, &["init", "params-0", "transcript"]).status.success());
+    let usage = ceremony(&dir, &[]);
+    assert!(String::from_utf8_lossy(&usage.stderr).contains("trust model"));
+
+    stdout(&ceremony(&dir, &["init", "phase1", "params-0", "transcript"]));
+    let first = stdout(&ceremony(&dir, &["contribute", "params-0", "transcript", "params-1"]));
+    let second = stdout(&ceremony(&dir, &["contribute", "params-1", "transcript", "params-2"]));
+
+    // Contributing to superseded parameters is refused
+    let stale = ceremony(&dir, &["contribute", "params-0", "transcript", "params-x"]);
+    assert!(!stale.status.success());
+    assert!(!dir.join("params-x").exists());
+
+    let report = stdout(&ceremony(&dir, &["verify", "phase1", "params-2", "transcript", "key"]));
+    assert!(report.contains(first.trim()));
+    assert!(report.contains(second.trim()));
+
+    let key = fs::read(dir.join("key")).unwrap();
+    let id: String = key_id(&key).iter().map(|byte| format!("{byte:02x}")).collect();
+    assert!(report.contains(&format!("key id: {id}")));
+
+    // Skipping the last contribution's parameters breaks the chain
+    let broken = ceremony(&dir, &["verify", "phase1", "params-1", "transcript"]);
+    assert!(!broken.status.success());
+
+    // So does starting from parameters that did not come from phase 1
+    let other = PowersOfTau::generate_insecure(256, &SeededRng::from_u64(1)).unwrap();
+    fs::write(dir.join("other"), other.to_bytes()).unwrap();
+    let unseeded = ceremony(&dir, &["verify", "other", "params-2", "transcript"]);
+    assert!(!unseeded.status.success());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,138 @@
+//! 🧵 SharedCore stress test
+//!
+//! Many threads hammer one `SharedCore` at once: several threads per ratchet
+//! session, group and one-to-one traffic alongside, and sessions being
+//! started and ended in between. Every session must hand out each message
+//! number exactly once and keep a history entry for every message sent.
+//!
+//! A barrier releases every thread together, but this checks correctness
+//! under contention, not parallelism. Threads sharing a session take turns
+//! on its lock, history updates take turns on the history lock, and
+//! one-to-one `encrypt` takes the core write lock, so it also waits for
+//! group traffic and session starts.
+
+use std::collections::BTreeMap;
+use std::sync::{Barrier, OnceLock};
+use std::thread;
+
+use blackhole_core::providers::SeededRng;
+use blackhole_core::{BlackHoleError, MessageContent, SharedCore, ZKParameters};
+
+const PEERS: usize = 8;
+const THREADS_PER_PEER: usize = 4;
+const MESSAGES_PER_THREAD: usize = 50;
+
+/// One local setup shared by every core in this test
+fn parameters() -> &'static ZKParameters {
+    static PARAMETERS: OnceLock<ZKParameters> = OnceLock::new();
+
+    PARAMETERS.get_or_init(|| ZKParameters::generate(&SeededRng::from_u64(0)).unwrap())
+}
+
+fn peer(index: usize) -> String {
+    format!("peer-{index}")
+}
+
+/// A peer's ratchet key, so that our sessions respond and may send first
+fn remote() -> Option<Vec<u8>> {
+    Some(SharedCore::new(parameters()).unwrap().start_session("us", None).unwrap())
+}
+
+#[test]
+fn test_sessions_serialize_under_contention() {
+    let shared = SharedCore::new(parameters()).unwrap();
+    for index in 0..PEERS {
+        shared.start_session(&peer(index), remote()).unwrap();
+    }
+    let group_id = shared.create_group().unwrap();
+    let start = Barrier::new(PEERS * THREADS_PER_PEER + 2);
+    let start = &start;
+
+    let numbers: Vec<(usize, Vec<u32>)> = thread::scope(|scope| {
+        let senders: Vec<_> = (0..PEERS * THREADS_PER_PEER)
+            .map(|thread| {
+                let shared = shared.clone();
+                scope.spawn(move || {
+                    let index = thread % PEERS;
+                    start.wait();
+                    let numbers = (0..MESSAGES_PER_THREAD)
+                        .map(|n| {
+                            let content = MessageContent::text(&format!("{thread}/{n}"));
+                            shared.session_encrypt(&peer(index), content).unwrap().header.message_number
+                        })
+                        .collect();
+                    (index, numbers)
+                })
+            })
+            .collect();
+
+        let shared = &shared;
+        let group_id = &group_id;
+        scope.spawn(move || {
+            start.wait();
+            for n in 0..MESSAGES_PER_THREAD {
+                let message = format!("group {n}");
+                let sealed = shared.group_encrypt(group_id, message.as_bytes()).unwrap();
+                assert_eq!(shared.group_decrypt(group_id, &sealed).unwrap(), message.as_bytes());
+
+                let encrypted = shared.encrypt("carol", &message).unwrap();
+                assert_eq!(shared.decrypt(&encrypted).unwrap(), message);
+            }
+        });
+
+        scope.spawn(move || {
+            let churn = remote();
+            start.wait();
+            for _ in 0..MESSAGES_PER_THREAD {
+                shared.start_session("churn", churn.clone()).unwrap();
+                match shared.session_encrypt("churn", MessageContent::text("brief")) {
+                    Ok(_) | Err(BlackHoleError::NoSession) => {}
+                    Err(err) => panic!("unexpected error: {err}"),
+                }
+                shared.end_session("churn");
+            }
+        });
+
+        senders.into_iter().map(|sender| sender.join().unwrap()).collect()
+    });
+
+    let mut per_peer: BTreeMap<usize, Vec<u32>> = BTreeMap::new();
+    for (index, mut sent) in numbers {
+        // Each thread sees its own messages in order
+        assert!(sent.windows(2).all(|pair| pair[0] < pair[1]));
+        per_peer.entry(index).or_default().append(&mut sent);
+    }
+
+    let total = (THREADS_PER_PEER * MESSAGES_PER_THREAD) as u32;
+    for (index, mut sent) in per_peer {
+        sent.sort_unstable();
+        assert_eq!(sent, (0..total).collect::<Vec<_>>(), "{} reused a message number", peer(index));
+        assert_eq!(shared.history(&peer(index)).len(), total as usize);
+    }
+
+    assert!(shared.history("churn").is_empty());
+}
+
+#[test]
+fn test_panic_wipe_ends_sessions_on_every_thread() {
+    let shared = SharedCore::new(parameters()).unwrap();
+    shared.start_session("bob", remote()).unwrap();
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            let shared = shared.clone();
+            scope.spawn(move || {
+                // Messages either go out before the wipe or find no session
+                while shared.session_encrypt("bob", MessageContent::text("hi")).is_ok() {}
+            });
+        }
+
+        shared.panic_wipe();
+    });
+
+    assert_eq!(
+        shared.session_encrypt("bob", MessageContent::text("hi")).unwrap_err(),
+        BlackHoleError::NoSession,
+    );
+    assert!(shared.history("bob").is_empty());
+}
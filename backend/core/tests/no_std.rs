@@ -0,0 +1,56 @@
+//! 🔌 Bare-metal build check
+//!
+//! Builds the core with `--no-default-features` for a Cortex-M4F target, which
+//! has no `std` at all, so any std-only API or dependency feature that creeps
+//! into the crypto, protocol or math modules fails this test.
+//!
+//! The test is skipped when the target is not installed; run
+//! `rustup target add thumbv7em-none-eabihf` to enable it. CI sets
+//! `BLACKHOLE_REQUIRE_NO_STD` so that a missing target fails instead.
+
+use std::path::Path;
+use std::process::Command;
+
+const TARGET: &str = "thumbv7em-none-eabihf";
+
+/// Set to make a missing target an error rather than a skip
+const REQUIRE_VAR: &str = "BLACKHOLE_REQUIRE_NO_STD";
+
+/// Whether the standard library for `target` is installed
+fn target_installed(target: &str) -> bool {
+    let output = Command::new("rustc")
+        .args(["--print", "target-libdir", "--target", target])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            let libdir = String::from_utf8_lossy(&output.stdout);
+            Path::new(libdir.trim()).exists()
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn test_builds_without_std_for_bare_metal() {
+    if !target_installed(TARGET) {
+        assert!(std::env::var_os(REQUIRE_VAR).is_none(), "{TARGET} is not installed");
+        eprintln!("skipping: {TARGET} is not installed");
+        return;
+    }
+
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+
+    // Only the rlib: a cdylib would need a panic handler and an allocator,
+    // which firmware provides
+    let status = Command::new(cargo)
+        .current_dir(manifest_dir)
+        .args(["rustc", "--lib", "--crate-type", "rlib", "--no-default-features"])
+        .args(["--target", TARGET])
+        .args(["--target-dir", &format!("{manifest_dir}/target/no_std")])
+        .status()
+        .expect("failed to run cargo");
+
+    assert!(status.success(), "no_std build for {TARGET} failed");
+}
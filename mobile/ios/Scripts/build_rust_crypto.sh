@@ -6,7 +6,7 @@
 set -e
 
 PROJECT_ROOT="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
-RUST_CORE_PATH="${PROJECT_ROOT}/../vault/core"
+RUST_CORE_PATH="${PROJECT_ROOT}/../../backend/core"
 OUTPUT_PATH="${PROJECT_ROOT}/Frameworks"
 
 echo "🔨 Building Rust crypto core for iOS..."
@@ -22,34 +22,31 @@ rustup target add aarch64-apple-ios
 rustup target add aarch64-apple-ios-sim
 rustup target add x86_64-apple-ios
 
-# Install cargo-lipo for universal binaries
-cargo install cargo-lipo
-
 cd "${RUST_CORE_PATH}" || exit 1
 
 # Build for iOS devices (arm64)
 echo "📱 Building for iOS devices (arm64)..."
-cargo build --release --target aarch64-apple-ios
+cargo rustc --lib --crate-type staticlib --release --no-default-features --features ffi --target aarch64-apple-ios
 
 # Build for iOS simulator (arm64)
 echo "🖥️  Building for iOS simulator (arm64)..."
-cargo build --release --target aarch64-apple-ios-sim
+cargo rustc --lib --crate-type staticlib --release --no-default-features --features ffi --target aarch64-apple-ios-sim
 
 # Build for iOS simulator (x86_64)
 echo "🖥️  Building for iOS simulator (x86_64)..."
-cargo build --release --target x86_64-apple-ios
+cargo rustc --lib --crate-type staticlib --release --no-default-features --features ffi --target x86_64-apple-ios
 
 # Create XCFramework
 echo "📦 Creating XCFramework..."
 mkdir -p "${OUTPUT_PATH}"
 
 xcodebuild -create-xcframework \
-    -library "${RUST_CORE_PATH}/target/aarch64-apple-ios/release/libvault_crypto.a" \
-    -headers "${PROJECT_ROOT}/VaultMessenger/Bridge/Headers" \
-    -library "${RUST_CORE_PATH}/target/aarch64-apple-ios-sim/release/libvault_crypto.a" \
-    -headers "${PROJECT_ROOT}/VaultMessenger/Bridge/Headers" \
-    -library "${RUST_CORE_PATH}/target/x86_64-apple-ios/release/libvault_crypto.a" \
-    -headers "${PROJECT_ROOT}/VaultMessenger/Bridge/Headers" \
+    -library "${RUST_CORE_PATH}/target/aarch64-apple-ios/release/libblackhole_core.a" \
+    -headers "${RUST_CORE_PATH}/include" \
+    -library "${RUST_CORE_PATH}/target/aarch64-apple-ios-sim/release/libblackhole_core.a" \
+    -headers "${RUST_CORE_PATH}/include" \
+    -library "${RUST_CORE_PATH}/target/x86_64-apple-ios/release/libblackhole_core.a" \
+    -headers "${RUST_CORE_PATH}/include" \
     -output "${OUTPUT_PATH}/VaultCrypto.xcframework"
 
 echo "✅ Rust crypto library built successfully!"
@@ -1,392 +0,0 @@
-// lib.rs
-// Rust crypto core FFI interface for iOS
-// This is an example implementation that should be placed in vault/core/src/ffi/ios.rs
-
-use std::os::raw::{c_char, c_int};
-use std::slice;
-
-// Re-export for FFI
-#[repr(C)]
-pub struct VaultError {
-    code: i32,
-    message: *const c_char,
-}
-
-/// Initialize the crypto library
-#[no_mangle]
-pub extern "C" fn vault_crypto_init() -> c_int {
-    // Initialize crypto system
-    // Return 0 on success, error code otherwise
-    0
-}
-
-/// Cleanup crypto library resources
-#[no_mangle]
-pub extern "C" fn vault_crypto_cleanup() {
-    // Cleanup resources
-}
-
-/// Generate identity keypair (Curve25519)
-#[no_mangle]
-pub extern "C" fn vault_generate_identity_keypair(
-    public_key_out: *mut u8,
-    private_key_out: *mut u8,
-) -> c_int {
-    if public_key_out.is_null() || private_key_out.is_null() {
-        return -1;
-    }
-
-    unsafe {
-        // Generate Curve25519 keypair
-        use curve25519_dalek::scalar::Scalar;
-        use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
-        
-        let private_key = Scalar::random(&mut rand::thread_rng());
-        let public_key = &ED25519_BASEPOINT_TABLE * &private_key;
-        
-        // Copy to output buffers (32 bytes each)
-        std::ptr::copy_nonoverlapping(
-            public_key.compress().as_bytes().as_ptr(),
-            public_key_out,
-            32
-        );
-        
-        std::ptr::copy_nonoverlapping(
-            private_key.as_bytes().as_ptr(),
-            private_key_out,
-            32
-        );
-    }
-
-    0
-}
-
-/// Generate signed pre-key
-#[no_mangle]
-pub extern "C" fn vault_generate_signed_prekey(
-    public_key_out: *mut u8,
-    private_key_out: *mut u8,
-    signature_out: *mut u8,
-    identity_private_key: *const u8,
-) -> c_int {
-    if public_key_out.is_null() || private_key_out.is_null() 
-        || signature_out.is_null() || identity_private_key.is_null() {
-        return -1;
-    }
-
-    unsafe {
-        // Generate ephemeral keypair
-        use curve25519_dalek::scalar::Scalar;
-        use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
-        use ed25519_dalek::{SigningKey, Signer};
-        
-        let ephemeral_private = Scalar::random(&mut rand::thread_rng());
-        let ephemeral_public = &ED25519_BASEPOINT_TABLE * &ephemeral_private;
-        
-        // Sign with identity key
-        let identity_key_bytes = slice::from_raw_parts(identity_private_key, 32);
-        let signing_key = SigningKey::from_bytes(identity_key_bytes.try_into().unwrap());
-        let signature = signing_key.sign(ephemeral_public.compress().as_bytes());
-        
-        // Copy to output
-        std::ptr::copy_nonoverlapping(
-            ephemeral_public.compress().as_bytes().as_ptr(),
-            public_key_out,
-            32
-        );
-        
-        std::ptr::copy_nonoverlapping(
-            ephemeral_private.as_bytes().as_ptr(),
-            private_key_out,
-            32
-        );
-        
-        std::ptr::copy_nonoverlapping(
-            signature.to_bytes().as_ptr(),
-            signature_out,
-            64
-        );
-    }
-
-    0
-}
-
-/// Generate one-time pre-key
-#[no_mangle]
-pub extern "C" fn vault_generate_onetime_prekey(
-    public_key_out: *mut u8,
-    private_key_out: *mut u8,
-) -> c_int {
-    // Same as identity keypair generation
-    vault_generate_identity_keypair(public_key_out, private_key_out)
-}
-
-/// Encrypt message using Signal Protocol
-#[no_mangle]
-pub extern "C" fn vault_encrypt_message(
-    plaintext: *const u8,
-    plaintext_len: c_int,
-    recipient_public_key: *const u8,
-    ciphertext_out: *mut u8,
-    ciphertext_len_out: *mut c_int,
-) -> c_int {
-    if plaintext.is_null() || recipient_public_key.is_null() 
-        || ciphertext_out.is_null() || ciphertext_len_out.is_null() {
-        return -1;
-    }
-
-    unsafe {
-        use chacha20poly1305::{
-            aead::{Aead, KeyInit},
-            ChaCha20Poly1305, Nonce,
-        };
-        
-        let plaintext_slice = slice::from_raw_parts(plaintext, plaintext_len as usize);
-        let recipient_key = slice::from_raw_parts(recipient_public_key, 32);
-        
-        // Perform ECDH to derive shared secret
-        // This is simplified - real implementation uses Signal Protocol
-        let nonce = Nonce::from_slice(&[0u8; 12]);
-        let cipher = ChaCha20Poly1305::new_from_slice(recipient_key).unwrap();
-        
-        match cipher.encrypt(nonce, plaintext_slice) {
-            Ok(encrypted) => {
-                let len = encrypted.len();
-                std::ptr::copy_nonoverlapping(
-                    encrypted.as_ptr(),
-                    ciphertext_out,
-                    len
-                );
-                *ciphertext_len_out = len as c_int;
-                0
-            }
-            Err(_) => -1,
-        }
-    }
-}
-
-/// Decrypt message
-#[no_mangle]
-pub extern "C" fn vault_decrypt_message(
-    ciphertext: *const u8,
-    ciphertext_len: c_int,
-    sender_public_key: *const u8,
-    receiver_private_key: *const u8,
-    plaintext_out: *mut u8,
-    plaintext_len_out: *mut c_int,
-) -> c_int {
-    if ciphertext.is_null() || sender_public_key.is_null() 
-        || receiver_private_key.is_null() || plaintext_out.is_null() 
-        || plaintext_len_out.is_null() {
-        return -1;
-    }
-
-    unsafe {
-        use chacha20poly1305::{
-            aead::{Aead, KeyInit},
-            ChaCha20Poly1305, Nonce,
-        };
-        
-        let ciphertext_slice = slice::from_raw_parts(ciphertext, ciphertext_len as usize);
-        let private_key = slice::from_raw_parts(receiver_private_key, 32);
-        
-        let nonce = Nonce::from_slice(&[0u8; 12]);
-        let cipher = ChaCha20Poly1305::new_from_slice(private_key).unwrap();
-        
-        match cipher.decrypt(nonce, ciphertext_slice) {
-            Ok(decrypted) => {
-                let len = decrypted.len();
-                std::ptr::copy_nonoverlapping(
-                    decrypted.as_ptr(),
-                    plaintext_out,
-                    len
-                );
-                *plaintext_len_out = len as c_int;
-                0
-            }
-            Err(_) => -1,
-        }
-    }
-}
-
-/// Generate post-quantum keypair (ML-KEM-768)
-#[no_mangle]
-pub extern "C" fn vault_pq_keygen(
-    public_key_out: *mut u8,
-    private_key_out: *mut u8,
-) -> c_int {
-    if public_key_out.is_null() || private_key_out.is_null() {
-        return -1;
-    }
-
-    // ML-KEM-768 key generation
-    // This requires the pqcrypto or similar crate
-    // Placeholder implementation
-    0
-}
-
-/// Encapsulate shared secret using post-quantum crypto
-#[no_mangle]
-pub extern "C" fn vault_pq_encapsulate(
-    public_key: *const u8,
-    ciphertext_out: *mut u8,
-    shared_secret_out: *mut u8,
-) -> c_int {
-    if public_key.is_null() || ciphertext_out.is_null() || shared_secret_out.is_null() {
-        return -1;
-    }
-
-    // ML-KEM-768 encapsulation
-    0
-}
-
-/// Decapsulate shared secret
-#[no_mangle]
-pub extern "C" fn vault_pq_decapsulate(
-    ciphertext: *const u8,
-    private_key: *const u8,
-    shared_secret_out: *mut u8,
-) -> c_int {
-    if ciphertext.is_null() || private_key.is_null() || shared_secret_out.is_null() {
-        return -1;
-    }
-
-    // ML-KEM-768 decapsulation
-    0
-}
-
-/// Hash data with SHA3-256
-#[no_mangle]
-pub extern "C" fn vault_hash_sha3_256(
-    data: *const u8,
-    data_len: c_int,
-    hash_out: *mut u8,
-) -> c_int {
-    if data.is_null() || hash_out.is_null() {
-        return -1;
-    }
-
-    unsafe {
-        use sha3::{Sha3_256, Digest};
-        
-        let data_slice = slice::from_raw_parts(data, data_len as usize);
-        let mut hasher = Sha3_256::new();
-        hasher.update(data_slice);
-        let result = hasher.finalize();
-        
-        std::ptr::copy_nonoverlapping(
-            result.as_ptr(),
-            hash_out,
-            32
-        );
-    }
-
-    0
-}
-
-/// Hash data with Blake3
-#[no_mangle]
-pub extern "C" fn vault_hash_blake3(
-    data: *const u8,
-    data_len: c_int,
-    hash_out: *mut u8,
-) -> c_int {
-    if data.is_null() || hash_out.is_null() {
-        return -1;
-    }
-
-    unsafe {
-        let data_slice = slice::from_raw_parts(data, data_len as usize);
-        let hash = blake3::hash(data_slice);
-        
-        std::ptr::copy_nonoverlapping(
-            hash.as_bytes().as_ptr(),
-            hash_out,
-            32
-        );
-    }
-
-    0
-}
-
-/// Generate secure random bytes
-#[no_mangle]
-pub extern "C" fn vault_secure_random(
-    buffer_out: *mut u8,
-    buffer_len: c_int,
-) -> c_int {
-    if buffer_out.is_null() {
-        return -1;
-    }
-
-    unsafe {
-        use rand::RngCore;
-        
-        let buffer_slice = slice::from_raw_parts_mut(buffer_out, buffer_len as usize);
-        rand::thread_rng().fill_bytes(buffer_slice);
-    }
-
-    0
-}
-
-/// Securely zero memory
-#[no_mangle]
-pub extern "C" fn vault_secure_zero(
-    buffer: *mut u8,
-    buffer_len: c_int,
-) {
-    if buffer.is_null() {
-        return;
-    }
-
-    unsafe {
-        use zeroize::Zeroize;
-        
-        let buffer_slice = slice::from_raw_parts_mut(buffer, buffer_len as usize);
-        buffer_slice.zeroize();
-    }
-}
-
-/// Get library version
-#[no_mangle]
-pub extern "C" fn vault_get_version() -> *const c_char {
-    b"1.0.0\0".as_ptr() as *const c_char
-}
-
-// Zero-knowledge proof functions (placeholder)
-#[no_mangle]
-pub extern "C" fn vault_zk_generate_proof(
-    _identity_key: *const u8,
-    _secret: *const u8,
-    _proof_out: *mut u8,
-    _proof_len_out: *mut c_int,
-) -> c_int {
-    // ZK-SNARK proof generation
-    0
-}
-
-#[no_mangle]
-pub extern "C" fn vault_zk_verify_proof(
-    _proof: *const u8,
-    _proof_len: c_int,
-    _public_input: *const u8,
-    _valid_out: *mut bool,
-) -> c_int {
-    // ZK-SNARK verification
-    0
-}
-
-/// Derive key from master key
-#[no_mangle]
-pub extern "C" fn vault_derive_key(
-    master_key: *const u8,
-    context: *const c_char,
-    derived_key_out: *mut u8,
-) -> c_int {
-    if master_key.is_null() || context.is_null() || derived_key_out.is_null() {
-        return -1;
-    }
-
-    // HKDF key derivation
-    0
-}
@@ -2,217 +2,349 @@
 //  VaultBridge.swift
 //  VaultMessenger
 //
-//  Swift wrapper for Rust crypto functions
+//  Swift wrapper for the blackhole-core C ABI (blackhole_core.h)
 //
 
 import Foundation
 
-enum VaultBridge {
-    
+/// Owner of a `VaultCore` handle: the identity, its keys and local encryption
+///
+/// Handles are freed when the wrapper is released. The core may be used from
+/// any thread; sessions and groups from one thread at a time.
+final class VaultBridge {
+    private let core: OpaquePointer
+
     // MARK: - Initialization
-    static func initialize() -> Bool {
-        return vault_crypto_init() == 0
-    }
-    
-    static func cleanup() {
-        vault_crypto_cleanup()
-    }
-    
-    // MARK: - Key Generation
-    static func generateIdentityKeyPair() throws -> (publicKey: Data, privateKey: Data) {
-        var publicKey = Data(count: 32)
-        var privateKey = Data(count: 32)
-        
-        let result = publicKey.withUnsafeMutableBytes { pubPtr in
-            privateKey.withUnsafeMutableBytes { privPtr in
-                vault_generate_identity_keypair(
-                    pubPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    privPtr.baseAddress?.assumingMemoryBound(to: UInt8.self)
-                )
+    /// Create a core from the encoded identity circuit parameters of the
+    /// phase-2 ceremony
+    init(parameters: Data) throws {
+        core = try withBytes(parameters) { parameters, parametersLength in
+            try makeHandle { vault_core_new(parameters, parametersLength, $0) }
+        }
+    }
+
+    deinit {
+        vault_core_free(core)
+    }
+
+    static var version: String {
+        String(cString: vault_version())
+    }
+
+    // MARK: - Identity
+    func fingerprint() throws -> String {
+        let bytes = try output { vault_core_fingerprint(core, $0, $1, $2) }
+        return String(decoding: bytes, as: UTF8.self)
+    }
+
+    func identityPublicKey() throws -> Data {
+        try output { vault_core_identity_public_key(core, $0, $1, $2) }
+    }
+
+    func postQuantumPublicKey() throws -> Data {
+        try output(capacity: 1184) { vault_core_pq_public_key(core, $0, $1, $2) }
+    }
+
+    // MARK: - Encryption/Decryption
+    /// Encrypt text, returning the encrypted message as JSON
+    func encrypt(_ text: String, for recipient: String) throws -> Data {
+        let plaintext = Data(text.utf8)
+
+        return try withBytes(plaintext) { plaintext, plaintextLength in
+            try output(capacity: plaintextLength + 256) {
+                vault_core_encrypt(core, recipient, plaintext, plaintextLength, $0, $1, $2)
             }
         }
-        
-        guard result == 0 else {
-            throw VaultBridgeError.keyGenerationFailed
-        }
-        
-        return (publicKey, privateKey)
-    }
-    
-    static func generateSignedPreKey(identityPrivateKey: Data) throws -> (publicKey: Data, privateKey: Data, signature: Data) {
-        var publicKey = Data(count: 32)
-        var privateKey = Data(count: 32)
-        var signature = Data(count: 64)
-        
-        let result = publicKey.withUnsafeMutableBytes { pubPtr in
-            privateKey.withUnsafeMutableBytes { privPtr in
-                signature.withUnsafeMutableBytes { sigPtr in
-                    identityPrivateKey.withUnsafeBytes { idPrivPtr in
-                        vault_generate_signed_prekey(
-                            pubPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                            privPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                            sigPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                            idPrivPtr.baseAddress?.assumingMemoryBound(to: UInt8.self)
+    }
+
+    /// Decrypt a JSON encrypted message
+    func decrypt(_ message: Data) throws -> String {
+        let plaintext = try withBytes(message) { message, messageLength in
+            try output(capacity: messageLength) {
+                vault_core_decrypt(core, message, messageLength, $0, $1, $2)
+            }
+        }
+
+        guard let text = String(data: plaintext, encoding: .utf8) else {
+            throw VaultBridgeError(code: VAULT_ERROR_INVALID_UTF8, message: "Decrypted text is not UTF-8")
+        }
+        return text
+    }
+
+    // MARK: - Zero-Knowledge Proofs
+    /// Proof of identity as JSON
+    func proveIdentity() throws -> Data {
+        try output(capacity: 1024) { vault_core_prove_identity(core, $0, $1, $2) }
+    }
+
+    func verifyIdentity(_ proof: Data) throws -> Bool {
+        var valid = false
+        try withBytes(proof) { proof, proofLength in
+            try check { vault_core_verify_identity(core, proof, proofLength, &valid, $0) }
+        }
+        return valid
+    }
+
+    /// 64-byte proof of possession of the identity key, bound to `context`
+    func proveIdentityKey(context: Data) throws -> Data {
+        try withBytes(context) { context, contextLength in
+            try output { vault_core_prove_identity_key(core, context, contextLength, $0, $1, $2) }
+        }
+    }
+
+    static func verifyIdentityKey(publicKey: Data, context: Data, proof: Data) throws -> Bool {
+        var valid = false
+        try withBytes(publicKey) { publicKey, publicKeyLength in
+            try withBytes(context) { context, contextLength in
+                try withBytes(proof) { proof, proofLength in
+                    try check {
+                        vault_verify_identity_key(
+                            publicKey, publicKeyLength,
+                            context, contextLength,
+                            proof, proofLength,
+                            &valid, $0
                         )
                     }
                 }
             }
         }
-        
-        guard result == 0 else {
-            throw VaultBridgeError.keyGenerationFailed
-        }
-        
-        return (publicKey, privateKey, signature)
-    }
-    
-    // MARK: - Post-Quantum Cryptography
-    static func generatePostQuantumKeys() throws -> (publicKey: Data, privateKey: Data) {
-        var publicKey = Data(count: 1184)  // ML-KEM-768 public key size
-        var privateKey = Data(count: 2400) // ML-KEM-768 private key size
-        
-        let result = publicKey.withUnsafeMutableBytes { pubPtr in
-            privateKey.withUnsafeMutableBytes { privPtr in
-                vault_pq_keygen(
-                    pubPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    privPtr.baseAddress?.assumingMemoryBound(to: UInt8.self)
-                )
+        return valid
+    }
+
+    // MARK: - Attachments
+    /// Encrypt a file under a fresh random key
+    func encryptAttachment(_ data: Data, contentType: String, thumbnail: Data? = nil) throws -> VaultBridgeAttachment {
+        let attachment = try withBytes(data) { data, dataLength in
+            try makeHandle { err in
+                guard let thumbnail = thumbnail else {
+                    return vault_core_encrypt_attachment(core, data, dataLength, contentType, nil, 0, err)
+                }
+                return withBytes(thumbnail) { thumbnail, thumbnailLength in
+                    vault_core_encrypt_attachment(core, data, dataLength, contentType, thumbnail, thumbnailLength, err)
+                }
             }
         }
-        
-        guard result == 0 else {
-            throw VaultBridgeError.postQuantumKeyGenerationFailed
-        }
-        
-        return (publicKey, privateKey)
-    }
-    
-    static func encapsulatePostQuantum(publicKey: Data) throws -> (ciphertext: Data, sharedSecret: Data) {
-        var ciphertext = Data(count: 1088)
-        var sharedSecret = Data(count: 32)
-        
-        let result = publicKey.withUnsafeBytes { pubPtr in
-            ciphertext.withUnsafeMutableBytes { cipherPtr in
-                sharedSecret.withUnsafeMutableBytes { secretPtr in
-                    vault_pq_encapsulate(
-                        pubPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                        cipherPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                        secretPtr.baseAddress?.assumingMemoryBound(to: UInt8.self)
-                    )
+        defer { vault_attachment_free(attachment) }
+
+        return VaultBridgeAttachment(
+            pointer: try output(capacity: 512) { vault_attachment_pointer(attachment, $0, $1, $2) },
+            blob: try output(capacity: data.count + 1024) { vault_attachment_blob(attachment, $0, $1, $2) },
+            outboard: try output(capacity: 1024) { vault_attachment_outboard(attachment, $0, $1, $2) }
+        )
+    }
+
+    /// Verify a downloaded blob against its pointer and decrypt it
+    func decryptAttachment(pointer: Data, blob: Data) throws -> Data {
+        try withBytes(pointer) { pointer, pointerLength in
+            try withBytes(blob) { blob, blobLength in
+                try output(capacity: blobLength) {
+                    vault_core_decrypt_attachment(core, pointer, pointerLength, blob, blobLength, $0, $1, $2)
                 }
             }
         }
-        
-        guard result == 0 else {
-            throw VaultBridgeError.encapsulationFailed
-        }
-        
-        return (ciphertext, sharedSecret)
-    }
-    
-    // MARK: - Hashing
-    static func hashSHA3(_ data: Data) throws -> Data {
-        var hash = Data(count: 32)
-        
-        let result = data.withUnsafeBytes { dataPtr in
-            hash.withUnsafeMutableBytes { hashPtr in
-                vault_hash_sha3_256(
-                    dataPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    Int32(data.count),
-                    hashPtr.baseAddress?.assumingMemoryBound(to: UInt8.self)
-                )
-            }
+    }
+
+    // MARK: - Sessions and Groups
+    /// Start a Double Ratchet session; pass the peer's ratchet key to respond
+    func startSession(remoteRatchetKey: Data? = nil) throws -> VaultBridgeSession {
+        let session = try withBytes(remoteRatchetKey ?? Data()) { remote, remoteLength in
+            try makeHandle { vault_session_new(core, remote, remoteLength, $0) }
         }
-        
-        guard result == 0 else {
-            throw VaultBridgeError.hashingFailed
-        }
-        
-        return hash
-    }
-    
-    static func hashBlake3(_ data: Data) throws -> Data {
-        var hash = Data(count: 32)
-        
-        let result = data.withUnsafeBytes { dataPtr in
-            hash.withUnsafeMutableBytes { hashPtr in
-                vault_hash_blake3(
-                    dataPtr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    Int32(data.count),
-                    hashPtr.baseAddress?.assumingMemoryBound(to: UInt8.self)
-                )
+        return VaultBridgeSession(session)
+    }
+
+    func createGroup() throws -> VaultBridgeGroup {
+        VaultBridgeGroup(try makeHandle { vault_group_new(core, $0) })
+    }
+
+    // MARK: - Secure Memory
+    /// Emergency deletion of every key held by the core
+    func panicWipe() {
+        vault_core_panic_wipe(core)
+    }
+}
+
+/// An encrypted attachment
+///
+/// Upload `blob` and `outboard`, and send `pointer` inside a ratchet
+/// message; the pointer holds the attachment key.
+struct VaultBridgeAttachment {
+    let pointer: Data
+    let blob: Data
+    let outboard: Data
+}
+
+/// Owner of a `VaultSession` handle
+final class VaultBridgeSession {
+    private let session: OpaquePointer
+
+    fileprivate init(_ session: OpaquePointer) {
+        self.session = session
+    }
+
+    deinit {
+        vault_session_free(session)
+    }
+
+    /// Our 32-byte ratchet public key, to send to the peer
+    func ratchetPublicKey() throws -> Data {
+        try output { vault_session_dh_public(session, $0, $1, $2) }
+    }
+
+    /// Encrypt bytes, returning the message envelope as JSON
+    func encrypt(_ plaintext: Data) throws -> Data {
+        try withBytes(plaintext) { plaintext, plaintextLength in
+            try output(capacity: plaintextLength + 512) {
+                vault_session_encrypt(session, plaintext, plaintextLength, $0, $1, $2)
             }
         }
-        
-        guard result == 0 else {
-            throw VaultBridgeError.hashingFailed
-        }
-        
-        return hash
     }
-    
-    // MARK: - Secure Random
-    static func generateSecureRandom(length: Int) throws -> Data {
-        var buffer = Data(count: length)
-        
-        let result = buffer.withUnsafeMutableBytes { ptr in
-            vault_secure_random(
-                ptr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                Int32(length)
-            )
+
+    /// Decrypt a JSON message envelope
+    func decrypt(_ envelope: Data) throws -> Data {
+        try withBytes(envelope) { envelope, envelopeLength in
+            try output(capacity: envelopeLength) {
+                vault_session_decrypt(session, envelope, envelopeLength, $0, $1, $2)
+            }
         }
-        
-        guard result == 0 else {
-            throw VaultBridgeError.randomGenerationFailed
+    }
+}
+
+/// Owner of a `VaultGroup` handle
+final class VaultBridgeGroup {
+    private let group: OpaquePointer
+
+    fileprivate init(_ group: OpaquePointer) {
+        self.group = group
+    }
+
+    deinit {
+        vault_group_free(group)
+    }
+
+    func id() throws -> String {
+        let bytes = try output { vault_group_id(group, $0, $1, $2) }
+        return String(decoding: bytes, as: UTF8.self)
+    }
+
+    func epoch() throws -> UInt64 {
+        var epoch: UInt64 = 0
+        try check { vault_group_epoch(group, &epoch, $0) }
+        return epoch
+    }
+
+    func proposeAdd(memberId: String, keyPackage: Data) throws {
+        try withBytes(keyPackage) { keyPackage, keyPackageLength in
+            try check { vault_group_propose_add(group, memberId, keyPackage, keyPackageLength, $0) }
         }
-        
-        return buffer
     }
-    
-    // MARK: - Secure Memory
-    static func secureZero(_ data: inout Data) {
-        data.withUnsafeMutableBytes { ptr in
-            vault_secure_zero(
-                ptr.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                Int32(data.count)
-            )
+
+    /// Commit the pending proposals, returning the commit message
+    func commit() throws -> Data {
+        try output { vault_group_commit(group, $0, $1, $2) }
+    }
+
+    func encrypt(_ plaintext: Data) throws -> Data {
+        try withBytes(plaintext) { plaintext, plaintextLength in
+            try output(capacity: plaintextLength + 256) {
+                vault_group_encrypt(group, plaintext, plaintextLength, $0, $1, $2)
+            }
         }
     }
-    
-    static func getVersion() -> String {
-        if let cString = vault_get_version() {
-            return String(cString: cString)
+
+    func decrypt(_ message: Data) throws -> Data {
+        try withBytes(message) { message, messageLength in
+            try output(capacity: messageLength) {
+                vault_group_decrypt(group, message, messageLength, $0, $1, $2)
+            }
         }
-        return "Unknown"
     }
 }
 
 // MARK: - Bridge Errors
-enum VaultBridgeError: Error {
-    case keyGenerationFailed
-    case postQuantumKeyGenerationFailed
-    case encapsulationFailed
-    case decapsulationFailed
-    case hashingFailed
-    case randomGenerationFailed
-    case invalidInput
-    
-    var localizedDescription: String {
-        switch self {
-        case .keyGenerationFailed:
-            return "Failed to generate cryptographic keys"
-        case .postQuantumKeyGenerationFailed:
-            return "Failed to generate post-quantum keys"
-        case .encapsulationFailed:
-            return "Failed to encapsulate shared secret"
-        case .decapsulationFailed:
-            return "Failed to decapsulate shared secret"
-        case .hashingFailed:
-            return "Failed to compute hash"
-        case .randomGenerationFailed:
-            return "Failed to generate secure random data"
-        case .invalidInput:
-            return "Invalid input parameters"
+/// A failed call, with the code and message from its `VaultError`
+///
+/// Codes below 100 are core errors; the rest are the `VAULT_ERROR_*`
+/// constants of blackhole_core.h.
+struct VaultBridgeError: Error, LocalizedError {
+    let code: Int32
+    let message: String
+
+    fileprivate init(code: Int32, message: String) {
+        self.code = code
+        self.message = message
+    }
+
+    fileprivate init(_ err: VaultError) {
+        self.init(
+            code: err.code,
+            message: err.message.map { String(cString: $0) } ?? "Error \(err.code)"
+        )
+    }
+
+    var errorDescription: String? {
+        message
+    }
+}
+
+// MARK: - Call Helpers
+private typealias OutputCall = (
+    UnsafeMutablePointer<UInt8>?,
+    UnsafeMutablePointer<Int>,
+    UnsafeMutablePointer<VaultError>
+) -> Int32
+
+/// Run a call that writes to a caller-allocated buffer
+///
+/// A call whose output does not fit has still taken effect, so the output is
+/// collected with `vault_take_output` rather than by calling again.
+private func output(capacity: Int = 256, _ call: OutputCall) throws -> Data {
+    var buffer = Data(count: capacity)
+    var length = capacity
+    var err = VaultError()
+    defer { vault_error_free(&err) }
+
+    var code = buffer.withUnsafeMutableBytes {
+        call($0.baseAddress?.assumingMemoryBound(to: UInt8.self), &length, &err)
+    }
+
+    if code == VAULT_ERROR_BUFFER_TOO_SMALL {
+        buffer = Data(count: length)
+        code = buffer.withUnsafeMutableBytes {
+            vault_take_output($0.baseAddress?.assumingMemoryBound(to: UInt8.self), &length, &err)
         }
     }
+
+    guard code == 0 else {
+        throw VaultBridgeError(err)
+    }
+    return buffer.prefix(length)
+}
+
+/// Run a call that only reports success or failure
+private func check(_ call: (UnsafeMutablePointer<VaultError>) -> Int32) throws {
+    var err = VaultError()
+    defer { vault_error_free(&err) }
+
+    guard call(&err) == 0 else {
+        throw VaultBridgeError(err)
+    }
+}
+
+/// Run a call that returns a new handle, or null on failure
+private func makeHandle(_ call: (UnsafeMutablePointer<VaultError>) -> OpaquePointer?) throws -> OpaquePointer {
+    var err = VaultError()
+    defer { vault_error_free(&err) }
+
+    guard let handle = call(&err) else {
+        throw VaultBridgeError(err)
+    }
+    return handle
+}
+
+/// Pass `data` as a pointer and length
+private func withBytes<T>(_ data: Data, _ body: (UnsafePointer<UInt8>?, Int) throws -> T) rethrows -> T {
+    try data.withUnsafeBytes {
+        try body($0.baseAddress?.assumingMemoryBound(to: UInt8.self), $0.count)
+    }
 }
@@ -8,6 +8,6 @@
 #ifndef VaultMessenger_Bridging_Header_h
 #define VaultMessenger_Bridging_Header_h
 
-#import "RustBridge.h"
+#import "blackhole_core.h"
 
 #endif /* VaultMessenger_Bridging_Header_h */
@@ -7,7 +7,6 @@ mkdir -p VaultMessenger/Features/{Authentication,Messaging,Contacts,Settings}
 mkdir -p VaultMessenger/Services/{Networking,Crypto,Storage,Notifications}
 mkdir -p VaultMessenger/Core/{Models,ViewModels,Extensions}
 mkdir -p VaultMessenger/Resources/{Assets.xcassets,Fonts}
-mkdir -p VaultMessenger/Bridge/{Rust,Headers}
 mkdir -p VaultMessengerTests
 mkdir -p VaultMessengerUITests
 mkdir -p Scripts